@@ -11,6 +11,10 @@ use gaptos::aptos_types::{ledger_info::LedgerInfoWithSignatures, validator_verif
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+#[cfg(test)]
+#[path = "block_retrieval_test.rs"]
+mod block_retrieval_test;
+
 pub const NUM_RETRIES: usize = 5;
 pub const NUM_PEERS_PER_RETRY: usize = 1;
 pub const RETRY_INTERVAL_MSEC: u64 = 500;
@@ -174,3 +178,145 @@ impl fmt::Display for BlockRetrievalResponse {
         }
     }
 }
+
+/// RPC to get the committed blocks of the given block numbers, with the ledger infos committing
+/// them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BlockNumberRetrievalRequest {
+    start_block_number: u64,
+    num_blocks: u64,
+}
+
+impl BlockNumberRetrievalRequest {
+    pub fn new(start_block_number: u64, num_blocks: u64) -> Self {
+        Self { start_block_number, num_blocks }
+    }
+
+    pub fn start_block_number(&self) -> u64 {
+        self.start_block_number
+    }
+
+    pub fn num_blocks(&self) -> u64 {
+        self.num_blocks
+    }
+}
+
+impl fmt::Display for BlockNumberRetrievalRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[BlockNumberRetrievalRequest starting from block number {} with {} blocks]",
+            self.start_block_number, self.num_blocks
+        )
+    }
+}
+
+/// Carries the committed blocks ordered by number, and the ledger infos committing them. The last
+/// block is the one of the last ledger info, every other block is its ancestor. Empty if the peer
+/// hasn't committed the first block requested.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BlockNumberRetrievalResponse {
+    blocks: Vec<Block>,
+    ledger_infos: Vec<LedgerInfoWithSignatures>,
+}
+
+impl BlockNumberRetrievalResponse {
+    pub fn new(blocks: Vec<Block>, ledger_infos: Vec<LedgerInfoWithSignatures>) -> Self {
+        Self { blocks, ledger_infos }
+    }
+
+    pub fn blocks(&self) -> &Vec<Block> {
+        &self.blocks
+    }
+
+    pub fn ledger_infos(&self) -> &Vec<LedgerInfoWithSignatures> {
+        &self.ledger_infos
+    }
+
+    /// The blocks have to form a chain from the requested block number up to the block of the
+    /// last ledger info, which is signed by `sig_verifier`, so that the chain is certified as a
+    /// whole. The other ledger infos are only checked against the blocks, their signatures are
+    /// verified if they are of the same epoch as the last one.
+    pub fn verify(
+        &self,
+        retrieval_request: BlockNumberRetrievalRequest,
+        sig_verifier: &ValidatorVerifier,
+    ) -> anyhow::Result<()> {
+        let Some(last_ledger_info) = self.ledger_infos.last() else {
+            ensure!(self.blocks.is_empty(), "blocks returned without ledger infos");
+            return Ok(());
+        };
+        let start = retrieval_request.start_block_number();
+        let last_block_number = last_ledger_info.ledger_info().block_number();
+        ensure!(
+            last_block_number >= start,
+            "ledger info of block number {} is before the requested block number {}",
+            last_block_number,
+            start,
+        );
+        ensure!(
+            self.blocks.len() as u64 == last_block_number - start + 1,
+            "blocks returned don't end at the last ledger info, expect {}, get {}",
+            last_block_number - start + 1,
+            self.blocks.len(),
+        );
+        self.blocks
+            .iter()
+            .enumerate()
+            .try_fold(None, |parent_id, (i, block)| {
+                // The genesis block is certified by its descendants like any other block
+                if !block.is_genesis_block() {
+                    block.verify_well_formed()?;
+                }
+                ensure!(
+                    block.block_number() == Some(start + i as u64),
+                    "block {} has block number {:?}, expect {}",
+                    block.id(),
+                    block.block_number(),
+                    start + i as u64,
+                );
+                if let Some(parent_id) = parent_id {
+                    ensure!(
+                        block.parent_id() == parent_id,
+                        "blocks doesn't form a chain: expect parent {}, get {}",
+                        parent_id,
+                        block.parent_id()
+                    );
+                }
+                Ok(Some(block.id()))
+            })?;
+        let last_epoch = last_ledger_info.ledger_info().epoch();
+        self.ledger_infos.iter().try_fold(None, |previous, ledger_info| {
+            let block_number = ledger_info.ledger_info().block_number();
+            ensure!(
+                block_number >= start && previous.map_or(true, |previous| block_number > previous),
+                "ledger info of block number {} is out of order",
+                block_number,
+            );
+            let block = &self.blocks[(block_number - start) as usize];
+            ensure!(
+                ledger_info.commit_info().id() == block.id(),
+                "ledger info of block number {} commits {}, expect {}",
+                block_number,
+                ledger_info.commit_info().id(),
+                block.id(),
+            );
+            if ledger_info.ledger_info().epoch() == last_epoch {
+                ledger_info.verify_signatures(sig_verifier)?;
+            }
+            Ok(Some(block_number))
+        })?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for BlockNumberRetrievalResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[BlockNumberRetrievalResponse: num_blocks: {}, num_ledger_infos: {}]",
+            self.blocks.len(),
+            self.ledger_infos.len(),
+        )
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::{
+        block_test_utils::{certificate_for_genesis, placeholder_certificate_for_block},
+        Block,
+    },
+    block_retrieval::{BlockNumberRetrievalRequest, BlockNumberRetrievalResponse},
+};
+use gaptos::aptos_crypto::HashValue;
+use gaptos::aptos_types::{
+    ledger_info::{generate_ledger_info_with_sig, LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    validator_verifier::random_validator_verifier,
+};
+
+/// Nil blocks numbered from 1, each one the child of the previous one.
+fn chain(signers: &[ValidatorSigner], len: u64) -> Vec<Block> {
+    let mut blocks: Vec<Block> = vec![];
+    for round in 1..=len {
+        let quorum_cert = match blocks.last() {
+            Some(parent) => placeholder_certificate_for_block(
+                signers,
+                parent.id(),
+                parent.round(),
+                parent.parent_id(),
+                parent.round() - 1,
+            ),
+            None => certificate_for_genesis(),
+        };
+        let block = Block::new_nil(round, quorum_cert, vec![]);
+        block.set_block_number(round);
+        blocks.push(block);
+    }
+    blocks
+}
+
+fn commit(signers: &[ValidatorSigner], block: &Block) -> LedgerInfoWithSignatures {
+    let mut ledger_info =
+        LedgerInfo::new(block.gen_block_info(HashValue::zero(), 0, None), HashValue::zero());
+    ledger_info.set_block_number(block.block_number().unwrap());
+    generate_ledger_info_with_sig(signers, ledger_info)
+}
+
+#[test]
+fn test_block_number_retrieval() {
+    let (signers, verifier) = random_validator_verifier(2, None, false);
+    let blocks = chain(&signers, 4);
+    let request = BlockNumberRetrievalRequest::new(2, 2);
+
+    let ledger_infos = vec![commit(&signers, &blocks[2]), commit(&signers, &blocks[3])];
+    BlockNumberRetrievalResponse::new(blocks[1..].to_vec(), ledger_infos.clone())
+        .verify(request.clone(), &verifier)
+        .unwrap();
+
+    // the peer hasn't committed the blocks
+    BlockNumberRetrievalResponse::new(vec![], vec![])
+        .verify(request.clone(), &verifier)
+        .unwrap();
+
+    // the last block isn't committed by the last ledger info
+    BlockNumberRetrievalResponse::new(blocks[1..3].to_vec(), ledger_infos.clone())
+        .verify(request.clone(), &verifier)
+        .unwrap_err();
+
+    // a block is missing in the chain
+    let gapped = vec![blocks[1].clone(), blocks[3].clone(), blocks[3].clone()];
+    BlockNumberRetrievalResponse::new(gapped, ledger_infos.clone())
+        .verify(request.clone(), &verifier)
+        .unwrap_err();
+
+    // the ledger info isn't signed by the validators
+    let (other_signers, _) = random_validator_verifier(2, None, false);
+    let forged = vec![commit(&signers, &blocks[2]), commit(&other_signers, &blocks[3])];
+    BlockNumberRetrievalResponse::new(blocks[1..].to_vec(), forged)
+        .verify(request, &verifier)
+        .unwrap_err();
+}
@@ -0,0 +1,132 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{block::Block, common::Author, vote::Vote};
+use anyhow::{bail, ensure, format_err, Context};
+use api_types::equivocation::{EquivocationKind, EquivocationPayload, SignedConsensusMessage};
+use gaptos::aptos_crypto::{hash::CryptoHash, traits::signing_message};
+use gaptos::aptos_types::{block_info::Round, validator_verifier::ValidatorVerifier};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+#[path = "equivocation_test.rs"]
+mod equivocation_test;
+
+/// Two conflicting messages signed by the same validator for the same round. Both messages are
+/// kept with their signatures, so that anyone knowing the validator set of the epoch can verify
+/// the misbehavior without trusting the reporter.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EquivocationEvidence {
+    /// Two different blocks proposed by the same author in one round
+    Proposal { first: Block, second: Block },
+    /// Two votes of the same author for different ledger infos in one round
+    Vote { first: Vote, second: Vote },
+}
+
+impl EquivocationEvidence {
+    pub fn kind(&self) -> EquivocationKind {
+        match self {
+            Self::Proposal { .. } => EquivocationKind::Proposal,
+            Self::Vote { .. } => EquivocationKind::Vote,
+        }
+    }
+
+    pub fn author(&self) -> Author {
+        match self {
+            Self::Proposal { first, .. } => first.author().expect("proposal without author"),
+            Self::Vote { first, .. } => first.author(),
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        match self {
+            Self::Proposal { first, .. } => first.epoch(),
+            Self::Vote { first, .. } => first.epoch(),
+        }
+    }
+
+    pub fn round(&self) -> Round {
+        match self {
+            Self::Proposal { first, .. } => first.round(),
+            Self::Vote { first, .. } => first.vote_data().proposed().round(),
+        }
+    }
+
+    /// Checks that both messages are signed by the same author of `verifier` for the same
+    /// epoch and round, and that they conflict.
+    pub fn verify(&self, verifier: &ValidatorVerifier) -> anyhow::Result<()> {
+        match self {
+            Self::Proposal { first, second } => {
+                let author = first
+                    .author()
+                    .ok_or_else(|| format_err!("first block is not a proposal"))?;
+                ensure!(second.author() == Some(author), "proposals of different authors");
+                ensure!(
+                    (first.epoch(), first.round()) == (second.epoch(), second.round()),
+                    "proposals of different rounds"
+                );
+                ensure!(first.id() != second.id(), "proposals of the same block");
+                for block in [first, second] {
+                    let signature = block
+                        .signature()
+                        .ok_or_else(|| format_err!("missing signature in proposal"))?;
+                    verifier
+                        .verify(author, block.block_data(), signature)
+                        .context("invalid proposal signature")?;
+                }
+            },
+            Self::Vote { first, second } => {
+                ensure!(first.author() == second.author(), "votes of different authors");
+                ensure!(
+                    (first.epoch(), first.vote_data().proposed().round())
+                        == (second.epoch(), second.vote_data().proposed().round()),
+                    "votes of different rounds"
+                );
+                ensure!(
+                    first.ledger_info().hash() != second.ledger_info().hash(),
+                    "votes for the same ledger info"
+                );
+                first.verify(verifier)?;
+                second.verify(verifier)?;
+            },
+        }
+        Ok(())
+    }
+
+    /// The evidence in the form handed to the execution layer. The signed messages are the
+    /// exact bytes the BLS12-381 signatures are over.
+    pub fn to_payload(&self) -> anyhow::Result<EquivocationPayload> {
+        let (first, second) = match self {
+            Self::Proposal { first, second } => {
+                (signed_block_data(first)?, signed_block_data(second)?)
+            },
+            Self::Vote { first, second } => (signed_ledger_info(first)?, signed_ledger_info(second)?),
+        };
+        Ok(EquivocationPayload {
+            kind: self.kind(),
+            epoch: self.epoch(),
+            round: self.round(),
+            author: self.author().into_bytes(),
+            first,
+            second,
+            evidence: bcs::to_bytes(self)?,
+        })
+    }
+}
+
+fn signed_block_data(block: &Block) -> anyhow::Result<SignedConsensusMessage> {
+    let Some(signature) = block.signature() else {
+        bail!("missing signature in proposal {}", block.id());
+    };
+    Ok(SignedConsensusMessage {
+        message: signing_message(block.block_data())?,
+        signature: signature.to_bytes().to_vec(),
+    })
+}
+
+fn signed_ledger_info(vote: &Vote) -> anyhow::Result<SignedConsensusMessage> {
+    Ok(SignedConsensusMessage {
+        message: signing_message(vote.ledger_info())?,
+        signature: vote.signature().to_bytes().to_vec(),
+    })
+}
@@ -0,0 +1,46 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Payload,
+    equivocation::EquivocationEvidence,
+};
+use api_types::equivocation::EquivocationKind;
+use gaptos::aptos_types::validator_verifier::random_validator_verifier;
+
+#[test]
+fn test_proposal_equivocation() {
+    let (signers, verifier) = random_validator_verifier(2, None, false);
+    let proposal = |signer_index: usize, timestamp_usecs| {
+        Block::new_proposal(
+            Payload::empty(false, true),
+            1,
+            timestamp_usecs,
+            certificate_for_genesis(),
+            &signers[signer_index],
+            Vec::new(),
+        )
+        .unwrap()
+    };
+
+    let evidence = EquivocationEvidence::Proposal { first: proposal(0, 1), second: proposal(0, 2) };
+    evidence.verify(&verifier).unwrap();
+    assert_eq!(evidence.author(), signers[0].author());
+    assert_eq!(evidence.round(), 1);
+
+    let payload = evidence.to_payload().unwrap();
+    assert_eq!(payload.kind, EquivocationKind::Proposal);
+    assert_eq!(payload.author, signers[0].author().into_bytes());
+    assert_ne!(payload.first.message, payload.second.message);
+    let decoded: EquivocationEvidence = bcs::from_bytes(&payload.evidence).unwrap();
+    assert_eq!(decoded, evidence);
+
+    // the same block twice is no equivocation
+    let same = EquivocationEvidence::Proposal { first: proposal(0, 1), second: proposal(0, 1) };
+    assert!(same.verify(&verifier).is_err());
+    // neither are blocks of different authors
+    let different_authors =
+        EquivocationEvidence::Proposal { first: proposal(0, 1), second: proposal(1, 2) };
+    assert!(different_authors.verify(&verifier).is_err());
+}
@@ -3,6 +3,7 @@
 
 use crate::common::{Payload, PayloadFilter};
 use anyhow::Result;
+use api_types::block_budget::BlockBudget;
 use futures::channel::oneshot;
 use std::{fmt, fmt::Formatter, time::Duration};
 
@@ -29,6 +30,8 @@ pub enum GetPayloadCommand {
         oneshot::Sender<Result<GetPayloadResponse>>,
         // block timestamp
         Duration,
+        // gas and byte budget of the block reported by the execution layer
+        BlockBudget,
     ),
 }
 
@@ -46,11 +49,12 @@ impl fmt::Display for GetPayloadCommand {
                 excluded,
                 _,
                 block_timestamp,
+                block_budget,
             ) => {
                 write!(
                     f,
-                    "GetPayloadRequest [max_txns: {}, max_txns_after_filtering: {} (soft: {}), max_bytes: {}, max_inline_txns: {}, max_inline_bytes:{}, return_non_full: {},  excluded: {}, block_timestamp: {:?}]",
-                    max_txns, max_txns_after_filtering, soft_max_txns_after_filtering, max_bytes, max_inline_txns, max_inline_bytes, return_non_full, excluded, block_timestamp
+                    "GetPayloadRequest [max_txns: {}, max_txns_after_filtering: {} (soft: {}), max_bytes: {}, max_inline_txns: {}, max_inline_bytes:{}, return_non_full: {},  excluded: {}, block_timestamp: {:?}, block_budget: {:?}]",
+                    max_txns, max_txns_after_filtering, soft_max_txns_after_filtering, max_bytes, max_inline_txns, max_inline_bytes, return_non_full, excluded, block_timestamp, block_budget
                 )
             },
         }
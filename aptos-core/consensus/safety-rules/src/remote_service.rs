@@ -9,7 +9,12 @@ use crate::{
 };
 use gaptos::aptos_logger::warn;
 use gaptos::aptos_secure_net::{NetworkClient, NetworkServer};
-use std::net::SocketAddr;
+use std::{net::SocketAddr, thread, time::Duration};
+
+/// Attempts per request to a remote SafetyRules service before giving up
+const MAX_REQUEST_ATTEMPTS: u32 = 3;
+/// Backoff before the next attempt, multiplied by the number of failed attempts
+const RETRY_BACKOFF_MS: u64 = 100;
 
 pub trait RemoteService {
     fn client(&self) -> SerializerClient {
@@ -71,12 +76,24 @@ impl RemoteClient {
 }
 
 impl TSerializerClient for RemoteClient {
+    /// The `NetworkClient` reconnects on the next attempt after a failure. Once all attempts
+    /// failed the error is returned, so that consensus keeps running and retries the operation
+    /// itself, e.g. by voting in a later round, instead of blocking on an unreachable signer.
     fn request(&mut self, input: SafetyRulesInput) -> Result<Vec<u8>, Error> {
         let input_message = serde_json::to_vec(&input)?;
+        let mut attempt = 1;
         loop {
             match self.process_one_message(&input_message) {
-                Err(err) => warn!("Failed to communicate with SafetyRules service: {}", err),
                 Ok(value) => return Ok(value),
+                Err(err) if attempt >= MAX_REQUEST_ATTEMPTS => return Err(err),
+                Err(err) => {
+                    warn!(
+                        "Failed to communicate with SafetyRules service, attempt {}: {}",
+                        attempt, err
+                    );
+                    thread::sleep(Duration::from_millis(RETRY_BACKOFF_MS * attempt as u64));
+                    attempt += 1;
+                },
             }
         }
     }
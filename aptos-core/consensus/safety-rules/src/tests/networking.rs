@@ -18,3 +18,13 @@ fn test_reconnect() {
     let state1 = safety_rules_manager.client().consensus_state().unwrap();
     assert_eq!(state0, state1);
 }
+
+#[test]
+fn test_unreachable_service() {
+    let port = gaptos::aptos_config::utils::get_available_port();
+    let server_addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    let safety_rules_manager = SafetyRulesManager::new_process(server_addr, 100);
+
+    // Requests fail after a bounded number of attempts instead of blocking consensus forever
+    assert!(safety_rules_manager.client().consensus_state().is_err());
+}
@@ -0,0 +1,61 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network::IncomingBlockNumberRetrievalRequest, network_interface::ConsensusMsg,
+    persistent_liveness_storage::PersistentLivenessStorage,
+};
+use aptos_consensus_types::{block_retrieval::BlockNumberRetrievalResponse, common::Author};
+use bytes::Bytes;
+use futures::StreamExt;
+use gaptos::aptos_channels::aptos_channel;
+use gaptos::aptos_logger::prelude::*;
+use std::{cmp::min, sync::Arc};
+
+/// A response carries at most this many blocks, plus the ones up to the next ledger info.
+const MAX_BLOCKS_PER_REQUEST: u64 = 100;
+
+/// Serves the committed blocks by block number from ConsensusDB. It runs outside of the epoch
+/// manager, the committed blocks don't depend on the current epoch.
+pub struct BlockNumberRetrievalServer {
+    storage: Arc<dyn PersistentLivenessStorage>,
+}
+
+impl BlockNumberRetrievalServer {
+    pub fn new(storage: Arc<dyn PersistentLivenessStorage>) -> Self {
+        Self { storage }
+    }
+
+    fn process(&self, request: IncomingBlockNumberRetrievalRequest) -> anyhow::Result<()> {
+        let (blocks, ledger_infos) = self.storage.consensus_db().get_committed_blocks(
+            request.req.start_block_number(),
+            min(request.req.num_blocks(), MAX_BLOCKS_PER_REQUEST),
+        )?;
+        debug!(
+            remote_peer = request.sender,
+            "process block number retrieval {}, {} blocks",
+            request.req,
+            blocks.len()
+        );
+        let response = Box::new(BlockNumberRetrievalResponse::new(blocks, ledger_infos));
+        let response_bytes = request
+            .protocol
+            .to_bytes(&ConsensusMsg::BlockNumberRetrievalResponse(response))?;
+        request
+            .response_sender
+            .send(Ok(Bytes::from(response_bytes)))
+            .map_err(|_| anyhow::anyhow!("Failed to send block number retrieval response"))
+    }
+
+    pub async fn start(
+        self,
+        mut rpc_rx: aptos_channel::Receiver<Author, IncomingBlockNumberRetrievalRequest>,
+    ) {
+        while let Some(request) = rpc_rx.next().await {
+            if let Err(e) = self.process(request) {
+                warn!(error = ?e, "Failed to process block number retrieval");
+            }
+        }
+        info!("Block number retrieval server stops");
+    }
+}
@@ -10,6 +10,7 @@ use crate::{
         BlockReader,
     },
     counters,
+    execution_divergence::{CertifiedBlock, DivergenceState},
     payload_manager::TPayloadManager,
     persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData, RootInfo},
     pipeline::execution_client::TExecutionClient,
@@ -117,6 +118,7 @@ pub struct BlockStore {
     back_pressure_for_test: AtomicBool,
     order_vote_enabled: bool,
     pending_blocks: Arc<Mutex<PendingBlocks>>,
+    divergence_state: Arc<DivergenceState>,
 }
 
 impl BlockStore {
@@ -130,6 +132,7 @@ impl BlockStore {
         payload_manager: Arc<dyn TPayloadManager>,
         order_vote_enabled: bool,
         pending_blocks: Arc<Mutex<PendingBlocks>>,
+        divergence_state: Arc<DivergenceState>,
     ) -> Self {
         let highest_2chain_tc = initial_data.highest_2chain_timeout_certificate();
         let (root, blocks, quorum_certs) = initial_data.take();
@@ -147,6 +150,7 @@ impl BlockStore {
             payload_manager,
             order_vote_enabled,
             pending_blocks,
+            divergence_state,
         ));
         block_on(block_store.recover_blocks());
         block_store
@@ -162,6 +166,7 @@ impl BlockStore {
         payload_manager: Arc<dyn TPayloadManager>,
         order_vote_enabled: bool,
         pending_blocks: Arc<Mutex<PendingBlocks>>,
+        divergence_state: Arc<DivergenceState>,
     ) -> Self {
         let highest_2chain_tc = initial_data.highest_2chain_timeout_certificate();
         let (root, blocks, quorum_certs) = initial_data.take();
@@ -179,6 +184,7 @@ impl BlockStore {
             payload_manager,
             order_vote_enabled,
             pending_blocks,
+            divergence_state,
         )
         .await;
         block_store.recover_blocks().await;
@@ -227,6 +233,7 @@ impl BlockStore {
         payload_manager: Arc<dyn TPayloadManager>,
         order_vote_enabled: bool,
         pending_blocks: Arc<Mutex<PendingBlocks>>,
+        divergence_state: Arc<DivergenceState>,
     ) -> Self {
         let RootInfo(root_block, root_qc, root_ordered_cert, root_commit_cert) = root;
 
@@ -260,6 +267,7 @@ impl BlockStore {
             back_pressure_for_test: AtomicBool::new(false),
             order_vote_enabled,
             pending_blocks,
+            divergence_state,
         };
 
         for block in blocks {
@@ -328,6 +336,8 @@ impl BlockStore {
         self.init_block_number(&blocks_to_commit);
         if recovery {
             for p_block in &blocks_to_commit {
+                // A node which diverged before the restart doesn't commit any further block
+                self.divergence_state.ensure_not_diverged()?;
                 let mut txns = vec![];
                 loop {
                     match self.payload_manager.get_transactions(p_block.block()).await {
@@ -383,7 +393,17 @@ impl BlockStore {
                     p_block.block().block_number().unwrap(),
                 ).await.unwrap();
                 if let Some(block_hash) = maybe_block_hash {
-                    assert_eq!(block_hash.data, compute_res.data);
+                    self.divergence_state.check_certified_result(
+                        Some(self.storage.consensus_db().as_ref()),
+                        CertifiedBlock {
+                            epoch: p_block.block().epoch(),
+                            round: p_block.block().round(),
+                            block_id: p_block.id(),
+                            block_number,
+                            certified_hash: block_hash.data,
+                        },
+                        &compute_res,
+                    )?;
                 }
                 let commit_block = BlockHashRef {
                     block_id: BlockId(*p_block.id()),
@@ -450,6 +470,7 @@ impl BlockStore {
             self.payload_manager.clone(),
             self.order_vote_enabled,
             self.pending_blocks.clone(),
+            self.divergence_state.clone(),
         )
         .await;
 
@@ -10,12 +10,14 @@ use aptos_consensus_types::{
     wrapped_ledger_info::WrappedLedgerInfo,
 };
 use gaptos::aptos_crypto::HashValue;
+pub use block_number_retrieval::BlockNumberRetrievalServer;
 pub use block_store::{
     sync_manager::{BlockRetriever, NeedFetchResult},
     BlockStore,
 };
 use std::{sync::Arc, time::Duration};
 
+mod block_number_retrieval;
 mod block_store;
 mod block_tree;
 pub mod pending_blocks;
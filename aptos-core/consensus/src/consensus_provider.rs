@@ -12,7 +12,8 @@ use crate::{
         publisher::ConsensusPublisher,
     },
     counters,
-    epoch_manager::EpochManager,
+    epoch_manager::{check_safety_rules_config, EpochManager},
+    execution_divergence::DivergenceState,
     network::NetworkTask,
     network_interface::{ConsensusMsg, ConsensusNetworkClient},
     persistent_liveness_storage::StorageWriteProxy,
@@ -24,6 +25,7 @@ use crate::{
     txn_notifier::MempoolNotifier,
     util::time_service::ClockTimeService,
 };
+use api_types::ExecutionChannel;
 use gaptos::aptos_bounded_executor::BoundedExecutor;
 use gaptos::aptos_config::config::NodeConfig;
 use gaptos::aptos_consensus_notifications::ConsensusNotificationSender;
@@ -56,6 +58,7 @@ pub fn start_consensus(
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
     gravity_args: &mut ConsensusAdapterArgs,
 ) -> (Runtime, Arc<StorageWriteProxy>, Arc<QuorumStoreDB>) {
+    check_safety_rules_config(node_config).expect("unsupported consensus config");
     let runtime = gaptos::aptos_runtimes::spawn_named_runtime("consensus".into(), None);
     let storage = Arc::new(StorageWriteProxy::new(
         gravity_args.consensus_db.as_ref().unwrap().clone(),
@@ -68,7 +71,13 @@ pub fn start_consensus(
         node_config.consensus.mempool_executed_txn_timeout_ms,
     ));
 
-    let g_executor = GravityBlockExecutor::new(BlockExecutor::new(aptos_db));
+    let divergence_state = Arc::new(DivergenceState::default());
+    divergence_state.restore(gravity_args.consensus_db.as_ref().unwrap());
+    let g_executor = GravityBlockExecutor::new(
+        BlockExecutor::new(aptos_db),
+        gravity_args.consensus_db.clone(),
+        divergence_state.clone(),
+    );
     let executor = Arc::new(g_executor);
     let execution_proxy = ExecutionProxy::new(
         executor.clone(),
@@ -77,6 +86,7 @@ pub fn start_consensus(
         runtime.handle(),
         TransactionFilter::new(node_config.execution.transaction_filter.clone()),
         node_config.consensus.enable_pre_commit,
+        divergence_state.clone(),
     );
 
     let time_service = Arc::new(ClockTimeService::new(runtime.handle().clone()));
@@ -102,6 +112,7 @@ pub fn start_consensus(
         rand_storage.clone(),
         node_config.consensus_observer,
         consensus_publisher.clone(),
+        gravity_args.execution_api.clone(),
     ));
 
     let epoch_mgr = EpochManager::new(
@@ -120,6 +131,7 @@ pub fn start_consensus(
         vtxn_pool,
         rand_storage,
         consensus_publisher,
+        divergence_state,
     );
 
     let (network_task, network_receiver) = NetworkTask::new(network_service_events, self_receiver);
@@ -141,6 +153,7 @@ pub fn start_consensus_observer(
     consensus_to_mempool_sender: mpsc::Sender<QuorumStoreRequest>,
     aptos_db: DbReaderWriter,
     reconfig_events: Option<ReconfigNotificationListener<DbBackedOnChainConfig>>,
+    execution_api: Option<Arc<dyn ExecutionChannel>>,
 ) -> Runtime {
     // Create a consensus observer runtime
     let runtime = gaptos::aptos_runtimes::spawn_named_runtime("observer".into(), None);
@@ -183,6 +196,7 @@ pub fn start_consensus_observer(
             runtime.handle(),
             TransactionFilter::new(node_config.execution.transaction_filter.clone()),
             node_config.consensus.enable_pre_commit,
+            Arc::new(DivergenceState::default()),
         );
 
         // Create the execution proxy client
@@ -198,6 +212,7 @@ pub fn start_consensus_observer(
             rand_storage.clone(),
             node_config.consensus_observer,
             consensus_publisher.clone(),
+            execution_api,
         ));
         execution_proxy_client as Arc<dyn TExecutionClient>
     } else {
@@ -116,3 +116,25 @@ fn test_dag() {
     let vote = Vote::new(node.metadata().clone(), Signature::dummy_signature());
     test_dag_type::<DagVoteSchema, <DagVoteSchema as Schema>::Key>(node.id(), vote, &db);
 }
+
+#[test]
+fn test_delete_dkg_transcripts_before() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir, &PathBuf::new());
+
+    for epoch in 1..=3 {
+        db.put::<DKGTranscriptSchema>(&DKGTranscriptKey::dealt(epoch), &vec![1])
+            .unwrap();
+        db.put::<DKGTranscriptSchema>(&DKGTranscriptKey::dealer_set(epoch), &vec![2])
+            .unwrap();
+    }
+    db.delete_dkg_transcripts_before(3).unwrap();
+
+    let epochs: Vec<_> = db
+        .get_all::<DKGTranscriptSchema>()
+        .unwrap()
+        .into_iter()
+        .map(|(key, _)| key.epoch)
+        .collect();
+    assert_eq!(epochs, vec![3, 3]);
+}
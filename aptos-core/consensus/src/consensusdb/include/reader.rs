@@ -8,40 +8,65 @@ use gaptos::aptos_types::epoch_change::EpochChangeProof;
 use gaptos::aptos_types::epoch_state::EpochState;
 use gaptos::aptos_types::ledger_info::{LedgerInfo, LedgerInfoWithSignatures};
 use gaptos::aptos_types::on_chain_config::ValidatorSet;
-use gaptos::aptos_types::on_chain_config::{ConsensusAlgorithmConfig, ProposerElectionType};
+use gaptos::aptos_types::on_chain_config::{
+    ConsensusAlgorithmConfig, DagConsensusConfigV1, ProposerElectionType,
+};
+use gaptos::aptos_types::on_chain_config::{
+    OnChainRandomnessConfig, RandomnessConfigMoveStruct, RandomnessConfigSeqNum,
+    ValidatorTxnConfig,
+};
 use gaptos::aptos_types::state_proof::StateProof;
 use gaptos::aptos_types::state_store::state_key::inner::StateKeyInner;
 use gaptos::aptos_types::validator_config::ValidatorConfig;
 use gaptos::aptos_types::validator_info::ValidatorInfo;
 use gaptos::aptos_types::validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier};
+use gaptos::aptos_types::waypoint::Waypoint;
 use gaptos::aptos_types::{
     on_chain_config::{ConfigurationResource, OnChainConsensusConfig},
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::Version,
 };
 
+/// Builds the validator set described by a gravity node config set. Validator indices follow
+/// the ordering of the set, so every node derives the same set from the same file.
+pub fn validator_infos(node_config_set: &GravityNodeConfigSet) -> Vec<ValidatorInfo> {
+    let mut result = vec![];
+    for (i, (addr, node_config)) in node_config_set.iter().enumerate() {
+        let public_key = bls12381::PublicKey::try_from(
+            hex::decode(node_config.consensus_public_key.as_bytes()).unwrap().as_slice()
+        )
+        .unwrap();
+        let config = ValidatorConfig::new(
+            public_key,
+            bcs::to_bytes(&vec![addr.clone()]).unwrap(),
+            bcs::to_bytes(&vec![addr.clone()]).unwrap(),
+            i as u64,
+        );
+        result.push(ValidatorInfo::new(
+            AccountAddress::try_from(node_config.account_address.clone()).unwrap(),
+            node_config.voting_power,
+            config,
+        ));
+    }
+    result
+}
+
+/// The genesis ledger info used when ConsensusDB holds no committed ledger info yet.
+pub fn genesis_ledger_info(node_config_set: &GravityNodeConfigSet) -> LedgerInfoWithSignatures {
+    LedgerInfoWithSignatures::genesis(
+        *ACCUMULATOR_PLACEHOLDER_HASH,
+        ValidatorSet::new(validator_infos(node_config_set)),
+    )
+}
+
+/// The waypoint every validator has to be configured with for the given node config set.
+pub fn genesis_waypoint(node_config_set: &GravityNodeConfigSet) -> Result<Waypoint> {
+    Waypoint::new_epoch_boundary(genesis_ledger_info(node_config_set).ledger_info())
+}
+
 impl ConsensusDB {
     pub fn mock_validators(&self) -> Vec<ValidatorInfo> {
-        let mut result = vec![];
-        for (i, (addr, node_config)) in self.node_config_set.iter().enumerate() {
-            // let x = hex::decode(node_config.consensus_public_key.as_bytes()).unwrap();
-            let public_key = bls12381::PublicKey::try_from(
-                hex::decode(node_config.consensus_public_key.as_bytes()).unwrap().as_slice()
-            )
-            .unwrap();
-            let config = ValidatorConfig::new(
-                public_key,
-                bcs::to_bytes(&vec![addr.clone()]).unwrap(),
-                bcs::to_bytes(&vec![addr.clone()]).unwrap(),
-                i as u64,
-            );
-            result.push(ValidatorInfo::new(
-                AccountAddress::try_from(node_config.account_address.clone()).unwrap(),
-                node_config.voting_power,
-                config,
-            ));
-        }
-        result
+        validator_infos(&self.node_config_set)
     }
 }
 
@@ -55,6 +80,82 @@ fn fixed_proposer() -> bool {
     std::env::var("FIXED_PROPOSER").map(|s| s.parse().unwrap()).unwrap_or(true)
 }
 
+// Randomness keys come from the local DKG run at epoch start, see `rand::dkg`. Disabled unless
+// `ENABLE_RANDOMNESS=true`, it has to be set on all validators.
+fn enable_randomness() -> bool {
+    std::env::var("ENABLE_RANDOMNESS").map(|s| s.parse().unwrap()).unwrap_or(false)
+}
+
+// Selects DAG instead of Jolteon with `GRAVITY_CONSENSUS_PROTOCOL=dag`, it has to be set on all
+// validators.
+fn dag_enabled() -> bool {
+    match std::env::var("GRAVITY_CONSENSUS_PROTOCOL").as_deref() {
+        Err(_) | Ok("jolteon") => false,
+        Ok("dag") => true,
+        Ok(protocol) => panic!(
+            "unknown GRAVITY_CONSENSUS_PROTOCOL {:?}, expected dag or jolteon",
+            protocol
+        ),
+    }
+}
+
+/// The consensus config served as the onchain one, the same on all validators started with the
+/// same env.
+pub fn onchain_consensus_config() -> OnChainConsensusConfig {
+    let mut consensus_conf = OnChainConsensusConfig::default();
+    // todo(gravity_byteyue): currently we set quorum_store_enabled=false
+    match &mut consensus_conf {
+        OnChainConsensusConfig::V1(_) => {}
+        OnChainConsensusConfig::V2(_) => {}
+        OnChainConsensusConfig::V3 { alg, vtxn } => {
+            if enable_randomness() {
+                *vtxn = ValidatorTxnConfig::default_enabled();
+            }
+            if dag_enabled() {
+                *alg = ConsensusAlgorithmConfig::DAG(DagConsensusConfigV1::default());
+            }
+            match alg {
+                ConsensusAlgorithmConfig::Jolteon {
+                    main,
+                    quorum_store_enabled,
+                } => {
+                    main.proposer_election_type = match fixed_proposer() {
+                        true => {
+                            info!("proposer_election_type use fixed proposer");
+                            ProposerElectionType::FixedProposer(1)
+                        }
+                        false => {
+                            info!("proposer_election_type use rotating proposer");
+                            ProposerElectionType::RotatingProposer(1)
+                        }
+                    };
+                    *quorum_store_enabled = enable_quorum_store();
+                }
+                ConsensusAlgorithmConfig::DAG(_) => {}
+                ConsensusAlgorithmConfig::JolteonV2 {
+                    main,
+                    quorum_store_enabled,
+                    order_vote_enabled,
+                } => {
+                    main.proposer_election_type = match fixed_proposer() {
+                        true => {
+                            info!("proposer_election_type use fixed proposer");
+                            ProposerElectionType::FixedProposer(1)
+                        }
+                        false => {
+                            info!("proposer_election_type use rotating proposer");
+                            ProposerElectionType::RotatingProposer(1)
+                        }
+                    };
+                    *quorum_store_enabled = enable_quorum_store();
+                    *order_vote_enabled = false;
+                }
+            }
+        },
+    }
+    consensus_conf
+}
+
 impl DbReader for ConsensusDB {
     fn get_read_delegatee(&self) -> &dyn DbReader {
         self
@@ -64,10 +165,7 @@ impl DbReader for ConsensusDB {
         match self.ledger_db.metadata_db().get_latest_ledger_info() {
             Some(ledger_info) => Ok(ledger_info),
             None => {
-                let genesis = LedgerInfoWithSignatures::genesis(
-                    *ACCUMULATOR_PLACEHOLDER_HASH,
-                    ValidatorSet::new(self.mock_validators()),
-                );
+                let genesis = genesis_ledger_info(&self.node_config_set);
                 info!("genesis is {:?}", genesis);
                 Ok(genesis)
             }
@@ -116,50 +214,15 @@ impl DbReader for ConsensusDB {
                     if path.contains("Validator") {
                         bcs::to_bytes(&ValidatorSet::new(self.mock_validators()))?
                     } else if path.contains("consensus") {
-                        let mut consensus_conf = OnChainConsensusConfig::default();
-                        // todo(gravity_byteyue): currently we set quorum_store_enabled=false
-                        match &mut consensus_conf {
-                            OnChainConsensusConfig::V1(_) => {}
-                            OnChainConsensusConfig::V2(_) => {}
-                            OnChainConsensusConfig::V3 { alg, vtxn } => match alg {
-                                ConsensusAlgorithmConfig::Jolteon {
-                                    main,
-                                    quorum_store_enabled,
-                                } => {
-                                    main.proposer_election_type = match fixed_proposer() {
-                                        true => {
-                                            info!("proposer_election_type use fixed proposer");
-                                            ProposerElectionType::FixedProposer(1)
-                                        }
-                                        false => {
-                                            info!("proposer_election_type use rotating proposer");
-                                            ProposerElectionType::RotatingProposer(1)
-                                        }
-                                    };
-                                    *quorum_store_enabled = enable_quorum_store();
-                                }
-                                ConsensusAlgorithmConfig::DAG(_) => {}
-                                ConsensusAlgorithmConfig::JolteonV2 {
-                                    main,
-                                    quorum_store_enabled,
-                                    order_vote_enabled,
-                                } => {
-                                    main.proposer_election_type = match fixed_proposer() {
-                                        true => {
-                                            info!("proposer_election_type use fixed proposer");
-                                            ProposerElectionType::FixedProposer(1)
-                                        }
-                                        false => {
-                                            info!("proposer_election_type use rotating proposer");
-                                            ProposerElectionType::RotatingProposer(1)
-                                        }
-                                    };
-                                    *quorum_store_enabled = enable_quorum_store();
-                                    *order_vote_enabled = false;
-                                }
-                            },
-                        }
-                        bcs::to_bytes(&bcs::to_bytes(&consensus_conf)?)?
+                        bcs::to_bytes(&bcs::to_bytes(&onchain_consensus_config())?)?
+                    } else if path.contains("randomness_config_seqnum") {
+                        bcs::to_bytes(&RandomnessConfigSeqNum::default_if_missing())?
+                    } else if path.contains("randomness_config") {
+                        let randomness_config = match enable_randomness() {
+                            true => OnChainRandomnessConfig::default_enabled(),
+                            false => OnChainRandomnessConfig::default_disabled(),
+                        };
+                        bcs::to_bytes(&RandomnessConfigMoveStruct::from(randomness_config))?
                     } else {
                         let mut resources = ConfigurationResource::default();
                         resources.epoch = 1;
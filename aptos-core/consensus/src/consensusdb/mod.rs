@@ -9,7 +9,10 @@ mod schema;
 
 use crate::error::DbError;
 use anyhow::Result;
-use aptos_consensus_types::{block::Block, quorum_cert::QuorumCert};
+use api_types::divergence::ExecutionDivergence;
+use aptos_consensus_types::{
+    block::Block, equivocation::EquivocationEvidence, quorum_cert::QuorumCert,
+};
 use gaptos::aptos_crypto::HashValue;
 use gaptos::aptos_logger::prelude::*;
 use gaptos::aptos_schemadb::{
@@ -20,15 +23,21 @@ use gaptos::aptos_storage_interface::AptosDbError;
 use ledger_db::LedgerDb;
 use rocksdb::ReadOptions;
 pub use schema::{
+    block::BlockIdSchema,
     block::BlockNumberSchema,
     block::BlockSchema,
     dag::{CertifiedNodeSchema, DagVoteSchema, NodeSchema},
+    dkg::{DKGTranscriptKey, DKGTranscriptKind, DKGTranscriptSchema},
+    equivocation::{EquivocationKey, EquivocationSchema},
+    execution_divergence::ExecutionDivergenceSchema,
     quorum_certificate::QCSchema,
 };
 use schema::{
-    block::BLOCK_NUMBER_CF_NAME,
+    block::{BLOCK_ID_CF_NAME, BLOCK_NUMBER_CF_NAME},
+    ledger_info::LedgerInfoSchema,
     single_entry::{SingleEntryKey, SingleEntrySchema},
-    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, LEDGER_INFO_CF_NAME, NODE_CF_NAME,
+    BLOCK_CF_NAME, CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, DKG_TRANSCRIPT_CF_NAME,
+    EQUIVOCATION_CF_NAME, EXECUTION_DIVERGENCE_CF_NAME, LEDGER_INFO_CF_NAME, NODE_CF_NAME,
     QC_CF_NAME, SINGLE_ENTRY_CF_NAME,
 };
 use serde::{Deserialize, Serialize};
@@ -60,7 +69,7 @@ pub fn create_checkpoint<P: AsRef<Path> + Clone>(db_path: P, checkpoint_path: P)
     Ok(())
 }
 
-#[derive(Default, Deserialize, Serialize)]
+#[derive(Clone, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct GravityNodeConfig {
     pub consensus_public_key: String,
@@ -97,6 +106,10 @@ impl ConsensusDB {
             DAG_VOTE_CF_NAME,
             LEDGER_INFO_CF_NAME,
             BLOCK_NUMBER_CF_NAME,
+            BLOCK_ID_CF_NAME,
+            DKG_TRANSCRIPT_CF_NAME,
+            EQUIVOCATION_CF_NAME,
+            EXECUTION_DIVERGENCE_CF_NAME,
             "ordered_anchor_id", // deprecated CF
         ];
 
@@ -119,7 +132,30 @@ impl ConsensusDB {
 
         let ledger_db = LedgerDb::new(db.clone());
 
-        Self { db, node_config_set, ledger_db }
+        let consensus_db = Self { db, node_config_set, ledger_db };
+        consensus_db
+            .index_block_numbers()
+            .expect("ConsensusDB block number index failed; unable to continue");
+        consensus_db
+    }
+
+    /// Indexes the blocks by number if the DB was written before the index existed.
+    fn index_block_numbers(&self) -> Result<(), DbError> {
+        let mut iter = self.db.iter::<BlockIdSchema>()?;
+        iter.seek_to_first();
+        if iter.next().is_some() {
+            return Ok(());
+        }
+        let block_numbers = self.get_all::<BlockNumberSchema>()?;
+        if block_numbers.is_empty() {
+            return Ok(());
+        }
+        info!("Indexing {} blocks by block number", block_numbers.len());
+        let batch = SchemaBatch::new();
+        block_numbers.iter().try_for_each(|(block_id, block_number)| {
+            batch.put::<BlockIdSchema>(block_number, block_id)
+        })?;
+        self.commit(batch)
     }
 
     pub fn get_data(
@@ -129,10 +165,8 @@ impl ConsensusDB {
         let last_vote = self.get_last_vote()?;
         let highest_2chain_timeout_certificate = self.get_highest_2chain_timeout_certificate()?;
         let block_number_to_block_id = self
-            .get_all::<BlockNumberSchema>()?
+            .get_block_ids_by_number_range(latest_block_number, u64::MAX)?
             .into_iter()
-            .filter(|(_, block_number)| block_number >= &latest_block_number)
-            .map(|(block_id, block_number)| (block_number, block_id))
             .collect::<HashMap<u64, HashValue>>();
         let block_id_to_block_number = block_number_to_block_id
             .iter()
@@ -203,11 +237,72 @@ impl ConsensusDB {
         }
         let batch = SchemaBatch::new();
         block_numbers.iter().try_for_each(|(block_number, block_id)| {
-            batch.put::<BlockNumberSchema>(block_id, block_number)
+            batch.put::<BlockNumberSchema>(block_id, block_number)?;
+            batch.put::<BlockIdSchema>(block_number, block_id)
         })?;
         self.commit(batch)
     }
 
+    /// Keeps one evidence per epoch, round, author and kind, a later one replaces it.
+    pub fn save_equivocation_evidence(
+        &self,
+        evidence: &EquivocationEvidence,
+    ) -> Result<(), DbError> {
+        self.put::<EquivocationSchema>(&EquivocationKey::from(evidence), evidence)
+    }
+
+    /// Returns the evidence of `epoch` or later, ordered by epoch and round.
+    pub fn get_equivocation_evidence(
+        &self,
+        epoch: u64,
+    ) -> Result<Vec<EquivocationEvidence>, DbError> {
+        Ok(self
+            .get_all::<EquivocationSchema>()?
+            .into_iter()
+            .filter(|(key, _)| key.epoch >= epoch)
+            .map(|(_, evidence)| evidence)
+            .collect())
+    }
+
+    /// Keeps one divergence per block number.
+    pub fn save_execution_divergence(
+        &self,
+        divergence: &ExecutionDivergence,
+    ) -> Result<(), DbError> {
+        self.put::<ExecutionDivergenceSchema>(&divergence.block_number, divergence)
+    }
+
+    /// Returns the divergences ordered by block number.
+    pub fn get_execution_divergences(&self) -> Result<Vec<ExecutionDivergence>, DbError> {
+        Ok(self
+            .get_all::<ExecutionDivergenceSchema>()?
+            .into_iter()
+            .map(|(_, divergence)| divergence)
+            .collect())
+    }
+
+    /// Removes all divergences, so that the node votes again after its next restart.
+    pub fn delete_execution_divergences(&self) -> Result<(), DbError> {
+        let block_numbers = self
+            .get_all::<ExecutionDivergenceSchema>()?
+            .into_iter()
+            .map(|(block_number, _)| block_number)
+            .collect();
+        self.delete::<ExecutionDivergenceSchema>(block_numbers)
+    }
+
+    /// Removes the DKG state of the epochs before `epoch`, their randomness keys are never used
+    /// again.
+    pub fn delete_dkg_transcripts_before(&self, epoch: u64) -> Result<(), DbError> {
+        let keys = self
+            .get_all::<DKGTranscriptSchema>()?
+            .into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.epoch < epoch)
+            .collect();
+        self.delete::<DKGTranscriptSchema>(keys)
+    }
+
     pub fn delete_blocks_and_quorum_certificates(
         &self,
         block_ids: Vec<HashValue>,
@@ -276,6 +371,23 @@ impl ConsensusDB {
         Ok(self.db.get::<S>(key)?)
     }
 
+    /// Flushes the memtables to disk, so that a restart doesn't depend on replaying the WAL.
+    pub fn flush(&self) -> Result<(), DbError> {
+        for cf_name in [
+            BLOCK_CF_NAME,
+            QC_CF_NAME,
+            SINGLE_ENTRY_CF_NAME,
+            LEDGER_INFO_CF_NAME,
+            BLOCK_NUMBER_CF_NAME,
+            BLOCK_ID_CF_NAME,
+            EQUIVOCATION_CF_NAME,
+            EXECUTION_DIVERGENCE_CF_NAME,
+        ] {
+            self.db.flush_cf(cf_name)?;
+        }
+        Ok(())
+    }
+
     pub fn get_block(&self, block_id: &HashValue) -> Result<Option<Block>, DbError> {
         let block = self.get::<BlockSchema>(block_id)?;
         if let Some(block) = &block {
@@ -287,6 +399,69 @@ impl ConsensusDB {
         }
         Ok(block)
     }
+
+    pub fn get_block_by_number(&self, block_number: u64) -> Result<Option<Block>, DbError> {
+        match self.get::<BlockIdSchema>(&block_number)? {
+            Some(block_id) => self.get_block(&block_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the ids of the blocks numbered from `start` to `end`, excluded, ordered by number.
+    pub fn get_block_ids_by_number_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<(u64, HashValue)>, DbError> {
+        let mut iter = self.db.iter::<BlockIdSchema>()?;
+        iter.seek(&start)?;
+        Ok(iter
+            .take_while(|res| res.as_ref().map_or(true, |(block_number, _)| *block_number < end))
+            .collect::<Result<Vec<_>, AptosDbError>>()?)
+    }
+
+    /// Returns the committed blocks numbered from `start`, ordered by number, with the ledger
+    /// infos committing them. A ledger info commits all the blocks up to its own, so the blocks
+    /// are read up to `limit` blocks and then on up to the next ledger info, the last block
+    /// returned is always the one of the last ledger info.
+    pub fn get_committed_blocks(
+        &self,
+        start: u64,
+        limit: u64,
+    ) -> Result<(Vec<Block>, Vec<LedgerInfoWithSignatures>), DbError> {
+        if limit == 0 {
+            return Ok((vec![], vec![]));
+        }
+        let target = start.saturating_add(limit - 1);
+        let mut ledger_infos = vec![];
+        let mut iter = self.db.iter::<LedgerInfoSchema>()?;
+        iter.seek(&start)?;
+        for res in iter {
+            let (block_number, ledger_info) = res?;
+            ledger_infos.push(ledger_info);
+            if block_number >= target {
+                break;
+            }
+        }
+        let end = match ledger_infos.last() {
+            Some(ledger_info) => ledger_info.ledger_info().block_number(),
+            None => return Ok((vec![], vec![])),
+        };
+        let blocks = self
+            .get_block_ids_by_number_range(start, end + 1)?
+            .into_iter()
+            .map(|(block_number, block_id)| {
+                self.get_block(&block_id)?.ok_or_else(|| {
+                    DbError::from(anyhow::anyhow!(
+                        "block {} of block number {} not found",
+                        block_id,
+                        block_number
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, DbError>>()?;
+        Ok((blocks, ledger_infos))
+    }
 }
 
 include!("include/reader.rs");
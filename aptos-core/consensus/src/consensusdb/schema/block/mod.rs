@@ -9,6 +9,13 @@
 //! |<---key---->|<---value--->|
 //! | block_hash |    block    |
 //! ```
+//!
+//! The block number of a block, and the block of a block number, are indexed both ways.
+//! ```text
+//! |<----key----->|<----value---->|
+//! |  block_hash  | block_number  |
+//! | block_number |  block_hash   |
+//! ```
 
 use crate::define_schema;
 use anyhow::Result;
@@ -24,6 +31,7 @@ use super::ensure_slice_len_eq;
 
 pub const BLOCK_CF_NAME: ColumnFamilyName = "block";
 pub const BLOCK_NUMBER_CF_NAME: ColumnFamilyName = "block_number";
+pub const BLOCK_ID_CF_NAME: ColumnFamilyName = "block_id";
 
 define_schema!(BlockSchema, HashValue, Block, BLOCK_CF_NAME);
 
@@ -69,5 +77,28 @@ impl ValueCodec<BlockNumberSchema> for u64 {
     }
 }
 
+define_schema!(BlockIdSchema, u64, HashValue, BLOCK_ID_CF_NAME);
+
+impl KeyCodec<BlockIdSchema> for u64 {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, std::mem::size_of::<Self>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<BlockIdSchema> for HashValue {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(HashValue::from_slice(data)?)
+    }
+}
+
 #[cfg(test)]
 mod test;
@@ -12,3 +12,15 @@ fn test_encode_decode() {
 }
 
 test_no_panic_decoding!(BlockSchema);
+
+#[test]
+fn test_encode_decode_block_id() {
+    assert_encode_decode::<BlockIdSchema>(&42, &HashValue::random());
+}
+
+// The macro defines a test of a fixed name
+mod block_id {
+    use super::*;
+
+    test_no_panic_decoding!(BlockIdSchema);
+}
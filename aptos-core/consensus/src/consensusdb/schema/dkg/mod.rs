@@ -0,0 +1,104 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the transcripts of the local DKG.
+//!
+//! For every epoch a validator keeps the transcript it dealt, so that peers always receive the
+//! same dealing even across restarts, its votes in the dealer set agreement, the agreed dealer
+//! set and the aggregated transcript its randomness keys are derived from.
+//! ```text
+//! |<-------key------->|<--------value-------->|
+//! |   epoch | kind    | bcs serialized bytes  |
+//! ```
+
+use super::ensure_slice_len_eq;
+use crate::define_schema;
+use anyhow::{format_err, Result};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use gaptos::aptos_schemadb::{
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName,
+};
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::{FromPrimitive, ToPrimitive};
+use std::mem::size_of;
+
+pub const DKG_TRANSCRIPT_CF_NAME: ColumnFamilyName = "dkg_transcript";
+
+define_schema!(
+    DKGTranscriptSchema,
+    DKGTranscriptKey,
+    Vec<u8>,
+    DKG_TRANSCRIPT_CF_NAME
+);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, FromPrimitive, ToPrimitive)]
+#[repr(u8)]
+pub enum DKGTranscriptKind {
+    // The transcript dealt by this validator
+    Dealt = 0,
+    // The aggregation of the transcripts of the agreed dealers
+    Aggregated = 1,
+    // The votes this validator signed in the dealer set agreement
+    Votes = 2,
+    // The commit certificate of the agreed dealer set
+    DealerSet = 3,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DKGTranscriptKey {
+    pub epoch: u64,
+    pub kind: DKGTranscriptKind,
+}
+
+impl DKGTranscriptKey {
+    pub fn dealt(epoch: u64) -> Self {
+        Self { epoch, kind: DKGTranscriptKind::Dealt }
+    }
+
+    pub fn aggregated(epoch: u64) -> Self {
+        Self { epoch, kind: DKGTranscriptKind::Aggregated }
+    }
+
+    pub fn votes(epoch: u64) -> Self {
+        Self { epoch, kind: DKGTranscriptKind::Votes }
+    }
+
+    pub fn dealer_set(epoch: u64) -> Self {
+        Self { epoch, kind: DKGTranscriptKind::DealerSet }
+    }
+}
+
+impl KeyCodec<DKGTranscriptSchema> for DKGTranscriptKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = Vec::with_capacity(size_of::<u64>() + size_of::<u8>());
+        encoded.write_u64::<BigEndian>(self.epoch)?;
+        encoded.push(
+            self.kind
+                .to_u8()
+                .ok_or_else(|| format_err!("ToPrimitive failed."))?,
+        );
+        Ok(encoded)
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<u64>() + size_of::<u8>())?;
+        let epoch = data.read_u64::<BigEndian>()?;
+        let kind = DKGTranscriptKind::from_u8(data.read_u8()?)
+            .ok_or_else(|| format_err!("FromPrimitive failed."))?;
+        Ok(Self { epoch, kind })
+    }
+}
+
+impl ValueCodec<DKGTranscriptSchema> for Vec<u8> {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.clone())
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(data.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,15 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use gaptos::aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+#[test]
+fn test_encode_decode() {
+    assert_encode_decode::<DKGTranscriptSchema>(&DKGTranscriptKey::dealt(1), &vec![1u8, 2u8]);
+    assert_encode_decode::<DKGTranscriptSchema>(&DKGTranscriptKey::aggregated(u64::MAX), &vec![]);
+    assert_encode_decode::<DKGTranscriptSchema>(&DKGTranscriptKey::votes(2), &vec![3u8]);
+    assert_encode_decode::<DKGTranscriptSchema>(&DKGTranscriptKey::dealer_set(2), &vec![4u8]);
+}
+
+test_no_panic_decoding!(DKGTranscriptSchema);
@@ -0,0 +1,93 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for equivocation evidence.
+//!
+//! Conflicting proposals and votes of a validator, kept so that they can be exported and used
+//! to slash it. Keys are ordered by epoch and round.
+//! ```text
+//! |<-----------------key----------------->|<--value-->|
+//! |  epoch | round | author | kind        |  evidence |
+//! ```
+
+use super::ensure_slice_len_eq;
+use crate::define_schema;
+use anyhow::{bail, Result};
+use api_types::equivocation::EquivocationKind;
+use aptos_consensus_types::{common::Author, equivocation::EquivocationEvidence};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use gaptos::aptos_schemadb::{
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName,
+};
+use std::mem::size_of;
+
+pub const EQUIVOCATION_CF_NAME: ColumnFamilyName = "equivocation";
+
+define_schema!(
+    EquivocationSchema,
+    EquivocationKey,
+    EquivocationEvidence,
+    EQUIVOCATION_CF_NAME
+);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EquivocationKey {
+    pub epoch: u64,
+    pub round: u64,
+    pub author: Author,
+    pub kind: EquivocationKind,
+}
+
+impl From<&EquivocationEvidence> for EquivocationKey {
+    fn from(evidence: &EquivocationEvidence) -> Self {
+        Self {
+            epoch: evidence.epoch(),
+            round: evidence.round(),
+            author: evidence.author(),
+            kind: evidence.kind(),
+        }
+    }
+}
+
+const KEY_LEN: usize = 2 * size_of::<u64>() + Author::LENGTH + size_of::<u8>();
+
+impl KeyCodec<EquivocationSchema> for EquivocationKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = Vec::with_capacity(KEY_LEN);
+        encoded.write_u64::<BigEndian>(self.epoch)?;
+        encoded.write_u64::<BigEndian>(self.round)?;
+        encoded.extend_from_slice(self.author.as_ref());
+        encoded.push(match self.kind {
+            EquivocationKind::Proposal => 0,
+            EquivocationKind::Vote => 1,
+        });
+        Ok(encoded)
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, KEY_LEN)?;
+        let epoch = data.read_u64::<BigEndian>()?;
+        let round = data.read_u64::<BigEndian>()?;
+        let author = Author::from_bytes(&data[..Author::LENGTH])?;
+        let kind = match data[Author::LENGTH] {
+            0 => EquivocationKind::Proposal,
+            1 => EquivocationKind::Vote,
+            kind => bail!("unknown equivocation kind {}", kind),
+        };
+        Ok(Self { epoch, round, author, kind })
+    }
+}
+
+impl ValueCodec<EquivocationSchema> for EquivocationEvidence {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
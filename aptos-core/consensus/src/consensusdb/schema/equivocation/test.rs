@@ -0,0 +1,30 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Payload,
+};
+use gaptos::aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use gaptos::aptos_types::validator_signer::ValidatorSigner;
+
+#[test]
+fn test_encode_decode() {
+    let signer = ValidatorSigner::from_int(0);
+    let proposal = |timestamp_usecs| {
+        Block::new_proposal(
+            Payload::empty(false, true),
+            1,
+            timestamp_usecs,
+            certificate_for_genesis(),
+            &signer,
+            Vec::new(),
+        )
+        .unwrap()
+    };
+    let evidence = EquivocationEvidence::Proposal { first: proposal(1), second: proposal(2) };
+    assert_encode_decode::<EquivocationSchema>(&EquivocationKey::from(&evidence), &evidence);
+}
+
+test_no_panic_decoding!(EquivocationSchema);
@@ -0,0 +1,55 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for execution divergences.
+//!
+//! Blocks whose local execution result differs from the certified one. The node stays halted as
+//! long as a divergence is kept. Keys are ordered by block number.
+//! ```text
+//! |<-----key----->|<---value--->|
+//! |  block number |  divergence |
+//! ```
+
+use super::ensure_slice_len_eq;
+use crate::define_schema;
+use anyhow::Result;
+use api_types::divergence::ExecutionDivergence;
+use byteorder::{BigEndian, ReadBytesExt};
+use gaptos::aptos_schemadb::{
+    schema::{KeyCodec, ValueCodec},
+    ColumnFamilyName,
+};
+use std::mem::size_of;
+
+pub const EXECUTION_DIVERGENCE_CF_NAME: ColumnFamilyName = "execution_divergence";
+
+define_schema!(
+    ExecutionDivergenceSchema,
+    u64,
+    ExecutionDivergence,
+    EXECUTION_DIVERGENCE_CF_NAME
+);
+
+impl KeyCodec<ExecutionDivergenceSchema> for u64 {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_key(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<u64>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+impl ValueCodec<ExecutionDivergenceSchema> for ExecutionDivergence {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,29 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use api_types::compute_res::TxnStatus;
+use gaptos::aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+#[test]
+fn test_encode_decode() {
+    let divergence = ExecutionDivergence {
+        epoch: 2,
+        round: 17,
+        block_id: [1; 32],
+        block_number: 42,
+        local_hash: [2; 32],
+        certified_hash: [3; 32],
+        txn_num: 1,
+        txn_status: vec![TxnStatus {
+            txn_hash: [4; 32],
+            nonce: 5,
+            sender: [6; 32],
+            is_discarded: false,
+        }],
+        detected_at_usecs: 1_700_000_000_000_000,
+    };
+    assert_encode_decode::<ExecutionDivergenceSchema>(&42, &divergence);
+}
+
+test_no_panic_decoding!(ExecutionDivergenceSchema);
@@ -4,6 +4,9 @@
 
 pub(crate) mod block;
 pub(crate) mod dag;
+pub(crate) mod dkg;
+pub(crate) mod equivocation;
+pub(crate) mod execution_divergence;
 pub(crate) mod quorum_certificate;
 pub(crate) mod single_entry;
 pub(crate) mod ledger_info;
@@ -41,5 +44,8 @@ macro_rules! define_schema {
 use gaptos::aptos_schemadb::ColumnFamilyName;
 pub use block::BLOCK_CF_NAME;
 pub use dag::{CERTIFIED_NODE_CF_NAME, DAG_VOTE_CF_NAME, NODE_CF_NAME};
+pub use dkg::DKG_TRANSCRIPT_CF_NAME;
+pub use equivocation::EQUIVOCATION_CF_NAME;
+pub use execution_divergence::EXECUTION_DIVERGENCE_CF_NAME;
 pub use quorum_certificate::QC_CF_NAME;
 pub use single_entry::SINGLE_ENTRY_CF_NAME;
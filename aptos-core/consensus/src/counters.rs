@@ -112,6 +112,26 @@ pub static COMMITTED_TXNS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the conflicting proposals and votes caught since last restart.
+pub static EQUIVOCATION_EVIDENCE: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_equivocation_evidence",
+        "Count of the equivocations caught since last restart. kind is proposal or vote",
+        &["kind"]
+    )
+    .unwrap()
+});
+
+/// Number of the first block whose local execution result differs from the certified one.
+pub static EXECUTION_DIVERGENCE_BLOCK_NUMBER: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_consensus_execution_divergence_block_number",
+        "Number of the first block whose local execution result differs from the certified one, \
+         0 if none. The node stops voting while it is set"
+    )
+    .unwrap()
+});
+
 //////////////////////
 // PROPOSAL VOTE COUNTERS
 //////////////////////
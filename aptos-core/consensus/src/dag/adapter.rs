@@ -43,7 +43,10 @@ use async_trait::async_trait;
 use futures_channel::mpsc::UnboundedSender;
 use std::{
     collections::{BTreeMap, HashMap},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -96,7 +99,10 @@ pub(crate) fn compute_initial_block_and_ledger_info(
 pub(super) struct OrderedNotifierAdapter {
     executor_channel: UnboundedSender<OrderedBlocks>,
     dag: Arc<DagStore>,
+    storage: Arc<dyn DAGStorage>,
     parent_block_info: Arc<RwLock<BlockInfo>>,
+    // the block buffer needs consecutive block numbers, assigned in order of the anchors
+    parent_block_number: AtomicU64,
     epoch_state: Arc<EpochState>,
     ledger_info_provider: Arc<RwLock<LedgerInfoProvider>>,
     block_ordered_ts: Arc<RwLock<BTreeMap<Round, Instant>>>,
@@ -107,15 +113,19 @@ impl OrderedNotifierAdapter {
     pub(super) fn new(
         executor_channel: UnboundedSender<OrderedBlocks>,
         dag: Arc<DagStore>,
+        storage: Arc<dyn DAGStorage>,
         epoch_state: Arc<EpochState>,
         parent_block_info: BlockInfo,
+        parent_block_number: u64,
         ledger_info_provider: Arc<RwLock<LedgerInfoProvider>>,
         allow_batches_without_pos_in_proposal: bool,
     ) -> Self {
         Self {
             executor_channel,
             dag,
+            storage,
             parent_block_info: Arc::new(RwLock::new(parent_block_info)),
+            parent_block_number: AtomicU64::new(parent_block_number),
             epoch_state,
             ledger_info_provider,
             block_ordered_ts: Arc::new(RwLock::new(BTreeMap::new())),
@@ -198,6 +208,20 @@ impl OrderedNotifier for OrderedNotifierAdapter {
             vec![],
             StateComputeResult::new_dummy(),
         );
+        let block_number = self.parent_block_number.fetch_add(1, Ordering::SeqCst) + 1;
+        block.block().set_block_number(block_number);
+        // The block buffer can't recover the block number of the block after a restart, same as
+        // the block store does for Jolteon
+        self.storage
+            .save_block_numbers(vec![(block_number, block.id())])
+            .unwrap_or_else(|e| {
+                panic!(
+                    "[DAG] failed to save block number {} of {}: {:?}",
+                    block_number,
+                    block.id(),
+                    e
+                )
+            });
         let block_info = block.block_info();
         let ledger_info_provider = self.ledger_info_provider.clone();
         let dag = self.dag.clone();
@@ -414,6 +438,10 @@ impl DAGStorage for StorageAdapter {
         Ok(self.aptos_db.get_latest_ledger_info()?)
     }
 
+    fn save_block_numbers(&self, block_numbers: Vec<(u64, HashValue)>) -> anyhow::Result<()> {
+        Ok(self.consensus_db.save_block_numbers(block_numbers)?)
+    }
+
     fn get_epoch_to_proposers(&self) -> HashMap<u64, Vec<Author>> {
         self.epoch_to_validators.clone()
     }
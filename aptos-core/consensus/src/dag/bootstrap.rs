@@ -505,6 +505,8 @@ impl DagBootstrapper {
             .storage
             .get_latest_ledger_info()
             .expect("latest ledger info must exist");
+        // a virtual genesis block of a new epoch takes the number of the epoch ending block
+        let parent_block_number = ledger_info_from_storage.ledger_info().block_number();
         let (parent_block_info, ledger_info) =
             compute_initial_block_and_ledger_info(ledger_info_from_storage);
 
@@ -533,8 +535,10 @@ impl DagBootstrapper {
         let ordered_notifier = Arc::new(OrderedNotifierAdapter::new(
             self.ordered_nodes_tx.clone(),
             dag.clone(),
+            self.storage.clone(),
             self.epoch_state.clone(),
             parent_block_info,
+            parent_block_number,
             ledger_info_provider.clone(),
             self.allow_batches_without_pos_in_proposal,
         ));
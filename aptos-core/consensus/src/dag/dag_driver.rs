@@ -24,6 +24,7 @@ use crate::{
     payload_client::PayloadClient,
 };
 use anyhow::{bail, ensure};
+use api_types::block_budget;
 use gaptos::aptos_collections::BoundedVecDeque;
 use gaptos::aptos_config::config::DagPayloadConfig;
 use aptos_consensus_types::common::{Author, Payload, PayloadFilter};
@@ -270,6 +271,7 @@ impl DagDriver {
                 0,
                 0.0,
                 self.time_service.now_unix_time(),
+                block_budget::block_budget(),
             )
             .await
         {
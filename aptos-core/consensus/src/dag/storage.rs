@@ -68,5 +68,9 @@ pub trait DAGStorage: Send + Sync {
 
     fn get_latest_ledger_info(&self) -> anyhow::Result<LedgerInfoWithSignatures>;
 
+    /// Persists the block numbers assigned to ordered blocks, so the block buffer can map them
+    /// back to block ids after a restart.
+    fn save_block_numbers(&self, block_numbers: Vec<(u64, HashValue)>) -> anyhow::Result<()>;
+
     fn get_epoch_to_proposers(&self) -> HashMap<u64, Vec<Author>>;
 }
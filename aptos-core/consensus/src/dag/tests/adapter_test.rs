@@ -0,0 +1,91 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    adapter::{LedgerInfoProvider, OrderedNotifier, OrderedNotifierAdapter},
+    dag_store::DagStore,
+    tests::{
+        dag_test::MockStorage,
+        helpers::{new_certified_node, MockPayloadManager, TEST_DAG_WINDOW},
+    },
+};
+use api_types::{u256_define::BlockId, ExternalBlock, ExternalBlockMeta};
+use block_buffer_manager::block_buffer_manager::{BlockBufferManager, BlockBufferManagerConfig};
+use futures_channel::mpsc::unbounded;
+use gaptos::aptos_infallible::RwLock;
+use gaptos::aptos_types::{
+    aggregate_signature::AggregateSignature,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_verifier::random_validator_verifier,
+};
+use std::{collections::HashMap, sync::Arc};
+
+#[tokio::test]
+async fn test_ordered_anchors_reach_block_buffer_with_consecutive_numbers() {
+    let (signers, validator_verifier) = random_validator_verifier(4, None, false);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+    let storage = Arc::new(MockStorage::new());
+    let dag = Arc::new(DagStore::new(
+        epoch_state.clone(),
+        storage.clone(),
+        Arc::new(MockPayloadManager {}),
+        1,
+        TEST_DAG_WINDOW,
+    ));
+    let ledger_info =
+        LedgerInfoWithSignatures::new(LedgerInfo::mock_genesis(None), AggregateSignature::empty());
+    let genesis_id = ledger_info.commit_info().id();
+    let (ordered_tx, mut ordered_rx) = unbounded();
+    let adapter = OrderedNotifierAdapter::new(
+        ordered_tx,
+        dag,
+        storage,
+        epoch_state,
+        ledger_info.commit_info().clone(),
+        10,
+        Arc::new(RwLock::new(LedgerInfoProvider::new(ledger_info))),
+        true,
+    );
+
+    let block_buffer_manager = BlockBufferManager::new(BlockBufferManagerConfig::default());
+    block_buffer_manager.init(10, HashMap::new()).await;
+
+    for round in 1..=3 {
+        let anchor = new_certified_node(round, signers[round as usize].author(), vec![]);
+        adapter.send_ordered_nodes(vec![Arc::new(anchor)], vec![]);
+        let ordered = ordered_rx.try_next().unwrap().unwrap();
+        // pushed the way the execution proxy does
+        for block in ordered.ordered_blocks {
+            block_buffer_manager
+                .set_ordered_blocks(BlockId::from_bytes(block.parent_id().as_slice()), ExternalBlock {
+                    block_meta: ExternalBlockMeta {
+                        block_id: BlockId(*block.id()),
+                        block_number: block.block().block_number().unwrap(),
+                        usecs: block.timestamp_usecs(),
+                        randomness: None,
+                        block_hash: None,
+                    },
+                    txns: vec![],
+                })
+                .await
+                .unwrap();
+        }
+    }
+
+    let blocks = block_buffer_manager.get_ordered_blocks(11, None).await.unwrap();
+    assert_eq!(
+        blocks
+            .iter()
+            .map(|(block, _)| block.block_meta.block_number)
+            .collect::<Vec<_>>(),
+        vec![11, 12, 13]
+    );
+    assert_eq!(blocks[0].1, BlockId(*genesis_id));
+    for pair in blocks.windows(2) {
+        assert_eq!(pair[1].1, pair[0].0.block_meta.block_id);
+    }
+}
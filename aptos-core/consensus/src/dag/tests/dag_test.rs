@@ -115,6 +115,10 @@ impl DAGStorage for MockStorage {
             .ok_or_else(|| anyhow::anyhow!("ledger info not set"))
     }
 
+    fn save_block_numbers(&self, _block_numbers: Vec<(u64, HashValue)>) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     fn get_epoch_to_proposers(&self) -> HashMap<u64, Vec<Author>> {
         self.epoch_state
             .as_ref()
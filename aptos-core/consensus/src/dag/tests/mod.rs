@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod adapter_test;
 mod dag_driver_tests;
 mod dag_network_test;
 mod dag_state_sync_tests;
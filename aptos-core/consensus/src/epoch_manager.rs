@@ -6,12 +6,14 @@ use crate::{
     block_storage::{
         pending_blocks::PendingBlocks,
         tracing::{observe_block, BlockStage},
-        BlockStore,
+        BlockNumberRetrievalServer, BlockStore,
     },
     consensus_observer::publisher::ConsensusPublisher,
+    consensusdb,
     counters,
     dag::{DagBootstrapper, DagCommitSigner, StorageAdapter},
     error::{error_kind, DbError},
+    execution_divergence::DivergenceState,
     liveness::{
         cached_proposer_election::CachedProposerElection,
         leader_reputation::{
@@ -45,9 +47,12 @@ use crate::{
         quorum_store_coordinator::CoordinatorCommand,
         quorum_store_db::QuorumStoreStorage,
     },
-    rand::rand_gen::{
-        storage::interface::RandStorage,
-        types::{AugmentedData, RandConfig},
+    rand::{
+        dkg::{CurrentDKGSession, DKGServer, LocalDKG},
+        rand_gen::{
+            storage::interface::RandStorage,
+            types::{AugmentedData, RandConfig},
+        },
     },
     recovery_manager::RecoveryManager,
     round_manager::{RoundManager, UnverifiedEvent, VerifiedEvent},
@@ -58,6 +63,7 @@ use gaptos::aptos_bounded_executor::BoundedExecutor;
 use gaptos::aptos_channels::{aptos_channel, message_queues::QueueStyle};
 use gaptos::aptos_config::config::{
     ConsensusConfig, DagConsensusConfig, ExecutionConfig, NodeConfig, QcAggregatorType,
+    SafetyRulesService,
 };
 use aptos_consensus_types::{
     common::{Author, Round},
@@ -175,7 +181,11 @@ pub struct EpochManager<P: OnChainConfigProvider> {
     proof_cache: ProofCache,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
     pending_blocks: Arc<Mutex<PendingBlocks>>,
-    key_storage: PersistentSafetyStorage,
+    /// `None` when safety rules run in a separate process, which then holds the only copy of the
+    /// consensus key.
+    key_storage: Option<PersistentSafetyStorage>,
+    dkg_session: CurrentDKGSession,
+    divergence_state: Arc<DivergenceState>,
 }
 
 impl<P: OnChainConfigProvider> EpochManager<P> {
@@ -196,6 +206,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         vtxn_pool: VTxnPoolState,
         rand_storage: Arc<dyn RandStorage<AugmentedData>>,
         consensus_publisher: Option<Arc<ConsensusPublisher>>,
+        divergence_state: Arc<DivergenceState>,
     ) -> Self {
         let author = node_config.validator_network.as_ref().unwrap().peer_id();
         let config = node_config.consensus.clone();
@@ -203,7 +214,10 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         let dag_config = node_config.dag_consensus.clone();
         let sr_config = &node_config.consensus.safety_rules;
         let safety_rules_manager = SafetyRulesManager::new(sr_config);
-        let key_storage = safety_rules_manager::storage(sr_config);
+        let key_storage = match sr_config.service {
+            SafetyRulesService::Process(_) => None,
+            _ => Some(safety_rules_manager::storage(sr_config)),
+        };
         Self {
             author,
             config,
@@ -246,7 +260,9 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 .build(),
             consensus_publisher,
             pending_blocks: Arc::new(Mutex::new(PendingBlocks::new())),
+            dkg_session: Arc::new(Mutex::new(None)),
             key_storage,
+            divergence_state,
         }
     }
 
@@ -681,7 +697,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         epoch_state: &EpochState,
         network_sender: NetworkSender,
         consensus_config: &OnChainConsensusConfig,
-        consensus_key: Arc<PrivateKey>,
+        consensus_key: Option<Arc<PrivateKey>>,
     ) -> (
         Arc<dyn TPayloadManager>,
         QuorumStoreClient,
@@ -714,7 +730,10 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 self.config.safety_rules.backend.clone(),
                 self.quorum_store_storage.clone(),
                 !consensus_config.is_dag_enabled(),
-                consensus_key,
+                consensus_key.expect(
+                    "quorum store signs batches with the consensus key, set ENABLE_QUORUM_STORE=false \
+                     when safety rules run in a separate process",
+                ),
             ))
         } else {
             info!("Building DirectMempool");
@@ -762,7 +781,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
 
     async fn start_round_manager(
         &mut self,
-        consensus_key: Arc<PrivateKey>,
+        consensus_key: Option<Arc<PrivateKey>>,
         recovery_data: RecoveryData,
         epoch_state: Arc<EpochState>,
         onchain_consensus_config: OnChainConsensusConfig,
@@ -836,6 +855,10 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         
         let maybe_pipeline_builder = if self.config.enable_pipeline {
             info!(epoch = epoch, "Create PipelineBuilder");
+            let consensus_sk = consensus_sk.expect(
+                "the pipeline signs with the consensus key, disable it when safety rules run in \
+                 a separate process",
+            );
             let signer = Arc::new(ValidatorSigner::new(self.author, (*consensus_sk).clone()));
             Some(self.execution_client.pipeline_builder(signer))
         } else {
@@ -855,6 +878,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             payload_manager,
             onchain_consensus_config.order_vote_enabled(),
             self.pending_blocks.clone(),
+            self.divergence_state.clone(),
         ).await);
 
         info!(epoch = epoch, "Create ProposalGenerator");
@@ -914,6 +938,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             onchain_randomness_config,
             onchain_jwk_consensus_config,
             fast_rand_config,
+            self.divergence_state.clone(),
         );
 
         round_manager.init(last_vote).await;
@@ -950,7 +975,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
 
     fn try_get_rand_config_for_new_epoch(
         &self,
-        consensus_key: Arc<PrivateKey>,
+        maybe_consensus_key: Option<Arc<PrivateKey>>,
         new_epoch_state: &EpochState,
         onchain_randomness_config: &OnChainRandomnessConfig,
         maybe_dkg_state: anyhow::Result<DKGState>,
@@ -962,6 +987,8 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         if !onchain_randomness_config.randomness_enabled() {
             return Err(NoRandomnessReason::FeatureDisabled);
         }
+        let consensus_key =
+            maybe_consensus_key.ok_or(NoRandomnessReason::ConsensusKeyUnavailable)?;
         let new_epoch = new_epoch_state.epoch;
 
         let dkg_state = maybe_dkg_state.map_err(NoRandomnessReason::DKGStateResourceMissing)?;
@@ -1089,6 +1116,27 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         Ok((rand_config, fast_rand_config))
     }
 
+    /// Gravity has no on-chain DKG, the validators of the new epoch run it among themselves.
+    async fn run_local_dkg(
+        &mut self,
+        epoch_state: Arc<EpochState>,
+        onchain_randomness_config: &OnChainRandomnessConfig,
+        consensus_key: Arc<PrivateKey>,
+    ) -> anyhow::Result<DKGState> {
+        let network_sender = self.create_network_sender(&epoch_state);
+        LocalDKG::new(
+            self.author,
+            epoch_state,
+            onchain_randomness_config,
+            consensus_key,
+            self.storage.consensus_db(),
+            self.dkg_session.clone(),
+            Arc::new(network_sender),
+        )
+        .run()
+        .await
+    }
+
     async fn start_new_epoch(&mut self, payload: OnChainConfigPayload<P>) {
         let validator_set: ValidatorSet = payload
             .get()
@@ -1107,7 +1155,6 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         let randomness_config_move_struct: anyhow::Result<RandomnessConfigMoveStruct> =
             payload.get();
         let onchain_jwk_consensus_config: anyhow::Result<OnChainJWKConsensusConfig> = payload.get();
-        let dkg_state = payload.get::<DKGState>();
 
         if let Err(error) = &onchain_consensus_config {
             error!("Failed to read on-chain consensus config {}", error);
@@ -1151,11 +1198,38 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         });
 
         let loaded_consensus_key = match self.load_consensus_key(&epoch_state.verifier) {
-            Ok(k) => Arc::new(k),
+            Ok(k) => k.map(Arc::new),
             Err(e) => {
                 panic!("load_consensus_key failed: {e}");
             },
         };
+        let quorum_store_enabled = self.enable_quorum_store(&consensus_config);
+        if let Err(e) = check_consensus_key_users(
+            loaded_consensus_key.is_some(),
+            quorum_store_enabled,
+            self.config.enable_pipeline,
+            consensus_config.is_dag_enabled(),
+        ) {
+            panic!("{e:#}");
+        }
+
+        let dkg_state = if consensus_config.is_vtxn_enabled()
+            && onchain_randomness_config.randomness_enabled()
+        {
+            match loaded_consensus_key.clone() {
+                Some(consensus_key) => {
+                    self.run_local_dkg(
+                        epoch_state.clone(),
+                        &onchain_randomness_config,
+                        consensus_key,
+                    )
+                    .await
+                },
+                None => Err(anyhow!("consensus key is not available, local DKG skipped")),
+            }
+        } else {
+            Err(anyhow!("randomness is disabled, local DKG skipped"))
+        };
 
         let rand_configs = self.try_get_rand_config_for_new_epoch(
             loaded_consensus_key.clone(),
@@ -1244,7 +1318,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         &mut self,
         epoch_state: &EpochState,
         consensus_config: &OnChainConsensusConfig,
-        consensus_key: Arc<PrivateKey>,
+        consensus_key: Option<Arc<PrivateKey>>,
     ) -> (
         NetworkSender,
         Arc<dyn PayloadClient>,
@@ -1279,7 +1353,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
     async fn start_new_epoch_with_joltean(
         &mut self,
         epoch_state: Arc<EpochState>,
-        consensus_key: Arc<PrivateKey>,
+        consensus_key: Option<Arc<PrivateKey>>,
         consensus_config: OnChainConsensusConfig,
         execution_config: OnChainExecutionConfig,
         onchain_randomness_config: OnChainRandomnessConfig,
@@ -1328,7 +1402,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
     async fn start_new_epoch_with_dag(
         &mut self,
         epoch_state: Arc<EpochState>,
-        loaded_consensus_key: Arc<PrivateKey>,
+        loaded_consensus_key: Option<Arc<PrivateKey>>,
         onchain_consensus_config: OnChainConsensusConfig,
         on_chain_execution_config: OnChainExecutionConfig,
         onchain_randomness_config: OnChainRandomnessConfig,
@@ -1341,9 +1415,13 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         rand_msg_rx: aptos_channel::Receiver<AccountAddress, IncomingRandGenRequest>,
     ) {
         let epoch = epoch_state.epoch;
+        let consensus_key = loaded_consensus_key.clone().expect(
+            "DAG signs with the consensus key, which is not supported when safety rules run in \
+             a separate process",
+        );
         let signer = Arc::new(ValidatorSigner::new(
             self.author,
-            (*loaded_consensus_key).clone()
+            (*consensus_key).clone()
         ));
         let commit_signer = Arc::new(DagCommitSigner::new(signer.clone()));
 
@@ -1428,11 +1506,8 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
 
     fn enable_quorum_store(&mut self, onchain_config: &OnChainConsensusConfig) -> bool {
         fail_point!("consensus::start_new_epoch::disable_qs", |_| false);
-        // TODO(gravity_byteyue): Use onchain config in the future
-        std::env::var("ENABLE_QUORUM_STORE")
-                .map(|s| s.parse().unwrap())
-                .unwrap_or(true)
-        // onchain_config.quorum_store_enabled()
+        // ConsensusDB serves the ENABLE_QUORUM_STORE env as the onchain config
+        onchain_config.quorum_store_enabled()
     }
 
     async fn process_message(
@@ -1755,6 +1830,13 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         mut round_timeout_sender_rx: gaptos::aptos_channels::Receiver<Round>,
         mut network_receivers: NetworkReceivers,
     ) {
+        tokio::spawn(
+            DKGServer::new(self.dkg_session.clone()).start(network_receivers.dkg_rpc_rx),
+        );
+        tokio::spawn(
+            BlockNumberRetrievalServer::new(self.storage.clone())
+                .start(network_receivers.block_number_rpc_rx),
+        );
         // initial start of the processor
         self.await_reconfig_notification().await;
         loop {
@@ -1800,22 +1882,69 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         OnChainJWKConsensusConfig::from((features, oidc_providers))
     }
 
-    fn load_consensus_key(&self, vv: &ValidatorVerifier) -> anyhow::Result<PrivateKey> {
+    /// Returns `None` when safety rules run in a separate process and hold the key.
+    fn load_consensus_key(&self, vv: &ValidatorVerifier) -> anyhow::Result<Option<PrivateKey>> {
+        let Some(key_storage) = &self.key_storage else {
+            return Ok(None);
+        };
         match vv.get_public_key(&self.author) {
-            Some(pk) => self
-                .key_storage
+            Some(pk) => key_storage
                 .consensus_sk_by_pk(pk)
+                .map(Some)
                 .map_err(|e| anyhow!("could not find sk by pk: {:?}", e)),
             None => {
                 warn!("could not find my pk in validator set, loading default sk!");
-                self.key_storage
+                key_storage
                     .default_consensus_sk()
+                    .map(Some)
                     .map_err(|e| anyhow!("could not load default sk: {e}"))
             },
         }
     }
 }
 
+/// Quorum store batches, the execution pipeline and DAG sign with the consensus key of this
+/// process, which it doesn't have when safety rules run in a separate process.
+fn check_consensus_key_users(
+    consensus_key_loaded: bool,
+    quorum_store_enabled: bool,
+    pipeline_enabled: bool,
+    dag_enabled: bool,
+) -> anyhow::Result<()> {
+    if consensus_key_loaded {
+        return Ok(());
+    }
+    let users: Vec<_> = [
+        (quorum_store_enabled, "quorum store (set ENABLE_QUORUM_STORE=false)"),
+        (pipeline_enabled, "the execution pipeline (set consensus.enable_pipeline: false)"),
+        (dag_enabled, "DAG (run Jolteon)"),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, user)| enabled.then_some(user))
+    .collect();
+    ensure!(
+        users.is_empty(),
+        "safety rules run in a separate process and hold the consensus key, but {} sign with it",
+        users.join(", ")
+    );
+    Ok(())
+}
+
+/// Refuses to start a node whose safety rules run in a separate process while components that
+/// need the consensus key are enabled, instead of stopping at the first epoch.
+pub fn check_safety_rules_config(node_config: &NodeConfig) -> anyhow::Result<()> {
+    let onchain_consensus_config = consensusdb::onchain_consensus_config();
+    check_consensus_key_users(
+        !matches!(
+            node_config.consensus.safety_rules.service,
+            SafetyRulesService::Process(_)
+        ),
+        onchain_consensus_config.quorum_store_enabled(),
+        node_config.consensus.enable_pipeline,
+        onchain_consensus_config.is_dag_enabled(),
+    )
+}
+
 #[derive(Debug)]
 pub enum NoRandomnessReason {
     VTxnDisabled,
@@ -1835,3 +1964,26 @@ pub enum NoRandomnessReason {
     KeyPairPersistError(anyhow::Error),
     MyPkNotFoundInValidatorSet,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_safety_rules_with_quorum_store_and_pipeline() {
+        // safety rules in this process hold the key for everyone
+        check_consensus_key_users(true, true, true, false).unwrap();
+
+        let error = check_consensus_key_users(false, true, true, false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("ENABLE_QUORUM_STORE=false"), "{}", error);
+        assert!(error.contains("consensus.enable_pipeline"), "{}", error);
+        assert!(!error.contains("DAG"), "{}", error);
+
+        // the epoch starts once nothing but safety rules needs the key
+        check_consensus_key_users(false, false, false, false).unwrap();
+        check_consensus_key_users(false, false, false, true).unwrap_err();
+        check_consensus_key_users(true, true, true, true).unwrap();
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Halts the validator when its execution layer computed a different result for a block than the
+//! one certified by a quorum, e.g. because of nondeterminism in the execution layer. Committing the
+//! certified result would hand the execution layer a block hash it never computed, and voting
+//! with the local results would never agree with the quorum again. So the divergence is recorded
+//! to ConsensusDB and the node stops voting and committing, until an operator removes the record
+//! and restarts it.
+
+use crate::{consensusdb::ConsensusDB, counters};
+use anyhow::{bail, Result};
+use api_types::{compute_res::ComputeRes, divergence::ExecutionDivergence};
+use gaptos::aptos_crypto::HashValue;
+use gaptos::aptos_infallible::duration_since_epoch;
+use gaptos::aptos_logger::{error, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The block whose execution result is certified by a commit proof
+pub struct CertifiedBlock {
+    pub epoch: u64,
+    pub round: u64,
+    pub block_id: HashValue,
+    pub block_number: u64,
+    pub certified_hash: [u8; 32],
+}
+
+/// Whether this node halted on a divergence, shared by the components that stop voting, signing
+/// and committing.
+#[derive(Default)]
+pub struct DivergenceState {
+    diverged: AtomicBool,
+}
+
+impl DivergenceState {
+    /// Whether this node saw its execution result diverge, in this run or before a restart.
+    pub fn has_diverged(&self) -> bool {
+        self.diverged.load(Ordering::SeqCst)
+    }
+
+    fn halt(&self, divergence: &ExecutionDivergence) {
+        self.diverged.store(true, Ordering::SeqCst);
+        counters::EXECUTION_DIVERGENCE_BLOCK_NUMBER.set(divergence.block_number as i64);
+    }
+
+    /// Halts the node again if ConsensusDB kept a divergence from before the restart.
+    pub fn restore(&self, consensus_db: &ConsensusDB) {
+        match consensus_db.get_execution_divergences() {
+            Ok(divergences) => {
+                if let Some(divergence) = divergences.first() {
+                    warn!(
+                        "Execution diverged at block {} before the restart, not voting: {:?}",
+                        divergence.block_number, divergence
+                    );
+                    self.halt(divergence);
+                }
+            },
+            Err(e) => error!(error = ?e, "Failed to read execution divergences"),
+        }
+    }
+
+    /// Compares the local execution result of a block with the certified one. On a mismatch,
+    /// records the divergence, halts the node and returns an error, so that the block isn't
+    /// committed.
+    pub fn check_certified_result(
+        &self,
+        consensus_db: Option<&ConsensusDB>,
+        block: CertifiedBlock,
+        local: &ComputeRes,
+    ) -> Result<()> {
+        if local.data == block.certified_hash {
+            return Ok(());
+        }
+        let divergence = ExecutionDivergence {
+            epoch: block.epoch,
+            round: block.round,
+            block_id: *block.block_id,
+            block_number: block.block_number,
+            local_hash: local.data,
+            certified_hash: block.certified_hash,
+            txn_num: local.txn_num,
+            txn_status: local.txn_status.as_ref().clone().unwrap_or_default(),
+            detected_at_usecs: duration_since_epoch().as_micros() as u64,
+        };
+        error!(
+            "Execution diverged at block {} ({}): local hash {}, certified hash {}, halting",
+            block.block_number,
+            block.block_id,
+            hex::encode(local.data),
+            hex::encode(block.certified_hash)
+        );
+        if let Some(consensus_db) = consensus_db {
+            if let Err(e) = consensus_db.save_execution_divergence(&divergence) {
+                error!(error = ?e, "Failed to save execution divergence");
+            }
+        }
+        self.halt(&divergence);
+        bail!("execution diverged at block {}", block.block_number)
+    }
+
+    /// Fails once the node diverged, so that no later block is committed.
+    pub fn ensure_not_diverged(&self) -> Result<()> {
+        if self.has_diverged() {
+            bail!("execution diverged, not committing");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gaptos::aptos_temppath::TempPath;
+    use std::path::PathBuf;
+
+    fn certified_block(certified_hash: [u8; 32]) -> CertifiedBlock {
+        CertifiedBlock {
+            epoch: 1,
+            round: 5,
+            block_id: HashValue::random(),
+            block_number: 3,
+            certified_hash,
+        }
+    }
+
+    #[test]
+    fn test_matching_result() {
+        let tmp_dir = TempPath::new();
+        let db = ConsensusDB::new(&tmp_dir, &PathBuf::new());
+        let local = ComputeRes::new([1u8; 32], 2, vec![]);
+        let state = DivergenceState::default();
+
+        state.check_certified_result(Some(&db), certified_block([1u8; 32]), &local).unwrap();
+        assert!(!state.has_diverged());
+        state.ensure_not_diverged().unwrap();
+        assert!(db.get_execution_divergences().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diverged_result() {
+        let tmp_dir = TempPath::new();
+        let db = ConsensusDB::new(&tmp_dir, &PathBuf::new());
+        let local = ComputeRes::new([1u8; 32], 2, vec![]);
+        let block = certified_block([2u8; 32]);
+        let block_id = block.block_id;
+        let state = DivergenceState::default();
+
+        state.check_certified_result(Some(&db), block, &local).unwrap_err();
+        assert!(state.has_diverged());
+        state.ensure_not_diverged().unwrap_err();
+        let divergences = db.get_execution_divergences().unwrap();
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].block_id, *block_id);
+        assert_eq!(divergences[0].block_number, 3);
+        assert_eq!(divergences[0].local_hash, [1u8; 32]);
+        assert_eq!(divergences[0].certified_hash, [2u8; 32]);
+        assert_eq!(divergences[0].txn_num, 2);
+
+        // a matching result doesn't resume the node
+        state.check_certified_result(Some(&db), certified_block([1u8; 32]), &local).unwrap();
+        assert!(state.has_diverged());
+    }
+
+    #[test]
+    fn test_restore() {
+        let tmp_dir = TempPath::new();
+        let db = ConsensusDB::new(&tmp_dir, &PathBuf::new());
+        let state = DivergenceState::default();
+        state.restore(&db);
+        assert!(!state.has_diverged());
+
+        let local = ComputeRes::new([1u8; 32], 2, vec![]);
+        let divergence = ExecutionDivergence {
+            epoch: 1,
+            round: 5,
+            block_id: [3u8; 32],
+            block_number: 3,
+            local_hash: local.data,
+            certified_hash: [2u8; 32],
+            txn_num: local.txn_num,
+            txn_status: vec![],
+            detected_at_usecs: 0,
+        };
+        db.save_execution_divergence(&divergence).unwrap();
+        let state = DivergenceState::default();
+        state.restore(&db);
+        assert!(state.has_diverged());
+        state.ensure_not_diverged().unwrap_err();
+    }
+}
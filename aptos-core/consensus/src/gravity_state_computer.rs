@@ -4,13 +4,14 @@
 
 use crate::consensusdb::ConsensusDB;
 use crate::counters::{APTOS_COMMIT_BLOCKS, APTOS_EXECUTION_TXNS};
+use crate::execution_divergence::{CertifiedBlock, DivergenceState};
 use crate::payload_client::user::quorum_store_client::QuorumStoreClient;
 use anyhow::Result;
-use api_types::u256_define::BlockId;
+use api_types::{u256_define::BlockId, ExecutionChannel};
 use gaptos::aptos_crypto::HashValue;
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_executor_types::{
-    BlockExecutorTrait, ExecutorResult, StateComputeResult,
+    BlockExecutorTrait, ExecutorError, ExecutorResult, StateComputeResult,
 };
 use gaptos::aptos_logger::info;
 use gaptos::aptos_types::block_executor::partitioner::ExecutableBlock;
@@ -28,6 +29,7 @@ use tokio::runtime::Runtime;
 pub struct ConsensusAdapterArgs {
     pub quorum_store_client: Option<Arc<QuorumStoreClient>>,
     pub consensus_db: Option<Arc<ConsensusDB>>,
+    pub execution_api: Option<Arc<dyn ExecutionChannel>>,
 }
 
 impl ConsensusAdapterArgs {
@@ -35,6 +37,7 @@ impl ConsensusAdapterArgs {
         Self {
             quorum_store_client: None,
             consensus_db: Some(consensus_db),
+            execution_api: None,
         }
     }
 
@@ -42,19 +45,81 @@ impl ConsensusAdapterArgs {
         self.quorum_store_client = quorum_store_client;
     }
 
+    pub fn set_execution_api(&mut self, execution_api: Arc<dyn ExecutionChannel>) {
+        self.execution_api = Some(execution_api);
+    }
+
     pub fn dummy() -> Self {
-        Self { quorum_store_client: None, consensus_db: None }
+        Self { quorum_store_client: None, consensus_db: None, execution_api: None }
     }
 }
 
 pub struct GravityBlockExecutor {
     inner: BlockExecutor,
     runtime: Runtime,
+    /// Execution divergences are recorded here
+    consensus_db: Option<Arc<ConsensusDB>>,
+    divergence_state: Arc<DivergenceState>,
 }
 
 impl GravityBlockExecutor {
-    pub(crate) fn new(inner: BlockExecutor) -> Self {
-        Self { inner, runtime: gaptos::aptos_runtimes::spawn_named_runtime("tmp".into(), None) }
+    pub(crate) fn new(
+        inner: BlockExecutor,
+        consensus_db: Option<Arc<ConsensusDB>>,
+        divergence_state: Arc<DivergenceState>,
+    ) -> Self {
+        Self {
+            inner,
+            runtime: gaptos::aptos_runtimes::spawn_named_runtime("tmp".into(), None),
+            consensus_db,
+            divergence_state,
+        }
+    }
+
+    /// Fails if a block of `block_ids` wasn't executed under the block number it is committed
+    /// with, if the local execution result of the block certified by `ledger_info_with_sigs`
+    /// differs from the certified one, or if the node diverged before.
+    fn check_certified_result(
+        &self,
+        block_ids: &[HashValue],
+        ledger_info_with_sigs: &LedgerInfoWithSignatures,
+    ) -> ExecutorResult<()> {
+        let to_executor_error =
+            |e: anyhow::Error| ExecutorError::InternalError { error: e.to_string() };
+        self.divergence_state.ensure_not_diverged().map_err(to_executor_error)?;
+        let ledger_info = ledger_info_with_sigs.ledger_info();
+        let block_id = ledger_info.commit_info().id();
+        let block_number = ledger_info.block_number();
+        if block_ids.last() != Some(&block_id) {
+            return Err(ExecutorError::InternalError {
+                error: format!("block {} is not the last committed block", block_id),
+            });
+        }
+        // The blocks of a commit have consecutive numbers, up to the certified one
+        let first_block_number = block_number + 1 - block_ids.len() as u64;
+        for (number, id) in (first_block_number..).zip(block_ids) {
+            let local = self
+                .runtime
+                .block_on(
+                    get_block_buffer_manager()
+                        .get_executed_res(BlockId::from_bytes(id.as_slice()), number),
+                )
+                .map_err(to_executor_error)?;
+            // Only the last block has its hash in the commit proof
+            if number == block_number {
+                let certified_block = CertifiedBlock {
+                    epoch: ledger_info.epoch(),
+                    round: ledger_info.round(),
+                    block_id,
+                    block_number,
+                    certified_hash: *ledger_info.block_hash(),
+                };
+                self.divergence_state
+                    .check_certified_result(self.consensus_db.as_deref(), certified_block, &local)
+                    .map_err(to_executor_error)?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -90,6 +155,7 @@ impl BlockExecutorTrait for GravityBlockExecutor {
         ledger_info_with_sigs: LedgerInfoWithSignatures,
     ) -> ExecutorResult<()> {
         if !block_ids.is_empty() {
+            self.check_certified_result(&block_ids, &ledger_info_with_sigs)?;
             let (block_id, block_hash) = (ledger_info_with_sigs.ledger_info().commit_info().id(), ledger_info_with_sigs.ledger_info().block_hash());
             let block_num = ledger_info_with_sigs.ledger_info().block_number();
             assert!(block_ids.last().unwrap().as_slice() == block_id.as_slice());
@@ -137,6 +203,7 @@ impl BlockExecutorTrait for GravityBlockExecutor {
         let block_num = ledger_info_with_sigs.ledger_info().block_number();
         let len = block_ids.len();
         if !block_ids.is_empty() {
+            self.check_certified_result(&block_ids, &ledger_info_with_sigs)?;
             self.runtime.block_on(async move {
                 get_block_buffer_manager().set_commit_blocks(block_ids.into_iter()
                 .enumerate()
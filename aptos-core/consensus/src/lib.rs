@@ -22,6 +22,7 @@ pub mod consensusdb;
 mod dag;
 mod epoch_manager;
 mod error;
+mod execution_divergence;
 mod liveness;
 mod logging;
 mod metrics_safety_rules;
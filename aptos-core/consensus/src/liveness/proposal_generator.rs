@@ -16,6 +16,7 @@ use crate::{
     util::time_service::TimeService,
 };
 use anyhow::{bail, ensure, format_err, Context};
+use api_types::block_budget;
 use gaptos::aptos_config::config::{
     ChainHealthBackoffValues, ExecutionBackpressureConfig, PipelineBackpressureValues,
 };
@@ -451,6 +452,9 @@ impl ProposalGenerator {
                 .collect();
             let validator_txn_filter =
                 vtxn_pool::TransactionFilter::PendingTxnHashSet(pending_validator_txn_hashes);
+            // Ask the execution layer how much gas/bytes the block may carry, so that the
+            // proposal is not truncated (and its tail discarded) at execution time.
+            let block_budget = block_budget::block_budget();
             let (validator_txns, mut payload) = self
                 .payload_client
                 .pull_payload(
@@ -469,6 +473,7 @@ impl ProposalGenerator {
                     pending_blocks.len(),
                     max_fill_fraction,
                     timestamp,
+                    block_budget,
                 )
                 .await
                 .context("Fail to retrieve payload")?;
@@ -9,7 +9,7 @@ use crate::{
 };
 use gaptos::aptos_config::config::QcAggregatorType;
 use aptos_consensus_types::{
-    common::Round, delayed_qc_msg::DelayedQcMsg, sync_info::SyncInfo,
+    common::{Author, Round}, delayed_qc_msg::DelayedQcMsg, sync_info::SyncInfo,
     timeout_2chain::TwoChainTimeoutWithPartialSignatures, vote::Vote,
 };
 use gaptos::aptos_crypto::HashValue;
@@ -311,6 +311,11 @@ impl RoundState {
         }
     }
 
+    /// The vote of `author` in the current round, if any
+    pub fn author_vote(&self, author: &Author) -> Option<&Vote> {
+        self.pending_votes.author_vote(author)
+    }
+
     pub fn record_vote(&mut self, vote: Vote) {
         if vote.vote_data().proposed().round() == self.current_round {
             self.vote_sent = Some(vote);
@@ -5,8 +5,8 @@ use super::proposer_election::ProposerElection;
 use aptos_consensus_types::{
     block::Block,
     common::{Author, Round},
+    equivocation::EquivocationEvidence,
 };
-use gaptos::aptos_crypto::HashValue;
 use gaptos::aptos_infallible::Mutex;
 use gaptos::aptos_logger::{error, warn, SecurityEvent};
 use std::{cmp::Ordering, sync::Arc};
@@ -14,10 +14,12 @@ use std::{cmp::Ordering, sync::Arc};
 // Wrapper around ProposerElection.
 //
 // Provides is_valid_proposal that remembers, and rejects if
-// the same leader proposes multiple blocks.
+// the same leader proposes multiple blocks. The conflicting proposals
+// are kept as evidence, see take_equivocations.
 pub struct UnequivocalProposerElection {
     proposer_election: Arc<dyn ProposerElection + Send + Sync>,
-    already_proposed: Mutex<(Round, HashValue)>,
+    already_proposed: Mutex<(Round, Option<Block>)>,
+    equivocations: Mutex<Vec<EquivocationEvidence>>,
 }
 
 impl ProposerElection for UnequivocalProposerElection {
@@ -35,7 +37,8 @@ impl UnequivocalProposerElection {
     pub fn new(proposer_election: Arc<dyn ProposerElection + Send + Sync>) -> Self {
         Self {
             proposer_election,
-            already_proposed: Mutex::new((0, HashValue::zero())),
+            already_proposed: Mutex::new((0, None)),
+            equivocations: Mutex::new(Vec::new()),
         }
     }
 
@@ -63,26 +66,34 @@ impl UnequivocalProposerElection {
             match block.round().cmp(&already_proposed.0) {
                 Ordering::Greater => {
                     already_proposed.0 = block.round();
-                    already_proposed.1 = block.id();
+                    already_proposed.1 = Some(block.clone());
                     true
                 },
-                Ordering::Equal => {
-                    if already_proposed.1 != block.id() {
+                Ordering::Equal => match &already_proposed.1 {
+                    Some(first) if first.id() != block.id() => {
                         error!(
                             SecurityEvent::InvalidConsensusProposal,
                             "Multiple proposals from {} for round {}: {} and {}",
                             author,
                             block.round(),
-                            already_proposed.1,
+                            first.id(),
                             block.id()
                         );
+                        self.equivocations.lock().push(EquivocationEvidence::Proposal {
+                            first: first.clone(),
+                            second: block.clone(),
+                        });
                         false
-                    } else {
-                        true
-                    }
+                    },
+                    _ => true,
                 },
                 Ordering::Less => false,
             }
         })
     }
+
+    /// Returns the conflicting proposals seen since the last call.
+    pub fn take_equivocations(&self) -> Vec<EquivocationEvidence> {
+        std::mem::take(&mut *self.equivocations.lock())
+    }
 }
@@ -6,6 +6,7 @@ use crate::liveness::unequivocal_proposer_election::UnequivocalProposerElection;
 use aptos_consensus_types::{
     block::{block_test_utils::certificate_for_genesis, Block},
     common::{Author, Payload, Round},
+    equivocation::EquivocationEvidence,
 };
 use gaptos::aptos_types::validator_signer::ValidatorSigner;
 use std::{collections::HashMap, sync::Arc};
@@ -92,8 +93,16 @@ fn test_is_valid_proposal() {
     assert!(pe.is_valid_proposal(&good_proposal));
     assert!(!pe.is_valid_proposal(&bad_author_proposal));
 
+    assert!(pe.take_equivocations().is_empty());
+
     // another proposal from the valid proposer should fail
     assert!(!pe.is_valid_proposal(&bad_duplicate_proposal));
+    // and is kept as evidence together with the first one
+    assert_eq!(pe.take_equivocations(), vec![EquivocationEvidence::Proposal {
+        first: good_proposal.clone(),
+        second: bad_duplicate_proposal.clone(),
+    }]);
+    assert!(pe.take_equivocations().is_empty());
     // good proposal still passes
     assert!(pe.is_valid_proposal(&good_proposal));
 
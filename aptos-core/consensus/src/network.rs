@@ -14,16 +14,19 @@ use crate::{
     network_interface::{ConsensusMsg, ConsensusNetworkClient, RPC},
     pipeline::commit_reliable_broadcast::CommitMessage,
     quorum_store::types::{Batch, BatchMsg, BatchRequest, BatchResponse},
-    rand::rand_gen::{
-        network_messages::{RandGenMessage, RandMessage},
-        types::{AugmentedData, FastShare, Share},
+    rand::{
+        dkg::{DKGMessage, DKGNetwork},
+        rand_gen::{
+            network_messages::{RandGenMessage, RandMessage},
+            types::{AugmentedData, FastShare, Share},
+        },
     },
 };
 use anyhow::{anyhow, bail, ensure};
 use gaptos::aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
 use gaptos::aptos_config::network_id::NetworkId;
 use aptos_consensus_types::{
-    block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
+    block_retrieval::{BlockNumberRetrievalRequest, BlockRetrievalRequest, BlockRetrievalResponse},
     common::Author,
     order_vote_msg::OrderVoteMsg,
     pipeline::{commit_decision::CommitDecision, commit_vote::CommitVote},
@@ -129,6 +132,24 @@ pub struct IncomingRandGenRequest {
     pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
 }
 
+#[derive(Debug)]
+pub struct IncomingDKGRequest {
+    pub req: DKGMessage,
+    pub sender: Author,
+    pub protocol: ProtocolId,
+    pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+}
+
+/// Served outside of the epoch manager from ConsensusDB, the committed blocks don't depend on the
+/// epoch
+#[derive(Debug)]
+pub struct IncomingBlockNumberRetrievalRequest {
+    pub req: BlockNumberRetrievalRequest,
+    pub sender: Author,
+    pub protocol: ProtocolId,
+    pub response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+}
+
 #[derive(Debug)]
 pub enum IncomingRpcRequest {
     BlockRetrieval(IncomingBlockRetrievalRequest),
@@ -166,6 +187,9 @@ pub struct NetworkReceivers {
         (AccountAddress, Discriminant<IncomingRpcRequest>),
         (AccountAddress, IncomingRpcRequest),
     >,
+    pub dkg_rpc_rx: aptos_channel::Receiver<AccountAddress, IncomingDKGRequest>,
+    pub block_number_rpc_rx:
+        aptos_channel::Receiver<AccountAddress, IncomingBlockNumberRetrievalRequest>,
 }
 
 #[async_trait::async_trait]
@@ -468,6 +492,25 @@ impl NetworkSender {
     }
 }
 
+#[async_trait]
+impl DKGNetwork for NetworkSender {
+    async fn request_dkg(
+        &self,
+        peer: Author,
+        msg: DKGMessage,
+        timeout: Duration,
+    ) -> anyhow::Result<DKGMessage> {
+        fail_point!("consensus::send::any", |_| {
+            Err(anyhow::anyhow!("Injected error in request_dkg"))
+        });
+
+        match self.send_rpc(peer, ConsensusMsg::DKGMessage(msg), timeout).await? {
+            ConsensusMsg::DKGMessage(response) => Ok(response),
+            _ => Err(anyhow!("Invalid response to dkg request")),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl QuorumStoreSender for NetworkSender {
     async fn request_batch(
@@ -632,6 +675,9 @@ pub struct NetworkTask {
         (AccountAddress, Discriminant<IncomingRpcRequest>),
         (AccountAddress, IncomingRpcRequest),
     >,
+    dkg_rpc_tx: aptos_channel::Sender<AccountAddress, IncomingDKGRequest>,
+    block_number_rpc_tx:
+        aptos_channel::Sender<AccountAddress, IncomingBlockNumberRetrievalRequest>,
     all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
 }
 
@@ -654,6 +700,10 @@ impl NetworkTask {
         );
         let (rpc_tx, rpc_rx) =
             aptos_channel::new(QueueStyle::FIFO, 10, Some(&counters::RPC_CHANNEL_MSGS));
+        // A leader and the dealing fetches of a peer send several requests at once
+        let (dkg_rpc_tx, dkg_rpc_rx) = aptos_channel::new(QueueStyle::FIFO, 10, None);
+        let (block_number_rpc_tx, block_number_rpc_rx) =
+            aptos_channel::new(QueueStyle::KLAST, 1, None);
 
         // Verify the network events have been constructed correctly
         let network_and_events = network_service_events.into_network_and_events();
@@ -673,12 +723,16 @@ impl NetworkTask {
                 consensus_messages_tx,
                 quorum_store_messages_tx,
                 rpc_tx,
+                dkg_rpc_tx,
+                block_number_rpc_tx,
                 all_events,
             },
             NetworkReceivers {
                 consensus_messages,
                 quorum_store_messages,
                 rpc_rx,
+                dkg_rpc_rx,
+                block_number_rpc_rx,
             },
         )
     }
@@ -847,6 +901,40 @@ impl NetworkTask {
                                 response_sender: callback,
                             })
                         },
+                        // Served outside of the epoch manager, which waits for the DKG to
+                        // complete when an epoch starts
+                        ConsensusMsg::DKGMessage(req) => {
+                            let req_with_callback = IncomingDKGRequest {
+                                req,
+                                sender: peer_id,
+                                protocol,
+                                response_sender: callback,
+                            };
+                            if let Err(e) = self.dkg_rpc_tx.push(peer_id, req_with_callback) {
+                                warn!(error = ?e, "aptos channel closed");
+                            };
+                            continue;
+                        },
+                        ConsensusMsg::BlockNumberRetrievalRequest(request) => {
+                            debug!(
+                                remote_peer = peer_id,
+                                event = LogEvent::ReceiveBlockRetrieval,
+                                "{}",
+                                request
+                            );
+                            let req_with_callback = IncomingBlockNumberRetrievalRequest {
+                                req: *request,
+                                sender: peer_id,
+                                protocol,
+                                response_sender: callback,
+                            };
+                            if let Err(e) =
+                                self.block_number_rpc_tx.push(peer_id, req_with_callback)
+                            {
+                                warn!(error = ?e, "aptos channel closed");
+                            };
+                            continue;
+                        },
                         _ => {
                             warn!(remote_peer = peer_id, "Unexpected msg: {:?}", msg);
                             continue;
@@ -8,11 +8,14 @@ use crate::{
     dag::DAGNetworkMessage,
     pipeline,
     quorum_store::types::{Batch, BatchMsg, BatchRequest, BatchResponse},
-    rand::rand_gen::network_messages::RandGenMessage,
+    rand::{dkg::DKGMessage, rand_gen::network_messages::RandGenMessage},
 };
 use gaptos::aptos_config::network_id::{NetworkId, PeerNetworkId};
 use aptos_consensus_types::{
-    block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse},
+    block_retrieval::{
+        BlockNumberRetrievalRequest, BlockNumberRetrievalResponse, BlockRetrievalRequest,
+        BlockRetrievalResponse,
+    },
     epoch_retrieval::EpochRetrievalRequest,
     order_vote_msg::OrderVoteMsg,
     pipeline::{commit_decision::CommitDecision, commit_vote::CommitVote},
@@ -80,6 +83,12 @@ pub enum ConsensusMsg {
     /// OrderVoteMsg is the struct that is broadcasted by a validator on receiving quorum certificate
     /// on a block.
     OrderVoteMsg(Box<OrderVoteMsg>),
+    /// Transcript exchange of the local DKG run at epoch start.
+    DKGMessage(DKGMessage),
+    /// RPC to get the committed blocks of a range of block numbers.
+    BlockNumberRetrievalRequest(Box<BlockNumberRetrievalRequest>),
+    /// Carries the committed blocks and the ledger infos committing them.
+    BlockNumberRetrievalResponse(Box<BlockNumberRetrievalResponse>),
 }
 
 /// Network type for consensus
@@ -107,6 +116,9 @@ impl ConsensusMsg {
             ConsensusMsg::CommitMessage(_) => "CommitMessage",
             ConsensusMsg::RandGenMessage(_) => "RandGenMessage",
             ConsensusMsg::BatchResponseV2(_) => "BatchResponseV2",
+            ConsensusMsg::DKGMessage(_) => "DKGMessage",
+            ConsensusMsg::BlockNumberRetrievalRequest(_) => "BlockNumberRetrievalRequest",
+            ConsensusMsg::BlockNumberRetrievalResponse(_) => "BlockNumberRetrievalResponse",
         }
     }
 }
@@ -530,12 +530,19 @@ impl DropConfigRound {
 mod tests {
     use super::*;
     use crate::{
+        block_storage::BlockNumberRetrievalServer,
+        consensusdb::ConsensusDB,
         network::{IncomingRpcRequest, NetworkTask},
         network_interface::{DIRECT_SEND, RPC},
+        persistent_liveness_storage::StorageWriteProxy,
     };
     use gaptos::aptos_config::network_id::{NetworkId, PeerNetworkId};
     use aptos_consensus_types::{
-        block_retrieval::{BlockRetrievalRequest, BlockRetrievalResponse, BlockRetrievalStatus},
+        block::block_test_utils::placeholder_certificate_for_block,
+        block_retrieval::{
+            BlockNumberRetrievalRequest, BlockRetrievalRequest, BlockRetrievalResponse,
+            BlockRetrievalStatus,
+        },
         common::Payload,
     };
     use gaptos::aptos_crypto::HashValue;
@@ -550,10 +557,17 @@ mod tests {
         },
         transport::ConnectionMetadata,
     };
-    use gaptos::aptos_types::validator_verifier::random_validator_verifier;
+    use gaptos::aptos_schemadb::SchemaBatch;
+    use gaptos::aptos_storage_interface::mock::MockDbReaderWriter;
+    use gaptos::aptos_temppath::TempPath;
+    use gaptos::aptos_types::{
+        ledger_info::{generate_ledger_info_with_sig, LedgerInfo},
+        validator_verifier::random_validator_verifier,
+    };
     use bytes::Bytes;
     use futures::{channel::oneshot, future};
     use maplit::hashmap;
+    use std::path::PathBuf;
 
     #[test]
     fn test_split_network_round() {
@@ -845,6 +859,133 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_block_number_retrieval_rpc() {
+        let runtime = consensus_runtime();
+        let _entered_runtime = runtime.enter();
+
+        let num_nodes = 2;
+        let mut receivers: Vec<NetworkReceivers> = Vec::new();
+        let mut playground = NetworkPlayground::new(runtime.handle().clone());
+        let mut nodes = Vec::new();
+        let (signers, validator_verifier) = random_validator_verifier(num_nodes, None, false);
+        let peers: Vec<_> = signers.iter().map(|signer| signer.author()).collect();
+        let peers_and_metadata = PeersAndMetadata::new(&[NetworkId::Validator]);
+
+        for (peer_id, peer) in peers.iter().enumerate() {
+            let (network_reqs_tx, network_reqs_rx) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+            let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+            let (consensus_tx, consensus_rx) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+            let (_conn_mgr_reqs_tx, conn_mgr_reqs_rx) = gaptos::aptos_channels::new_test(1024);
+            let network_sender = network::NetworkSender::new(
+                PeerManagerRequestSender::new(network_reqs_tx),
+                ConnectionRequestSender::new(connection_reqs_tx),
+            );
+            let network_client = NetworkClient::new(
+                DIRECT_SEND.into(),
+                RPC.into(),
+                hashmap! {NetworkId::Validator => network_sender},
+                peers_and_metadata.clone(),
+            );
+            let consensus_network_client = ConsensusNetworkClient::new(network_client);
+            add_peer_to_storage(&peers_and_metadata, peer, &[
+                ProtocolId::ConsensusDirectSendJson,
+                ProtocolId::ConsensusDirectSendBcs,
+                ProtocolId::ConsensusRpcJson,
+            ]);
+            playground.add_node(
+                TwinId { id: peer_id, author: *peer },
+                consensus_tx,
+                network_reqs_rx,
+                conn_mgr_reqs_rx,
+            );
+
+            let (self_sender, self_receiver) = gaptos::aptos_channels::new_unbounded_test();
+            nodes.push(NetworkSender::new(
+                *peer,
+                consensus_network_client,
+                self_sender,
+                validator_verifier.clone(),
+            ));
+            let network_events = NetworkEvents::new(consensus_rx, None, true);
+            let network_service_events =
+                NetworkServiceEvents::new(hashmap! {NetworkId::Validator => network_events});
+            let (task, receiver) = NetworkTask::new(network_service_events, self_receiver);
+            receivers.push(receiver);
+            runtime.handle().spawn(task.start());
+        }
+
+        // node 1 committed blocks 1 to 4, by ledger infos of blocks 3 and 4. Blocks are saved
+        // before they are numbered, like a block store does.
+        let mut blocks: Vec<Block> = vec![];
+        for round in 1..=4 {
+            let quorum_cert = match blocks.last() {
+                Some(parent) => placeholder_certificate_for_block(
+                    &signers,
+                    parent.id(),
+                    parent.round(),
+                    parent.parent_id(),
+                    parent.round() - 1,
+                ),
+                None => certificate_for_genesis(),
+            };
+            blocks.push(Block::new_nil(round, quorum_cert, vec![]));
+        }
+        let tmp_dir = TempPath::new();
+        let db = Arc::new(ConsensusDB::new(&tmp_dir, &PathBuf::new()));
+        db.save_blocks_and_quorum_certificates(blocks.clone(), vec![])
+            .unwrap();
+        db.save_block_numbers((1..).zip(blocks.iter().map(Block::id)).collect())
+            .unwrap();
+        let batch = SchemaBatch::new();
+        for (block_number, block) in (1..).zip(&blocks).skip(2) {
+            let mut ledger_info = LedgerInfo::new(
+                block.gen_block_info(HashValue::zero(), 0, None),
+                HashValue::zero(),
+            );
+            ledger_info.set_block_number(block_number);
+            let ledger_info = generate_ledger_info_with_sig(&signers, ledger_info);
+            db.ledger_db
+                .metadata_db()
+                .put_ledger_info(&ledger_info, &batch)
+                .unwrap();
+        }
+        db.ledger_db.metadata_db().write_schemas(batch).unwrap();
+        let storage = Arc::new(StorageWriteProxy::new(db, Arc::new(MockDbReaderWriter)));
+        let receiver_1 = receivers.remove(1);
+        runtime.handle().spawn(
+            BlockNumberRetrievalServer::new(storage).start(receiver_1.block_number_rpc_rx),
+        );
+
+        let node0 = nodes[0].clone();
+        let peer1 = peers[1];
+        let request_blocks = move |request: BlockNumberRetrievalRequest| {
+            let node0 = node0.clone();
+            async move {
+                let msg = ConsensusMsg::BlockNumberRetrievalRequest(Box::new(request));
+                match node0.send_rpc(peer1, msg, Duration::from_secs(5)).await.unwrap() {
+                    ConsensusMsg::BlockNumberRetrievalResponse(response) => *response,
+                    _ => panic!("unexpected response"),
+                }
+            }
+        };
+        timed_block_on(&runtime, async {
+            // served up to the first ledger info at or after the last block requested
+            let request = BlockNumberRetrievalRequest::new(2, 2);
+            let response = request_blocks(request.clone()).await;
+            response.verify(request, &validator_verifier).unwrap();
+            let block_ids: Vec<_> = response.blocks().iter().map(Block::id).collect();
+            assert_eq!(block_ids, vec![blocks[1].id(), blocks[2].id()]);
+            assert_eq!(response.ledger_infos().len(), 1);
+
+            // blocks which aren't committed yet
+            let request = BlockNumberRetrievalRequest::new(5, 2);
+            let response = request_blocks(request.clone()).await;
+            response.verify(request, &validator_verifier).unwrap();
+            assert!(response.blocks().is_empty());
+        });
+    }
+
     #[test]
     fn test_bad_message() {
         let runtime = consensus_runtime();
@@ -10,6 +10,7 @@ use crate::{
     payload_client::{user::UserPayloadClient, PayloadClient},
 };
 use aptos_consensus_types::common::{Payload, PayloadFilter};
+use api_types::block_budget::BlockBudget;
 use gaptos::aptos_logger::debug;
 use gaptos::aptos_types::{on_chain_config::ValidatorTxnConfig, validator_txn::ValidatorTransaction};
 use gaptos::aptos_validator_transaction_pool as vtxn_pool;
@@ -80,6 +81,7 @@ impl PayloadClient for MixedPayloadClient {
         pending_uncommitted_blocks: usize,
         recent_max_fill_fraction: f32,
         block_timestamp: Duration,
+        block_budget: BlockBudget,
     ) -> anyhow::Result<(Vec<ValidatorTransaction>, Payload), QuorumStoreError> {
         // Pull validator txns first.
         let validator_txn_pull_timer = Instant::now();
@@ -129,6 +131,7 @@ impl PayloadClient for MixedPayloadClient {
                 pending_uncommitted_blocks,
                 recent_max_fill_fraction,
                 block_timestamp,
+                block_budget,
             )
             .await?;
 
@@ -172,6 +175,7 @@ async fn mixed_payload_client_should_prioritize_validator_txns() {
             0,
             0.,
             gaptos::aptos_infallible::duration_since_epoch(),
+            BlockBudget::unlimited(),
         )
         .await
         .unwrap()
@@ -198,6 +202,7 @@ async fn mixed_payload_client_should_prioritize_validator_txns() {
             0,
             0.,
             gaptos::aptos_infallible::duration_since_epoch(),
+            BlockBudget::unlimited(),
         )
         .await
         .unwrap()
@@ -224,6 +229,7 @@ async fn mixed_payload_client_should_prioritize_validator_txns() {
             0,
             0.,
             gaptos::aptos_infallible::duration_since_epoch(),
+            BlockBudget::unlimited(),
         )
         .await
         .unwrap()
@@ -250,6 +256,7 @@ async fn mixed_payload_client_should_prioritize_validator_txns() {
             0,
             0.,
             gaptos::aptos_infallible::duration_since_epoch(),
+            BlockBudget::unlimited(),
         )
         .await
         .unwrap()
@@ -294,6 +301,7 @@ async fn mixed_payload_client_should_respect_validator_txn_feature_flag() {
             0,
             0.,
             gaptos::aptos_infallible::duration_since_epoch(),
+            BlockBudget::unlimited(),
         )
         .await
         .unwrap()
@@ -3,6 +3,7 @@
 
 use crate::error::QuorumStoreError;
 use aptos_consensus_types::common::{Payload, PayloadFilter};
+use api_types::block_budget::BlockBudget;
 use gaptos::aptos_types::validator_txn::ValidatorTransaction;
 use gaptos::aptos_validator_transaction_pool::TransactionFilter;
 use futures::future::BoxFuture;
@@ -31,5 +32,6 @@ pub trait PayloadClient: Send + Sync {
         pending_uncommitted_blocks: usize,
         recent_max_fill_fraction: f32,
         block_timestamp: Duration,
+        block_budget: BlockBudget,
     ) -> anyhow::Result<(Vec<ValidatorTransaction>, Payload), QuorumStoreError>;
 }
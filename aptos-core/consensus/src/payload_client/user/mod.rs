@@ -3,6 +3,7 @@
 
 use crate::error::QuorumStoreError;
 use aptos_consensus_types::common::{Payload, PayloadFilter};
+use api_types::block_budget::BlockBudget;
 #[cfg(test)]
 use gaptos::aptos_types::transaction::SignedTransaction;
 use futures::future::BoxFuture;
@@ -29,6 +30,7 @@ pub trait UserPayloadClient: Send + Sync {
         pending_uncommitted_blocks: usize,
         recent_max_fill_fraction: f32,
         block_timestamp: Duration,
+        block_budget: BlockBudget,
     ) -> anyhow::Result<Payload, QuorumStoreError>;
 }
 
@@ -63,6 +65,7 @@ impl UserPayloadClient for DummyClient {
         _pending_uncommitted_blocks: usize,
         _recent_max_fill_fraction: f32,
         _block_timestamp: Duration,
+        _block_budget: BlockBudget,
     ) -> anyhow::Result<Payload, QuorumStoreError> {
         let timer = Instant::now();
         let mut nxt_txn_idx = 0;
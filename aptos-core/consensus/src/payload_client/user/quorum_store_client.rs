@@ -9,6 +9,7 @@ use aptos_consensus_types::{
     common::{Payload, PayloadFilter},
     request_response::{GetPayloadCommand, GetPayloadResponse},
 };
+use api_types::block_budget::BlockBudget;
 use gaptos::aptos_logger::info;
 use fail::fail_point;
 use futures::future::BoxFuture;
@@ -54,6 +55,7 @@ impl QuorumStoreClient {
         return_non_full: bool,
         exclude_payloads: PayloadFilter,
         block_timestamp: Duration,
+        block_budget: BlockBudget,
     ) -> anyhow::Result<Payload, QuorumStoreError> {
         let (callback, callback_rcv) = oneshot::channel();
         let req = GetPayloadCommand::GetPayloadRequest(
@@ -67,6 +69,7 @@ impl QuorumStoreClient {
             exclude_payloads.clone(),
             callback,
             block_timestamp,
+            block_budget,
         );
         // send to shared mempool
         self.consensus_to_quorum_store_sender
@@ -105,6 +108,7 @@ impl UserPayloadClient for QuorumStoreClient {
         pending_uncommitted_blocks: usize,
         recent_max_fill_fraction: f32,
         block_timestamp: Duration,
+        block_budget: BlockBudget,
     ) -> anyhow::Result<Payload, QuorumStoreError> {
         let return_non_full = recent_max_fill_fraction
             < self.wait_for_full_blocks_above_recent_fill_threshold
@@ -136,6 +140,7 @@ impl UserPayloadClient for QuorumStoreClient {
                     return_non_full || return_empty || done,
                     exclude.clone(),
                     block_timestamp,
+                    block_budget,
                 )
                 .await?;
             if payload.is_empty() && !return_empty && !done {
@@ -157,6 +162,7 @@ impl UserPayloadClient for QuorumStoreClient {
             max_bytes = max_bytes,
             max_inline_items = max_inline_items,
             max_inline_bytes = max_inline_bytes,
+            block_budget = ?block_budget,
             pending_ordering = pending_ordering,
             return_empty = return_empty,
             return_non_full = return_non_full,
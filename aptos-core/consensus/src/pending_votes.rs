@@ -99,6 +99,11 @@ impl PendingVotes {
         }
     }
 
+    /// The vote of `author` in this round, if any
+    pub fn author_vote(&self, author: &Author) -> Option<&Vote> {
+        self.author_to_vote.get(author).map(|(vote, _)| vote)
+    }
+
     /// Insert a vote and if the vote is valid, return a QuorumCertificate preferentially over a
     /// TimeoutCertificate if either can can be formed
     pub fn insert_vote(
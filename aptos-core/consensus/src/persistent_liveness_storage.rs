@@ -6,8 +6,9 @@ use crate::{consensusdb::ConsensusDB, epoch_manager::LivenessStorageData, error:
 use anyhow::{format_err, Result};
 use api_types::ExecutionArgs;
 use aptos_consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
-    vote_data::VoteData, wrapped_ledger_info::WrappedLedgerInfo,
+    block::Block, equivocation::EquivocationEvidence, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, vote::Vote, vote_data::VoteData,
+    wrapped_ledger_info::WrappedLedgerInfo,
 };
 use gaptos::aptos_crypto::{
     hash::{ACCUMULATOR_PLACEHOLDER_HASH, GENESIS_BLOCK_ID},
@@ -53,6 +54,9 @@ pub trait PersistentLivenessStorage: Send + Sync {
     /// Persist consensus' state
     fn save_vote(&self, vote: &Vote) -> Result<()>;
 
+    /// Persist proof that a validator signed conflicting proposals or votes
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()>;
+
     /// Construct data that can be recovered from ledger
     fn recover_from_ledger(&self) -> LedgerRecoveryData;
 
@@ -403,6 +407,10 @@ impl PersistentLivenessStorage for StorageWriteProxy {
         Ok(self.db.save_vote(bcs::to_bytes(vote)?)?)
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()> {
+        Ok(self.db.save_equivocation_evidence(evidence)?)
+    }
+
     fn recover_from_ledger(&self) -> LedgerRecoveryData {
         let latest_ledger_info =
             self.aptos_db.get_latest_ledger_info().expect("Failed to get latest ledger info.");
@@ -4,6 +4,7 @@
 
 use crate::{
     consensus_observer::publisher::ConsensusPublisher,
+    execution_divergence::DivergenceState,
     network::{IncomingCommitRequest, NetworkSender},
     pipeline::{
         buffer_manager::{create_channel, BufferManager, OrderedBlocks, ResetRequest},
@@ -42,6 +43,7 @@ pub fn prepare_phases_and_buffer_manager(
     highest_committed_round: u64,
     consensus_observer_config: ConsensusObserverConfig,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
+    divergence_state: Arc<DivergenceState>,
 ) -> (
     PipelinePhase<ExecutionSchedulePhase>,
     PipelinePhase<ExecutionWaitPhase>,
@@ -83,7 +85,7 @@ pub fn prepare_phases_and_buffer_manager(
     let (signing_phase_response_tx, signing_phase_response_rx) =
         create_channel::<SigningResponse>();
 
-    let signing_phase_processor = SigningPhase::new(safety_rules);
+    let signing_phase_processor = SigningPhase::new(safety_rules, divergence_state);
     let signing_phase = PipelinePhase::new(
         signing_phase_request_rx,
         Some(signing_phase_response_tx),
@@ -26,6 +26,7 @@ use crate::{
     transaction_shuffler::create_transaction_shuffler,
 };
 use anyhow::Result;
+use api_types::ExecutionChannel;
 use gaptos::aptos_bounded_executor::BoundedExecutor;
 use gaptos::aptos_channels::{aptos_channel, message_queues::QueueStyle};
 use gaptos::aptos_config::config::{ConsensusConfig, ConsensusObserverConfig};
@@ -60,7 +61,7 @@ pub trait TExecutionClient: Send + Sync {
     /// Initialize the execution phase for a new epoch.
     async fn start_epoch(
         &self,
-        maybe_consensus_key: Arc<PrivateKey>,
+        maybe_consensus_key: Option<Arc<PrivateKey>>,
         epoch_state: Arc<EpochState>,
         commit_signer_provider: Arc<dyn CommitSignerProvider>,
         payload_manager: Arc<dyn TPayloadManager>,
@@ -159,6 +160,8 @@ pub struct ExecutionProxyClient {
     rand_storage: Arc<dyn RandStorage<AugmentedData>>,
     consensus_observer_config: ConsensusObserverConfig,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
+    // supplies the conflict keys for the conflict key shuffler
+    execution_api: Option<Arc<dyn ExecutionChannel>>,
 }
 
 impl ExecutionProxyClient {
@@ -172,6 +175,7 @@ impl ExecutionProxyClient {
         rand_storage: Arc<dyn RandStorage<AugmentedData>>,
         consensus_observer_config: ConsensusObserverConfig,
         consensus_publisher: Option<Arc<ConsensusPublisher>>,
+        execution_api: Option<Arc<dyn ExecutionChannel>>,
     ) -> Self {
         Self {
             consensus_config,
@@ -184,12 +188,13 @@ impl ExecutionProxyClient {
             rand_storage,
             consensus_observer_config,
             consensus_publisher,
+            execution_api,
         }
     }
 
     fn spawn_decoupled_execution(
         &self,
-        consensus_sk: Arc<PrivateKey>,
+        maybe_consensus_sk: Option<Arc<PrivateKey>>,
         commit_signer_provider: Arc<dyn CommitSignerProvider>,
         epoch_state: Arc<EpochState>,
         rand_config: Option<RandConfig>,
@@ -222,6 +227,8 @@ impl ExecutionProxyClient {
                 let (rand_ready_block_tx, rand_ready_block_rx) = unbounded::<OrderedBlocks>();
 
                 let (reset_tx_to_rand_manager, reset_rand_manager_rx) = unbounded::<ResetRequest>();
+                let consensus_sk = maybe_consensus_sk
+                    .expect("randomness is only enabled with a consensus key available");
                 let signer = Arc::new(ValidatorSigner::new(self.author, (*consensus_sk).clone()));
 
                 let rand_manager = RandManager::<Share, AugmentedData>::new(
@@ -283,6 +290,7 @@ impl ExecutionProxyClient {
             highest_committed_round,
             consensus_observer_config,
             consensus_publisher,
+            self.execution_proxy.divergence_state(),
         );
 
         tokio::spawn(execution_schedule_phase.start());
@@ -298,7 +306,7 @@ impl ExecutionProxyClient {
 impl TExecutionClient for ExecutionProxyClient {
     async fn start_epoch(
         &self,
-        maybe_consensus_key: Arc<PrivateKey>,
+        maybe_consensus_key: Option<Arc<PrivateKey>>,
         epoch_state: Arc<EpochState>,
         commit_signer_provider: Arc<dyn CommitSignerProvider>,
         payload_manager: Arc<dyn TPayloadManager>,
@@ -323,8 +331,10 @@ impl TExecutionClient for ExecutionProxyClient {
             self.consensus_publisher.clone(),
         );
 
-        let transaction_shuffler =
-            create_transaction_shuffler(onchain_execution_config.transaction_shuffler_type());
+        let transaction_shuffler = create_transaction_shuffler(
+            onchain_execution_config.transaction_shuffler_type(),
+            self.execution_api.clone(),
+        );
         let block_executor_onchain_config =
             onchain_execution_config.block_executor_onchain_config();
         let transaction_deduper =
@@ -495,7 +505,7 @@ pub struct DummyExecutionClient;
 impl TExecutionClient for DummyExecutionClient {
     async fn start_epoch(
         &self,
-        _maybe_consensus_key: Arc<PrivateKey>,
+        _maybe_consensus_key: Option<Arc<PrivateKey>>,
         _epoch_state: Arc<EpochState>,
         _commit_signer_provider: Arc<dyn CommitSignerProvider>,
         _payload_manager: Arc<dyn TPayloadManager>,
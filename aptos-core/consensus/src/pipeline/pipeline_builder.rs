@@ -5,6 +5,7 @@ use crate::{
     block_preparer::BlockPreparer,
     block_storage::tracing::{observe_block, BlockStage},
     counters::{self, update_counters_for_block, update_counters_for_compute_res, update_counters_for_compute_result},
+    execution_divergence::DivergenceState,
     execution_pipeline::SIG_VERIFY_POOL,
     monitor,
     payload_manager::TPayloadManager,
@@ -70,6 +71,7 @@ pub struct PipelineBuilder {
     payload_manager: Arc<dyn TPayloadManager>,
     txn_notifier: Arc<dyn TxnNotifier>,
     block_metadata: Arc<Mutex<HashMap<BlockId, ExternalBlockMeta>>>,
+    divergence_state: Arc<DivergenceState>,
 }
 
 fn spawn_shared_fut<
@@ -154,6 +156,7 @@ impl PipelineBuilder {
         state_sync_notifier: Arc<dyn ConsensusNotificationSender>,
         payload_manager: Arc<dyn TPayloadManager>,
         txn_notifier: Arc<dyn TxnNotifier>,
+        divergence_state: Arc<DivergenceState>,
     ) -> Self {
         Self {
             block_preparer,
@@ -166,6 +169,7 @@ impl PipelineBuilder {
             payload_manager,
             txn_notifier,
             block_metadata: Arc::new(Mutex::new(HashMap::new())),
+            divergence_state,
         }
     }
 
@@ -272,6 +276,7 @@ impl PipelineBuilder {
                 order_proof_rx.resubscribe(),
                 commit_proof_rx.resubscribe(),
                 self.signer.clone(),
+                self.divergence_state.clone(),
                 block.clone(),
             ),
             &mut abort_handles,
@@ -572,6 +577,7 @@ impl PipelineBuilder {
         mut order_proof_rx: tokio::sync::broadcast::Receiver<()>,
         mut commit_proof_rx: tokio::sync::broadcast::Receiver<LedgerInfoWithSignatures>,
         signer: Arc<ValidatorSigner>,
+        divergence_state: Arc<DivergenceState>,
         block: Arc<Block>,
     ) -> TaskResult<CommitVoteResult> {
         let (compute_result, epoch_end_timestamp) = ledger_update_phase.await?;
@@ -588,6 +594,10 @@ impl PipelineBuilder {
             }
         }
 
+        if divergence_state.has_diverged() {
+            return Err(anyhow!("execution diverged, stop signing commit votes"))?;
+        }
+
         let _tracker = Tracker::new("sign_commit_vote", &block);
         let mut block_info = block.gen_block_info(
             compute_result.root_hash(),
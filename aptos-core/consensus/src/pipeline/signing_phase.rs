@@ -2,7 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::pipeline::pipeline_phase::StatelessPipeline;
+use crate::{execution_divergence::DivergenceState, pipeline::pipeline_phase::StatelessPipeline};
 use gaptos::aptos_crypto::bls12381;
 use aptos_safety_rules::Error;
 use gaptos::aptos_types::ledger_info::{LedgerInfo, LedgerInfoWithSignatures};
@@ -53,11 +53,18 @@ pub struct SigningResponse {
 
 pub struct SigningPhase {
     safety_rule_handle: Arc<dyn CommitSignerProvider>,
+    divergence_state: Arc<DivergenceState>,
 }
 
 impl SigningPhase {
-    pub fn new(safety_rule_handle: Arc<dyn CommitSignerProvider>) -> Self {
-        Self { safety_rule_handle }
+    pub fn new(
+        safety_rule_handle: Arc<dyn CommitSignerProvider>,
+        divergence_state: Arc<DivergenceState>,
+    ) -> Self {
+        Self {
+            safety_rule_handle,
+            divergence_state,
+        }
     }
 }
 
@@ -74,10 +81,14 @@ impl StatelessPipeline for SigningPhase {
             commit_ledger_info,
         } = req;
 
+        let signature_result = if self.divergence_state.has_diverged() {
+            Err(Error::InternalError("execution diverged, stop signing commit votes".into()))
+        } else {
+            self.safety_rule_handle
+                .sign_commit_vote(ordered_ledger_info, commit_ledger_info.clone())
+        };
         SigningResponse {
-            signature_result: self
-                .safety_rule_handle
-                .sign_commit_vote(ordered_ledger_info, commit_ledger_info.clone()),
+            signature_result,
             commit_ledger_info,
         }
     }
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    execution_divergence::DivergenceState,
     metrics_safety_rules::MetricsSafetyRules,
     network::{IncomingCommitRequest, NetworkSender},
     network_interface::{ConsensusMsg, ConsensusNetworkClient, DIRECT_SEND, RPC},
@@ -160,6 +161,7 @@ pub async fn prepare_buffer_manager(
         0,
         ConsensusObserverConfig::default(),
         None,
+        Arc::new(DivergenceState::default()),
     );
 
     (
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    execution_divergence::DivergenceState,
     pipeline::{
         buffer_manager::{create_channel, Receiver, Sender},
         pipeline_phase::{CountedRequest, PipelinePhase},
@@ -127,7 +128,7 @@ async fn signing_phase_tests() {
 
     let (safety_rule_handle, signers) = prepare_safety_rules().await;
 
-    let signing_phase = SigningPhase::new(safety_rule_handle);
+    let signing_phase = SigningPhase::new(safety_rule_handle, Arc::new(DivergenceState::default()));
 
     // unit tests
     let mut unit_phase_tester = PhaseTester::<SigningPhase>::new();
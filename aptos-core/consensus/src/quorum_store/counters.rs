@@ -265,6 +265,15 @@ pub static PROOF_QUEUE_FULLY_UTILIZED: Lazy<Histogram> = Lazy::new(|| {
     .unwrap()
 });
 
+pub static BLOCK_BUDGET_EXHAUSTED: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "quorum_store_block_budget_exhausted_in_proposal",
+        "Histogram for whether the execution layer block budget cut the block proposal short",
+        [0.0, 1.0].to_vec(),
+    )
+    .unwrap()
+});
+
 /// Histogram for the total size of transactions per block when pulled for consensus.
 pub static BLOCK_BYTES_WHEN_PULL: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
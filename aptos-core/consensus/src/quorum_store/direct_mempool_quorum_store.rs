@@ -1,8 +1,12 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{monitor, quorum_store::counters};
+use crate::{
+    monitor,
+    quorum_store::{counters, utils::txns_cost},
+};
 use anyhow::Result;
+use api_types::block_budget::{BlockBudget, BlockBudgetTracker};
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter, TransactionInProgress, TransactionSummary},
     request_response::{GetPayloadCommand, GetPayloadResponse},
@@ -23,6 +27,15 @@ use std::{
 };
 use tokio::time::timeout;
 
+/// Number of leading `txns` that fit into the block budget. At least one txn is kept, so
+/// that a txn exceeding the whole budget on its own does not get stuck in mempool forever.
+fn num_txns_within_budget(txns: &[SignedTransaction], block_budget: BlockBudget) -> usize {
+    let mut budget = BlockBudgetTracker::new(block_budget);
+    txns.iter()
+        .position(|txn| !budget.try_consume(txns_cost(std::slice::from_ref(txn))))
+        .map_or(txns.len(), |num_txns| num_txns.max(1))
+}
+
 pub struct DirectMempoolQuorumStore {
     consensus_receiver: Receiver<GetPayloadCommand>,
     mempool_sender: Sender<QuorumStoreRequest>,
@@ -99,6 +112,7 @@ impl DirectMempoolQuorumStore {
         return_non_full: bool,
         payload_filter: PayloadFilter,
         callback: oneshot::Sender<Result<GetPayloadResponse>>,
+        block_budget: BlockBudget,
     ) {
         let get_batch_start_time = Instant::now();
         let exclude_txns = match payload_filter {
@@ -117,7 +131,11 @@ impl DirectMempoolQuorumStore {
                 error!("GetBatch failed");
                 (vec![], counters::REQUEST_FAIL_LABEL)
             },
-            Ok(txns) => (txns, counters::REQUEST_SUCCESS_LABEL),
+            Ok(mut txns) => {
+                // Txns cut off by the budget are left in mempool for the next block.
+                txns.truncate(num_txns_within_budget(&txns, block_budget));
+                (txns, counters::REQUEST_SUCCESS_LABEL)
+            },
         };
         counters::quorum_store_service_latency(
             counters::GET_BATCH_LABEL,
@@ -155,6 +173,7 @@ impl DirectMempoolQuorumStore {
                 payload_filter,
                 callback,
                 _block_timestamp,
+                block_budget,
             ) => {
                 self.handle_block_request(
                     max_txns_after_filtering,
@@ -162,6 +181,7 @@ impl DirectMempoolQuorumStore {
                     return_non_full,
                     payload_filter,
                     callback,
+                    block_budget,
                 )
                 .await;
             },
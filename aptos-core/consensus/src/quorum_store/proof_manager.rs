@@ -7,9 +7,10 @@ use crate::{
     quorum_store::{
         batch_generator::BackPressure,
         counters,
-        utils::{BatchSortKey, ProofQueue},
+        utils::{txns_cost, BatchSortKey, ProofQueue},
     },
 };
+use api_types::block_budget::{self, BlockBudgetTracker, TxnCost};
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter, ProofWithData, TxnSummaryWithExpiration},
     proof_of_store::{BatchInfo, ProofOfStore, ProofOfStoreMsg},
@@ -78,11 +79,25 @@ impl BatchQueue {
             .sum()
     }
 
+    /// Cost of `batch` against the execution layer's block budget. The cost of batches that are
+    /// not available locally yet is estimated by the execution layer from their size.
+    fn batch_cost(&self, batch: &BatchInfo) -> TxnCost {
+        match self
+            .batch_store
+            .get_batch_from_local(batch.digest())
+            .map(|mut persisted_value| persisted_value.take_payload())
+        {
+            Ok(Some(txns)) => txns_cost(&txns),
+            _ => block_budget::estimate_cost(batch.num_txns(), batch.num_bytes()),
+        }
+    }
+
     pub fn pull_batches(
         &mut self,
         max_txns: u64,
         max_bytes: u64,
         excluded_batches: Vec<BatchInfo>,
+        budget: &mut BlockBudgetTracker,
     ) -> Vec<(BatchInfo, Vec<SignedTransaction>)> {
         let mut result: Vec<(BatchInfo, Vec<SignedTransaction>)> = vec![];
         let mut num_txns = 0;
@@ -108,6 +123,20 @@ impl BatchQueue {
                             self.batch_store.get_batch_from_local(batch.digest())
                         {
                             if let Some(txns) = persisted_value.take_payload() {
+                                let cost = txns_cost(&txns);
+                                if !budget.try_consume(cost) {
+                                    if !budget.is_empty() {
+                                        full = true;
+                                        return false;
+                                    }
+                                    warn!(
+                                        "QS: inline batch {} exceeds the block budget {:?} with {:?}, proposing it alone",
+                                        batch.digest(),
+                                        budget.budget(),
+                                        cost
+                                    );
+                                    budget.force_consume(cost);
+                                }
                                 num_txns += batch.num_txns();
                                 num_bytes += batch.num_bytes();
                                 result.push((batch.clone(), txns.clone()));
@@ -207,6 +236,36 @@ impl ProofManager {
             self.proofs_for_consensus.remaining_txns_and_proofs();
     }
 
+    /// Keeps the longest prefix of `proofs` whose batches fit into the block budget. A batch
+    /// that exceeds the whole budget on its own is still proposed alone, as it could never
+    /// be included otherwise.
+    fn take_proofs_within_budget(
+        &self,
+        proofs: Vec<ProofOfStore>,
+        budget: &mut BlockBudgetTracker,
+    ) -> (Vec<ProofOfStore>, bool) {
+        let mut result = Vec::with_capacity(proofs.len());
+        for proof in proofs {
+            let cost = self.batch_queue.batch_cost(proof.info());
+            if budget.try_consume(cost) {
+                result.push(proof);
+                continue;
+            }
+            if budget.is_empty() {
+                warn!(
+                    "QS: batch {} exceeds the block budget {:?} with {:?}, proposing it alone",
+                    proof.info().digest(),
+                    budget.budget(),
+                    cost
+                );
+                budget.force_consume(cost);
+                result.push(proof);
+            }
+            return (result, true);
+        }
+        (result, false)
+    }
+
     pub(crate) fn handle_proposal_request(&mut self, msg: GetPayloadCommand) {
         match msg {
             GetPayloadCommand::GetPayloadRequest(
@@ -220,6 +279,7 @@ impl ProofManager {
                 filter,
                 callback,
                 block_timestamp,
+                block_budget,
             ) => {
                 let excluded_batches: HashSet<_> = match filter {
                     PayloadFilter::Empty => HashSet::new(),
@@ -229,7 +289,7 @@ impl ProofManager {
                     PayloadFilter::InQuorumStore(proofs) => proofs,
                 };
 
-                let (proof_block, cur_unique_txns, proof_queue_fully_utilized) =
+                let (mut proof_block, cur_unique_txns, mut proof_queue_fully_utilized) =
                     self.proofs_for_consensus.pull_proofs(
                         &excluded_batches,
                         max_txns,
@@ -239,6 +299,16 @@ impl ProofManager {
                         return_non_full,
                         block_timestamp,
                     );
+                let mut budget = BlockBudgetTracker::new(block_budget);
+                let mut budget_exhausted = false;
+                if !block_budget.is_unlimited() {
+                    (proof_block, budget_exhausted) =
+                        self.take_proofs_within_budget(proof_block, &mut budget);
+                    // The remaining proofs stay in the queue for the next proposals, and there
+                    // is no room left for inline batches either.
+                    proof_queue_fully_utilized &= !budget_exhausted;
+                }
+                counters::BLOCK_BUDGET_EXHAUSTED.observe(if budget_exhausted { 1.0 } else { 0.0 });
                 counters::NUM_PROOF_OF_STORE_IN_PROPOSAL.observe(proof_block.len() as f64);
                 counters::NUM_BATCHES_WITHOUT_PROOF_OF_STORE.observe(self.batch_queue.len() as f64);
                 counters::PROOF_QUEUE_FULLY_UTILIZED
@@ -263,6 +333,7 @@ impl ProofManager {
                             .cloned()
                             .chain(proof_block.iter().map(|proof| proof.info().clone()))
                             .collect(),
+                        &mut budget,
                     );
                 }
                 let inline_txns = inline_block
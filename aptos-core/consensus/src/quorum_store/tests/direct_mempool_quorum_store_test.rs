@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::quorum_store::direct_mempool_quorum_store::DirectMempoolQuorumStore;
+use api_types::block_budget::BlockBudget;
 use aptos_consensus_types::{
     common::PayloadFilter,
     request_response::{GetPayloadCommand, GetPayloadResponse},
@@ -40,6 +41,7 @@ async fn test_block_request_no_txns() {
             PayloadFilter::DirectMempool(vec![]),
             consensus_callback,
             gaptos::aptos_infallible::duration_since_epoch(),
+            BlockBudget::unlimited(),
         ))
         .unwrap();
 
@@ -4,6 +4,7 @@
 use crate::quorum_store::{
     proof_manager::ProofManager, tests::batch_store_test::batch_store_for_test,
 };
+use api_types::block_budget::BlockBudget;
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter},
     proof_of_store::{BatchId, BatchInfo, ProofOfStore},
@@ -50,6 +51,15 @@ async fn get_proposal(
     proof_manager: &mut ProofManager,
     max_txns: u64,
     filter: &[BatchInfo],
+) -> Payload {
+    get_proposal_with_budget(proof_manager, max_txns, filter, BlockBudget::unlimited()).await
+}
+
+async fn get_proposal_with_budget(
+    proof_manager: &mut ProofManager,
+    max_txns: u64,
+    filter: &[BatchInfo],
+    block_budget: BlockBudget,
 ) -> Payload {
     let (callback_tx, callback_rx) = oneshot::channel();
     let filter_set = HashSet::from_iter(filter.iter().cloned());
@@ -64,6 +74,7 @@ async fn get_proposal(
         PayloadFilter::InQuorumStore(filter_set),
         callback_tx,
         gaptos::aptos_infallible::duration_since_epoch(),
+        block_budget,
     );
     proof_manager.handle_proposal_request(req);
     let GetPayloadResponse::GetPayloadResponse(payload) = callback_rx.await.unwrap().unwrap();
@@ -141,6 +152,29 @@ async fn test_max_txns_from_block_to_execute() {
     );
 }
 
+#[tokio::test]
+async fn test_block_budget() {
+    let mut proof_manager = create_proof_manager();
+    let peer = PeerId::random();
+
+    // Batches missing from the local store are accounted by their size, 1 byte each here
+    let proofs: Vec<_> = (1..=3).map(|i| create_proof(peer, 10, i)).collect();
+    proof_manager.receive_proofs(proofs.clone());
+
+    let payload =
+        get_proposal_with_budget(&mut proof_manager, 100, &[], BlockBudget::new(None, Some(2)))
+            .await;
+    assert_payload_response(payload, &proofs[..2], None);
+
+    // A batch exceeding the whole budget is still proposed on its own
+    let payload =
+        get_proposal_with_budget(&mut proof_manager, 100, &[], BlockBudget::new(None, Some(0)))
+            .await;
+    assert_payload_response(payload, &proofs[..1], None);
+
+    get_proposal_and_assert(&mut proof_manager, 100, &[], &proofs).await;
+}
+
 #[tokio::test]
 async fn test_block_timestamp_expiration() {
     let mut proof_manager = create_proof_manager();
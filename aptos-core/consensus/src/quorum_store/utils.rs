@@ -8,7 +8,11 @@ use aptos_consensus_types::{
 };
 use gaptos::aptos_logger::prelude::*;
 use aptos_mempool::{QuorumStoreRequest, QuorumStoreResponse};
-use gaptos::aptos_types::{transaction::SignedTransaction, PeerId};
+use api_types::block_budget::{self, TxnCost};
+use gaptos::aptos_types::{
+    transaction::{SignedTransaction, TransactionPayload},
+    PeerId,
+};
 use chrono::Utc;
 use futures::channel::{mpsc::Sender, oneshot};
 use gaptos::move_core_types::account_address::AccountAddress;
@@ -21,6 +25,21 @@ use std::{
 };
 use tokio::time::timeout;
 
+/// The cost of `txns` against the block budget reported by the execution layer.
+pub(crate) fn txns_cost(txns: &[SignedTransaction]) -> TxnCost {
+    let mut cost = TxnCost::default();
+    for txn in txns {
+        cost += match txn.payload() {
+            TransactionPayload::GTxnBytes(bytes) => block_budget::txn_cost(bytes),
+            _ => TxnCost {
+                gas: 0,
+                bytes: txn.raw_txn_bytes_len() as u64,
+            },
+        };
+    }
+    cost
+}
+
 pub(crate) struct Timeouts<T> {
     timeouts: VecDeque<(i64, T)>,
 }
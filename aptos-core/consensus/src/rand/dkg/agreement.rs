@@ -0,0 +1,317 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Aggregating the dealings of different quorums would leave the validators with different keys,
+//! so they agree on the dealer set first. Every attempt has a leader, which proposes dealers
+//! holding a quorum of the voting power. The validators sign a prepare vote for the proposal, and
+//! a commit vote once a quorum prepared it, locking on its prepare certificate. A locked validator
+//! only prepares another set if the proposal carries a newer prepare certificate, so once a set is
+//! committed by a quorum no other set can be.
+
+use anyhow::{anyhow, ensure};
+use aptos_consensus_types::common::Author;
+use gaptos::aptos_crypto::bls12381::Signature;
+use gaptos::aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
+use gaptos::aptos_types::epoch_state::EpochState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DealerSet {
+    /// Validator indices in increasing order
+    pub dealers: Vec<u64>,
+    /// Read from the clock of the leader, agreed along with the dealers
+    pub start_time_us: u64,
+}
+
+impl DealerSet {
+    /// Checks that the dealers are distinct validators holding a quorum of the voting power.
+    pub fn verify(&self, epoch_state: &EpochState) -> anyhow::Result<()> {
+        ensure!(
+            self.dealers.windows(2).all(|pair| pair[0] < pair[1]),
+            "dealers are not sorted: {:?}",
+            self.dealers
+        );
+        let validators = epoch_state.verifier.get_ordered_account_addresses();
+        let authors = self
+            .dealers
+            .iter()
+            .map(|index| {
+                validators
+                    .get(*index as usize)
+                    .ok_or_else(|| anyhow!("no validator at index {}", index))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        epoch_state
+            .verifier
+            .check_voting_power(authors.into_iter(), true)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum VotePhase {
+    Prepare,
+    Commit,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, CryptoHasher, BCSCryptoHash)]
+pub struct DealerSetVoteData {
+    pub epoch: u64,
+    pub attempt: u64,
+    pub phase: VotePhase,
+    pub dealer_set: DealerSet,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DealerSetVote {
+    pub author: Author,
+    pub data: DealerSetVoteData,
+    pub signature: Signature,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DealerSetCertificate {
+    pub data: DealerSetVoteData,
+    pub signatures: BTreeMap<Author, Signature>,
+}
+
+impl DealerSetCertificate {
+    pub fn verify(&self, epoch_state: &EpochState, phase: VotePhase) -> anyhow::Result<()> {
+        ensure!(
+            self.data.epoch == epoch_state.epoch,
+            "certificate of epoch {}, current epoch {}",
+            self.data.epoch,
+            epoch_state.epoch
+        );
+        ensure!(self.data.phase == phase, "expected a {:?} certificate", phase);
+        self.data.dealer_set.verify(epoch_state)?;
+        epoch_state
+            .verifier
+            .check_voting_power(self.signatures.keys(), true)?;
+        for (author, signature) in &self.signatures {
+            epoch_state.verifier.verify(*author, &self.data, signature)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DealerSetProposal {
+    pub epoch: u64,
+    pub attempt: u64,
+    pub dealer_set: DealerSet,
+    /// The newest prepare certificate the leader collected, its set is proposed again since it
+    /// may be committed already
+    pub justify: Option<DealerSetCertificate>,
+}
+
+/// The validators take turns leading the attempts in validator index order.
+pub fn leader(epoch_state: &EpochState, attempt: u64) -> Author {
+    let validators = epoch_state.verifier.get_ordered_account_addresses();
+    validators[(attempt % validators.len() as u64) as usize]
+}
+
+/// The votes a validator signed in the agreement. The attempts it votes in only increase, and a
+/// vote is never changed once signed.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Votes {
+    prepared: Option<DealerSetVoteData>,
+    committed: Option<DealerSetVoteData>,
+    /// The newest prepare certificate a commit vote was signed for
+    lock: Option<DealerSetCertificate>,
+}
+
+impl Votes {
+    pub fn lock(&self) -> Option<&DealerSetCertificate> {
+        self.lock.as_ref()
+    }
+
+    fn last_attempt(&self) -> Option<u64> {
+        self.prepared
+            .iter()
+            .chain(self.committed.iter())
+            .map(|data| data.attempt)
+            .max()
+    }
+
+    /// Returns the prepare vote for `proposal`. The caller checks that it holds the dealings of
+    /// the proposed dealers.
+    pub fn prepare(
+        &mut self,
+        epoch_state: &EpochState,
+        proposal: &DealerSetProposal,
+    ) -> anyhow::Result<DealerSetVoteData> {
+        let data = DealerSetVoteData {
+            epoch: proposal.epoch,
+            attempt: proposal.attempt,
+            phase: VotePhase::Prepare,
+            dealer_set: proposal.dealer_set.clone(),
+        };
+        // The leader retries when our vote is lost
+        if self.prepared.as_ref() == Some(&data) {
+            return Ok(data);
+        }
+        ensure!(
+            proposal.epoch == epoch_state.epoch,
+            "proposal of epoch {}, current epoch {}",
+            proposal.epoch,
+            epoch_state.epoch
+        );
+        proposal.dealer_set.verify(epoch_state)?;
+        if let Some(last_attempt) = self.last_attempt() {
+            ensure!(
+                proposal.attempt > last_attempt,
+                "already voted in attempt {}",
+                last_attempt
+            );
+        }
+        if let Some(justify) = &proposal.justify {
+            justify.verify(epoch_state, VotePhase::Prepare)?;
+            ensure!(
+                justify.data.attempt < proposal.attempt
+                    && justify.data.dealer_set == proposal.dealer_set,
+                "proposal doesn't match its prepare certificate"
+            );
+        }
+        if let Some(lock) = &self.lock {
+            ensure!(
+                lock.data.dealer_set == proposal.dealer_set
+                    || proposal
+                        .justify
+                        .as_ref()
+                        .is_some_and(|justify| justify.data.attempt > lock.data.attempt),
+                "locked on the dealer set prepared in attempt {}",
+                lock.data.attempt
+            );
+        }
+        self.prepared = Some(data.clone());
+        Ok(data)
+    }
+
+    /// Returns the commit vote for the set prepared by `prepared` and locks on it.
+    pub fn commit(
+        &mut self,
+        epoch_state: &EpochState,
+        prepared: DealerSetCertificate,
+    ) -> anyhow::Result<DealerSetVoteData> {
+        let data = DealerSetVoteData {
+            phase: VotePhase::Commit,
+            ..prepared.data.clone()
+        };
+        if self.committed.as_ref() == Some(&data) {
+            return Ok(data);
+        }
+        prepared.verify(epoch_state, VotePhase::Prepare)?;
+        if let Some(committed) = &self.committed {
+            ensure!(
+                data.attempt > committed.attempt,
+                "already committed in attempt {}",
+                committed.attempt
+            );
+        }
+        // Committing in an older attempt than we prepared in would skip the lock check of the
+        // newer proposal
+        if let Some(prepared) = &self.prepared {
+            ensure!(
+                data.attempt >= prepared.attempt,
+                "already prepared in attempt {}",
+                prepared.attempt
+            );
+        }
+        self.lock = Some(prepared);
+        self.committed = Some(data.clone());
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gaptos::aptos_types::{
+        validator_signer::ValidatorSigner, validator_verifier::random_validator_verifier,
+    };
+
+    fn dealer_set(dealers: Vec<u64>) -> DealerSet {
+        DealerSet {
+            dealers,
+            start_time_us: 0,
+        }
+    }
+
+    fn proposal(
+        attempt: u64,
+        dealers: Vec<u64>,
+        justify: Option<DealerSetCertificate>,
+    ) -> DealerSetProposal {
+        DealerSetProposal {
+            epoch: 1,
+            attempt,
+            dealer_set: dealer_set(dealers),
+            justify,
+        }
+    }
+
+    fn certify(signers: &[ValidatorSigner], data: DealerSetVoteData) -> DealerSetCertificate {
+        let signatures = signers
+            .iter()
+            .map(|signer| (signer.author(), signer.sign(&data).unwrap()))
+            .collect();
+        DealerSetCertificate { data, signatures }
+    }
+
+    #[test]
+    fn test_lock_on_prepared_dealer_set() {
+        let (signers, verifier) = random_validator_verifier(4, None, false);
+        let epoch_state = EpochState::new(1, verifier);
+        let mut votes = Votes::default();
+
+        // less than a quorum dealt
+        votes
+            .prepare(&epoch_state, &proposal(0, vec![0, 1], None))
+            .unwrap_err();
+        let prepared = votes
+            .prepare(&epoch_state, &proposal(0, vec![0, 1, 2], None))
+            .unwrap();
+        // one set per attempt
+        votes
+            .prepare(&epoch_state, &proposal(0, vec![1, 2, 3], None))
+            .unwrap_err();
+        // the prepare certificate isn't signed by a quorum
+        votes
+            .commit(&epoch_state, certify(&signers[..2], prepared.clone()))
+            .unwrap_err();
+        votes
+            .commit(&epoch_state, certify(&signers[..3], prepared.clone()))
+            .unwrap();
+        assert_eq!(votes.lock().unwrap().data, prepared);
+
+        votes
+            .prepare(&epoch_state, &proposal(1, vec![1, 2, 3], None))
+            .unwrap_err();
+        // a prepare certificate older than the lock doesn't justify another set
+        let older = certify(&signers[1..], DealerSetVoteData {
+            attempt: 0,
+            dealer_set: dealer_set(vec![1, 2, 3]),
+            ..prepared.clone()
+        });
+        votes
+            .prepare(&epoch_state, &proposal(2, vec![1, 2, 3], Some(older)))
+            .unwrap_err();
+        let newer = certify(&signers[1..], DealerSetVoteData {
+            attempt: 2,
+            dealer_set: dealer_set(vec![1, 2, 3]),
+            ..prepared.clone()
+        });
+        votes
+            .prepare(&epoch_state, &proposal(3, vec![1, 2, 3], Some(newer)))
+            .unwrap();
+        // no commit vote in an attempt older than the last prepare vote
+        votes
+            .commit(&epoch_state, certify(&signers[..3], DealerSetVoteData {
+                attempt: 2,
+                ..prepared
+            }))
+            .unwrap_err();
+    }
+}
@@ -1,2 +1,931 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
+
+//! Gravity never runs the on-chain DKG, so the validators of an epoch run it among themselves
+//! when the epoch starts. Every validator deals one transcript and collects the dealings of the
+//! others. Once dealers holding a quorum of the voting power have dealt, the validators agree on
+//! the dealer set (see `agreement`), so an offline validator doesn't stop the epoch, and
+//! aggregate the dealings of that set in validator index order. The aggregated transcript is
+//! turned into the `DKGState` the randomness keys of the epoch are derived from.
+
+mod agreement;
+
+use crate::{
+    consensusdb::{ConsensusDB, DKGTranscriptKey, DKGTranscriptSchema},
+    network::IncomingDKGRequest,
+    network_interface::ConsensusMsg,
+};
+use agreement::{leader, DealerSetVoteData, VotePhase, Votes};
+pub use agreement::{DealerSet, DealerSetCertificate, DealerSetProposal, DealerSetVote};
+use anyhow::{anyhow, ensure};
+use aptos_consensus_types::common::Author;
+use aptos_network::protocols::network::RpcError;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{future::join_all, StreamExt};
+use gaptos::aptos_channels::aptos_channel;
+use gaptos::aptos_crypto::{bls12381::PrivateKey, Uniform};
+use gaptos::aptos_infallible::{duration_since_epoch, Mutex};
+use gaptos::aptos_logger::prelude::*;
+use gaptos::aptos_types::{
+    dkg::{DKGSessionMetadata, DKGSessionState, DKGState, DKGTrait, DefaultDKG},
+    epoch_state::EpochState,
+    on_chain_config::OnChainRandomnessConfig,
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorConsensusInfoMoveStruct},
+};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::time::Instant;
+
+type Transcript = <DefaultDKG as DKGTrait>::Transcript;
+type PublicParams = <DefaultDKG as DKGTrait>::PublicParams;
+
+const DKG_RPC_TIMEOUT: Duration = Duration::from_secs(5);
+const DKG_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+/// Every round of leaders gets one more of it than the previous round
+const DKG_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DKGTranscriptRequest {
+    pub epoch: u64,
+    pub dealer: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DKGTranscriptResponse {
+    pub epoch: u64,
+    pub dealer: u64,
+    pub transcript_bytes: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum DKGMessage {
+    TranscriptRequest(DKGTranscriptRequest),
+    TranscriptResponse(DKGTranscriptResponse),
+    /// Asks for the prepare certificate a validator is locked on
+    LockRequest(u64),
+    LockResponse(Option<DealerSetCertificate>),
+    Proposal(DealerSetProposal),
+    /// Sent by the leader once a quorum prepared its proposal
+    PrepareCertificate(DealerSetCertificate),
+    Vote(DealerSetVote),
+    /// Asks for the commit certificate of the agreed dealer set
+    DealerSetRequest(u64),
+    DealerSetResponse(Option<DealerSetCertificate>),
+}
+
+/// Sends the requests of the local DKG to the other validators.
+#[async_trait]
+pub trait DKGNetwork: Send + Sync {
+    async fn request_dkg(
+        &self,
+        peer: Author,
+        msg: DKGMessage,
+        timeout: Duration,
+    ) -> anyhow::Result<DKGMessage>;
+}
+
+/// The session of the newest epoch, answered by `DKGServer` until the next epoch replaces it.
+pub type CurrentDKGSession = Arc<Mutex<Option<Arc<DKGSession>>>>;
+
+/// The DKG session of `epoch_state`: the validators of the epoch deal for themselves.
+pub fn session_metadata(
+    epoch_state: &EpochState,
+    randomness_config: &OnChainRandomnessConfig,
+) -> DKGSessionMetadata {
+    let verifier = &epoch_state.verifier;
+    let validator_set: Vec<_> = verifier
+        .get_ordered_account_addresses_iter()
+        .map(|author| {
+            ValidatorConsensusInfoMoveStruct::from(ValidatorConsensusInfo::new(
+                author,
+                verifier
+                    .get_public_key(&author)
+                    .expect("validator in the verifier has a public key"),
+                verifier
+                    .get_voting_power(&author)
+                    .expect("validator in the verifier has a voting power"),
+            ))
+        })
+        .collect();
+    DKGSessionMetadata {
+        dealer_epoch: epoch_state.epoch.saturating_sub(1),
+        randomness_config: randomness_config.clone().into(),
+        dealer_validator_set: validator_set.clone(),
+        target_validator_set: validator_set,
+    }
+}
+
+/// The state of the local DKG of one epoch that the peers ask for.
+pub struct DKGSession {
+    epoch_state: Arc<EpochState>,
+    pub_params: PublicParams,
+    signer: ValidatorSigner,
+    consensus_db: Arc<ConsensusDB>,
+    /// Verified dealings by dealer index
+    dealings: Mutex<BTreeMap<u64, Transcript>>,
+    votes: Mutex<Votes>,
+    /// The newest attempt a leader asked this validator to vote in
+    highest_attempt: AtomicU64,
+}
+
+impl DKGSession {
+    fn new(
+        epoch_state: Arc<EpochState>,
+        pub_params: PublicParams,
+        signer: ValidatorSigner,
+        consensus_db: Arc<ConsensusDB>,
+    ) -> anyhow::Result<Self> {
+        let votes = match consensus_db
+            .get::<DKGTranscriptSchema>(&DKGTranscriptKey::votes(epoch_state.epoch))?
+        {
+            Some(votes) => bcs::from_bytes(&votes)?,
+            None => Votes::default(),
+        };
+        Ok(Self {
+            epoch_state,
+            pub_params,
+            signer,
+            consensus_db,
+            dealings: Mutex::new(BTreeMap::new()),
+            votes: Mutex::new(votes),
+            highest_attempt: AtomicU64::new(0),
+        })
+    }
+
+    fn epoch(&self) -> u64 {
+        self.epoch_state.epoch
+    }
+
+    fn add_dealing(&self, dealer: u64, transcript: Transcript) {
+        self.dealings.lock().insert(dealer, transcript);
+    }
+
+    fn dealers(&self) -> Vec<u64> {
+        self.dealings.lock().keys().copied().collect()
+    }
+
+    fn missing_dealers<'a>(&self, dealers: impl Iterator<Item = &'a u64>) -> Vec<u64> {
+        let dealings = self.dealings.lock();
+        dealers
+            .filter(|dealer| !dealings.contains_key(dealer))
+            .copied()
+            .collect()
+    }
+
+    fn has_quorum_of_dealings(&self) -> bool {
+        let validators = self.epoch_state.verifier.get_ordered_account_addresses();
+        let dealers = self.dealers();
+        self.epoch_state
+            .verifier
+            .check_voting_power(dealers.iter().map(|dealer| &validators[*dealer as usize]), true)
+            .is_ok()
+    }
+
+    /// Aggregates the dealings of `dealers`, which must all be collected.
+    fn aggregate(&self, dealers: &[u64]) -> Transcript {
+        let dealings = self.dealings.lock();
+        let mut transcripts = dealers
+            .iter()
+            .map(|dealer| dealings[dealer].clone());
+        let mut aggregated = transcripts
+            .next()
+            .expect("dealer set should not be empty");
+        for transcript in transcripts {
+            DefaultDKG::aggregate_transcripts(&self.pub_params, &mut aggregated, transcript);
+        }
+        aggregated
+    }
+
+    fn dealer_set(&self) -> anyhow::Result<Option<DealerSetCertificate>> {
+        self.consensus_db
+            .get::<DKGTranscriptSchema>(&DKGTranscriptKey::dealer_set(self.epoch()))?
+            .map(|dealer_set| bcs::from_bytes(&dealer_set))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn save_dealer_set(&self, dealer_set: &DealerSetCertificate) -> anyhow::Result<()> {
+        self.consensus_db.put::<DKGTranscriptSchema>(
+            &DKGTranscriptKey::dealer_set(self.epoch()),
+            &bcs::to_bytes(dealer_set)?,
+        )?;
+        Ok(())
+    }
+
+    fn highest_attempt(&self) -> u64 {
+        self.highest_attempt.load(Ordering::Relaxed)
+    }
+
+    fn vote(
+        &self,
+        vote: impl FnOnce(&mut Votes) -> anyhow::Result<DealerSetVoteData>,
+    ) -> anyhow::Result<DKGMessage> {
+        let mut votes = self.votes.lock();
+        let mut updated = votes.clone();
+        let data = vote(&mut updated)?;
+        // Persisted before it is sent, a restart must not make us vote twice in an attempt
+        self.consensus_db.put::<DKGTranscriptSchema>(
+            &DKGTranscriptKey::votes(self.epoch()),
+            &bcs::to_bytes(&updated)?,
+        )?;
+        *votes = updated;
+        let signature = self.signer.sign(&data)?;
+        Ok(DKGMessage::Vote(DealerSetVote {
+            author: self.signer.author(),
+            data,
+            signature,
+        }))
+    }
+
+    /// Returns the response to a request of `sender`.
+    pub fn process(&self, sender: Author, msg: DKGMessage) -> anyhow::Result<DKGMessage> {
+        let epoch = self.epoch();
+        match msg {
+            DKGMessage::TranscriptRequest(request) => {
+                ensure!(request.epoch == epoch, "not in epoch {}", request.epoch);
+                let transcript_bytes = match self.dealings.lock().get(&request.dealer) {
+                    Some(transcript) => bcs::to_bytes(transcript)?,
+                    None => return Err(anyhow!("no dealing of validator {}", request.dealer)),
+                };
+                Ok(DKGMessage::TranscriptResponse(DKGTranscriptResponse {
+                    epoch,
+                    dealer: request.dealer,
+                    transcript_bytes,
+                }))
+            },
+            DKGMessage::LockRequest(request_epoch) => {
+                ensure!(request_epoch == epoch, "not in epoch {}", request_epoch);
+                Ok(DKGMessage::LockResponse(self.votes.lock().lock().cloned()))
+            },
+            DKGMessage::Proposal(proposal) => {
+                ensure!(
+                    leader(&self.epoch_state, proposal.attempt) == sender,
+                    "{} doesn't lead attempt {}",
+                    sender,
+                    proposal.attempt
+                );
+                self.highest_attempt
+                    .fetch_max(proposal.attempt, Ordering::Relaxed);
+                let missing = self.missing_dealers(proposal.dealer_set.dealers.iter());
+                ensure!(missing.is_empty(), "missing the dealings of {:?}", missing);
+                self.vote(|votes| votes.prepare(&self.epoch_state, &proposal))
+            },
+            DKGMessage::PrepareCertificate(prepared) => {
+                ensure!(
+                    leader(&self.epoch_state, prepared.data.attempt) == sender,
+                    "{} doesn't lead attempt {}",
+                    sender,
+                    prepared.data.attempt
+                );
+                self.highest_attempt
+                    .fetch_max(prepared.data.attempt, Ordering::Relaxed);
+                self.vote(|votes| votes.commit(&self.epoch_state, prepared))
+            },
+            DKGMessage::DealerSetRequest(request_epoch) => {
+                ensure!(request_epoch == epoch, "not in epoch {}", request_epoch);
+                Ok(DKGMessage::DealerSetResponse(self.dealer_set()?))
+            },
+            _ => Err(anyhow!("unexpected DKG request")),
+        }
+    }
+}
+
+pub struct LocalDKG {
+    author: Author,
+    epoch_state: Arc<EpochState>,
+    session_metadata: DKGSessionMetadata,
+    consensus_key: Arc<PrivateKey>,
+    consensus_db: Arc<ConsensusDB>,
+    current_session: CurrentDKGSession,
+    network: Arc<dyn DKGNetwork>,
+    rpc_timeout: Duration,
+    retry_interval: Duration,
+    attempt_timeout: Duration,
+}
+
+impl LocalDKG {
+    pub fn new(
+        author: Author,
+        epoch_state: Arc<EpochState>,
+        randomness_config: &OnChainRandomnessConfig,
+        consensus_key: Arc<PrivateKey>,
+        consensus_db: Arc<ConsensusDB>,
+        current_session: CurrentDKGSession,
+        network: Arc<dyn DKGNetwork>,
+    ) -> Self {
+        let session_metadata = session_metadata(&epoch_state, randomness_config);
+        Self {
+            author,
+            epoch_state,
+            session_metadata,
+            consensus_key,
+            consensus_db,
+            current_session,
+            network,
+            rpc_timeout: DKG_RPC_TIMEOUT,
+            retry_interval: DKG_RETRY_INTERVAL,
+            attempt_timeout: DKG_ATTEMPT_TIMEOUT,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_timeouts(
+        mut self,
+        rpc_timeout: Duration,
+        retry_interval: Duration,
+        attempt_timeout: Duration,
+    ) -> Self {
+        self.rpc_timeout = rpc_timeout;
+        self.retry_interval = retry_interval;
+        self.attempt_timeout = attempt_timeout;
+        self
+    }
+
+    /// Returns the DKG state of the epoch, running the DKG unless it already completed before a
+    /// restart. Only returns once the validators agreed on the dealer set and its dealings are
+    /// collected.
+    pub async fn run(self) -> anyhow::Result<DKGState> {
+        let epoch = self.epoch_state.epoch;
+        let my_index = self
+            .epoch_state
+            .verifier
+            .address_to_validator_index()
+            .get(&self.author)
+            .copied()
+            .ok_or_else(|| anyhow!("{} is not in the validator set", self.author))?;
+        // The randomness keys of the previous epochs are never used again
+        self.consensus_db.delete_dkg_transcripts_before(epoch)?;
+
+        let pub_params = DefaultDKG::new_public_params(&self.session_metadata);
+        let dealt = self.deal(&pub_params, my_index)?;
+        let session = Arc::new(DKGSession::new(
+            self.epoch_state.clone(),
+            pub_params,
+            ValidatorSigner::new(self.author, (*self.consensus_key).clone()),
+            self.consensus_db.clone(),
+        )?);
+        session.add_dealing(my_index as u64, dealt);
+        *self.current_session.lock() = Some(session.clone());
+
+        let dealer_set = match session.dealer_set()? {
+            Some(dealer_set) => dealer_set,
+            None => {
+                let dealer_set = self.agree(&session).await;
+                session.save_dealer_set(&dealer_set)?;
+                dealer_set
+            },
+        };
+        let dealer_set = dealer_set.data.dealer_set;
+
+        let aggregated_key = DKGTranscriptKey::aggregated(epoch);
+        let transcript = match self.consensus_db.get::<DKGTranscriptSchema>(&aggregated_key)? {
+            Some(transcript) => {
+                info!(epoch = epoch, "[DKG] Recovered aggregated transcript");
+                transcript
+            },
+            None => {
+                self.collect_dealings(&session, &dealer_set.dealers).await;
+                let aggregated = session.aggregate(&dealer_set.dealers);
+                let transcript = bcs::to_bytes(&aggregated)?;
+                self.consensus_db
+                    .put::<DKGTranscriptSchema>(&aggregated_key, &transcript)?;
+                info!(
+                    epoch = epoch,
+                    "[DKG] Aggregated the transcripts of validators {:?}", dealer_set.dealers
+                );
+                transcript
+            },
+        };
+
+        Ok(DKGState {
+            last_completed: Some(DKGSessionState {
+                metadata: self.session_metadata,
+                start_time_us: dealer_set.start_time_us,
+                transcript,
+            }),
+            in_progress: None,
+        })
+    }
+
+    fn deal(&self, pub_params: &PublicParams, my_index: usize) -> anyhow::Result<Transcript> {
+        let dealt_key = DKGTranscriptKey::dealt(self.epoch_state.epoch);
+        if let Some(transcript) = self.consensus_db.get::<DKGTranscriptSchema>(&dealt_key)? {
+            return Ok(bcs::from_bytes(&transcript)?);
+        }
+        let mut rng = thread_rng();
+        let input_secret = <DefaultDKG as DKGTrait>::InputSecret::generate(&mut rng);
+        let transcript = DefaultDKG::generate_transcript(
+            &mut rng,
+            pub_params,
+            &input_secret,
+            my_index as u64,
+            &self.consensus_key,
+        );
+        // Persisted before it is served, peers must never see two different dealings from us
+        self.consensus_db
+            .put::<DKGTranscriptSchema>(&dealt_key, &bcs::to_bytes(&transcript)?)?;
+        info!(epoch = self.epoch_state.epoch, "[DKG] Dealt transcript");
+        Ok(transcript)
+    }
+
+    async fn request(
+        &self,
+        session: &DKGSession,
+        peer: Author,
+        msg: DKGMessage,
+    ) -> anyhow::Result<DKGMessage> {
+        if peer == self.author {
+            session.process(self.author, msg)
+        } else {
+            self.network.request_dkg(peer, msg, self.rpc_timeout).await
+        }
+    }
+
+    /// Fetches the missing dealings of `dealers` once, from the dealer and from another
+    /// validator that may hold it while the dealer is unreachable, a different one every round.
+    async fn fetch_dealings(&self, session: &DKGSession, dealers: &[u64], round: u64) {
+        let epoch = session.epoch();
+        let validators = self.epoch_state.verifier.get_ordered_account_addresses();
+        let num_validators = validators.len() as u64;
+        let requests = session
+            .missing_dealers(dealers.iter())
+            .into_iter()
+            .flat_map(|dealer| {
+                let relay = (dealer + 1 + round % (num_validators - 1)) % num_validators;
+                [(dealer, dealer), (dealer, relay)]
+            })
+            .map(|(dealer, peer)| (dealer, validators[peer as usize]))
+            .filter(|(_, peer)| *peer != self.author);
+        let responses = join_all(requests.map(|(dealer, peer)| async move {
+            let request = DKGMessage::TranscriptRequest(DKGTranscriptRequest { epoch, dealer });
+            (dealer, peer, self.request(session, peer, request).await)
+        }))
+        .await;
+        for (dealer, peer, response) in responses {
+            let transcript = response.and_then(|response| match response {
+                DKGMessage::TranscriptResponse(response)
+                    if response.epoch == epoch && response.dealer == dealer =>
+                {
+                    verify_dealing(&session.pub_params, dealer as usize, &response.transcript_bytes)
+                },
+                _ => Err(anyhow!("invalid response to transcript request")),
+            });
+            match transcript {
+                Ok(transcript) => session.add_dealing(dealer, transcript),
+                Err(e) => debug!(
+                    epoch = epoch,
+                    remote_peer = peer,
+                    error = ?e,
+                    "[DKG] Failed to fetch the transcript of validator {}", dealer
+                ),
+            }
+        }
+    }
+
+    async fn collect_dealings(&self, session: &DKGSession, dealers: &[u64]) {
+        let mut round = 0;
+        while !session.missing_dealers(dealers.iter()).is_empty() {
+            self.fetch_dealings(session, dealers, round).await;
+            round += 1;
+            tokio::time::sleep(self.retry_interval).await;
+        }
+    }
+
+    /// Returns a dealer set committed by a quorum, as reported by any peer.
+    async fn fetch_dealer_set(&self, session: &DKGSession) -> Option<DealerSetCertificate> {
+        let request = DKGMessage::DealerSetRequest(session.epoch());
+        let responses = join_all(
+            self.epoch_state
+                .verifier
+                .get_ordered_account_addresses_iter()
+                .filter(|peer| *peer != self.author)
+                .map(|peer| self.request(session, peer, request.clone())),
+        )
+        .await;
+        responses.into_iter().find_map(|response| match response {
+            Ok(DKGMessage::DealerSetResponse(Some(dealer_set))) => {
+                match dealer_set.verify(&self.epoch_state, VotePhase::Commit) {
+                    Ok(()) => Some(dealer_set),
+                    Err(e) => {
+                        warn!(error = ?e, "[DKG] Invalid dealer set");
+                        None
+                    },
+                }
+            },
+            _ => None,
+        })
+    }
+
+    fn attempt_timeout(&self, attempt: u64) -> Duration {
+        let round = attempt / self.epoch_state.verifier.len() as u64;
+        self.attempt_timeout * (round + 1) as u32
+    }
+
+    /// Runs attempts until a dealer set is committed, leading the ones of this validator.
+    async fn agree(&self, session: &DKGSession) -> DealerSetCertificate {
+        let epoch = session.epoch();
+        let all_validators: Vec<u64> = (0..self.epoch_state.verifier.len() as u64).collect();
+        let mut attempt = 0;
+        let mut deadline = Instant::now() + self.attempt_timeout(attempt);
+        let mut led_attempt = None;
+        let mut round = 0;
+        loop {
+            self.fetch_dealings(session, &all_validators, round).await;
+            round += 1;
+            if let Some(dealer_set) = self.fetch_dealer_set(session).await {
+                info!(
+                    epoch = epoch,
+                    "[DKG] Dealers {:?} agreed in attempt {}",
+                    dealer_set.data.dealer_set.dealers,
+                    dealer_set.data.attempt
+                );
+                return dealer_set;
+            }
+            // Catches up with the leaders that moved on already
+            if session.highest_attempt() > attempt {
+                attempt = session.highest_attempt();
+                deadline = Instant::now() + self.attempt_timeout(attempt);
+            } else if Instant::now() >= deadline {
+                attempt += 1;
+                deadline = Instant::now() + self.attempt_timeout(attempt);
+            }
+            if led_attempt != Some(attempt)
+                && leader(&self.epoch_state, attempt) == self.author
+                && session.has_quorum_of_dealings()
+            {
+                led_attempt = Some(attempt);
+                match tokio::time::timeout_at(deadline, self.lead(session, attempt)).await {
+                    Ok(dealer_set) => return dealer_set,
+                    Err(_) => warn!(epoch = epoch, "[DKG] Attempt {} timed out", attempt),
+                }
+                continue;
+            }
+            tokio::time::sleep(self.retry_interval).await;
+        }
+    }
+
+    async fn lead(&self, session: &DKGSession, attempt: u64) -> DealerSetCertificate {
+        let epoch = session.epoch();
+        // Waits for the locks of the slower validators too, a validator locked on a newer set
+        // than the proposed one rejects it
+        let locks = self
+            .collect(
+                session,
+                DKGMessage::LockRequest(epoch),
+                self.rpc_timeout,
+                |_, response| match response {
+                    DKGMessage::LockResponse(lock) => {
+                        if let Some(lock) = &lock {
+                            lock.verify(&self.epoch_state, VotePhase::Prepare)?;
+                        }
+                        Ok(lock)
+                    },
+                    _ => Err(anyhow!("invalid response to lock request")),
+                },
+            )
+            .await;
+        let justify = locks
+            .into_values()
+            .flatten()
+            .max_by_key(|lock| lock.data.attempt);
+        let dealer_set = match &justify {
+            Some(lock) => lock.data.dealer_set.clone(),
+            None => DealerSet {
+                dealers: session.dealers(),
+                start_time_us: duration_since_epoch().as_micros() as u64,
+            },
+        };
+        info!(
+            epoch = epoch,
+            "[DKG] Proposing dealers {:?} in attempt {}", dealer_set.dealers, attempt
+        );
+
+        let proposal = DealerSetProposal {
+            epoch,
+            attempt,
+            dealer_set: dealer_set.clone(),
+            justify,
+        };
+        let prepared = self
+            .collect_votes(session, DKGMessage::Proposal(proposal), DealerSetVoteData {
+                epoch,
+                attempt,
+                phase: VotePhase::Prepare,
+                dealer_set,
+            })
+            .await;
+        let commit = DealerSetVoteData {
+            phase: VotePhase::Commit,
+            ..prepared.data.clone()
+        };
+        self.collect_votes(session, DKGMessage::PrepareCertificate(prepared), commit)
+            .await
+    }
+
+    async fn collect_votes(
+        &self,
+        session: &DKGSession,
+        msg: DKGMessage,
+        data: DealerSetVoteData,
+    ) -> DealerSetCertificate {
+        let signatures = self
+            .collect(session, msg, Duration::ZERO, |author, response| match response {
+                DKGMessage::Vote(vote) if vote.author == author && vote.data == data => {
+                    self.epoch_state
+                        .verifier
+                        .verify(author, &vote.data, &vote.signature)?;
+                    Ok(vote.signature)
+                },
+                _ => Err(anyhow!("invalid vote")),
+            })
+            .await;
+        DealerSetCertificate { data, signatures }
+    }
+
+    /// Sends `msg` to the validators until a quorum responds, and keeps retrying the others for
+    /// `grace` afterwards.
+    async fn collect<T>(
+        &self,
+        session: &DKGSession,
+        msg: DKGMessage,
+        grace: Duration,
+        verify: impl Fn(Author, DKGMessage) -> anyhow::Result<T>,
+    ) -> BTreeMap<Author, T> {
+        let mut responses = BTreeMap::new();
+        let mut quorum_at: Option<Instant> = None;
+        loop {
+            let pending: Vec<Author> = self
+                .epoch_state
+                .verifier
+                .get_ordered_account_addresses_iter()
+                .filter(|author| !responses.contains_key(author))
+                .collect();
+            if pending.is_empty() || quorum_at.is_some_and(|at| at.elapsed() >= grace) {
+                return responses;
+            }
+            let results = join_all(pending.into_iter().map(|peer| {
+                let msg = msg.clone();
+                async move { (peer, self.request(session, peer, msg).await) }
+            }))
+            .await;
+            for (peer, result) in results {
+                match result.and_then(|response| verify(peer, response)) {
+                    Ok(value) => {
+                        responses.insert(peer, value);
+                    },
+                    Err(e) => debug!(
+                        epoch = session.epoch(),
+                        remote_peer = peer,
+                        error = ?e,
+                        "[DKG] Request failed"
+                    ),
+                }
+            }
+            if quorum_at.is_none()
+                && self
+                    .epoch_state
+                    .verifier
+                    .check_voting_power(responses.keys(), true)
+                    .is_ok()
+            {
+                quorum_at = Some(Instant::now());
+            }
+            if quorum_at.is_none() || !grace.is_zero() {
+                tokio::time::sleep(self.retry_interval).await;
+            }
+        }
+    }
+}
+
+fn verify_dealing(
+    pub_params: &PublicParams,
+    dealer: usize,
+    transcript: &[u8],
+) -> anyhow::Result<Transcript> {
+    let transcript: Transcript = bcs::from_bytes(transcript)?;
+    DefaultDKG::verify_transcript(pub_params, &transcript)?;
+    let dealers = DefaultDKG::get_dealers(&transcript);
+    ensure!(
+        dealers.len() == 1 && dealers.contains(&(dealer as u64)),
+        "transcript is not dealt by validator {} alone: {:?}",
+        dealer,
+        dealers
+    );
+    Ok(transcript)
+}
+
+/// Answers the requests of the peers for the current DKG session. It runs outside of the epoch
+/// manager, which is blocked on `LocalDKG::run` while a new epoch starts.
+pub struct DKGServer {
+    current_session: CurrentDKGSession,
+}
+
+impl DKGServer {
+    pub fn new(current_session: CurrentDKGSession) -> Self {
+        Self { current_session }
+    }
+
+    pub async fn start(self, mut rpc_rx: aptos_channel::Receiver<Author, IncomingDKGRequest>) {
+        while let Some(request) = rpc_rx.next().await {
+            let session = self.current_session.lock().clone();
+            let response = match session {
+                Some(session) => session.process(request.sender, request.req),
+                None => Err(anyhow!("no DKG session yet")),
+            };
+            match response {
+                Ok(msg) => {
+                    let response = request
+                        .protocol
+                        .to_bytes(&ConsensusMsg::DKGMessage(msg))
+                        .map(Bytes::from)
+                        .map_err(RpcError::Error);
+                    if request.response_sender.send(response).is_err() {
+                        warn!("[DKG] Failed to respond to request");
+                    }
+                },
+                // Dropping the response sender fails the rpc, the peer retries later
+                Err(e) => debug!(
+                    remote_peer = request.sender,
+                    error = ?e,
+                    "[DKG] Rejected request"
+                ),
+            }
+        }
+        info!("[DKG] Server stops");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gaptos::aptos_crypto::bls12381;
+    use gaptos::aptos_temppath::TempPath;
+    use gaptos::aptos_types::{
+        dkg::real_dkg::maybe_dk_from_bls_sk, validator_verifier::ValidatorVerifier,
+    };
+    use std::{collections::BTreeSet, path::PathBuf, str::FromStr};
+
+    fn validators(weights: &[u64]) -> (Vec<bls12381::PrivateKey>, EpochState) {
+        let private_keys: Vec<_> = weights
+            .iter()
+            .map(|_| bls12381::PrivateKey::generate_for_testing())
+            .collect();
+        let infos = private_keys
+            .iter()
+            .zip(weights)
+            .enumerate()
+            .map(|(i, (sk, weight))| {
+                ValidatorConsensusInfo::new(
+                    Author::from_str(&format!("{:x}", i)).unwrap(),
+                    bls12381::PublicKey::from(sk),
+                    *weight,
+                )
+            })
+            .collect();
+        (private_keys, EpochState::new(1, ValidatorVerifier::new(infos)))
+    }
+
+    /// Hands the requests straight to the sessions of the other validators.
+    struct LocalNetwork {
+        author: Author,
+        sessions: Arc<BTreeMap<Author, CurrentDKGSession>>,
+    }
+
+    #[async_trait]
+    impl DKGNetwork for LocalNetwork {
+        async fn request_dkg(
+            &self,
+            peer: Author,
+            msg: DKGMessage,
+            _timeout: Duration,
+        ) -> anyhow::Result<DKGMessage> {
+            let session = self.sessions[&peer]
+                .lock()
+                .clone()
+                .ok_or_else(|| anyhow!("{} is offline", peer))?;
+            session.process(self.author, msg)
+        }
+    }
+
+    #[test]
+    fn test_aggregate_dealings_of_all_validators() {
+        let (private_keys, epoch_state) = validators(&[1, 2, 3]);
+        let metadata =
+            session_metadata(&epoch_state, &OnChainRandomnessConfig::default_enabled());
+        assert_eq!(metadata.dealer_epoch + 1, epoch_state.epoch);
+        let pub_params = DefaultDKG::new_public_params(&metadata);
+
+        let mut rng = thread_rng();
+        let dealings: Vec<_> = private_keys
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| {
+                let input_secret = <DefaultDKG as DKGTrait>::InputSecret::generate(&mut rng);
+                let transcript = DefaultDKG::generate_transcript(
+                    &mut rng,
+                    &pub_params,
+                    &input_secret,
+                    i as u64,
+                    sk,
+                );
+                bcs::to_bytes(&transcript).unwrap()
+            })
+            .collect();
+        assert!(verify_dealing(&pub_params, 1, &dealings[0]).is_err());
+
+        let mut transcripts = dealings
+            .iter()
+            .enumerate()
+            .map(|(i, dealing)| verify_dealing(&pub_params, i, dealing).unwrap());
+        let mut aggregated = transcripts.next().unwrap();
+        for transcript in transcripts {
+            DefaultDKG::aggregate_transcripts(&pub_params, &mut aggregated, transcript);
+        }
+        DefaultDKG::verify_transcript(&pub_params, &aggregated).unwrap();
+        assert_eq!(
+            DefaultDKG::get_dealers(&aggregated),
+            BTreeSet::from([0, 1, 2])
+        );
+        for (i, sk) in private_keys.iter().enumerate() {
+            DefaultDKG::decrypt_secret_share_from_transcript(
+                &pub_params,
+                &aggregated,
+                i as u64,
+                &maybe_dk_from_bls_sk(sk).unwrap(),
+            )
+            .unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_agree_without_an_offline_dealer() {
+        let (private_keys, epoch_state) = validators(&[1, 1, 1, 1]);
+        let epoch_state = Arc::new(epoch_state);
+        let authors = epoch_state.verifier.get_ordered_account_addresses();
+        let sessions: Arc<BTreeMap<_, CurrentDKGSession>> = Arc::new(
+            authors
+                .iter()
+                .map(|author| (*author, Arc::new(Mutex::new(None))))
+                .collect(),
+        );
+        let db_paths: Vec<_> = authors.iter().map(|_| TempPath::new()).collect();
+
+        // Validator 0 leads the first attempt and never deals
+        let runs = (1..authors.len()).map(|i| {
+            let dkg = LocalDKG::new(
+                authors[i],
+                epoch_state.clone(),
+                &OnChainRandomnessConfig::default_enabled(),
+                Arc::new(private_keys[i].clone()),
+                Arc::new(ConsensusDB::new(&db_paths[i], &PathBuf::new())),
+                sessions[&authors[i]].clone(),
+                Arc::new(LocalNetwork {
+                    author: authors[i],
+                    sessions: sessions.clone(),
+                }),
+            )
+            .with_timeouts(
+                Duration::from_millis(100),
+                Duration::from_millis(50),
+                Duration::from_secs(5),
+            );
+            tokio::spawn(dkg.run())
+        });
+        let sessions: Vec<DKGSessionState> = join_all(runs)
+            .await
+            .into_iter()
+            .map(|run| run.unwrap().unwrap().last_completed.unwrap())
+            .collect();
+
+        for session in &sessions[1..] {
+            assert_eq!(session.transcript, sessions[0].transcript);
+            assert_eq!(session.start_time_us, sessions[0].start_time_us);
+        }
+        let pub_params = DefaultDKG::new_public_params(&sessions[0].metadata);
+        let aggregated: Transcript = bcs::from_bytes(&sessions[0].transcript).unwrap();
+        DefaultDKG::verify_transcript(&pub_params, &aggregated).unwrap();
+        assert_eq!(
+            DefaultDKG::get_dealers(&aggregated),
+            BTreeSet::from([1, 2, 3])
+        );
+        for (i, sk) in private_keys.iter().enumerate().skip(1) {
+            DefaultDKG::decrypt_secret_share_from_transcript(
+                &pub_params,
+                &aggregated,
+                i as u64,
+                &maybe_dk_from_bls_sk(sk).unwrap(),
+            )
+            .unwrap();
+        }
+    }
+}
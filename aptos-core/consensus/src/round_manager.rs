@@ -14,6 +14,7 @@ use crate::{
         QC_AGGREGATED_FROM_VOTES, SYNC_INFO_RECEIVED_WITH_NEWER_CERT,
     },
     error::{error_kind, VerifyError},
+    execution_divergence::DivergenceState,
     liveness::{
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
@@ -33,6 +34,7 @@ use crate::{
     util::is_vtxn_expected,
 };
 use anyhow::{bail, ensure, Context};
+use api_types::equivocation::{EquivocationKind, GLOBAL_EQUIVOCATION_HANDLER};
 use gaptos::aptos_channels::aptos_channel;
 use gaptos::aptos_config::config::ConsensusConfig;
 use aptos_consensus_types::{
@@ -40,6 +42,7 @@ use aptos_consensus_types::{
     block_data::BlockType,
     common::{Author, Round},
     delayed_qc_msg::DelayedQcMsg,
+    equivocation::EquivocationEvidence,
     order_vote_msg::OrderVoteMsg,
     proof_of_store::{ProofCache, ProofOfStoreMsg, SignedBatchInfoMsg},
     proposal_msg::ProposalMsg,
@@ -250,6 +253,7 @@ pub struct RoundManager {
     // To avoid duplicate broadcasts for the same block, we keep track of blocks for
     // which we recently broadcasted fast shares.
     blocks_with_broadcasted_fast_shares: LruCache<HashValue, ()>,
+    divergence_state: Arc<DivergenceState>,
     futures: FuturesUnordered<Pin<Box<dyn Future<Output = (anyhow::Result<()>, Block)> + Send>>>,
 }
 
@@ -270,6 +274,7 @@ impl RoundManager {
         randomness_config: OnChainRandomnessConfig,
         jwk_consensus_config: OnChainJWKConsensusConfig,
         fast_rand_config: Option<RandConfig>,
+        divergence_state: Arc<DivergenceState>,
     ) -> Self {
         // when decoupled execution is false,
         // the counter is still static.
@@ -299,6 +304,7 @@ impl RoundManager {
             fast_rand_config,
             pending_order_votes: PendingOrderVotes::new(),
             blocks_with_broadcasted_fast_shares: LruCache::new(5),
+            divergence_state,
             futures: FuturesUnordered::new(),
         }
     }
@@ -689,7 +695,9 @@ impl RoundManager {
     }
 
     fn sync_only(&self) -> bool {
-        let sync_or_not = self.local_config.sync_only || self.block_store.vote_back_pressure();
+        let sync_or_not = self.local_config.sync_only
+            || self.block_store.vote_back_pressure()
+            || self.divergence_state.has_diverged();
         if self.block_store.vote_back_pressure() {
             warn!("Vote back pressure is set");
         }
@@ -860,8 +868,12 @@ impl RoundManager {
             self.local_config.max_receiving_block_bytes,
         );
 
+        let is_valid_proposal = self.proposer_election.is_valid_proposal(&proposal);
+        for evidence in self.proposer_election.take_equivocations() {
+            self.report_equivocation(evidence);
+        }
         ensure!(
-            self.proposer_election.is_valid_proposal(&proposal),
+            is_valid_proposal,
             "[RoundManager] Proposer {} for block {} is not a valid proposer for this round or created duplicate proposal",
             author,
             proposal,
@@ -1121,6 +1133,10 @@ impl RoundManager {
         vote: &Vote,
         qc: Arc<QuorumCert>,
     ) -> anyhow::Result<()> {
+        ensure!(
+            !self.divergence_state.has_diverged(),
+            "[RoundManager] execution diverged, stop order voting"
+        );
         if let Some(proposed_block) = self.block_store.get_block(vote.vote_data().proposed().id()) {
             // Generate an order vote with ledger_info = proposed_block
             let order_vote_proposal = proposed_block.order_vote_proposal(qc.clone());
@@ -1228,10 +1244,38 @@ impl RoundManager {
         let vote_reception_result = self
             .round_state
             .insert_vote(vote, &self.epoch_state.verifier);
+        if let VoteReceptionResult::EquivocateVote = vote_reception_result {
+            if let Some(first) = self.round_state.author_vote(&vote.author()) {
+                self.report_equivocation(EquivocationEvidence::Vote {
+                    first: first.clone(),
+                    second: vote.clone(),
+                });
+            }
+        }
         self.process_vote_reception_result(vote, vote_reception_result)
             .await
     }
 
+    /// Persists the evidence to ConsensusDB and hands it to the execution layer. Both messages
+    /// were verified before, so the evidence holds against the validator set of this epoch.
+    fn report_equivocation(&self, evidence: EquivocationEvidence) {
+        counters::EQUIVOCATION_EVIDENCE
+            .with_label_values(&[match evidence.kind() {
+                EquivocationKind::Proposal => "proposal",
+                EquivocationKind::Vote => "vote",
+            }])
+            .inc();
+        if let Err(e) = self.storage.save_equivocation_evidence(&evidence) {
+            error!(error = ?e, "[RoundManager] Failed to save equivocation evidence");
+        }
+        if let Some(handler) = GLOBAL_EQUIVOCATION_HANDLER.get() {
+            match evidence.to_payload() {
+                Ok(payload) => handler(payload),
+                Err(e) => error!(error = ?e, "[RoundManager] Failed to encode equivocation evidence"),
+            }
+        }
+    }
+
     async fn process_vote_reception_result(
         &mut self,
         vote: &Vote,
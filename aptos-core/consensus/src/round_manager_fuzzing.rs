@@ -6,6 +6,7 @@
 
 use crate::{
     block_storage::{pending_blocks::PendingBlocks, BlockStore},
+    execution_divergence::DivergenceState,
     liveness::{
         proposal_generator::{
             ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
@@ -96,6 +97,7 @@ fn build_empty_store(
         false,
         Arc::new(Mutex::new(PendingBlocks::new())),
         None,
+        Arc::new(DivergenceState::default()),
     ))
 }
 
@@ -226,6 +228,7 @@ fn create_node_for_fuzzing() -> RoundManager {
         OnChainRandomnessConfig::default_enabled(),
         OnChainJWKConsensusConfig::default_enabled(),
         None,
+        Arc::new(DivergenceState::default()),
     )
 }
 
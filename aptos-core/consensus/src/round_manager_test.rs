@@ -4,6 +4,7 @@
 
 use crate::{
     block_storage::{pending_blocks::PendingBlocks, BlockReader, BlockStore},
+    execution_divergence::DivergenceState,
     liveness::{
         proposal_generator::{
             ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
@@ -295,6 +296,7 @@ impl NodeSetup {
             false,
             Arc::new(Mutex::new(PendingBlocks::new())),
             None,
+            Arc::new(DivergenceState::default()),
         ));
 
         let proposer_election = Self::create_proposer_election(proposers.clone());
@@ -343,6 +345,7 @@ impl NodeSetup {
             onchain_randomness_config.clone(),
             onchain_jwk_consensus_config.clone(),
             None,
+            Arc::new(DivergenceState::default()),
         );
         block_on(round_manager.init(last_vote_sent));
         Self {
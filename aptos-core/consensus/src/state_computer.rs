@@ -9,6 +9,7 @@ use crate::{
     block_preparer::BlockPreparer,
     counters,
     error::StateSyncError,
+    execution_divergence::DivergenceState,
     execution_pipeline::ExecutionPipeline,
     monitor,
     payload_manager::TPayloadManager,
@@ -90,6 +91,7 @@ pub struct ExecutionProxy {
     transaction_filter: Arc<TransactionFilter>,
     execution_pipeline: ExecutionPipeline,
     state: RwLock<Option<MutableState>>,
+    divergence_state: Arc<DivergenceState>,
 }
 
 impl ExecutionProxy {
@@ -120,6 +122,7 @@ impl ExecutionProxy {
         handle: &tokio::runtime::Handle,
         txn_filter: TransactionFilter,
         enable_pre_commit: bool,
+        divergence_state: Arc<DivergenceState>,
     ) -> Self {
         let (tx, mut rx) =
             gaptos::aptos_channels::new::<NotificationType>(10, &counters::PENDING_STATE_SYNC_NOTIFICATION);
@@ -147,9 +150,14 @@ impl ExecutionProxy {
             transaction_filter: Arc::new(txn_filter),
             execution_pipeline,
             state: RwLock::new(None),
+            divergence_state,
         }
     }
 
+    pub fn divergence_state(&self) -> Arc<DivergenceState> {
+        self.divergence_state.clone()
+    }
+
     fn transactions_to_commit(
         &self,
         executed_block: &PipelinedBlock,
@@ -208,6 +216,7 @@ impl ExecutionProxy {
             self.state_sync_notifier.clone(),
             payload_manager,
             self.txn_notifier.clone(),
+            self.divergence_state.clone(),
         )
     }
 }
@@ -348,14 +357,12 @@ impl StateComputer for ExecutionProxy {
         let executor = self.executor.clone();
         let proof = finality_proof.clone();
 
+        // Fails if the execution diverged, the node stops committing then
         monitor!(
             "commit_block",
-            tokio::task::spawn_blocking(move || {
-                executor.commit_ledger(block_ids, proof).expect("Failed to commit blocks");
-            })
-            .await
+            tokio::task::spawn_blocking(move || executor.commit_ledger(block_ids, proof)).await
         )
-        .expect("spawn_blocking failed");
+        .expect("spawn_blocking failed")?;
 
         let blocks = blocks.to_vec();
         let wrapped_callback = move || {
@@ -578,12 +585,13 @@ async fn test_commit_sync_race() {
         &tokio::runtime::Handle::current(),
         TransactionFilter::new(Filter::empty()),
         true,
+        Arc::new(DivergenceState::default()),
     );
 
     executor.new_epoch(
         &EpochState::empty(),
         Arc::new(DirectMempoolPayloadManager {}),
-        create_transaction_shuffler(TransactionShufflerType::NoShuffling),
+        create_transaction_shuffler(TransactionShufflerType::NoShuffling, None),
         BlockExecutorConfigFromOnchain::new_no_block_limit(),
         create_transaction_deduper(TransactionDeduperType::NoDedup),
         false,
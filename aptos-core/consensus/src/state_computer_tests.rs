@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    error::MempoolError, pipeline::pipeline_phase::CountedRequest, state_computer::ExecutionProxy, state_replication::StateComputer, transaction_deduper::NoOpDeduper, transaction_filter::TransactionFilter, transaction_shuffler::NoOpShuffler, txn_notifier::TxnNotifier
+    error::MempoolError, execution_divergence::DivergenceState, pipeline::pipeline_phase::CountedRequest, state_computer::ExecutionProxy, state_replication::StateComputer, transaction_deduper::NoOpDeduper, transaction_filter::TransactionFilter, transaction_shuffler::NoOpShuffler, txn_notifier::TxnNotifier
 };
 
 use gaptos::aptos_config::config::transaction_filter_type::Filter;
@@ -144,6 +144,7 @@ async fn schedule_compute_should_discover_validator_txns() {
         &Handle::current(),
         TransactionFilter::new(Filter::empty()),
         true,
+        Arc::new(DivergenceState::default()),
     );
 
     let validator_txn_0 = ValidatorTransaction::dummy(vec![0xFF; 99]);
@@ -198,6 +199,7 @@ async fn commit_should_discover_validator_txns() {
         &tokio::runtime::Handle::current(),
         TransactionFilter::new(Filter::empty()),
         true,
+        Arc::new(DivergenceState::default()),
     );
 
     let validator_txn_0 = ValidatorTransaction::dummy(vec![0xFF; 99]);
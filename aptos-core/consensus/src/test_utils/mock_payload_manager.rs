@@ -6,6 +6,7 @@ use crate::{
     payload_client::{user::quorum_store_client::QuorumStoreClient, PayloadClient},
 };
 use anyhow::Result;
+use api_types::block_budget::BlockBudget;
 use aptos_consensus_types::{
     block::block_test_utils::random_payload,
     common::{Payload, PayloadFilter},
@@ -70,6 +71,7 @@ impl PayloadClient for MockPayloadManager {
         _pending_uncommitted_blocks: usize,
         _recent_fill_fraction: f32,
         _block_timestamp: Duration,
+        _block_budget: BlockBudget,
     ) -> Result<(Vec<ValidatorTransaction>, Payload), QuorumStoreError> {
         // generate 1k txn is too slow with coverage instrumentation
         Ok((
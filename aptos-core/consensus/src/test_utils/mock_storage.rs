@@ -10,7 +10,8 @@ use crate::{
 };
 use anyhow::Result;
 use aptos_consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
+    block::Block, equivocation::EquivocationEvidence, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
 };
 use gaptos::aptos_crypto::HashValue;
 use gaptos::aptos_infallible::Mutex;
@@ -30,6 +31,7 @@ pub struct MockSharedStorage {
 
     // Liveness state
     pub highest_2chain_timeout_certificate: Mutex<Option<TwoChainTimeoutCertificate>>,
+    pub equivocations: Mutex<Vec<EquivocationEvidence>>,
     pub validator_set: ValidatorSet,
 }
 
@@ -41,6 +43,7 @@ impl MockSharedStorage {
             lis: Mutex::new(HashMap::new()),
             last_vote: Mutex::new(None),
             highest_2chain_timeout_certificate: Mutex::new(None),
+            equivocations: Mutex::new(Vec::new()),
             validator_set,
         }
     }
@@ -201,6 +204,11 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(())
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()> {
+        self.shared_storage.equivocations.lock().push(evidence.clone());
+        Ok(())
+    }
+
     fn recover_from_ledger(&self) -> LedgerRecoveryData {
         self.get_ledger_recovery_data()
     }
@@ -283,6 +291,10 @@ impl PersistentLivenessStorage for EmptyStorage {
         Ok(())
     }
 
+    fn save_equivocation_evidence(&self, _: &EquivocationEvidence) -> Result<()> {
+        Ok(())
+    }
+
     fn recover_from_ledger(&self) -> LedgerRecoveryData {
         LedgerRecoveryData::new(LedgerInfoWithSignatures::new(
             LedgerInfo::mock_genesis(None),
@@ -29,7 +29,8 @@ mod mock_state_computer;
 mod mock_storage;
 
 use crate::{
-    block_storage::pending_blocks::PendingBlocks, pipeline::execution_client::DummyExecutionClient,
+    block_storage::pending_blocks::PendingBlocks, execution_divergence::DivergenceState,
+    pipeline::execution_client::DummyExecutionClient,
     util::mock_time_service::SimulatedTimeService,
 };
 use aptos_consensus_types::{block::block_test_utils::gen_test_certificate, common::Payload};
@@ -97,6 +98,7 @@ pub async fn build_empty_tree() -> Arc<BlockStore> {
         Arc::from(DirectMempoolPayloadManager::new()),
         false,
         Arc::new(Mutex::new(PendingBlocks::new())),
+        Arc::new(DivergenceState::default()),
     ))
 }
 
@@ -0,0 +1,171 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::transaction_shuffler::TransactionShuffler;
+use api_types::{ConflictKey, ExecutionChannel};
+use aptos_mempool::core_mempool::transaction::VerifiedTxn;
+use gaptos::aptos_logger::warn;
+use gaptos::aptos_types::transaction::SignedTransaction;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+/// How many not yet placed transactions are looked at, in the original order, for one that does
+/// not conflict with the window. Bounds the shuffling to O(n * MAX_LOOK_AHEAD).
+const MAX_LOOK_AHEAD: usize = 256;
+
+/// A transaction shuffler for payloads which are opaque to consensus, e.g. EVM transactions. The
+/// conflict keys of every transaction (a `to` address, touched storage slots, a contract id...)
+/// are supplied by the execution layer through `ExecutionChannel::conflict_keys`, and the 32 byte
+/// sender address is always added as an implicit key, so it has to match how the execution layer
+/// keys accounts. Like `SenderAwareShuffler`, it maintains the keys of the
+/// last `conflict_window_size` transactions added to the block and prefers the first transaction
+/// which shares no key with them. If there is none, the first remaining transaction is added.
+///
+/// The relative order of the transactions of a sender is always kept, since only the earliest
+/// remaining transaction of each sender is a candidate. If the execution layer returns no keys,
+/// the block is left unchanged.
+pub struct ConflictKeyAwareShuffler {
+    conflict_window_size: usize,
+    execution_api: Arc<dyn ExecutionChannel>,
+}
+
+impl ConflictKeyAwareShuffler {
+    pub fn new(conflict_window_size: usize, execution_api: Arc<dyn ExecutionChannel>) -> Self {
+        Self { conflict_window_size, execution_api }
+    }
+}
+
+impl TransactionShuffler for ConflictKeyAwareShuffler {
+    fn shuffle(&self, txns: Vec<SignedTransaction>) -> Vec<SignedTransaction> {
+        if txns.len() <= 1 || self.conflict_window_size == 0 {
+            return txns;
+        }
+
+        let verified_txns: Vec<api_types::VerifiedTxn> =
+            txns.iter().map(|txn| VerifiedTxn::from(txn).into()).collect();
+        let Some(keys) = self.execution_api.conflict_keys(&verified_txns) else {
+            return txns;
+        };
+        if keys.len() != txns.len() {
+            warn!(
+                "execution layer returned conflict keys for {} of {} txns, skip shuffling",
+                keys.len(),
+                txns.len()
+            );
+            return txns;
+        }
+
+        let keys = txns
+            .iter()
+            .zip(keys)
+            .map(|(txn, mut keys)| {
+                keys.push(txn.sender().to_vec());
+                keys
+            })
+            .collect();
+        let order = shuffle_by_conflict_keys(keys, self.conflict_window_size);
+        let mut txns: Vec<_> = txns.into_iter().map(Some).collect();
+        order.into_iter().map(|index| txns[index].take().expect("txn is placed once")).collect()
+    }
+}
+
+/// Returns the shuffled order of transactions with the given conflict keys, as indices into
+/// `keys`. The last key of every transaction is its sender, and a transaction is only placed
+/// after the earlier transactions of the same sender.
+fn shuffle_by_conflict_keys(
+    keys: Vec<Vec<ConflictKey>>,
+    conflict_window_size: usize,
+) -> Vec<usize> {
+    let num_txns = keys.len();
+
+    // Intern the keys, so that the window only counts integers
+    let mut key_ids: HashMap<ConflictKey, usize> = HashMap::new();
+    let mut txn_keys: Vec<Vec<usize>> = Vec::with_capacity(num_txns);
+    // the previous transaction of the same sender, which has to be placed first
+    let mut prev_of_sender: Vec<Option<usize>> = Vec::with_capacity(num_txns);
+    let mut last_of_sender: HashMap<usize, usize> = HashMap::new();
+    for (index, keys) in keys.into_iter().enumerate() {
+        let mut ids: Vec<usize> = keys
+            .into_iter()
+            .map(|key| {
+                let next_id = key_ids.len();
+                *key_ids.entry(key).or_insert(next_id)
+            })
+            .collect();
+        let sender_id = *ids.last().expect("sender key");
+        prev_of_sender.push(last_of_sender.insert(sender_id, index));
+        ids.sort_unstable();
+        ids.dedup();
+        txn_keys.push(ids);
+    }
+
+    let mut key_counts = vec![0usize; key_ids.len()];
+    let mut window: VecDeque<usize> = VecDeque::with_capacity(conflict_window_size + 1);
+    let mut placed = vec![false; num_txns];
+    let mut remaining: VecDeque<usize> = (0..num_txns).collect();
+    let mut order = Vec::with_capacity(num_txns);
+    while !remaining.is_empty() {
+        // The first remaining transaction is always the earliest of its sender, so it is the
+        // fallback when every candidate conflicts.
+        let position = remaining
+            .iter()
+            .take(MAX_LOOK_AHEAD)
+            .position(|&index| {
+                prev_of_sender[index].map_or(true, |prev| placed[prev])
+                    && txn_keys[index].iter().all(|key| key_counts[*key] == 0)
+            })
+            .unwrap_or(0);
+        let index = remaining.remove(position).expect("position is in range");
+
+        placed[index] = true;
+        for key in &txn_keys[index] {
+            key_counts[*key] += 1;
+        }
+        window.push_back(index);
+        if window.len() > conflict_window_size {
+            let dropped = window.pop_front().expect("window is not empty");
+            for key in &txn_keys[dropped] {
+                key_counts[*key] -= 1;
+            }
+        }
+        order.push(index);
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shuffle_by_conflict_keys;
+
+    fn keys(txns: &[(u8, &[u8])]) -> Vec<Vec<Vec<u8>>> {
+        txns.iter()
+            .map(|(sender, keys)| {
+                let mut keys: Vec<Vec<u8>> = keys.iter().map(|key| vec![*key]).collect();
+                keys.push(vec![0xff, *sender]);
+                keys
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_non_conflicting_order_is_kept() {
+        let txns = keys(&[(1, &[10]), (2, &[11]), (3, &[12]), (4, &[])]);
+        assert_eq!(shuffle_by_conflict_keys(txns, 4), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_conflicting_txns_are_spread() {
+        // txns 0, 1 and 2 call the same contract
+        let txns = keys(&[(1, &[10]), (2, &[10]), (3, &[10]), (4, &[11]), (5, &[12])]);
+        assert_eq!(shuffle_by_conflict_keys(txns, 2), vec![0, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn test_sender_order_is_kept() {
+        // the second txn of sender 1 does not conflict, but has to follow its first txn
+        let txns = keys(&[(2, &[10]), (1, &[10]), (1, &[11]), (3, &[12])]);
+        assert_eq!(shuffle_by_conflict_keys(txns, 2), vec![0, 3, 1, 2]);
+    }
+}
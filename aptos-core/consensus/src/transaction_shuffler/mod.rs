@@ -1,11 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use api_types::ExecutionChannel;
+use conflict_key_aware::ConflictKeyAwareShuffler;
 use gaptos::aptos_logger::info;
 use gaptos::aptos_types::{on_chain_config::TransactionShufflerType, transaction::SignedTransaction};
 use sender_aware::SenderAwareShuffler;
 use std::sync::Arc;
 
+mod conflict_key_aware;
 mod deprecated_fairness;
 mod sender_aware;
 mod use_case_aware;
@@ -24,11 +27,38 @@ impl TransactionShuffler for NoOpShuffler {
     }
 }
 
+/// Conflict window size of the conflict key shuffler if `GRAVITY_TXN_SHUFFLER_WINDOW` is unset
+const DEFAULT_CONFLICT_KEY_WINDOW_SIZE: usize = 32;
+
+/// Creates the shuffler of `shuffler_type`, unless `GRAVITY_TXN_SHUFFLER=conflict_key` selects
+/// the shuffler driven by the conflict keys of `execution_api`. Like the onchain config, the
+/// setting must be the same on all validators.
 pub fn create_transaction_shuffler(
     shuffler_type: TransactionShufflerType,
+    execution_api: Option<Arc<dyn ExecutionChannel>>,
 ) -> Arc<dyn TransactionShuffler> {
     use TransactionShufflerType::*;
 
+    // TODO(gravity): move to the onchain config
+    match std::env::var("GRAVITY_TXN_SHUFFLER").as_deref() {
+        Err(_) => {},
+        Ok("conflict_key") => {
+            let execution_api =
+                execution_api.expect("the conflict key shuffler needs an execution layer");
+            let conflict_window_size = std::env::var("GRAVITY_TXN_SHUFFLER_WINDOW")
+                .map(|size| size.parse().expect("GRAVITY_TXN_SHUFFLER_WINDOW is not a number"))
+                .unwrap_or(DEFAULT_CONFLICT_KEY_WINDOW_SIZE);
+            info!(
+                "Using conflict key aware transaction shuffling with conflict window size {}",
+                conflict_window_size
+            );
+            return Arc::new(ConflictKeyAwareShuffler::new(conflict_window_size, execution_api));
+        },
+        Ok(shuffler) => {
+            panic!("unknown GRAVITY_TXN_SHUFFLER {:?}, expected conflict_key", shuffler)
+        },
+    }
+
     match shuffler_type {
         NoShuffling => {
             info!("Using no-op transaction shuffling");
@@ -5,6 +5,7 @@
 use crate::{
     counters,
     epoch_manager::EpochManager,
+    execution_divergence::DivergenceState,
     network::NetworkTask,
     network_interface::{ConsensusNetworkClient, DIRECT_SEND, RPC},
     network_tests::{NetworkPlayground, TwinId},
@@ -165,6 +166,7 @@ impl SMRNode {
             Arc::new(InMemRandDb::new()),
             None,
             None,
+            Arc::new(DivergenceState::default()),
         );
         let (network_task, network_receiver) =
             NetworkTask::new(network_service_events, self_receiver);
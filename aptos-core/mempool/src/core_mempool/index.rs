@@ -21,7 +21,8 @@ pub type AccountTransactions = BTreeMap<u64, MempoolTransaction>;
 
 /// PriorityIndex represents the main Priority Queue in Mempool.
 /// It's used to form the transaction block for Consensus.
-/// Transactions are ordered by gas price. Second level ordering is done by expiration time.
+/// Transactions are ordered by ranking score, the tip they pay per gas. Second level ordering is
+/// done by account and sequence number.
 ///
 /// We don't store the full content of transactions in the index.
 /// Instead we use `OrderedQueueKey` - logical reference to the transaction in the main store.
@@ -50,6 +51,7 @@ impl PriorityIndex {
 
     fn make_key(&self, txn: &MempoolTransaction) -> OrderedQueueKey {
         OrderedQueueKey {
+            gas_ranking_score: txn.ranking_score(),
             address: txn.verified_txn().sender(),
             sequence_number: txn.verified_txn().sequence_number(),
             hash: txn.get_hash(),
@@ -60,6 +62,11 @@ impl PriorityIndex {
         self.data.iter().rev()
     }
 
+    /// The key of the txn that is pulled last
+    pub(crate) fn lowest(&self) -> Option<&OrderedQueueKey> {
+        self.data.first()
+    }
+
     pub(crate) fn size(&self) -> usize {
         self.data.len()
     }
@@ -67,6 +74,7 @@ impl PriorityIndex {
 
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
 pub struct OrderedQueueKey {
+    pub gas_ranking_score: u64,
     pub address: AccountAddress,
     pub sequence_number: u64,
     pub hash: HashValue,
@@ -86,6 +94,10 @@ impl PartialOrd for OrderedQueueKey {
 
 impl Ord for OrderedQueueKey {
     fn cmp(&self, other: &OrderedQueueKey) -> Ordering {
+        match self.gas_ranking_score.cmp(&other.gas_ranking_score) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
         match self.address.cmp(&other.address) {
             Ordering::Equal => {}
             ordering => return ordering,
@@ -0,0 +1,161 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An on-disk journal of the txns in mempool. Mempool keeps txns only in memory, so the journal
+//! is replayed on startup to keep pending user txns across restarts.
+
+use crate::core_mempool::{index::TxnPointer, transaction::VerifiedTxn};
+use anyhow::{ensure, Result};
+use api_types::u256_define::TxnHash;
+use gaptos::aptos_crypto::HashValue;
+use gaptos::aptos_logger::info;
+use gaptos::aptos_schemadb::{
+    schema::{KeyCodec, Schema, ValueCodec},
+    ColumnFamilyName, Options, SchemaBatch, DB,
+};
+use gaptos::aptos_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, time::Instant};
+
+/// The journal is only kept if this env var names its directory
+pub const MEMPOOL_JOURNAL_DIR_ENV: &str = "GRAVITY_MEMPOOL_JOURNAL_DIR";
+
+/// The name of the mempool journal db file
+pub const MEMPOOL_JOURNAL_DB_NAME: &str = "mempoolJournalDB";
+
+const JOURNAL_TXN_CF_NAME: ColumnFamilyName = "journal_txn";
+
+/// A journaled txn with the sequence number its sender had on the execution layer when the txn
+/// was added.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct JournalEntry {
+    txn: api_types::VerifiedTxn,
+    // the txn skips its hash when serialized
+    committed_hash: HashValue,
+    pub account_seq_num: u64,
+}
+
+impl JournalEntry {
+    pub fn sender(&self) -> AccountAddress {
+        AccountAddress::new(self.txn.sender.bytes())
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.txn.sequence_number
+    }
+
+    pub fn into_txn(self) -> VerifiedTxn {
+        let _ = self.txn.committed_hash.set(TxnHash::new(*self.committed_hash));
+        self.txn.into()
+    }
+}
+
+/// Txns keyed by sender and sequence number, so that the txns of a sender are adjacent and
+/// ordered by sequence number.
+#[derive(Debug)]
+struct JournalTxnSchema;
+
+impl Schema for JournalTxnSchema {
+    type Key = (AccountAddress, u64);
+    type Value = JournalEntry;
+
+    const COLUMN_FAMILY_NAME: ColumnFamilyName = JOURNAL_TXN_CF_NAME;
+}
+
+impl KeyCodec<JournalTxnSchema> for (AccountAddress, u64) {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut key = self.0.to_vec();
+        key.extend_from_slice(&self.1.to_be_bytes());
+        Ok(key)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == AccountAddress::LENGTH + 8,
+            "unexpected journal key length {}",
+            data.len()
+        );
+        let (sender, sequence_number) = data.split_at(AccountAddress::LENGTH);
+        Ok((AccountAddress::from_bytes(sender)?, u64::from_be_bytes(sequence_number.try_into()?)))
+    }
+}
+
+impl ValueCodec<JournalTxnSchema> for JournalEntry {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+pub struct MempoolJournal {
+    db: DB,
+}
+
+impl MempoolJournal {
+    /// Opens the journal in the directory named by `GRAVITY_MEMPOOL_JOURNAL_DIR`, if set.
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var(MEMPOOL_JOURNAL_DIR_ENV).ok()?;
+        Some(Self::open(dir).expect("MempoolJournal open failed; unable to continue"))
+    }
+
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let path = dir.as_ref().join(MEMPOOL_JOURNAL_DB_NAME);
+        let instant = Instant::now();
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = DB::open(path.clone(), MEMPOOL_JOURNAL_DB_NAME, vec![JOURNAL_TXN_CF_NAME], &opts)?;
+        info!("Opened MempoolJournal at {:?} in {} ms", path, instant.elapsed().as_millis());
+        Ok(Self { db })
+    }
+
+    /// Records `txn`, replacing a journaled txn of the same sender and sequence number.
+    pub(crate) fn append(&self, txn: &VerifiedTxn, account_seq_num: u64) -> Result<()> {
+        let entry = JournalEntry {
+            txn: txn.clone().into(),
+            committed_hash: txn.committed_hash,
+            account_seq_num,
+        };
+        let batch = SchemaBatch::new();
+        batch.put::<JournalTxnSchema>(&(txn.sender, txn.sequence_number), &entry)?;
+        Ok(self.db.write_schemas(batch)?)
+    }
+
+    /// Removes the txn at `sender`/`sequence_number` if its hash is `hash`.
+    pub(crate) fn remove(
+        &self,
+        sender: AccountAddress,
+        sequence_number: u64,
+        hash: &HashValue,
+    ) -> Result<()> {
+        let key = (sender, sequence_number);
+        let Some(entry) = self.db.get::<JournalTxnSchema>(&key)? else {
+            return Ok(());
+        };
+        if entry.committed_hash != *hash {
+            return Ok(());
+        }
+        let batch = SchemaBatch::new();
+        batch.delete::<JournalTxnSchema>(&key)?;
+        Ok(self.db.write_schemas(batch)?)
+    }
+
+    /// Removes `txns` in one write, whatever txn is journaled at their sender and sequence number.
+    pub(crate) fn remove_txns(&self, txns: &[TxnPointer]) -> Result<()> {
+        let batch = SchemaBatch::new();
+        for txn in txns {
+            batch.delete::<JournalTxnSchema>(&(txn.sender, txn.sequence_number))?;
+        }
+        Ok(self.db.write_schemas(batch)?)
+    }
+
+    /// Returns all journaled txns, ordered by sender and sequence number.
+    pub(crate) fn entries(&self) -> Result<Vec<JournalEntry>> {
+        let mut iter = self.db.iter::<JournalTxnSchema>()?;
+        iter.seek_to_first();
+        iter.map(|item| Ok(item?.1)).collect()
+    }
+}
@@ -7,6 +7,7 @@
 use crate::{
     core_mempool::{
         index::TxnPointer,
+        journal::MempoolJournal,
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
         transaction_store::TransactionStore,
     },
@@ -17,14 +18,16 @@ use crate::{
         MempoolSenderBucket, MultiBucketTimelineIndexIds, TimelineIndexIdentifier,
     },
 };
+use api_types::txn_filter;
 use gaptos::aptos_config::config::NodeConfig;
+use block_buffer_manager::block_buffer_manager::{TxnPoolEvent, TxnRef};
 use aptos_consensus_types::common::{TransactionInProgress, TransactionSummary};
 use gaptos::aptos_crypto::HashValue;
 use gaptos::aptos_logger::prelude::*;
 use gaptos::aptos_types::{
     account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
-    transaction::{use_case::UseCaseKey, SignedTransaction},
+    transaction::{use_case::UseCaseKey, SignedTransaction, TransactionPayload},
     vm_status::DiscardedVMStatus,
 };
 use std::{
@@ -39,25 +42,99 @@ use super::transaction::VerifiedTxn;
 pub struct Mempool {
     // Stores the metadata of all transactions in mempool (of all states).
     transactions: TransactionStore,
+    // Records the txns from the execution layer pool, if enabled
+    journal: Option<MempoolJournal>,
 }
 
 impl Mempool {
     pub fn new(config: &NodeConfig) -> Self {
         Mempool {
             transactions: TransactionStore::new(&config.mempool),
+            journal: None,
+        }
+    }
+
+    /// Adds the txns of `journal` that are not committed yet according to `account_seq_num`,
+    /// which returns the sequence number of an account on the execution layer, then records
+    /// the later changes of mempool in `journal`.
+    pub(crate) fn replay_journal(
+        &mut self,
+        journal: MempoolJournal,
+        account_seq_num: impl Fn(&AccountAddress) -> Option<u64>,
+    ) {
+        let entries = journal.entries();
+        // txns evicted while replaying are removed from the journal
+        self.journal = Some(journal);
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("failed to read the mempool journal, skip replay: {:?}", e);
+                return;
+            },
+        };
+        let mut account_seq_nums = HashMap::new();
+        let (mut replayed, mut discarded) = (0, 0);
+        for entry in entries {
+            let sender = entry.sender();
+            let sequence_number = entry.sequence_number();
+            let db_sequence_number = *account_seq_nums
+                .entry(sender)
+                .or_insert_with(|| account_seq_num(&sender).unwrap_or(entry.account_seq_num));
+            let txn = entry.into_txn();
+            let hash = txn.committed_hash;
+            let status = self.send_user_txn(
+                txn,
+                db_sequence_number,
+                TimelineState::NotReady,
+                true,
+                None,
+                Some(BroadcastPeerPriority::Primary),
+            );
+            if status.code == MempoolStatusCode::Accepted {
+                replayed += 1;
+            } else {
+                discarded += 1;
+                self.update_journal(|journal| journal.remove(sender, sequence_number, &hash));
+            }
+        }
+        counters::CORE_MEMPOOL_JOURNAL_REPLAYED_TXNS.inc_by(replayed);
+        info!("replayed {} txns from the mempool journal, discarded {}", replayed, discarded);
+    }
+
+    fn update_journal(&self, update: impl FnOnce(&MempoolJournal) -> anyhow::Result<()>) {
+        if let Some(journal) = &self.journal {
+            if let Err(e) = update(journal) {
+                warn!("failed to update the mempool journal: {:?}", e);
+            }
+        }
+    }
+
+    /// Removes the txns that left mempool on commit or eviction from the journal.
+    fn remove_from_journal(&mut self) {
+        let removed = self.transactions.take_removed_txns();
+        if !removed.is_empty() {
+            self.update_journal(|journal| journal.remove_txns(&removed));
         }
     }
 
     /// This function will be called once the transaction has been stored.
     pub(crate) fn commit_transaction(&mut self, sender: &AccountAddress, sequence_number: u64) {
-        debug!(
-            "commit txn {} {}",
-            sender,
-            sequence_number
-        );
-        counters::MEMPOOL_TXN_COMMIT_COUNT.inc();
-        self.transactions
-            .commit_transaction(sender, sequence_number);
+        self.commit_transactions(&[(*sender, sequence_number)]);
+    }
+
+    /// Commits the txns of a commit notification, the journal is updated once for all of them.
+    pub(crate) fn commit_transactions(&mut self, txns: &[(AccountAddress, u64)]) {
+        for (sender, sequence_number) in txns {
+            debug!(
+                "commit txn {} {}",
+                sender,
+                sequence_number
+            );
+            counters::MEMPOOL_TXN_COMMIT_COUNT.inc();
+            self.transactions
+                .commit_transaction(sender, *sequence_number);
+        }
+        self.remove_from_journal();
     }
 
     pub(crate) fn log_commit_transaction(
@@ -125,6 +202,65 @@ impl Mempool {
         self.log_reject_transaction(sender, sequence_number, label);
         self.transactions
             .reject_transaction(sender, sequence_number, hash);
+        self.update_journal(|journal| journal.remove(*sender, sequence_number, hash));
+    }
+
+    /// Removes a txn that the execution layer dropped from its pool. Nothing is removed if the
+    /// txn at `sender`/`sequence_number` is a different one.
+    pub(crate) fn discard_transaction(
+        &mut self,
+        sender: &AccountAddress,
+        sequence_number: u64,
+        hash: &HashValue,
+    ) {
+        self.log_reject_transaction(sender, sequence_number, counters::COMMIT_DISCARDED_LABEL);
+        self.transactions
+            .reject_transaction(sender, sequence_number, hash);
+        self.update_journal(|journal| journal.remove(*sender, sequence_number, hash));
+    }
+
+    /// Follows a change of the execution layer's txn pool.
+    pub(crate) fn apply_txn_pool_event(&mut self, event: TxnPoolEvent) {
+        let discard = |mempool: &mut Self, txn: TxnRef| {
+            mempool.discard_transaction(
+                &AccountAddress::new(txn.sender.bytes()),
+                txn.sequence_number,
+                &HashValue::new(txn.hash),
+            )
+        };
+        let add = |mempool: &mut Self, txn: api_types::VerifiedTxnWithAccountSeqNum| {
+            let account_seq_num = txn.account_seq_num;
+            let txn: VerifiedTxn = txn.txn.into();
+            let journaled = mempool.journal.is_some().then(|| txn.clone());
+            let status = mempool.send_user_txn(
+                txn,
+                account_seq_num,
+                TimelineState::NotReady,
+                true,
+                None,
+                Some(BroadcastPeerPriority::Primary),
+            );
+            if status.code != MempoolStatusCode::Accepted {
+                warn!("failed to add txn from the execution layer pool: {:?}", status);
+            } else if let Some(txn) = journaled {
+                mempool.update_journal(|journal| journal.append(&txn, account_seq_num));
+            }
+        };
+        match event {
+            TxnPoolEvent::Pending(txn) => add(self, txn),
+            TxnPoolEvent::Replaced {
+                replaced,
+                replacement,
+            } => {
+                discard(self, replaced);
+                add(self, replacement);
+            },
+            TxnPoolEvent::Discarded(txn) => discard(self, txn),
+            TxnPoolEvent::Mined {
+                sender,
+                sequence_number,
+            } => self.commit_transaction(&AccountAddress::new(sender.bytes()), sequence_number),
+        }
     }
 
     pub(crate) fn log_txn_latency(
@@ -272,6 +408,15 @@ impl Mempool {
         self.transactions.get_by_hash(hash)
     }
 
+    fn check_txn_filter(txn: &SignedTransaction) -> Result<(), String> {
+        match txn.payload() {
+            TransactionPayload::GTxnBytes(bytes) => {
+                txn_filter::check_txn(&txn.sender().into_bytes(), bytes)
+            },
+            _ => Ok(()),
+        }
+    }
+
     /// Used to add a transaction to the Mempool.
     /// Performs basic validation: checks account's sequence number.
     pub(crate) fn send_user_txn(
@@ -291,9 +436,11 @@ impl Mempool {
                 .txns(TxnsLog::new_txn(txn.sender(), txn.sequence_number())),
         );
         let sender = txn.sender();
-        const ZERO_RANKING_SCORE: u64 = 10;
-        // we use sequence number as ranking score, the smaller the sequence number, the higher the ranking score
-        let ranking_score = ZERO_RANKING_SCORE;
+        if let Err(reason) = txn_filter::check_txn(&sender.into_bytes(), txn.bytes()) {
+            return MempoolStatus::new(MempoolStatusCode::VmError)
+                .with_message(format!("transaction denied by txn filter: {}", reason));
+        }
+        let ranking_score = txn.ranking_score();
         // don't accept old transactions (e.g. seq is less than account's current seq_number)
         if txn.sequence_number() < db_sequence_number {
             return MempoolStatus::new(MempoolStatusCode::InvalidSeqNumber).with_message(format!(
@@ -318,6 +465,8 @@ impl Mempool {
 
         let submitted_by_label = txn_info.insertion_info().submitted_by_label();
         let status = self.transactions.insert(txn_info);
+        // the insertion may have evicted txns
+        self.remove_from_journal();
         let now = gaptos::aptos_infallible::duration_since_epoch().as_millis() as u64;
 
         if status.code == MempoolStatusCode::Accepted {
@@ -445,11 +594,25 @@ impl Mempool {
 
         let mut block = Vec::with_capacity(result_size);
         let mut full_bytes = false;
+        // The rules may have changed since admission. Once a txn is denied, the later txns of
+        // its sender can't be executed either.
+        let mut denied_senders = HashSet::new();
         for (sender, sequence_number) in result {
+            if denied_senders.contains(&sender) {
+                continue;
+            }
             if let Some((txn, ranking_score)) = self
                 .transactions
                 .get_with_ranking_score(&sender, sequence_number)
             {
+                if let Err(reason) = Self::check_txn_filter(&txn) {
+                    debug!(
+                        "skip txn {} of {} denied by txn filter: {}",
+                        sequence_number, sender, reason
+                    );
+                    denied_senders.insert(sender);
+                    continue;
+                }
                 let txn_size = txn.txn_bytes_len() as u64;
                 if total_bytes + txn_size > max_bytes {
                     full_bytes = true;
@@ -3,11 +3,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod index;
+mod journal;
 mod mempool;
 pub mod transaction;
 mod transaction_store;
 
 pub use self::{
+    journal::{MempoolJournal, MEMPOOL_JOURNAL_DIR_ENV},
     mempool::Mempool as CoreMempool, transaction::TimelineState,
     transaction_store::TXN_INDEX_ESTIMATED_BYTES,
 };
@@ -6,6 +6,7 @@ use crate::{counters, network::BroadcastPeerPriority};
 use api_types::{
     account::{ExternalAccountAddress, ExternalChainId},
     u256_define::TxnHash,
+    TxnPriority,
 };
 use gaptos::aptos_crypto::{HashValue, Uniform};
 use gaptos::aptos_types::{
@@ -43,12 +44,22 @@ impl From<&SignedTransaction> for VerifiedTxn {
             TransactionPayload::GTxnBytes(bytes) => bytes.clone(),
             _ => panic!("Unexpected TransactionPayload type"),
         };
+        // Only the tip survives the round trip through `SignedTransaction`
+        let priority = TxnPriority {
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: Some(signed_txn.gas_unit_price() as u128)
+                .filter(|tip| *tip > 0),
+            gas_limit: Some(signed_txn.max_gas_amount()).filter(|limit| *limit != u64::MAX),
+            expiration_timestamp_secs: Some(signed_txn.expiration_timestamp_secs())
+                .filter(|secs| *secs != u64::MAX),
+        };
         Self {
             bytes,
             sender: signed_txn.sender(),
             sequence_number: signed_txn.sequence_number(),
             chain_id: signed_txn.chain_id(),
             committed_hash: signed_txn.committed_hash(),
+            priority,
         }
     }
 }
@@ -59,9 +70,10 @@ impl Into<SignedTransaction> for &VerifiedTxn {
             self.sender,
             self.sequence_number,
             TransactionPayload::GTxnBytes(self.bytes.clone()),
-            u64::MAX,
-            0,
-            u64::MAX,
+            self.priority.gas_limit.unwrap_or(u64::MAX),
+            // quorum store buckets and orders batches by gas unit price
+            self.ranking_score(),
+            self.priority.expiration_timestamp_secs.unwrap_or(u64::MAX),
             self.chain_id,
         );
         SignedTransaction::new_with_committed_hash(
@@ -81,7 +93,14 @@ impl VerifiedTxn {
         chain_id: ChainId,
         committed_hash: HashValue,
     ) -> Self {
-        Self { bytes, sender, sequence_number, chain_id, committed_hash }
+        Self {
+            bytes,
+            sender,
+            sequence_number,
+            chain_id,
+            committed_hash,
+            priority: TxnPriority::default(),
+        }
     }
 
     pub fn bytes(&self) -> &Vec<u8> {
@@ -103,6 +122,11 @@ impl VerifiedTxn {
     pub fn get_hash(&self) -> HashValue {
         self.committed_hash
     }
+
+    /// Txns paying a higher tip per gas are pulled first and evicted last
+    pub fn ranking_score(&self) -> u64 {
+        self.priority.tip_per_gas()
+    }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -118,6 +142,7 @@ pub struct VerifiedTxn {
     pub(crate) sequence_number: u64,
     pub(crate) chain_id: chain_id::ChainId,
     pub(crate) committed_hash: HashValue,
+    pub(crate) priority: TxnPriority,
 }
 
 impl std::fmt::Debug for VerifiedTxn {
@@ -135,6 +160,7 @@ impl From<api_types::VerifiedTxn> for VerifiedTxn {
             sequence_number: value.sequence_number,
             chain_id: value.chain_id.into_u64().into(),
             committed_hash,
+            priority: value.priority,
         }
     }
 }
@@ -148,6 +174,7 @@ impl From<VerifiedTxn> for api_types::VerifiedTxn {
             ExternalChainId::new(value.chain_id.into()),
             TxnHash::new(*value.committed_hash),
         )
+        .with_priority(value.priority)
     }
 }
 
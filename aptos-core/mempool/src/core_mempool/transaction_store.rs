@@ -4,7 +4,7 @@
 
 use crate::{
     core_mempool::{
-        index::AccountTransactions,
+        index::{AccountTransactions, TxnPointer},
         mempool::Mempool,
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
     },
@@ -62,6 +62,8 @@ pub struct TransactionStore {
     hash_index: HashMap<HashValue, (AccountAddress, u64)>,
     // estimated size in bytes
     size_bytes: usize,
+    // txns removed on commit or evicted since the last `take_removed_txns`
+    removed_txns: Vec<TxnPointer>,
 
     // configuration
     capacity: usize,
@@ -90,6 +92,7 @@ impl TransactionStore {
             hash_index: HashMap::new(),
             // estimated size in bytes
             size_bytes: 0,
+            removed_txns: vec![],
 
             // configuration
             capacity: config.capacity,
@@ -285,7 +288,7 @@ impl TransactionStore {
     }
 
     /// Checks if Mempool is full.
-    /// If it's full, tries to free some space by evicting transactions from the ParkingLot.
+    /// If it's full, tries to free some space by evicting transactions paying a lower tip.
     /// We only evict on attempt to insert a transaction that would be ready for broadcast upon insertion.
     fn check_is_full_after_eviction(
         &mut self,
@@ -293,11 +296,46 @@ impl TransactionStore {
         curr_sequence_number: u64,
     ) -> bool {
         if self.is_full() && self.check_txn_ready(txn, curr_sequence_number) {
-            // try to free some space in Mempool from ParkingLot by evicting a non-ready txn
+            self.evict_lower_ranked(txn.ranking_score());
         }
         self.is_full()
     }
 
+    /// Evicts txns until mempool is not full, starting from the account of the lowest ranked
+    /// ready txn. The last txn of that account is evicted, so that no account is left with a gap
+    /// in its sequence numbers. Stops at the first account ranked `ranking_score` or higher, or
+    /// whose last txn is.
+    fn evict_lower_ranked(&mut self, ranking_score: u64) {
+        while self.is_full() {
+            let Some(lowest) = self.priority_index.lowest() else {
+                return;
+            };
+            if lowest.gas_ranking_score >= ranking_score {
+                return;
+            }
+            let Some(txns) = self.transactions.get_mut(&lowest.address) else {
+                return;
+            };
+            if !txns.last_key_value().is_some_and(|(_, txn)| txn.ranking_score() < ranking_score) {
+                return;
+            }
+            let Some((_, evicted)) = txns.pop_last() else {
+                return;
+            };
+            let address = evicted.sender();
+            debug!(
+                "evict txn ({} {}) with ranking score {} for ranking score {}",
+                address,
+                evicted.sequence_number(),
+                evicted.ranking_score(),
+                ranking_score
+            );
+            counters::CORE_MEMPOOL_EVICTED_TXNS.inc();
+            self.index_remove(&evicted);
+            self.removed_txns.push(TxnPointer::from(&evicted));
+        }
+    }
+
     fn is_full(&self) -> bool {
         self.size_bytes >= self.capacity_bytes
     }
@@ -471,6 +509,7 @@ impl TransactionStore {
                     transaction.verified_txn().sequence_number(),
                 );
                 self.index_remove(transaction);
+                self.removed_txns.push(TxnPointer::from(transaction));
             }
             trace!(
                 LogSchema::new(LogEntry::CleanCommittedTxn).txns(rm_txns),
@@ -481,6 +520,11 @@ impl TransactionStore {
         }
     }
 
+    /// Returns the txns removed on commit or evicted since the last call.
+    pub(crate) fn take_removed_txns(&mut self) -> Vec<TxnPointer> {
+        std::mem::take(&mut self.removed_txns)
+    }
+
     /// Handles transaction commit.
     /// It includes deletion of all transactions with sequence number <= `account_sequence_number`
     /// and potential promotion of sequential txns to PriorityIndex/TimelineIndex.
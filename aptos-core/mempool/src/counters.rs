@@ -30,6 +30,7 @@ pub const COMMIT_ACCEPTED_BLOCK_LABEL: &str = "commit_accepted_block";
 pub const COMMIT_REJECTED_LABEL: &str = "commit_rejected";
 pub const COMMIT_REJECTED_DUPLICATE_LABEL: &str = "commit_rejected_duplicate";
 pub const COMMIT_IGNORED_LABEL: &str = "commit_ignored";
+pub const COMMIT_DISCARDED_LABEL: &str = "commit_discarded";
 pub const CONSENSUS_READY_LABEL: &str = "consensus_ready";
 pub const CONSENSUS_PULLED_LABEL: &str = "consensus_pulled";
 pub const BROADCAST_READY_LABEL: &str = "broadcast_ready";
@@ -169,6 +170,24 @@ pub static CORE_MEMPOOL_REMOVED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counter tracking number of txns evicted from a full core mempool for higher ranked txns
+pub static CORE_MEMPOOL_EVICTED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_core_mempool_evicted_txns_count",
+        "Number of txns evicted from core mempool for higher ranked txns"
+    )
+    .unwrap()
+});
+
+/// Counter tracking number of txns added back to core mempool from the journal on startup
+pub static CORE_MEMPOOL_JOURNAL_REPLAYED_TXNS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_core_mempool_journal_replayed_txns_count",
+        "Number of txns added back to core mempool from the journal on startup"
+    )
+    .unwrap()
+});
+
 /// Counter tracking number of txns received that are idempotent duplicates
 pub static CORE_MEMPOOL_IDEMPOTENT_TXNS: Lazy<IntCounter> = Lazy::new(|| {
     register_int_counter!(
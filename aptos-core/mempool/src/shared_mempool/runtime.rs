@@ -2,15 +2,15 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
-    core_mempool::{CoreMempool, TimelineState},
-    network::{BroadcastPeerPriority, MempoolSyncMsg},
+    core_mempool::{CoreMempool, MempoolJournal},
+    network::MempoolSyncMsg,
     shared_mempool::{
         coordinator::{coordinator, gc_coordinator, snapshot_job},
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
     QuorumStoreRequest,
 };
-use api_types::ExecutionChannel;
+use api_types::{account::ExternalAccountAddress, ExecutionChannel};
 use gaptos::aptos_config::config::{NodeConfig, NodeType};
 use gaptos::aptos_event_notifications::{DbBackedOnChainConfig, ReconfigNotificationListener};
 use gaptos::aptos_infallible::Mutex;
@@ -88,24 +88,11 @@ async fn retrieve_from_execution_routine(
 ) {
     info!("start retrieve_from_execution_routine");
     loop {
-        match get_block_buffer_manager().pop_txns(usize::MAX).await {
-            Ok(txns) => {
-                info!("the recv_pending_txns size is {:?}", txns.len());
-                txns.into_iter().for_each(|txn_with_number| {
-                    let r = mempool.lock().send_user_txn(
-                        txn_with_number.txn.into(),
-                        txn_with_number.account_seq_num,
-                        TimelineState::NotReady,
-                        true,
-                        None,
-                        Some(BroadcastPeerPriority::Primary),
-                    );
-                    match r.code{
-                        gaptos::aptos_types::mempool_status::MempoolStatusCode::Accepted => {},
-                        _ => panic!("{:?}", r),
-                    }
-                    // TODO(gravity_byteyue): handle error msg
-                });
+        match get_block_buffer_manager().pop_txn_events(usize::MAX).await {
+            Ok(events) => {
+                info!("the recv_txn_pool_events size is {:?}", events.len());
+                let mut mempool = mempool.lock();
+                events.into_iter().for_each(|event| mempool.apply_txn_pool_event(event));
             }
             Err(e) => {
                 warn!("Error when recv peding txns {:?}", e);
@@ -130,7 +117,14 @@ pub fn bootstrap(
 ) -> Vec<Runtime> {
     let runtime = gaptos::aptos_runtimes::spawn_named_runtime("shared-mem".into(), None);
     let retrive_runtime = gaptos::aptos_runtimes::spawn_named_runtime("retrive".into(), None);
-    let mempool = Arc::new(Mutex::new(CoreMempool::new(config)));
+    let mut mempool = CoreMempool::new(config);
+    if let Some(journal) = MempoolJournal::from_env() {
+        // must be replayed before txns from the execution layer pool arrive
+        mempool.replay_journal(journal, |sender| {
+            execution_api.account_seq_num(&ExternalAccountAddress::new(sender.into_bytes()))
+        });
+    }
+    let mempool = Arc::new(Mutex::new(mempool));
     retrive_runtime.handle().spawn(retrieve_from_execution_routine(mempool.clone()));
     start_shared_mempool(
         runtime.handle(),
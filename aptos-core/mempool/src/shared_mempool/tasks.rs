@@ -420,6 +420,7 @@ pub(crate) fn process_committed_transactions(
         history.compute_tracking_set()
     };
 
+    let mut committed = Vec::with_capacity(transactions.len());
     for transaction in transactions {
         pool.log_commit_transaction(
             &transaction.sender,
@@ -429,8 +430,9 @@ pub(crate) fn process_committed_transactions(
                 .map(|name| (transaction.use_case.clone(), name)),
             block_timestamp,
         );
-        pool.commit_transaction(&transaction.sender, transaction.sequence_number);
+        committed.push((transaction.sender, transaction.sequence_number));
     }
+    pool.commit_transactions(&committed);
 }
 
 pub(crate) fn process_rejected_transactions(
@@ -3,21 +3,36 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    core_mempool::{sender_bucket, CoreMempool, SubmittedBy, TimelineState},
+    core_mempool::{
+        sender_bucket,
+        transaction::{VerifiedTxn, TXN_FIXED_ESTIMATED_BYTES},
+        CoreMempool, MempoolJournal, SubmittedBy, TimelineState,
+    },
     network::BroadcastPeerPriority,
     tests::common::{
-        add_signed_txn, add_txn, add_txns_to_mempool, setup_mempool,
+        add_signed_txn, add_txn, add_txns_to_mempool, send_user_txn, setup_mempool,
         setup_mempool_with_broadcast_buckets, txn_bytes_len, TestTransaction,
     },
 };
+use api_types::{
+    account::ExternalAccountAddress,
+    txn_filter::{set_txn_filter_rules, TxnFilterRules},
+    VerifiedTxnWithAccountSeqNum,
+};
+use block_buffer_manager::block_buffer_manager::{TxnPoolEvent, TxnRef};
 use gaptos::aptos_config::config::{MempoolConfig, NodeConfig};
 use aptos_consensus_types::common::{TransactionInProgress, TransactionSummary};
-use gaptos::aptos_crypto::HashValue;
+use gaptos::aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, Uniform};
+use gaptos::aptos_temppath::TempPath;
 use gaptos::aptos_types::{
-    transaction::SignedTransaction, vm_status::DiscardedVMStatus,
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{RawTransaction, SignedTransaction, TransactionPayload},
+    vm_status::DiscardedVMStatus,
 };
 use itertools::Itertools;
 use maplit::btreemap;
+use rand::{rngs::StdRng, SeedableRng};
 use std::time::{Duration, Instant};
 
 // #[test]
@@ -997,6 +1012,156 @@ fn test_get_transaction_by_hash_after_the_txn_is_updated() {
     assert_eq!(txn_by_new_hash, Some(new_txn));
 }
 
+#[test]
+fn test_txn_pool_events() {
+    let mut pool = setup_mempool().0;
+    let pool_txn = |txn: &SignedTransaction| VerifiedTxnWithAccountSeqNum {
+        txn: VerifiedTxn::from(txn).into(),
+        account_seq_num: 0,
+    };
+    let txn_ref = |txn: &SignedTransaction| TxnRef {
+        sender: ExternalAccountAddress::new(txn.sender().into_bytes()),
+        sequence_number: txn.sequence_number(),
+        hash: *txn.committed_hash(),
+    };
+
+    let txn = TestTransaction::new(0, 0, 1).make_signed_transaction();
+    pool.apply_txn_pool_event(TxnPoolEvent::Pending(pool_txn(&txn)));
+    assert!(pool.get_by_hash(txn.committed_hash()).is_some());
+
+    // The replacement takes the nonce of the replaced txn
+    let replacement = TestTransaction::new(0, 0, 2).make_signed_transaction();
+    pool.apply_txn_pool_event(TxnPoolEvent::Replaced {
+        replaced: txn_ref(&txn),
+        replacement: pool_txn(&replacement),
+    });
+    assert!(pool.get_by_hash(txn.committed_hash()).is_none());
+    assert!(pool.get_by_hash(replacement.committed_hash()).is_some());
+
+    // Discarding a stale hash must not remove the txn that now holds the nonce
+    pool.apply_txn_pool_event(TxnPoolEvent::Discarded(txn_ref(&txn)));
+    assert!(pool.get_by_hash(replacement.committed_hash()).is_some());
+    pool.apply_txn_pool_event(TxnPoolEvent::Discarded(txn_ref(&replacement)));
+    assert!(pool.get_by_hash(replacement.committed_hash()).is_none());
+
+    let txn = TestTransaction::new(1, 0, 1).make_signed_transaction();
+    pool.apply_txn_pool_event(TxnPoolEvent::Pending(pool_txn(&txn)));
+    pool.apply_txn_pool_event(TxnPoolEvent::Mined {
+        sender: txn_ref(&txn).sender,
+        sequence_number: 0,
+    });
+    assert!(pool.get_by_hash(txn.committed_hash()).is_none());
+}
+
+#[test]
+fn test_journal_replay() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let pool_txn = |txn: &SignedTransaction| VerifiedTxnWithAccountSeqNum {
+        txn: VerifiedTxn::from(txn).into(),
+        account_seq_num: 0,
+    };
+    let txns: Vec<_> = [(0, 0), (0, 1), (0, 2), (1, 0), (2, 0), (3, 0)]
+        .into_iter()
+        .map(|(address, seq)| TestTransaction::new(address, seq, 1).make_signed_transaction())
+        .collect();
+
+    let mut pool = setup_mempool().0;
+    pool.replay_journal(MempoolJournal::open(dir.path()).unwrap(), |_| None);
+    for txn in &txns {
+        pool.apply_txn_pool_event(TxnPoolEvent::Pending(pool_txn(txn)));
+    }
+    // committed and discarded txns are removed from the journal
+    pool.commit_transaction(&TestTransaction::get_address(0), 0);
+    pool.apply_txn_pool_event(TxnPoolEvent::Discarded(TxnRef {
+        sender: ExternalAccountAddress::new(txns[3].sender().into_bytes()),
+        sequence_number: 0,
+        hash: *txns[3].committed_hash(),
+    }));
+    drop(pool);
+
+    // the execution layer committed the txn of account 2 before the restart
+    let mut pool = setup_mempool().0;
+    pool.replay_journal(MempoolJournal::open(dir.path()).unwrap(), |sender| {
+        (*sender == TestTransaction::get_address(2)).then_some(1)
+    });
+    let replayed: Vec<_> =
+        txns.iter().map(|txn| pool.get_by_hash(txn.committed_hash()).is_some()).collect();
+    assert_eq!(replayed, vec![false, true, true, false, false, true]);
+}
+
+#[test]
+fn test_journal_drops_evicted_txns() {
+    let dir = TempPath::new();
+    dir.create_as_dir().unwrap();
+    let pool_txn = |txn: &SignedTransaction| VerifiedTxnWithAccountSeqNum {
+        txn: VerifiedTxn::from(txn).into(),
+        account_seq_num: 0,
+    };
+    // the gas price of test txns is the tip
+    let txns: Vec<_> = [(0, 1), (1, 100), (2, 10)]
+        .into_iter()
+        .map(|(address, tip)| TestTransaction::new(address, 0, tip).make_signed_transaction())
+        .collect();
+
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.capacity_bytes = 2 * TXN_FIXED_ESTIMATED_BYTES;
+    let mut pool = CoreMempool::new(&config);
+    pool.replay_journal(MempoolJournal::open(dir.path()).unwrap(), |_| None);
+    for txn in &txns {
+        pool.apply_txn_pool_event(TxnPoolEvent::Pending(pool_txn(txn)));
+    }
+    assert!(pool.get_by_hash(txns[0].committed_hash()).is_none());
+    drop(pool);
+
+    let mut pool = setup_mempool().0;
+    pool.replay_journal(MempoolJournal::open(dir.path()).unwrap(), |_| None);
+    let replayed: Vec<_> =
+        txns.iter().map(|txn| pool.get_by_hash(txn.committed_hash()).is_some()).collect();
+    assert_eq!(replayed, vec![false, true, true]);
+}
+
+#[test]
+fn test_tip_ranking_and_eviction() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.capacity_bytes = 3 * TXN_FIXED_ESTIMATED_BYTES;
+    let mut pool = CoreMempool::new(&config);
+
+    // the gas price of test txns is the tip
+    for (address, tip) in [(0, 1), (1, 100), (2, 10)] {
+        send_user_txn(&mut pool, TestTransaction::new(address, 0, tip)).unwrap();
+    }
+    let batch = pool.get_batch(10, 10240, true, btreemap![]);
+    let tips: Vec<_> = batch.iter().map(SignedTransaction::gas_unit_price).collect();
+    assert_eq!(tips, vec![100, 10, 1]);
+
+    // a full mempool evicts the cheapest txn for a higher tip, but not for a lower one
+    send_user_txn(&mut pool, TestTransaction::new(3, 0, 50)).unwrap();
+    assert!(send_user_txn(&mut pool, TestTransaction::new(4, 0, 5)).is_err());
+    let batch = pool.get_batch(10, 10240, true, btreemap![]);
+    let tips: Vec<_> = batch.iter().map(SignedTransaction::gas_unit_price).collect();
+    assert_eq!(tips, vec![100, 50, 10]);
+}
+
+#[test]
+fn test_eviction_keeps_higher_ranked_tail() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.capacity_bytes = 3 * TXN_FIXED_ESTIMATED_BYTES;
+    let mut pool = CoreMempool::new(&config);
+
+    // the lowest ranked account has a cheap head and an expensive tail
+    send_user_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    send_user_txn(&mut pool, TestTransaction::new(0, 1, 1000)).unwrap();
+    send_user_txn(&mut pool, TestTransaction::new(1, 0, 100)).unwrap();
+
+    // evicting the tail for a lower tip isn't allowed, and the head can't go before it
+    assert!(send_user_txn(&mut pool, TestTransaction::new(2, 0, 50)).is_err());
+    let batch = pool.get_batch(10, 10240, true, btreemap![]);
+    let mut tips: Vec<_> = batch.iter().map(SignedTransaction::gas_unit_price).collect();
+    tips.sort();
+    assert_eq!(tips, vec![1, 100, 1000]);
+}
+
 #[test]
 fn test_bytes_limit() {
     let mut config = NodeConfig::generate_random_config();
@@ -1185,3 +1350,38 @@ fn test_include_gas_upgraded() {
     });
     assert_eq!(batch.len(), 0);
 }
+
+#[test]
+fn test_get_batch_skips_txns_denied_by_txn_filter() {
+    let mut pool = setup_mempool().0;
+    // The rules are global, deny a sender no other test uses
+    let denied = AccountAddress::random();
+    let privkey = Ed25519PrivateKey::generate(&mut StdRng::from_seed([0u8; 32]));
+    for sequence_number in 0..2 {
+        let txn = RawTransaction::new(
+            denied,
+            sequence_number,
+            TransactionPayload::GTxnBytes(vec![]),
+            100,
+            1,
+            u64::MAX,
+            ChainId::test(),
+        )
+        .sign(&privkey, privkey.public_key())
+        .unwrap()
+        .into_inner();
+        add_signed_txn(&mut pool, txn).unwrap();
+    }
+    let allowed = send_user_txn(&mut pool, TestTransaction::new(0, 0, 1)).unwrap();
+    assert_eq!(pool.get_batch(10, 10240, true, btreemap![]).len(), 3);
+
+    // the rules changed after the txns were admitted
+    set_txn_filter_rules(TxnFilterRules {
+        deny_senders: vec![denied.to_hex()],
+        ..TxnFilterRules::default()
+    })
+    .unwrap();
+    let batch = pool.get_batch(10, 10240, true, btreemap![]);
+    set_txn_filter_rules(TxnFilterRules::default()).unwrap();
+    assert_eq!(batch, vec![allowed]);
+}
@@ -1,8 +1,24 @@
 use api::GravityNodeArgs;
-use clap::Parser;
-use std::ffi::OsString;
+use clap::{Parser, ValueEnum};
+use std::{ffi::OsString, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, ValueEnum, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum Profile {
+    /// Transfers between uniformly chosen accounts
+    Transfer,
+    /// Sets of random keys
+    KvSet,
+    /// Transfers into a handful of hot accounts
+    HotAccount,
+    /// Sets of random keys, submitted in bursts
+    Burst,
+}
 
 /// This is the entrypoint to the executable.
+///
+/// The bench node executes its own blocks and assumes an empty consensus db, so start every
+/// run from a fresh data dir.
 #[derive(Debug, Parser)]
 #[command(name = "KVStore", version, about = "For bench")]
 pub(crate) struct Cli {
@@ -12,11 +28,52 @@ pub(crate) struct Cli {
     #[arg(long)]
     pub log_dir: String,
 
+    /// Whether this node generates the load. Other nodes only execute the blocks.
     #[arg(long)]
     pub leader: bool,
 
+    #[arg(long, value_enum, default_value_t = Profile::Transfer)]
+    pub workload: Profile,
+
+    /// Submit at this fixed rate (txns per second) regardless of commits, i.e. open loop.
+    /// Without it the load is closed loop, bounded by `--max-in-flight`.
+    #[arg(long)]
+    pub rate: Option<u64>,
+
+    /// Max submitted but not yet committed txns in closed loop
+    #[arg(long, default_value_t = 1000)]
+    pub max_in_flight: u64,
+
+    /// Number of sending accounts
+    #[arg(long, default_value_t = 1000)]
+    pub accounts: u64,
+
+    /// Number of receiving accounts of the hot-account workload
+    #[arg(long, default_value_t = 4)]
+    pub hot_accounts: u64,
+
+    /// Txns per burst of the burst workload
+    #[arg(long, default_value_t = 10000)]
+    pub burst_size: u64,
+
+    #[arg(long, default_value_t = 5)]
+    pub burst_interval_secs: u64,
+
+    /// Load is submitted but not measured during the warm up
+    #[arg(long, default_value_t = 10)]
+    pub warmup_secs: u64,
+
+    /// Length of the measured phase
+    #[arg(long, default_value_t = 60)]
+    pub duration_secs: u64,
+
+    /// How long to wait for txns submitted in the measured phase to commit after it ends
+    #[arg(long, default_value_t = 10)]
+    pub drain_secs: u64,
+
+    /// Also write the report as json to this file
     #[arg(long)]
-    pub port: Option<u16>,
+    pub report: Option<PathBuf>,
 }
 
 impl Cli {
@@ -0,0 +1,133 @@
+use crate::cli::{Cli, Profile};
+use crate::kv::KvExecutor;
+use crate::stats::{Recorder, Report};
+use crate::workload::Workload;
+use api_types::VerifiedTxnWithAccountSeqNum;
+use block_buffer_manager::get_block_buffer_manager;
+use log::info;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// How often the open loop tops up the submitted txns to the target rate.
+const OPEN_LOOP_TICK: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy, Debug)]
+pub enum RateControl {
+    /// Submit `rate` txns per second, whether they commit or not.
+    OpenLoop { rate: u64 },
+    /// Keep at most `max_in_flight` txns submitted but not committed.
+    ClosedLoop { max_in_flight: u64 },
+    /// Submit `size` txns at once every `interval`.
+    Burst { size: u64, interval: Duration },
+}
+
+pub struct Driver {
+    workload: Workload,
+    rate_control: RateControl,
+    warmup: Duration,
+    duration: Duration,
+    drain: Duration,
+    recorder: Arc<Recorder>,
+    executor: Arc<KvExecutor>,
+    committed: watch::Receiver<u64>,
+}
+
+impl Driver {
+    pub fn new(cli: &Cli, recorder: Arc<Recorder>, executor: Arc<KvExecutor>) -> Self {
+        let rate_control = match (cli.workload, cli.rate) {
+            (Profile::Burst, _) => RateControl::Burst {
+                size: cli.burst_size,
+                interval: Duration::from_secs(cli.burst_interval_secs),
+            },
+            (_, Some(rate)) => RateControl::OpenLoop { rate },
+            (_, None) => RateControl::ClosedLoop { max_in_flight: cli.max_in_flight },
+        };
+        Self {
+            workload: Workload::new(cli.workload, cli.accounts, cli.hot_accounts, 0),
+            rate_control,
+            warmup: Duration::from_secs(cli.warmup_secs),
+            duration: Duration::from_secs(cli.duration_secs),
+            drain: Duration::from_secs(cli.drain_secs),
+            recorder,
+            committed: executor.subscribe_committed(),
+            executor,
+        }
+    }
+
+    async fn submit(&mut self, num: u64) {
+        let mut txns = Vec::with_capacity(num as usize);
+        let now = Instant::now();
+        for _ in 0..num {
+            let raw_txn = self.workload.next_txn();
+            let account_seq_num = self.executor.account_seq_num(&raw_txn.account).await;
+            self.recorder.on_submit((raw_txn.account(), raw_txn.sequence_number()), now);
+            txns.push(VerifiedTxnWithAccountSeqNum { txn: raw_txn.into_verified(), account_seq_num });
+        }
+        get_block_buffer_manager().push_txns(txns).await;
+    }
+
+    /// Submits load until `until`, following the rate control.
+    async fn run_phase(&mut self, until: Instant) {
+        let phase_start = Instant::now();
+        let mut sent = 0u64;
+        while Instant::now() < until {
+            match self.rate_control {
+                RateControl::OpenLoop { rate } => {
+                    let elapsed = phase_start.elapsed().as_secs_f64();
+                    let target = (rate as f64 * elapsed) as u64;
+                    self.submit(target - sent).await;
+                    sent = target;
+                    tokio::time::sleep(OPEN_LOOP_TICK).await;
+                }
+                RateControl::ClosedLoop { max_in_flight } => {
+                    let in_flight = self.recorder.in_flight();
+                    if in_flight < max_in_flight {
+                        self.submit(max_in_flight - in_flight).await;
+                    }
+                    let _ = tokio::time::timeout(
+                        until.saturating_duration_since(Instant::now()),
+                        self.committed.changed(),
+                    )
+                    .await;
+                }
+                RateControl::Burst { size, interval } => {
+                    self.submit(size).await;
+                    tokio::time::sleep(interval.min(until.saturating_duration_since(Instant::now())))
+                        .await;
+                }
+            }
+        }
+    }
+
+    pub async fn run(mut self) -> Report {
+        info!(
+            "start {:?} load with {:?}, warm up {:?}, measure {:?}",
+            self.workload.profile(),
+            self.rate_control,
+            self.warmup,
+            self.duration
+        );
+        let start = Instant::now();
+        self.run_phase(start + self.warmup).await;
+
+        let measure_start = Instant::now();
+        self.recorder.start_measure(measure_start);
+        self.run_phase(measure_start + self.duration).await;
+        self.recorder.end_measure(Instant::now());
+
+        let drain_end = Instant::now() + self.drain;
+        while self.recorder.measured_in_flight() > 0 && Instant::now() < drain_end {
+            let _ = tokio::time::timeout(
+                drain_end.saturating_duration_since(Instant::now()),
+                self.committed.changed(),
+            )
+            .await;
+        }
+        let rate = match self.rate_control {
+            RateControl::OpenLoop { rate } => Some(rate),
+            _ => None,
+        };
+        self.recorder.report(self.workload.profile(), rate)
+    }
+}
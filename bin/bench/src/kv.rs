@@ -1,160 +1,167 @@
-use crate::should_produce_txn;
-use crate::stateful_mempool::Mempool;
-use crate::txn::RawTxn;
-use api_types::compute_res::ComputeRes;
-use api_types::u256_define::TxnHash;
-use api_types::{
-    u256_define::BlockId, ExecError, ExecTxn, ExecutionChannel, ExternalBlock, ExternalBlockMeta, ExternalPayloadAttr, VerifiedTxn, VerifiedTxnWithAccountSeqNum
-};
-use async_trait::async_trait;
-use log::info;
-use std::collections::{HashMap, HashSet};
-use std::hash::{DefaultHasher, Hash, Hasher};
-use std::sync::atomic::{AtomicU64, Ordering};
-use tokio::sync::mpsc::Receiver;
-use tokio::sync::Mutex;
-use tokio::time::Instant;
+use crate::stats::{Recorder, TxnKey};
+use crate::txn::{RawTxn, TxnOp};
+use api_types::account::ExternalAccountAddress;
+use api_types::compute_res::TxnStatus;
+use api_types::{simple_hash, ExternalBlock};
+use block_buffer_manager::get_block_buffer_manager;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
 
-#[derive(Default)]
-struct BlockStatus {
-    txn_number: u64,
-}
+const INITIAL_BALANCE: u64 = 1_000_000_000;
+/// How long to wait before asking the block buffer again after it failed.
+const ERROR_BACKOFF: Duration = Duration::from_millis(100);
 
-struct CounterTimer {
-    call_count: u32,
-    last_time: Instant,
-    txn_num_in_block: u32,
-    count_round: u32,
+#[derive(Default)]
+struct State {
+    store: HashMap<String, String>,
+    balances: HashMap<ExternalAccountAddress, u64>,
+    nonces: HashMap<ExternalAccountAddress, u64>,
+    last_block_hash: [u8; 32],
 }
 
-impl CounterTimer {
-    pub fn new() -> Self {
-        Self {
-            call_count: 0,
-            last_time: Instant::now(),
-            txn_num_in_block: std::env::var("BLOCK_TXN_NUMS")
-                .map(|s| s.parse().unwrap())
-                .unwrap_or(1000),
-            count_round: std::env::var("COUNT_ROUND").map(|s| s.parse().unwrap()).unwrap_or(10),
-        }
-    }
-    pub fn count(&mut self) {
-        self.call_count += 1;
-        if self.call_count == self.count_round {
-            let now = Instant::now();
-            let duration = now.duration_since(self.last_time);
-            info!(
-                "Time taken for the last {:?} blocks to be produced: {:?}",
-                self.count_round, duration,
-            );
-            self.call_count = 0;
-            self.last_time = now;
+impl State {
+    fn apply(&mut self, txn: &RawTxn) {
+        match txn.op() {
+            TxnOp::Set { key, val } => {
+                self.store.insert(key.clone(), val.clone());
+            }
+            TxnOp::Transfer { to, amount } => {
+                let from = self.balances.entry(txn.account()).or_insert(INITIAL_BALANCE);
+                // A transfer without enough balance still consumes the sequence number
+                if *from >= *amount {
+                    *from -= amount;
+                    *self.balances.entry(to.clone()).or_insert(INITIAL_BALANCE) += amount;
+                }
+            }
         }
+        self.nonces.insert(txn.account(), txn.sequence_number() + 1);
     }
 }
 
-pub struct KvStore {
-    _store: Mutex<HashMap<String, String>>,
-    mempool: Mempool,
-    block_status: Mutex<HashMap<ExternalPayloadAttr, BlockStatus>>,
-    block_len: Mutex<HashMap<BlockId, usize>>,
+/// Executes the ordered blocks of the block buffer against an in memory kv store and reports
+/// the committed txns to the `Recorder`.
+pub struct KvExecutor {
+    state: Mutex<State>,
+    executed_txns: Mutex<HashMap<u64, Vec<TxnKey>>>,
+    recorder: Arc<Recorder>,
+    /// The number of the last committed block
+    committed: watch::Sender<u64>,
 }
 
-impl KvStore {
-    pub fn new() -> Self {
-        KvStore {
-            _store: Mutex::new(HashMap::new()),
-            mempool: Mempool::new(),
-            block_status: Mutex::new(HashMap::new()),
-            block_len: Mutex::new(HashMap::new()),
+impl KvExecutor {
+    pub fn new(recorder: Arc<Recorder>) -> Self {
+        KvExecutor {
+            state: Mutex::new(State::default()),
+            executed_txns: Mutex::new(HashMap::new()),
+            recorder,
+            committed: watch::channel(0).0,
         }
     }
 
-    pub async fn get(&self, key: &str) -> Option<String> {
-        let data = self._store.lock().await;
-        data.get(key).cloned()
+    /// The next sequence number of `account` as of the last executed block.
+    pub async fn account_seq_num(&self, account: &ExternalAccountAddress) -> u64 {
+        self.state.lock().await.nonces.get(account).copied().unwrap_or(0)
     }
 
-    pub async fn set(&self, key: String, val: String) {
-        let mut store = self._store.lock().await;
-        store.insert(key, val);
+    /// The receiver sees every commit after it was last marked seen, so a commit isn't lost while
+    /// the caller isn't waiting.
+    pub fn subscribe_committed(&self) -> watch::Receiver<u64> {
+        self.committed.subscribe()
     }
-}
-
-static RECV_TXN_SUM: AtomicU64 = AtomicU64::new(0);
-static SEND_TXN_SUM: AtomicU64 = AtomicU64::new(0);
-static COMPUTE_RES_SUM: AtomicU64 = AtomicU64::new(0);
 
-#[async_trait]
-impl ExecutionChannel for KvStore {
-    async fn send_user_txn(&self, txn: ExecTxn) -> Result<TxnHash, ExecError> {
-        match txn {
-            ExecTxn::RawTxn(bytes) => self.mempool.add_raw_txn(bytes).await,
-            ExecTxn::VerifiedTxn(verified_txn) => self.mempool.add_verified_txn(verified_txn).await,
+    async fn execute_block(&self, block: ExternalBlock) {
+        let block_number = block.block_meta.block_number;
+        let mut state = self.state.lock().await;
+        let mut hash_input = state.last_block_hash.to_vec();
+        let mut txn_status = Vec::with_capacity(block.txns.len());
+        let mut keys = Vec::with_capacity(block.txns.len());
+        for txn in &block.txns {
+            let raw_txn = RawTxn::from_bytes(txn.bytes());
+            state.apply(&raw_txn);
+            let txn_hash = txn.committed_hash();
+            hash_input.extend_from_slice(&txn_hash);
+            txn_status.push(TxnStatus {
+                txn_hash,
+                sender: raw_txn.account().bytes(),
+                nonce: raw_txn.sequence_number(),
+                is_discarded: false,
+            });
+            keys.push((raw_txn.account(), raw_txn.sequence_number()));
         }
-        Ok(TxnHash::random())
-    }
+        let block_hash = simple_hash::hash_to_fixed_array(&hash_input);
+        state.last_block_hash = block_hash;
+        drop(state);
 
-    async fn recv_unbroadcasted_txn(&self) -> Result<Vec<VerifiedTxn>, ExecError> {
-        Ok(self.mempool.recv_unbroadcasted_txn().await)
+        self.executed_txns.lock().await.insert(block_number, keys);
+        get_block_buffer_manager()
+            .set_compute_res(
+                block.block_meta.block_id,
+                block_hash,
+                block_number,
+                Arc::new(Some(txn_status)),
+            )
+            .await
+            .expect("failed to set compute res");
     }
 
-    async fn check_block_txns(
-        &self,
-        payload_attr: ExternalPayloadAttr,
-        txns: Vec<VerifiedTxn>,
-    ) -> Result<bool, ExecError> {
-        let mut block = self.block_status.lock().await;
-        let status = block.entry(payload_attr).or_insert(BlockStatus::default());
-        let new_txn_number = status.txn_number + txns.len() as u64;
-        if new_txn_number > 100 {
-            Ok(false)
-        } else {
-            status.txn_number = new_txn_number;
-            Ok(true)
+    pub async fn start_execution(&self, latest_block_number: u64) {
+        let mut start_ordered_block = latest_block_number + 1;
+        loop {
+            let exec_blocks =
+                match get_block_buffer_manager().get_ordered_blocks(start_ordered_block, None).await
+                {
+                    Ok(exec_blocks) => exec_blocks,
+                    Err(e) => {
+                        warn!("failed to get ordered blocks: {}", e);
+                        tokio::time::sleep(ERROR_BACKOFF).await;
+                        continue;
+                    }
+                };
+            if exec_blocks.is_empty() {
+                continue;
+            }
+            start_ordered_block = exec_blocks.last().unwrap().0.block_meta.block_number + 1;
+            for (block, _parent_id) in exec_blocks {
+                self.execute_block(block).await;
+            }
         }
     }
 
-    async fn send_pending_txns(&self) -> Result<Vec<VerifiedTxnWithAccountSeqNum>, ExecError> {
-        let txns = match should_produce_txn().await {
-            true => Ok(self.mempool.pending_txns().await),
-            false => Ok(vec![]),
-        }?;
-        RECV_TXN_SUM.fetch_add(txns.len() as u64, Ordering::Relaxed);
-        info!("RECV_TXN_SUM: {}", RECV_TXN_SUM.load(Ordering::Relaxed));
-        Ok(txns)
-    }
-
-    async fn recv_ordered_block(
-        &self,
-        parent_id: BlockId,
-        ordered_block: ExternalBlock,
-    ) -> Result<(), ExecError> {
-        {
-            let mut block_len = self.block_len.lock().await;
-            block_len.insert(ordered_block.block_meta.block_id, ordered_block.txns.len());
+    pub async fn start_commit(&self, latest_block_number: u64) {
+        let mut start_commit_num = latest_block_number + 1;
+        loop {
+            let block_ids =
+                match get_block_buffer_manager().get_committed_blocks(start_commit_num, None).await
+                {
+                    Ok(block_ids) => block_ids,
+                    Err(e) => {
+                        warn!("failed to get committed blocks: {}", e);
+                        tokio::time::sleep(ERROR_BACKOFF).await;
+                        continue;
+                    }
+                };
+            if block_ids.is_empty() {
+                continue;
+            }
+            let now = Instant::now();
+            let mut committed_txns = 0;
+            for block_id_num_hash in &block_ids {
+                let keys = self.executed_txns.lock().await.remove(&block_id_num_hash.num);
+                for key in keys.unwrap_or_default() {
+                    self.recorder.on_commit(&key, now);
+                    committed_txns += 1;
+                }
+            }
+            start_commit_num = block_ids.last().unwrap().num + 1;
+            info!("committed {} txns up to block {}", committed_txns, start_commit_num - 1);
+            self.committed.send_replace(start_commit_num - 1);
+            get_block_buffer_manager()
+                .set_state(start_commit_num - 1, start_commit_num - 1)
+                .await
+                .unwrap();
         }
-        SEND_TXN_SUM.fetch_add(ordered_block.txns.len() as u64, Ordering::Relaxed);
-        info!("SEND_TXN_SUM: {}", SEND_TXN_SUM.load(Ordering::Relaxed));
-        Ok(())
-    }
-
-    async fn send_executed_block_hash(
-        &self,
-        head: ExternalBlockMeta,
-    ) -> Result<ComputeRes, ExecError> {
-        let mut r = ComputeRes::random();
-        r.txn_num = {
-            let mut block_len = self.block_len.lock().await;
-            block_len.remove(&head.block_id).unwrap().clone()
-        } as u64;
-        COMPUTE_RES_SUM.fetch_add(r.txn_num, Ordering::Relaxed);
-        info!("COMPUTE_RES_SUM: {}", COMPUTE_RES_SUM.load(Ordering::Relaxed));
-        Ok(r)
-    }
-
-    async fn recv_committed_block_info(&self, head: BlockId) -> Result<(), ExecError> {
-        info!("enter send_committed_block_info");
-        Ok(())
     }
 }
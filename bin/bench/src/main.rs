@@ -1,113 +1,27 @@
 mod cli;
+mod driver;
 mod kv;
-mod stateful_mempool;
+mod stats;
 mod txn;
+mod workload;
 
 use std::{sync::Arc, thread};
 
-use api::{check_bootstrap_config, consensus_api::ConsensusEngine, NodeConfig};
+use api::{check_bootstrap_config, consensus_api::ConsensusEngine};
 use api_types::{
-    account::ExternalAccountAddress, default_recover::DefaultRecovery, ConsensusApi, ExecTxn, ExecutionChannel, ExecutionLayer
+    mock_execution_layer::MockExecutionApi, simple_hash, ExecutionLayer,
+    GLOBAL_CRYPTO_TXN_HASHER,
 };
 use clap::Parser;
 use cli::Cli;
+use driver::Driver;
 use flexi_logger::{FileSpec, Logger, WriteMode};
-use kv::KvStore;
+use kv::KvExecutor;
 use log::info;
-use once_cell::sync::OnceCell;
-use rand::Rng;
-use tokio::sync::RwLock;
-use txn::RawTxn;
-use warp::Filter;
+use stats::Recorder;
 
-struct TestConsensusLayer {
-    consensus_engine: Arc<dyn ConsensusApi>,
-    execution_api: Arc<dyn ExecutionChannel>,
-}
-
-impl TestConsensusLayer {
-    fn new(node_config: NodeConfig, execution_client: Arc<dyn ExecutionChannel>) -> Self {
-        let safe_hash = [0u8; 32];
-        let head_hash = [0u8; 32];
-        let finalized_hash = [0u8; 32];
-        Self {
-            consensus_engine: ConsensusEngine::init(
-                node_config,
-                ExecutionLayer{
-                    execution_api: execution_client.clone(),
-                    recovery_api: Arc::new(DefaultRecovery {}),
-                },
-                1337,
-            ),
-            execution_api: execution_client,
-        }
-    }
-
-    async fn random_txns(num: u64) -> Vec<ExecTxn> {
-        let mut txns = Vec::with_capacity(num as usize);
-        for i in 0..num {
-            let key = format!("random_key_{}", i);
-            let val = format!("random_value_{}", i);
-            let raw_txn =
-                RawTxn { account: generate_random_address(), sequence_number: 1, key, val };
-            let exec_txn = ExecTxn::RawTxn(raw_txn.to_bytes());
-            txns.push(exec_txn);
-        }
-        txns
-    }
-
-    async fn run(self) {
-        loop {
-            if should_produce_txn().await {
-                info!("start produce new txn");
-                let txn_num_in_block =
-                    std::env::var("BLOCK_TXN_NUMS").map(|s| s.parse().unwrap()).unwrap_or(1000);
-                TestConsensusLayer::random_txns(txn_num_in_block).await.into_iter().for_each(
-                    |txn| {
-                        let execution = self.execution_api.clone();
-                        tokio::spawn(async move {
-                            let _ = execution.send_user_txn(txn).await;
-                        });
-                    },
-                );
-            }
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await
-        }
-    }
-}
-
-static IS_LEADER: OnceCell<bool> = OnceCell::new();
-static PRODUCE_TXN: OnceCell<RwLock<bool>> = OnceCell::new();
-
-#[derive(Debug, serde::Deserialize)]
-struct ProduceTxnQuery {
-    value: bool,
-}
-
-async fn handle_produce_txn(query: ProduceTxnQuery) -> Result<impl warp::Reply, warp::Rejection> {
-    let global_flag = PRODUCE_TXN.get().expect("PRODUCE_TXN is not initialized");
-    {
-        let mut flag = global_flag.write().await;
-        *flag = query.value;
-    }
-    Ok(format!("ProduceTxn updated to: {}", query.value))
-}
-
-async fn get_produce_txn() -> bool {
-    let global_flag = PRODUCE_TXN.get().expect("PRODUCE_TXN is not initialized");
-    let flag = global_flag.read().await;
-    *flag
-}
-
-pub async fn should_produce_txn() -> bool {
-    *IS_LEADER.get().expect("No is leader set") && get_produce_txn().await
-}
-
-fn generate_random_address() -> ExternalAccountAddress {
-    let mut rng = rand::thread_rng();
-    let random_bytes: [u8; 32] = rng.gen();
-    ExternalAccountAddress::new(random_bytes)
-}
+/// The bench always starts from an empty chain.
+const LATEST_BLOCK_NUMBER: u64 = 0;
 
 #[tokio::main]
 async fn main() {
@@ -119,33 +33,46 @@ async fn main() {
         .write_mode(WriteMode::BufferAndFlush)
         .start()
         .unwrap();
-    let is_leader = cli.leader;
-    if is_leader && cli.port.is_none() {
-        panic!("Please also set port when enable leader");
-    }
-    IS_LEADER.set(cli.leader).expect("Failed to set is leader");
-    PRODUCE_TXN.set(RwLock::new(true)).expect("Failed to initialize PRODUCE_TXN");
-    let port = cli.port.clone();
+    GLOBAL_CRYPTO_TXN_HASHER.get_or_init(|| Box::new(simple_hash::hash_to_fixed_array));
+
+    let recorder = Arc::new(Recorder::new());
+    let executor = Arc::new(KvExecutor::new(recorder.clone()));
+    let driver = cli.leader.then(|| Driver::new(&cli, recorder, executor.clone()));
+    let report_path = cli.report.clone();
 
     cli.run(move || {
         tokio::spawn(async move {
-            let execution_api = Arc::new(KvStore::new());
-            let execution = execution_api.clone();
+            let consensus_executor = executor.clone();
             let _ = thread::spawn(move || {
-                let cl = TestConsensusLayer::new(gcei_config, execution.clone());
-                tokio::runtime::Runtime::new().unwrap().block_on(cl.run());
+                tokio::runtime::Runtime::new().unwrap().block_on(async move {
+                    let _consensus_engine = ConsensusEngine::init(
+                        gcei_config,
+                        ExecutionLayer { execution_api: Arc::new(MockExecutionApi {}) },
+                        1337,
+                        LATEST_BLOCK_NUMBER,
+                    )
+                    .await;
+                    let execution = consensus_executor.clone();
+                    tokio::spawn(async move { execution.start_execution(LATEST_BLOCK_NUMBER).await });
+                    consensus_executor.start_commit(LATEST_BLOCK_NUMBER).await;
+                });
             });
 
-            if *IS_LEADER.get().expect("No is leader") {
-                let route = warp::path!("ProduceTxn")
-                    .and(warp::query::<ProduceTxnQuery>())
-                    .and_then(handle_produce_txn);
-
-                warp::serve(route).run(([0, 0, 0, 0], port.expect("No port"))).await;
-            } else {
+            let Some(driver) = driver else {
                 loop {
                     tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
                 }
+            };
+            while !block_buffer_manager::get_block_buffer_manager().is_ready() {
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            }
+            let report = driver.run().await;
+            let report_json = serde_json::to_string_pretty(&report).unwrap();
+            info!("bench report {}", report_json);
+            println!("{}", report_json);
+            if let Some(path) = report_path {
+                std::fs::write(&path, &report_json)
+                    .unwrap_or_else(|e| panic!("failed to write report to {:?}: {}", path, e));
             }
         })
     })
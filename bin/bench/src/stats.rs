@@ -0,0 +1,221 @@
+use crate::cli::Profile;
+use api_types::account::ExternalAccountAddress;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+pub type TxnKey = (ExternalAccountAddress, u64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    WarmUp,
+    Measure,
+    Drain,
+}
+
+struct Submitted {
+    at: Instant,
+    measured: bool,
+}
+
+struct Inner {
+    phase: Phase,
+    measure_start: Option<Instant>,
+    measure_end: Option<Instant>,
+    pending: HashMap<TxnKey, Submitted>,
+    submitted: u64,
+    committed: u64,
+    latencies_us: Vec<u64>,
+}
+
+/// Tracks every submitted txn until its commit notification. Only txns submitted during the
+/// measured phase count towards the latency, only commits during it towards the TPS.
+pub struct Recorder {
+    inner: Mutex<Inner>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                phase: Phase::WarmUp,
+                measure_start: None,
+                measure_end: None,
+                pending: HashMap::new(),
+                submitted: 0,
+                committed: 0,
+                latencies_us: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn start_measure(&self, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.phase = Phase::Measure;
+        inner.measure_start = Some(now);
+    }
+
+    pub fn end_measure(&self, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.phase = Phase::Drain;
+        inner.measure_end = Some(now);
+    }
+
+    pub fn on_submit(&self, key: TxnKey, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        let measured = inner.phase == Phase::Measure;
+        if measured {
+            inner.submitted += 1;
+        }
+        inner.pending.insert(key, Submitted { at: now, measured });
+    }
+
+    /// Returns whether `key` was submitted by this node.
+    pub fn on_commit(&self, key: &TxnKey, now: Instant) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(submitted) = inner.pending.remove(key) else {
+            return false;
+        };
+        if inner.phase == Phase::Measure {
+            inner.committed += 1;
+        }
+        if submitted.measured {
+            let latency = now.duration_since(submitted.at);
+            inner.latencies_us.push(latency.as_micros() as u64);
+        }
+        true
+    }
+
+    pub fn in_flight(&self) -> u64 {
+        self.inner.lock().unwrap().pending.len() as u64
+    }
+
+    /// Number of txns submitted in the measured phase that have not committed yet.
+    pub fn measured_in_flight(&self) -> u64 {
+        self.inner.lock().unwrap().pending.values().filter(|s| s.measured).count() as u64
+    }
+
+    pub fn report(&self, profile: Profile, rate: Option<u64>) -> Report {
+        let inner = self.inner.lock().unwrap();
+        let duration = match (inner.measure_start, inner.measure_end) {
+            (Some(start), Some(end)) => end.duration_since(start),
+            _ => Duration::ZERO,
+        };
+        let mut latencies = inner.latencies_us.clone();
+        latencies.sort_unstable();
+        let to_ms = |us: u64| us as f64 / 1000.0;
+        Report {
+            workload: profile,
+            rate,
+            duration_secs: duration.as_secs_f64(),
+            submitted: inner.submitted,
+            committed: inner.committed,
+            uncommitted: inner.pending.values().filter(|s| s.measured).count() as u64,
+            tps: tps(inner.committed, duration),
+            latency_ms: LatencyReport {
+                mean: to_ms(mean(&latencies)),
+                p50: to_ms(percentile(&latencies, 50.0)),
+                p90: to_ms(percentile(&latencies, 90.0)),
+                p99: to_ms(percentile(&latencies, 99.0)),
+                p999: to_ms(percentile(&latencies, 99.9)),
+                max: to_ms(latencies.last().copied().unwrap_or(0)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LatencyReport {
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub p999: f64,
+    pub max: f64,
+}
+
+/// Result of a run. `rate` is `None` for closed loop.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub workload: Profile,
+    pub rate: Option<u64>,
+    pub duration_secs: f64,
+    pub submitted: u64,
+    pub committed: u64,
+    /// Txns submitted in the measured phase that did not commit before the drain ended
+    pub uncommitted: u64,
+    pub tps: f64,
+    pub latency_ms: LatencyReport,
+}
+
+/// Nearest rank percentile of `sorted`, 0 if it is empty.
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn mean(values: &[u64]) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    (values.iter().map(|v| *v as u128).sum::<u128>() / values.len() as u128) as u64
+}
+
+fn tps(committed: u64, duration: Duration) -> f64 {
+    if duration.is_zero() {
+        return 0.0;
+    }
+    committed as f64 / duration.as_secs_f64()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(seq: u64) -> TxnKey {
+        (ExternalAccountAddress::new([1u8; 32]), seq)
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 50.0), 50);
+        assert_eq!(percentile(&sorted, 99.0), 99);
+        assert_eq!(percentile(&sorted, 99.9), 100);
+        assert_eq!(percentile(&sorted, 0.0), 1);
+        assert_eq!(percentile(&[], 50.0), 0);
+        assert_eq!(percentile(&[7], 99.9), 7);
+    }
+
+    #[test]
+    fn test_warm_up_is_not_measured() {
+        let recorder = Recorder::new();
+        let start = Instant::now();
+        recorder.on_submit(key(0), start);
+        recorder.start_measure(start + Duration::from_secs(1));
+        // Submitted during the warm up, committed during the measured phase
+        assert!(recorder.on_commit(&key(0), start + Duration::from_secs(2)));
+        recorder.on_submit(key(1), start + Duration::from_secs(2));
+        recorder.on_submit(key(2), start + Duration::from_secs(2));
+        assert!(recorder.on_commit(&key(1), start + Duration::from_secs(3)));
+        recorder.end_measure(start + Duration::from_secs(3));
+        // Committed during the drain, counts towards the latency only
+        assert!(recorder.on_commit(&key(2), start + Duration::from_secs(5)));
+        assert!(!recorder.on_commit(&key(3), start + Duration::from_secs(5)));
+
+        let report = recorder.report(Profile::Transfer, None);
+        assert_eq!(report.submitted, 2);
+        assert_eq!(report.committed, 2);
+        assert_eq!(report.uncommitted, 0);
+        assert_eq!(report.tps, 1.0);
+        assert_eq!(report.latency_ms.p50, 1000.0);
+        assert_eq!(report.latency_ms.max, 3000.0);
+        assert_eq!(recorder.in_flight(), 0);
+    }
+}
@@ -6,12 +6,17 @@ use api_types::{
 };
 use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum TxnOp {
+    Set { key: String, val: String },
+    Transfer { to: ExternalAccountAddress, amount: u64 },
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct RawTxn {
     pub(crate) account: ExternalAccountAddress,
     pub(crate) sequence_number: u64,
-    pub(crate) key: String,
-    pub(crate) val: String,
+    pub(crate) op: TxnOp,
 }
 
 impl From<VerifiedTxn> for RawTxn {
@@ -22,8 +27,8 @@ impl From<VerifiedTxn> for RawTxn {
 }
 
 impl RawTxn {
-    pub fn from_bytes(bytes: Vec<u8>) -> Self {
-        let txn: RawTxn = serde_json::from_slice(&bytes).unwrap();
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let txn: RawTxn = serde_json::from_slice(bytes).unwrap();
         txn
     }
 
@@ -31,12 +36,8 @@ impl RawTxn {
         serde_json::to_vec(self).unwrap()
     }
 
-    pub fn key(&self) -> &String {
-        &self.key
-    }
-
-    pub fn val(&self) -> &String {
-        &self.val
+    pub fn op(&self) -> &TxnOp {
+        &self.op
     }
 
     pub fn into_verified(self) -> VerifiedTxn {
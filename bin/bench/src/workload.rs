@@ -0,0 +1,62 @@
+use crate::cli::Profile;
+use crate::txn::{RawTxn, TxnOp};
+use api_types::account::ExternalAccountAddress;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn account_address(index: u64) -> ExternalAccountAddress {
+    let mut bytes = [0u8; 32];
+    bytes[24..].copy_from_slice(&index.to_be_bytes());
+    ExternalAccountAddress::new(bytes)
+}
+
+/// Generates the txns of a workload profile. Senders are picked round robin so that every
+/// account advances its sequence number evenly.
+pub struct Workload {
+    profile: Profile,
+    accounts: Vec<ExternalAccountAddress>,
+    next_seq_nums: Vec<u64>,
+    hot_accounts: u64,
+    next_sender: usize,
+    rng: StdRng,
+}
+
+impl Workload {
+    pub fn new(profile: Profile, accounts: u64, hot_accounts: u64, seed: u64) -> Self {
+        assert!(accounts > 0, "at least one account is required");
+        Self {
+            profile,
+            accounts: (0..accounts).map(account_address).collect(),
+            next_seq_nums: vec![0; accounts as usize],
+            hot_accounts: hot_accounts.clamp(1, accounts),
+            next_sender: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+
+    pub fn next_txn(&mut self) -> RawTxn {
+        let sender = self.next_sender;
+        self.next_sender = (self.next_sender + 1) % self.accounts.len();
+        let sequence_number = self.next_seq_nums[sender];
+        self.next_seq_nums[sender] += 1;
+
+        let op = match self.profile {
+            Profile::Transfer => {
+                let to = self.rng.gen_range(0..self.accounts.len());
+                TxnOp::Transfer { to: self.accounts[to].clone(), amount: 1 }
+            }
+            Profile::HotAccount => {
+                let to = self.rng.gen_range(0..self.hot_accounts) as usize;
+                TxnOp::Transfer { to: self.accounts[to].clone(), amount: 1 }
+            }
+            Profile::KvSet | Profile::Burst => TxnOp::Set {
+                key: format!("key_{}", self.rng.gen::<u32>()),
+                val: format!("val_{}", self.rng.gen::<u64>()),
+            },
+        };
+        RawTxn { account: self.accounts[sender].clone(), sequence_number, op }
+    }
+}
@@ -0,0 +1,143 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+/// Talks to the admin api of a running gravity node.
+#[derive(Debug, Parser)]
+#[command(name = "gravity-admin", version, about = "Operate a running gravity node")]
+pub struct Cli {
+    /// Address of the node admin listener.
+    #[arg(
+        long = "admin_address",
+        env = "GRAVITY_ADMIN_ADDRESS",
+        default_value = "127.0.0.1:1999",
+        global = true
+    )]
+    pub admin_address: String,
+
+    /// Bearer token of the admin api.
+    #[arg(long, env = "GRAVITY_ADMIN_TOKEN", global = true, hide_env_values = true)]
+    pub token: Option<String>,
+
+    /// File holding the bearer token of the admin api.
+    #[arg(long = "token_file", env = "GRAVITY_ADMIN_TOKEN_FILE", global = true)]
+    pub token_file: Option<PathBuf>,
+
+    /// CA certificate of the admin listener, enables https.
+    #[arg(long = "ca_cert", global = true)]
+    pub ca_cert: Option<PathBuf>,
+
+    /// PEM file holding the client certificate and its private key, for mutual tls.
+    #[arg(long = "client_identity", global = true, requires = "ca_cert")]
+    pub client_identity: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Read or change the log filter of the node.
+    #[command(subcommand)]
+    LogFilter(LogFilterCommand),
+    /// Capture, list and download cpu profiles and heap dumps.
+    #[command(subcommand)]
+    Profile(ProfileCommand),
+    /// Read or change the rules that deny txns at mempool admission and block preparation.
+    #[command(subcommand)]
+    TxnFilter(TxnFilterCommand),
+    /// List the conflicting proposals and votes seen by the node.
+    Equivocations {
+        /// Only list the evidence of this epoch and later.
+        #[arg(long, default_value_t = 0)]
+        epoch: u64,
+    },
+    /// List or clear the blocks whose local execution result diverged from the certified one.
+    #[command(subcommand)]
+    ExecutionDivergences(ExecutionDivergenceCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExecutionDivergenceCommand {
+    /// Print the recorded divergences.
+    List,
+    /// Remove the recorded divergences, the node votes again after its next restart.
+    Clear,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum LogFilterCommand {
+    /// Print the current and the default log filter.
+    Get,
+    /// Set the log filter, e.g. `info,aptos_consensus=trace`.
+    Set {
+        /// `level` or `module=level` directives separated by commas.
+        filter: String,
+        /// Revert to the default filter after this many seconds.
+        #[arg(long = "revert_after")]
+        revert_after: Option<u64>,
+    },
+    /// Revert to the default log filter.
+    Reset,
+}
+
+#[derive(Debug, Args)]
+pub struct TxnFilterRules {
+    /// Deny txns of this sender, 20 or 32 bytes hex.
+    #[arg(long = "deny_sender")]
+    pub deny_senders: Vec<String>,
+    /// Deny txns calling this address, 20 or 32 bytes hex.
+    #[arg(long = "deny_to")]
+    pub deny_to: Vec<String>,
+    /// Deny txns calling this 4 bytes hex function selector.
+    #[arg(long = "deny_selector")]
+    pub deny_selectors: Vec<String>,
+    /// Deny txns larger than this many bytes.
+    #[arg(long = "max_txn_bytes")]
+    pub max_txn_bytes: Option<u64>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TxnFilterCommand {
+    /// Print the current rules.
+    Get,
+    /// Add rules to the current ones, e.g. to block a contract during an incident.
+    Add(TxnFilterRules),
+    /// Replace all rules.
+    Set(TxnFilterRules),
+    /// Remove all rules.
+    Clear,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum CpuProfileFormat {
+    Flamegraph,
+    Pprof,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommand {
+    /// Sample the cpu of the node, returns once the profile is captured.
+    Cpu {
+        #[arg(long, default_value_t = 30)]
+        seconds: u64,
+        /// Sampling frequency in hz.
+        #[arg(long)]
+        frequency: Option<i32>,
+        #[arg(long, value_enum, default_value_t = CpuProfileFormat::Flamegraph)]
+        format: CpuProfileFormat,
+    },
+    /// Dump the jemalloc heap profile, heap profiling must be active.
+    HeapDump {
+        /// Included in the name of the dump.
+        name: String,
+    },
+    /// List the captured profiles and dumps.
+    List,
+    /// Download a captured profile or dump.
+    Download {
+        name: String,
+        /// Defaults to `name` in the current directory.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
@@ -0,0 +1,180 @@
+mod cli;
+
+use anyhow::Context;
+use clap::Parser;
+use cli::{
+    Cli, Command, CpuProfileFormat, ExecutionDivergenceCommand, LogFilterCommand, ProfileCommand,
+    TxnFilterCommand, TxnFilterRules,
+};
+use reqwest::{Certificate, Client, Identity, Method, RequestBuilder};
+use serde_json::{json, Value};
+use std::{path::PathBuf, time::Duration};
+
+struct AdminClient {
+    client: Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl AdminClient {
+    fn new(cli: &Cli) -> anyhow::Result<Self> {
+        let token = match (&cli.token, &cli.token_file) {
+            (Some(token), _) => Some(token.clone()),
+            (None, Some(path)) => Some(
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("failed to read token file {}", path.display()))?,
+            ),
+            (None, None) => None,
+        }
+        .map(|token| token.trim().to_string());
+
+        let mut builder = Client::builder().use_rustls_tls();
+        let scheme = match &cli.ca_cert {
+            Some(ca_cert) => {
+                let pem = std::fs::read(ca_cert)
+                    .with_context(|| format!("failed to read {}", ca_cert.display()))?;
+                builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+                if let Some(identity) = &cli.client_identity {
+                    let pem = std::fs::read(identity)
+                        .with_context(|| format!("failed to read {}", identity.display()))?;
+                    builder = builder.identity(Identity::from_pem(&pem)?);
+                }
+                "https"
+            }
+            None => "http",
+        };
+        Ok(Self {
+            client: builder.build()?,
+            base_url: format!("{}://{}", scheme, cli.admin_address),
+            token,
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.client.request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn send_raw(&self, request: RequestBuilder) -> anyhow::Result<Vec<u8>> {
+        let response = request.send().await.context("failed to reach the admin api")?;
+        let status = response.status();
+        let body = response.bytes().await?.to_vec();
+        anyhow::ensure!(
+            status.is_success(),
+            "admin api returned {}: {}",
+            status,
+            String::from_utf8_lossy(&body)
+        );
+        Ok(body)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> anyhow::Result<Value> {
+        let body = self.send_raw(request).await?;
+        Ok(serde_json::from_slice(&body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned())))
+    }
+}
+
+fn rules_json(rules: &TxnFilterRules) -> Value {
+    json!({
+        "deny_senders": rules.deny_senders,
+        "deny_to": rules.deny_to,
+        "deny_selectors": rules.deny_selectors,
+        "max_txn_bytes": rules.max_txn_bytes,
+    })
+}
+
+/// Appends the lists of `added` to the lists of `current`, `max_txn_bytes` is replaced if set.
+fn merge_rules(mut current: Value, added: &TxnFilterRules) -> Value {
+    for (key, values) in [
+        ("deny_senders", &added.deny_senders),
+        ("deny_to", &added.deny_to),
+        ("deny_selectors", &added.deny_selectors),
+    ] {
+        if !current[key].is_array() {
+            current[key] = json!([]);
+        }
+        current[key].as_array_mut().unwrap().extend(values.iter().map(|value| json!(value)));
+    }
+    if added.max_txn_bytes.is_some() {
+        current["max_txn_bytes"] = json!(added.max_txn_bytes);
+    }
+    current
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let client = AdminClient::new(&cli)?;
+    let response = match &cli.command {
+        Command::LogFilter(LogFilterCommand::Get) => {
+            client.send(client.request(Method::GET, "/log_filter")).await?
+        }
+        Command::LogFilter(LogFilterCommand::Set { filter, revert_after }) => {
+            let body = json!({ "filter": filter, "revert_after_secs": revert_after });
+            client.send(client.request(Method::POST, "/log_filter").json(&body)).await?
+        }
+        Command::LogFilter(LogFilterCommand::Reset) => {
+            client.send(client.request(Method::POST, "/log_filter/reset")).await?
+        }
+        Command::TxnFilter(TxnFilterCommand::Get) => {
+            client.send(client.request(Method::GET, "/txn_filter")).await?
+        }
+        Command::TxnFilter(TxnFilterCommand::Add(rules)) => {
+            let current = client.send(client.request(Method::GET, "/txn_filter")).await?;
+            let body = merge_rules(current, rules);
+            client.send(client.request(Method::POST, "/txn_filter").json(&body)).await?
+        }
+        Command::TxnFilter(TxnFilterCommand::Set(rules)) => {
+            let body = rules_json(rules);
+            client.send(client.request(Method::POST, "/txn_filter").json(&body)).await?
+        }
+        Command::TxnFilter(TxnFilterCommand::Clear) => {
+            client.send(client.request(Method::POST, "/txn_filter").json(&json!({}))).await?
+        }
+        Command::Equivocations { epoch } => {
+            let request = client.request(Method::GET, "/equivocations").query(&[("epoch", epoch)]);
+            client.send(request).await?
+        }
+        Command::ExecutionDivergences(ExecutionDivergenceCommand::List) => {
+            client.send(client.request(Method::GET, "/execution_divergences")).await?
+        }
+        Command::ExecutionDivergences(ExecutionDivergenceCommand::Clear) => {
+            client.send(client.request(Method::DELETE, "/execution_divergences")).await?
+        }
+        Command::Profile(ProfileCommand::Cpu { seconds, frequency, format }) => {
+            let format = match format {
+                CpuProfileFormat::Flamegraph => "flamegraph",
+                CpuProfileFormat::Pprof => "pprof",
+            };
+            let body = json!({ "seconds": seconds, "frequency": frequency, "format": format });
+            // the request only returns once the profile is captured
+            let request = client
+                .request(Method::POST, "/cpu_prof")
+                .timeout(Duration::from_secs(seconds + 60))
+                .json(&body);
+            client.send(request).await?
+        }
+        Command::Profile(ProfileCommand::HeapDump { name }) => {
+            let body = json!({ "name": name });
+            client.send(client.request(Method::POST, "/heap_dump").json(&body)).await?
+        }
+        Command::Profile(ProfileCommand::List) => {
+            client.send(client.request(Method::GET, "/profiles")).await?
+        }
+        Command::Profile(ProfileCommand::Download { name, output }) => {
+            let content = client
+                .send_raw(client.request(Method::GET, &format!("/profiles/{}", name)))
+                .await?;
+            let output = output.clone().unwrap_or_else(|| PathBuf::from(name));
+            std::fs::write(&output, &content)
+                .with_context(|| format!("failed to write {}", output.display()))?;
+            json!({ "name": name, "size": content.len(), "output": output })
+        }
+    };
+    println!("{}", serde_json::to_string_pretty(&response)?);
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use clap::Parser;
+use std::{path::PathBuf, str::FromStr};
+
+/// A validator entry given on the command line as `<listen_address>[=<voting_power>]`,
+/// e.g. `/ip4/127.0.0.1/tcp/2024=1`.
+#[derive(Clone, Debug)]
+pub struct ValidatorArg {
+    pub address: String,
+    pub voting_power: u64,
+}
+
+impl FromStr for ValidatorArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, voting_power) = match s.split_once('=') {
+            Some((address, power)) => {
+                let power =
+                    power.parse::<u64>().map_err(|e| format!("invalid voting power {}: {}", power, e))?;
+                (address, power)
+            }
+            None => (s, 1),
+        };
+        if !address.starts_with('/') {
+            return Err(format!("{} is not a multiaddr, e.g. /ip4/127.0.0.1/tcp/2024", address));
+        }
+        if voting_power == 0 {
+            return Err(format!("validator {} must have a non-zero voting power", address));
+        }
+        Ok(Self { address: address.to_string(), voting_power })
+    }
+}
+
+/// Generates the keys and configuration files of a gravity validator cluster.
+#[derive(Debug, Parser)]
+#[command(name = "gravity-genesis", version, about = "Generate genesis files for a gravity cluster")]
+pub struct Cli {
+    /// Validator listen address with an optional voting power, may be repeated.
+    /// Format: `<multiaddr>[=<voting_power>]`, the voting power defaults to 1.
+    #[arg(long = "validator", required = true, num_args = 1..)]
+    pub validators: Vec<ValidatorArg>,
+
+    /// Directory the generated files are written to.
+    #[arg(long = "output_dir", default_value = "./genesis_output")]
+    pub output_dir: PathBuf,
+
+    /// Directory the nodes are deployed into, used for the absolute paths in validator.yaml.
+    /// Node `i` (1-based) lives in `<deploy_root>/node<i>`.
+    #[arg(long = "deploy_root", default_value = "/tmp")]
+    pub deploy_root: PathBuf,
+
+    /// Inspection service port of node1, node `i` uses `base + i - 1`.
+    #[arg(long = "inspection_port_base", default_value_t = 10000)]
+    pub inspection_port_base: u16,
+
+    /// Overwrite the output directory if it already exists.
+    #[arg(long)]
+    pub force: bool,
+}
@@ -0,0 +1,244 @@
+use anyhow::{ensure, Result};
+use aptos_consensus::consensusdb::{genesis_waypoint, GravityNodeConfig, GravityNodeConfigSet};
+use gaptos::aptos_crypto::{
+    bls12381, ed25519::Ed25519PrivateKey, x25519, PrivateKey, Uniform, ValidCryptoMaterial,
+};
+use gaptos::aptos_types::account_address::from_identity_public_key;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::cli::ValidatorArg;
+
+/// The keys of a single validator, serialized as `validator-identity.yaml`.
+#[derive(Serialize)]
+pub struct ValidatorIdentity {
+    pub account_address: String,
+    pub account_private_key: String,
+    pub consensus_private_key: String,
+    pub network_private_key: String,
+}
+
+#[derive(Serialize)]
+struct DiscoveryPeer {
+    addresses: Vec<String>,
+    keys: Vec<String>,
+    role: String,
+}
+
+pub struct GeneratedValidator {
+    pub node_name: String,
+    pub address: String,
+    pub identity: ValidatorIdentity,
+    pub config: GravityNodeConfig,
+}
+
+pub fn generate_validator<R: rand::RngCore + rand::CryptoRng>(
+    rng: &mut R,
+    index: usize,
+    validator: &ValidatorArg,
+    all_addresses: &[String],
+) -> GeneratedValidator {
+    let account_private_key = Ed25519PrivateKey::generate(rng);
+    let consensus_private_key = bls12381::PrivateKey::generate(rng);
+    let network_private_key = x25519::PrivateKey::generate(rng);
+    let network_public_key = network_private_key.public_key();
+    // The noise handshake expects the peer id to be derived from the network key
+    let account_address = from_identity_public_key(network_public_key).to_hex();
+
+    let identity = ValidatorIdentity {
+        account_address: account_address.clone(),
+        account_private_key: hex::encode(account_private_key.to_bytes()),
+        consensus_private_key: format!("0x{}", hex::encode(consensus_private_key.to_bytes())),
+        network_private_key: hex::encode(network_private_key.to_bytes()),
+    };
+    let config = GravityNodeConfig {
+        consensus_public_key: hex::encode(consensus_private_key.public_key().to_bytes()),
+        account_address,
+        network_public_key: hex::encode(network_public_key.to_bytes()),
+        trusted_peers_map: all_addresses
+            .iter()
+            .filter(|addr| **addr != validator.address)
+            .cloned()
+            .collect(),
+        public_ip_address: validator.address.clone(),
+        voting_power: validator.voting_power,
+    };
+    GeneratedValidator {
+        node_name: format!("node{}", index + 1),
+        address: validator.address.clone(),
+        identity,
+        config,
+    }
+}
+
+fn validator_yaml(node_dir: &Path, listen_address: &str, inspection_port: u16) -> String {
+    let node_dir = node_dir.display();
+    format!(
+        r#"base:
+  role: "validator"
+  data_dir: "{node_dir}/data"
+  waypoint:
+    from_file: "{node_dir}/genesis/waypoint.txt"
+
+consensus:
+  safety_rules:
+    backend:
+      type: "on_disk_storage"
+      path: {node_dir}/data/secure_storage.json
+    initial_safety_rules_config:
+      from_file:
+        waypoint:
+          from_file: {node_dir}/genesis/waypoint.txt
+        identity_blob_path: {node_dir}/genesis/validator-identity.yaml
+  enable_pipeline: true
+
+validator_network:
+  network_id: validator
+  listen_address: "{listen_address}"
+  discovery_method:
+    file:
+      path: "{node_dir}/discovery"
+      interval_secs: 3600
+  mutual_authentication: true
+  identity:
+    type: "from_file"
+    path: {node_dir}/genesis/validator-identity.yaml
+
+storage:
+  dir: "{node_dir}/data"
+
+node_config_path: "{node_dir}/genesis/nodes_config.json"
+
+log_file_path: "{node_dir}/consensus_log"
+
+inspection_service:
+  port: {inspection_port}
+  address: 0.0.0.0
+
+# https_server_address: "127.0.0.1:1998"
+# https_cert_pem_path: "/absolute_path/cert.pem"
+# https_key_pem_path: "/absolute_path/key.pem"
+"#
+    )
+}
+
+fn discovery(validators: &[GeneratedValidator]) -> BTreeMap<String, DiscoveryPeer> {
+    validators
+        .iter()
+        .map(|v| {
+            let peer = DiscoveryPeer {
+                addresses: vec![format!(
+                    "{}/noise-ik/{}/handshake/0",
+                    v.address, v.config.network_public_key
+                )],
+                keys: vec![v.config.network_public_key.clone()],
+                role: "Validator".to_string(),
+            };
+            (v.config.account_address.clone(), peer)
+        })
+        .collect()
+}
+
+/// Writes the cluster layout expected by `deploy_utils/deploy.sh`:
+///
+/// ```text
+/// <output_dir>/nodes_config.json
+/// <output_dir>/discovery
+/// <output_dir>/node<i>/genesis/{validator-identity.yaml, validator.yaml, waypoint.txt}
+/// ```
+pub fn write_cluster(
+    output_dir: &Path,
+    deploy_root: &Path,
+    inspection_port_base: u16,
+    validators: Vec<GeneratedValidator>,
+) -> Result<PathBuf> {
+    ensure!(!validators.is_empty(), "at least one validator is required");
+    let node_config_set: GravityNodeConfigSet = validators
+        .iter()
+        .map(|v| (v.address.clone(), v.config.clone()))
+        .collect();
+    let waypoint = genesis_waypoint(&node_config_set)?;
+
+    fs::create_dir_all(output_dir)?;
+    let nodes_config = serde_json::to_string_pretty(&node_config_set)?;
+    fs::write(output_dir.join("nodes_config.json"), &nodes_config)?;
+    fs::write(output_dir.join("discovery"), serde_yaml::to_string(&discovery(&validators))?)?;
+
+    for (i, validator) in validators.iter().enumerate() {
+        let genesis_dir = output_dir.join(&validator.node_name).join("genesis");
+        fs::create_dir_all(&genesis_dir)?;
+        let inspection_port = inspection_port_base
+            .checked_add(i as u16)
+            .ok_or_else(|| anyhow::anyhow!("inspection port overflow for {}", validator.node_name))?;
+        fs::write(
+            genesis_dir.join("validator-identity.yaml"),
+            serde_yaml::to_string(&validator.identity)?,
+        )?;
+        fs::write(
+            genesis_dir.join("validator.yaml"),
+            validator_yaml(
+                &deploy_root.join(&validator.node_name),
+                &validator.address,
+                inspection_port,
+            ),
+        )?;
+        fs::write(genesis_dir.join("waypoint.txt"), waypoint.to_string())?;
+        fs::write(genesis_dir.join("nodes_config.json"), &nodes_config)?;
+    }
+    Ok(output_dir.to_path_buf())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn validators(n: u16) -> Vec<ValidatorArg> {
+        (0..n)
+            .map(|i| format!("/ip4/127.0.0.1/tcp/{}={}", 2024 + i, i + 1).parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn generated_cluster_is_consistent() {
+        let args = validators(4);
+        let addresses: Vec<_> = args.iter().map(|v| v.address.clone()).collect();
+        let mut rng = StdRng::from_seed([7u8; 32]);
+        let generated: Vec<_> = args
+            .iter()
+            .enumerate()
+            .map(|(i, v)| generate_validator(&mut rng, i, v, &addresses))
+            .collect();
+        for (i, v) in generated.iter().enumerate() {
+            assert_eq!(v.config.account_address, v.config.network_public_key);
+            assert_eq!(v.config.trusted_peers_map.len(), 3);
+            assert!(!v.config.trusted_peers_map.contains(&v.address));
+            assert_eq!(v.config.voting_power, i as u64 + 1);
+        }
+
+        let dir = std::env::temp_dir().join(format!("gravity_genesis_{}", std::process::id()));
+        write_cluster(&dir, Path::new("/tmp"), 10000, generated).unwrap();
+        let node_config_set: GravityNodeConfigSet =
+            serde_yaml::from_str(&fs::read_to_string(dir.join("nodes_config.json")).unwrap())
+                .unwrap();
+        let waypoint = fs::read_to_string(dir.join("node4/genesis/waypoint.txt")).unwrap();
+        assert_eq!(waypoint, genesis_waypoint(&node_config_set).unwrap().to_string());
+        assert!(waypoint.starts_with("0:"));
+        let validator_yaml = fs::read_to_string(dir.join("node2/genesis/validator.yaml")).unwrap();
+        assert!(validator_yaml.contains("/ip4/127.0.0.1/tcp/2025"));
+        assert!(validator_yaml.contains("port: 10001"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn parse_validator_arg() {
+        let v: ValidatorArg = "/ip4/10.0.0.1/tcp/6180".parse().unwrap();
+        assert_eq!(v.voting_power, 1);
+        assert!("/ip4/10.0.0.1/tcp/6180=0".parse::<ValidatorArg>().is_err());
+        assert!("10.0.0.1:6180".parse::<ValidatorArg>().is_err());
+    }
+}
@@ -0,0 +1,43 @@
+mod cli;
+mod genesis;
+
+use std::collections::HashSet;
+
+use clap::Parser;
+use cli::Cli;
+use genesis::{generate_validator, write_cluster};
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let addresses: Vec<String> = cli.validators.iter().map(|v| v.address.clone()).collect();
+    let unique: HashSet<_> = addresses.iter().collect();
+    anyhow::ensure!(unique.len() == addresses.len(), "duplicate validator address in {:?}", addresses);
+
+    if cli.output_dir.exists() {
+        anyhow::ensure!(
+            cli.force,
+            "output dir {} already exists, pass --force to overwrite it",
+            cli.output_dir.display()
+        );
+        std::fs::remove_dir_all(&cli.output_dir)?;
+    }
+
+    let mut rng = rand::rngs::OsRng;
+    let validators = cli
+        .validators
+        .iter()
+        .enumerate()
+        .map(|(i, v)| generate_validator(&mut rng, i, v, &addresses))
+        .collect::<Vec<_>>();
+    for v in &validators {
+        println!(
+            "{} {} account {} voting power {}",
+            v.node_name, v.address, v.config.account_address, v.config.voting_power
+        );
+    }
+    let output_dir =
+        write_cluster(&cli.output_dir, &cli.deploy_root, cli.inspection_port_base, validators)?;
+    println!("genesis files written to {}", output_dir.display());
+    Ok(())
+}
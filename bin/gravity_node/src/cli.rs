@@ -4,7 +4,8 @@ use greth::reth::{chainspec::EthereumChainSpecParser, cli::Commands};
 use greth::reth_chainspec::ChainSpec;
 use greth::reth_cli::chainspec::ChainSpecParser;
 use greth::reth_cli_commands::node::NoArgs;
-use greth::reth_cli_runner::CliRunner;
+use greth::reth::tasks::TaskManager;
+use greth::reth_cli_runner::{tokio_runtime, CliContext, CliRunner};
 use greth::reth_db::DatabaseEnv;
 use greth::reth_network::EthNetworkPrimitives;
 use greth::reth_node_builder::{NodeBuilder, WithLaunchContext};
@@ -16,8 +17,9 @@ use std::{
     fmt::{self},
     future::Future,
     sync::Arc,
+    time::Duration,
 };
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// The main reth cli interface.
 ///
@@ -147,7 +149,9 @@ impl<C: ChainSpecParser<ChainSpec = ChainSpec>, Ext: clap::Args + fmt::Debug> Cl
         match self.command {
             Commands::Node(command) => {
                 println!("Running node command, {:?}", command.dev);
-                runner.run_command_until_exit(|ctx| command.execute(ctx, launcher))
+                // ctrl-c and SIGTERM are handled by gravity, which stops the launcher once
+                // consensus has shut down
+                run_node_until_exit(|ctx| command.execute(ctx, launcher))
             }
             Commands::Init(command) => {
                 println!("Running init command");
@@ -191,3 +195,37 @@ impl<C: ChainSpecParser<ChainSpec = ChainSpec>, Ext: clap::Args + fmt::Debug> Cl
         Ok(guard)
     }
 }
+
+/// How long reth tasks get to finish once the node command returned.
+const RETH_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Like `CliRunner::run_command_until_exit`, but doesn't stop the node on ctrl-c or SIGTERM, so
+/// that reth keeps executing and committing the blocks consensus drains during its shutdown.
+fn run_node_until_exit<F>(command: impl FnOnce(CliContext) -> F) -> eyre::Result<()>
+where
+    F: Future<Output = eyre::Result<()>>,
+{
+    let tokio_runtime = tokio_runtime()?;
+    let mut task_manager = TaskManager::new(tokio_runtime.handle().clone());
+    let context = CliContext { task_executor: task_manager.executor() };
+    let fut = command(context);
+    let res = tokio_runtime.block_on(async {
+        tokio::select! {
+            res = fut => res,
+            err = &mut task_manager => Err(err.into()),
+        }
+    });
+    if !task_manager.graceful_shutdown_with_timeout(RETH_SHUTDOWN_TIMEOUT) {
+        warn!("reth tasks did not finish within {:?}", RETH_SHUTDOWN_TIMEOUT);
+    }
+    // Dropping the runtime waits for blocking tasks, give up on them after a while
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("tokio-runtime-shutdown".to_string())
+        .spawn(move || {
+            drop(tokio_runtime);
+            let _ = tx.send(());
+        })?;
+    let _ = rx.recv_timeout(RETH_SHUTDOWN_TIMEOUT);
+    res
+}
@@ -13,16 +13,21 @@ pub struct AptosConsensus {
 }
 
 impl AptosConsensus {
-    pub async fn init(node_config: NodeConfig, execution_client: Arc<RethCoordinator>, chain_id: u64, latest_block_number: u64) {
+    pub async fn init(
+        node_config: NodeConfig,
+        execution_client: Arc<RethCoordinator>,
+        chain_id: u64,
+        latest_block_number: u64,
+    ) -> Arc<ConsensusEngine> {
         let execution_layer = ExecutionLayer {
             execution_api: execution_client.clone(),
         };
 
-        let consensus_engine = ConsensusEngine::init(
+        ConsensusEngine::init(
             node_config,
             execution_layer,
             chain_id, // Chain ID
             latest_block_number
-        ).await;
+        ).await
     }
 }
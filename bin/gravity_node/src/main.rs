@@ -1,5 +1,4 @@
 use alloy_eips::BlockHashOrNumber;
-use alloy_primitives::TxHash;
 use block_buffer_manager::block_buffer_manager::BlockBufferManager;
 use block_buffer_manager::get_block_buffer_manager;
 use greth::gravity_storage;
@@ -15,7 +14,7 @@ use greth::reth_pipe_exec_layer_ext_v2::ExecutionArgs;
 use greth::reth_provider;
 use greth::reth_transaction_pool;
 
-use api::check_bootstrap_config;
+use api::{check_bootstrap_config, consensus_api::ConsensusEngine};
 use consensus::aptos::AptosConsensus;
 use gravity_storage::block_view_storage::BlockViewStorage;
 use reth::rpc::builder::auth::AuthServerHandle;
@@ -26,14 +25,14 @@ use reth_pipe_exec_layer_ext_v2::PipeExecLayerApi;
 use reth_provider::BlockHashReader;
 use reth_provider::BlockNumReader;
 use reth_provider::BlockReader;
-use reth_transaction_pool::TransactionPool;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
-use tracing::info;
+use tracing::{info, warn};
 mod cli;
 mod consensus;
 mod reth_cli;
 mod reth_coordinator;
+mod txn_cache;
 
 use crate::cli::Cli;
 use std::cell::OnceCell;
@@ -41,6 +40,7 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::sync::OnceLock;
 use std::thread;
+use std::time::Duration;
 
 use crate::reth_cli::RethCli;
 use clap::Parser;
@@ -53,7 +53,6 @@ struct ConsensusArgs {
     pub engine_api: AuthServerHandle,
     pub pipeline_api: PipeExecLayerApi<BlockViewStorage<BlockchainProvider<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>>>,
     pub provider: BlockchainProvider<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>,
-    pub tx_listener: tokio::sync::mpsc::Receiver<TxHash>,
     pub pool: reth_transaction_pool::Pool<
         reth_transaction_pool::TransactionValidationTaskExecutor<
             reth_transaction_pool::EthTransactionValidator<
@@ -70,6 +69,7 @@ fn run_reth(
     tx: mpsc::Sender<(ConsensusArgs, u64)>,
     cli: Cli<EthereumChainSpecParser>,
     execution_args_rx: oneshot::Receiver<ExecutionArgs>,
+    gravity_stopped_rx: oneshot::Receiver<anyhow::Result<()>>,
 ) {
     reth_cli_util::sigsegv_handler::install();
 
@@ -95,8 +95,6 @@ fn run_reth(
                     })
                     .await?;
                 let chain_spec = handle.node.chain_spec();
-                let pending_listener: tokio::sync::mpsc::Receiver<TxHash> =
-                    handle.node.pool.pending_transactions_listener();
                 let engine_cli = handle.node.auth_server_handle().clone();
                 let provider: BlockchainProvider<
                     reth_node_api::NodeTypesWithDBAdapter<EthereumNode, Arc<reth_db::DatabaseEnv>>,
@@ -140,11 +138,17 @@ fn run_reth(
                     engine_api: engine_cli,
                     pipeline_api: pipeline_api_v2,
                     provider,
-                    tx_listener: pending_listener,
                     pool,
                 };
                 tx.send((args, latest_block_number)).await.ok();
-                handle.node_exit_future.await
+                tokio::select! {
+                    res = handle.node_exit_future => res,
+                    res = gravity_stopped_rx => match res {
+                        Ok(Ok(())) => Ok(()),
+                        Ok(Err(e)) => Err(eyre::eyre!("gravity shut down uncleanly: {:#}", e)),
+                        Err(_) => Err(eyre::eyre!("gravity exited without shutting down")),
+                    },
+                }
             }
         })
     } {
@@ -153,27 +157,90 @@ fn run_reth(
     }
 }
 
+/// How long in flight blocks get to commit on shutdown, override with `GRAVITY_SHUTDOWN_DRAIN_SECS`.
+const DEFAULT_SHUTDOWN_DRAIN_SECS: u64 = 60;
+
+fn shutdown_drain_timeout() -> Duration {
+    Duration::from_secs(
+        std::env::var("GRAVITY_SHUTDOWN_DRAIN_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(DEFAULT_SHUTDOWN_DRAIN_SECS),
+    )
+}
+
+/// Resolves on ctrl-c or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async { tokio::signal::ctrl_c().await.expect("failed to listen for ctrl-c") };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => info!("received ctrl-c"),
+        _ = terminate => info!("received SIGTERM"),
+    }
+}
+
+/// Drains the in flight blocks through commit, then stops consensus and the reth coordinator.
+async fn shutdown(
+    consensus_engine: &ConsensusEngine,
+    coordinator: &RethCoordinator,
+) -> anyhow::Result<()> {
+    let drain_timeout = shutdown_drain_timeout();
+    info!("shutting down gravity, draining blocks for at most {:?}", drain_timeout);
+    let consensus_res = consensus_engine.shutdown(drain_timeout).await;
+    let coordinator_res = coordinator.shutdown().await;
+    let latest_commit_block_number = consensus_res?;
+    coordinator_res?;
+    info!("gravity shut down at block {}", latest_commit_block_number);
+    Ok(())
+}
+
 fn main() {
     let (tx, mut rx) = tokio::sync::mpsc::channel(1);
     let cli = Cli::parse();
     let gcei_config = check_bootstrap_config(cli.gravity_node_config.node_config_path.clone());
     let (execution_args_tx, execution_args_rx) = oneshot::channel();
+    let (gravity_stopped_tx, gravity_stopped_rx) = oneshot::channel();
     thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async move {
-            if let Some((args, latest_block_number)) = rx.recv().await {
-                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        let res = rt.block_on(async move {
+            let (args, latest_block_number) = tokio::select! {
+                args = rx.recv() => match args {
+                    Some(args) => args,
+                    None => return Ok(()),
+                },
+                // reth is still starting, there is nothing to drain
+                _ = shutdown_signal() => return Ok(()),
+            };
+            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
-                let client = RethCli::new(args).await;
-                let chain_id = client.chain_id();
-                let coordinator =
-                    Arc::new(RethCoordinator::new(client, latest_block_number, execution_args_tx));
-                AptosConsensus::init(gcei_config, coordinator.clone(), chain_id, latest_block_number).await;
-                coordinator.send_execution_args().await;
-                coordinator.run().await;
-                tokio::signal::ctrl_c().await.unwrap();
+            let client = RethCli::new(args).await;
+            let chain_id = client.chain_id();
+            let coordinator =
+                Arc::new(RethCoordinator::new(client, latest_block_number, execution_args_tx));
+            let consensus_engine = AptosConsensus::init(
+                gcei_config,
+                coordinator.clone(),
+                chain_id,
+                latest_block_number,
+            )
+            .await;
+            coordinator.send_execution_args().await;
+            coordinator.run().await;
+            tokio::select! {
+                _ = shutdown_signal() => {}
+                _ = coordinator.failed() => warn!("a reth coordinator loop failed, shutting down"),
             }
+            shutdown(&consensus_engine, &coordinator).await
         });
+        let _ = gravity_stopped_tx.send(res);
     });
-    run_reth(tx, cli, execution_args_rx);
+    run_reth(tx, cli, execution_args_rx, gravity_stopped_rx);
 }
@@ -1,8 +1,13 @@
+use crate::txn_cache::{TxnCache, TxnKey};
 use crate::ConsensusArgs;
+use alloy_consensus::Transaction as _;
 use alloy_eips::{eip4895::Withdrawals, BlockId, BlockNumberOrTag, Decodable2718, Encodable2718};
 use alloy_primitives::{
     private::alloy_rlp::{Decodable, Encodable},
-    Address, TxHash, B256,
+    keccak256, Address, TxHash, B256,
+};
+use api_types::block_budget::{
+    BlockBudget, BlockResourceMeter, TxnCost, GLOBAL_BLOCK_RESOURCE_METER,
 };
 use api_types::u256_define::{BlockId as ExternalBlockId, TxnHash};
 use api_types::{
@@ -11,18 +16,30 @@ use api_types::{
 };
 use api_types::{
     compute_res::TxnStatus,
+    txn_filter::{TxnCall, GLOBAL_TXN_CALL_DECODER},
     GLOBAL_CRYPTO_TXN_HASHER,
 };
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
-use api_types::{ExecutionBlocks, ExternalBlock, VerifiedTxn, VerifiedTxnWithAccountSeqNum};
+use api_types::{
+    ConflictKey, ExecError, ExecutionBlocks, ExternalBlock, TxnPriority, VerifiedTxn,
+    VerifiedTxnWithAccountSeqNum,
+};
+use block_buffer_manager::block_buffer_manager::{TxnPoolEvent, TxnRef};
 use block_buffer_manager::get_block_buffer_manager;
 use rayon::iter::IntoParallelRefMutIterator;
 use core::panic;
-use greth::{reth_ethereum_engine_primitives::EthPayloadAttributes, reth_transaction_pool::{EthPooledTransaction, ValidPoolTransaction}};
+use futures::StreamExt;
+use greth::{
+    reth_ethereum_engine_primitives::EthPayloadAttributes,
+    reth_transaction_pool::{
+        AllTransactionsEvents, EthPooledTransaction, FullTransactionEvent, PoolTransaction,
+        TransactionOrigin, ValidPoolTransaction,
+    },
+};
 use greth::reth_node_api::NodeTypesWithDBAdapter;
 use greth::reth_node_ethereum::EthereumNode;
 use greth::reth_pipe_exec_layer_ext_v2::{ExecutedBlockMeta, OrderedBlock, PipeExecLayerApi};
-use greth::reth_primitives::TransactionSigned;
+use greth::reth_primitives::{RecoveredTx, TransactionSigned};
 use greth::reth_provider::providers::BlockchainProvider;
 use greth::reth_provider::{
     AccountReader, BlockNumReader, BlockReaderIdExt, ChainSpecProvider, DatabaseProviderFactory,
@@ -48,8 +65,9 @@ pub struct RethCli {
         >,
     >,
     chain_id: u64,
+    block_gas_limit: u64,
     provider: BlockchainProvider<NodeTypesWithDBAdapter<EthereumNode, Arc<DatabaseEnv>>>,
-    txn_listener: Mutex<tokio::sync::mpsc::Receiver<TxHash>>,
+    pool_events: Mutex<AllTransactionsEvents<EthPooledTransaction>>,
     pool: greth::reth_transaction_pool::Pool<
         greth::reth_transaction_pool::TransactionValidationTaskExecutor<
             greth::reth_transaction_pool::EthTransactionValidator<
@@ -62,7 +80,20 @@ pub struct RethCli {
         >,
         greth::reth_transaction_pool::blobstore::DiskFileBlobStore,
     >,
-    txn_cache: Mutex<HashMap<(ExternalAccountAddress, u64), Arc<ValidPoolTransaction<EthPooledTransaction>>>>,
+    txn_cache: Mutex<TxnCache<Arc<ValidPoolTransaction<EthPooledTransaction>>>>,
+}
+
+const DEFAULT_TXN_CACHE_CAPACITY: usize = 100_000;
+
+fn txn_cache_capacity() -> usize {
+    std::env::var("TXN_CACHE_CAPACITY")
+        .ok()
+        .and_then(|capacity| capacity.parse().ok())
+        .unwrap_or(DEFAULT_TXN_CACHE_CAPACITY)
+}
+
+fn txn_ref((sender, sequence_number): TxnKey, hash: TxHash) -> TxnRef {
+    TxnRef { sender, sequence_number, hash: hash.0 }
 }
 
 pub fn convert_account(acc: Address) -> ExternalAccountAddress {
@@ -75,6 +106,62 @@ fn calculate_txn_hash(bytes: &Vec<u8>) -> [u8; 32] {
     alloy_primitives::utils::keccak256(bytes.clone()).as_slice().try_into().unwrap()
 }
 
+/// The intrinsic gas of a plain transfer, no txn has a lower gas limit.
+const MIN_TXN_GAS: u64 = 21_000;
+
+/// Lets consensus build blocks within the gas limit reth executes them with, since reth
+/// discards the txns of a block past its gas limit.
+struct RethBlockResourceMeter {
+    block_gas_limit: u64,
+}
+
+impl BlockResourceMeter for RethBlockResourceMeter {
+    fn block_budget(&self) -> BlockBudget {
+        BlockBudget::new(Some(self.block_gas_limit), None)
+    }
+
+    fn txn_cost(&self, txn: &[u8]) -> TxnCost {
+        let gas = TransactionSigned::decode_2718(&mut &txn[..]).map_or(0, |txn| txn.gas_limit());
+        TxnCost { gas, bytes: txn.len() as u64 }
+    }
+
+    fn estimate_cost(&self, num_txns: u64, num_bytes: u64) -> TxnCost {
+        TxnCost { gas: num_txns.saturating_mul(MIN_TXN_GAS), bytes: num_bytes }
+    }
+}
+
+/// Decodes the callee and selector of a txn for `api_types::txn_filter`.
+fn decode_txn_call(txn: &[u8]) -> Option<TxnCall> {
+    let txn = TransactionSigned::decode_2718(&mut &txn[..]).ok()?;
+    Some(TxnCall {
+        to: txn.to().map(|to| convert_account(to).bytes()),
+        selector: txn.input().get(..4).map(|selector| selector.try_into().unwrap()),
+    })
+}
+
+/// The conflict keys of a txn for the conflict key shuffler: the callee and every address and
+/// storage slot of its access list. Undecodable txns have no keys.
+pub(crate) fn txn_conflict_keys(txn: &[u8]) -> Vec<ConflictKey> {
+    let Ok(txn) = TransactionSigned::decode_2718(&mut &txn[..]) else {
+        return vec![];
+    };
+    let mut keys: Vec<ConflictKey> = txn.to().map(address_conflict_key).into_iter().collect();
+    for item in txn.access_list().into_iter().flat_map(|list| list.iter()) {
+        let address_key = address_conflict_key(item.address);
+        for slot in &item.storage_keys {
+            keys.push([address_key.as_slice(), slot.as_slice()].concat());
+        }
+        keys.push(address_key);
+    }
+    keys
+}
+
+/// Addresses are padded like txn senders, so that a txn calling an account conflicts with the
+/// txns sent by it.
+fn address_conflict_key(address: Address) -> ConflictKey {
+    convert_account(address).bytes().to_vec()
+}
+
 impl RethCli {
     pub async fn new(args: ConsensusArgs) -> Self {
         let chian_info = args.provider.chain_spec().chain;
@@ -82,15 +169,20 @@ impl RethCli {
             greth::reth_chainspec::ChainKind::Named(n) => n as u64,
             greth::reth_chainspec::ChainKind::Id(id) => id,
         };
+        let block_gas_limit = args.provider.chain_spec().genesis.gas_limit;
         GLOBAL_CRYPTO_TXN_HASHER.get_or_init(|| Box::new(calculate_txn_hash));
+        GLOBAL_TXN_CALL_DECODER.get_or_init(|| Box::new(decode_txn_call));
+        GLOBAL_BLOCK_RESOURCE_METER
+            .get_or_init(|| Box::new(RethBlockResourceMeter { block_gas_limit }));
         RethCli {
             auth: args.engine_api,
             pipe_api: args.pipeline_api,
             chain_id,
+            block_gas_limit,
             provider: args.provider,
-            txn_listener: Mutex::new(args.tx_listener),
+            pool_events: Mutex::new(args.pool.all_transactions_event_listener()),
             pool: args.pool,
-            txn_cache: Mutex::new(HashMap::new()),
+            txn_cache: Mutex::new(TxnCache::new(txn_cache_capacity())),
         }
     }
 
@@ -98,11 +190,65 @@ impl RethCli {
         self.chain_id
     }
 
+    /// The nonce of `account` in the latest persisted state, 0 for unknown accounts.
+    pub fn account_nonce(&self, account: &ExternalAccountAddress) -> Option<u64> {
+        let address = Address::from_slice(&account.bytes()[12..]);
+        match self.provider.basic_account(&address) {
+            Ok(account) => Some(account.map_or(0, |account| account.nonce)),
+            Err(e) => {
+                warn!("failed to read the nonce of {}: {:?}", address, e);
+                None
+            }
+        }
+    }
+
     fn txn_to_signed(bytes: &mut [u8], chain_id: u64) -> (Address, TransactionSigned) {
         let txn = TransactionSigned::decode_2718(&mut bytes.as_ref()).unwrap();
         (txn.recover_signer().unwrap(), txn)
     }
 
+    fn decode_pooled_txn(bytes: &[u8]) -> Result<EthPooledTransaction, String> {
+        let txn = TransactionSigned::decode_2718(&mut &bytes[..]).map_err(|e| e.to_string())?;
+        let signer = txn.recover_signer().ok_or("invalid signature")?;
+        EthPooledTransaction::try_from_consensus(RecoveredTx::from_signed_transaction(txn, signer))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Adds the txns submitted by clients to the pool in a single call, returns a result per txn
+    /// in order. The pool events forward the accepted ones to consensus.
+    pub async fn add_external_txns(&self, txns: Vec<Vec<u8>>) -> Vec<Result<TxnHash, ExecError>> {
+        // `None` marks the decoded txns, their results are filled in from the pool below
+        let mut results = Vec::with_capacity(txns.len());
+        let mut pooled = Vec::with_capacity(txns.len());
+        for bytes in txns {
+            match Self::decode_pooled_txn(&bytes) {
+                Ok(txn) => {
+                    pooled.push(txn);
+                    results.push(None);
+                }
+                Err(e) => {
+                    warn!("failed to decode submitted txn: {}", e);
+                    results.push(Some(Err(ExecError::InternalError)));
+                }
+            }
+        }
+        let mut added =
+            self.pool.add_transactions(TransactionOrigin::External, pooled).await.into_iter();
+        results
+            .into_iter()
+            .map(|result| {
+                result.unwrap_or_else(|| match added.next() {
+                    Some(Ok(hash)) => Ok(TxnHash::new(hash.0)),
+                    Some(Err(e)) => {
+                        warn!("pool rejected submitted txn: {}", e);
+                        Err(ExecError::InternalError)
+                    }
+                    None => Err(ExecError::InternalError),
+                })
+            })
+            .collect()
+    }
+
     pub async fn push_ordered_block(
         &self,
         mut block: ExternalBlock,
@@ -120,7 +266,12 @@ impl RethCli {
             let mut cache = self.txn_cache.lock().await;
             for (idx, txn) in block.txns.iter().enumerate() {
                 let key = (txn.sender.clone(), txn.sequence_number);
-                if let Some(cached_txn) = cache.remove(&key) {
+                // The pool may have replaced the ordered txn since, only the ordered one is used
+                let hash = match txn.committed_hash.get() {
+                    Some(hash) => TxHash::from(hash.0),
+                    None => keccak256(&txn.bytes),
+                };
+                if let Some(cached_txn) = cache.get(&key, &hash) {
                     senders[idx] = Some(cached_txn.sender());
                     transactions[idx] = Some(cached_txn.transaction.transaction().tx().clone());
                 }
@@ -142,6 +293,13 @@ impl RethCli {
 
         let senders: Vec<_> = senders.into_iter().map(|x| x.unwrap()).collect();
         let transactions: Vec<_> = transactions.into_iter().map(|x| x.unwrap()).collect();
+        let requested_gas: u64 = transactions.iter().map(|txn| txn.gas_limit()).sum();
+        if requested_gas > self.block_gas_limit {
+            warn!(
+                "block {} requests {} gas over the block gas limit {}, the overflowing txns will be discarded",
+                block.block_meta.block_number, requested_gas, self.block_gas_limit
+            );
+        }
 
         
         let randao = match block.block_meta.randomness {
@@ -188,39 +346,89 @@ impl RethCli {
         Ok(())
     }
 
+    /// Forwards the pool events to the consensus mempool, in the order the pool emits them.
     pub async fn start_mempool(&self) -> Result<(), String> {
-        debug!("start process pending transactions");
-        let mut mut_txn_listener = self.txn_listener.lock().await;
-        while let Some(txn_hash) = mut_txn_listener.recv().await {
-            let pool_txn = self.pool.get(&txn_hash).unwrap();
-            let sender = pool_txn.sender();
-            let nonce = pool_txn.nonce();
-            let txn = pool_txn.transaction.transaction().tx();
-            let account_nonce =
-                self.provider.basic_account(&sender).unwrap().map(|x| x.nonce).unwrap_or(nonce);
-            // Since the consensus layer might use the bytes to recalculate the hash, we need to encode the transaction
-            let bytes = txn.encoded_2718();
-
-            let vtxn = VerifiedTxnWithAccountSeqNum {
-                txn: VerifiedTxn {
-                    bytes,
-                    sender: convert_account(sender),
-                    sequence_number: nonce,
-                    chain_id: ExternalChainId::new(0),
-                    committed_hash: TxnHash::from_bytes(txn.hash().as_slice()).into(),
-                },
-                account_seq_num: account_nonce,
+        debug!("start process txn pool events");
+        let mut pool_events = self.pool_events.lock().await;
+        while let Some(event) = pool_events.next().await {
+            let event = match event {
+                FullTransactionEvent::Pending(txn_hash) => self.on_pending_txn(txn_hash).await,
+                // A replacement that became pending was already forwarded as `Replaced`
+                FullTransactionEvent::Replaced { transaction, .. } => {
+                    let txn_hash = *transaction.hash();
+                    self.txn_cache
+                        .lock()
+                        .await
+                        .remove_by_hash(&txn_hash)
+                        .map(|(key, _)| TxnPoolEvent::Discarded(txn_ref(key, txn_hash)))
+                }
+                FullTransactionEvent::Discarded(txn_hash) |
+                FullTransactionEvent::Invalid(txn_hash) => self
+                    .txn_cache
+                    .lock()
+                    .await
+                    .remove_by_hash(&txn_hash)
+                    .map(|(key, _)| TxnPoolEvent::Discarded(txn_ref(key, txn_hash))),
+                FullTransactionEvent::Mined { tx_hash, .. } => self
+                    .txn_cache
+                    .lock()
+                    .await
+                    .remove_by_hash(&tx_hash)
+                    .map(|((sender, sequence_number), _)| TxnPoolEvent::Mined {
+                        sender,
+                        sequence_number,
+                    }),
+                FullTransactionEvent::Queued(_) | FullTransactionEvent::Propagated(_) => None,
             };
-            {       
-                self.txn_cache.lock().await
-                    .insert((vtxn.txn.sender().clone(), vtxn.txn.seq_number()), pool_txn.clone());
+            if let Some(event) = event {
+                get_block_buffer_manager().push_txn_event(event).await;
             }
-            get_block_buffer_manager().push_txn(vtxn).await;
         }
-        debug!("end process pending transactions");
+        debug!("end process txn pool events");
         Ok(())
     }
 
+    async fn on_pending_txn(&self, txn_hash: TxHash) -> Option<TxnPoolEvent> {
+        // The txn may have left the pool since the event was emitted
+        let pool_txn = self.pool.get(&txn_hash)?;
+        if self.txn_cache.lock().await.contains_hash(&txn_hash) {
+            return None;
+        }
+        let sender = pool_txn.sender();
+        let nonce = pool_txn.nonce();
+        let txn = pool_txn.transaction.transaction().tx();
+        let account_nonce =
+            self.provider.basic_account(&sender).unwrap().map(|x| x.nonce).unwrap_or(nonce);
+        // Since the consensus layer might use the bytes to recalculate the hash, we need to encode the transaction
+        let bytes = txn.encoded_2718();
+
+        let vtxn = VerifiedTxnWithAccountSeqNum {
+            txn: VerifiedTxn {
+                bytes,
+                sender: convert_account(sender),
+                sequence_number: nonce,
+                chain_id: ExternalChainId::new(0),
+                committed_hash: TxnHash::from_bytes(txn.hash().as_slice()).into(),
+                // EVM txns don't expire, reth evicts them from its own pool
+                priority: TxnPriority {
+                    max_fee_per_gas: Some(txn.max_fee_per_gas()),
+                    max_priority_fee_per_gas: txn.max_priority_fee_per_gas(),
+                    gas_limit: Some(txn.gas_limit()),
+                    expiration_timestamp_secs: None,
+                },
+            },
+            account_seq_num: account_nonce,
+        };
+        let key = (vtxn.txn.sender().clone(), vtxn.txn.seq_number());
+        let replaced = self.txn_cache.lock().await.insert(key.clone(), txn_hash, pool_txn);
+        Some(match replaced {
+            Some(replaced_hash) => {
+                TxnPoolEvent::Replaced { replaced: txn_ref(key, replaced_hash), replacement: vtxn }
+            }
+            None => TxnPoolEvent::Pending(vtxn),
+        })
+    }
+
     pub async fn start_execution(&self) -> Result<(), String> {
         let mut start_ordered_block = self.provider.last_block_number().unwrap() + 1;
         loop {
@@ -233,6 +441,10 @@ impl RethCli {
             }
             let exec_blocks = exec_blocks.unwrap();
             if exec_blocks.is_empty() {
+                if get_block_buffer_manager().is_stopping() {
+                    info!("stop execution before block {}", start_ordered_block);
+                    return Ok(());
+                }
                 info!("no ordered blocks");
                 continue;
             }
@@ -257,6 +469,15 @@ impl RethCli {
             let block_id = ExternalBlockId::from_bytes(execution_result.block_id.as_slice());
             let block_number = execution_result.block_number;
             let tx_infos = execution_result.txs_info;
+            let num_discarded = tx_infos.iter().filter(|tx_info| tx_info.is_discarded).count();
+            if num_discarded > 0 {
+                warn!(
+                    "block {} discarded {} of {} txns",
+                    block_number,
+                    num_discarded,
+                    tx_infos.len()
+                );
+            }
             let txn_status = Arc::new(Some(
                 tx_infos
                     .iter()
@@ -1,15 +1,18 @@
 pub mod queue;
 pub mod state;
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::reth_cli::{convert_account, RethCli};
+use crate::reth_cli::{convert_account, txn_conflict_keys, RethCli};
+use api_types::account::ExternalAccountAddress;
+use api_types::block_budget;
 use api_types::compute_res::{ComputeRes, TxnStatus};
 use api_types::u256_define::TxnHash;
 use api_types::{
-    u256_define::BlockId, ExecError, ExecTxn, ExecutionChannel, ExternalBlock, ExternalBlockMeta,
-    ExternalPayloadAttr, VerifiedTxn, VerifiedTxnWithAccountSeqNum,
+    u256_define::BlockId, ConflictKey, ExecError, ExecTxn, ExecutionChannel, ExternalBlock,
+    ExternalBlockMeta, ExternalPayloadAttr, VerifiedTxn, VerifiedTxnWithAccountSeqNum,
 };
 use async_trait::async_trait;
 use block_buffer_manager::get_block_buffer_manager;
@@ -17,15 +20,18 @@ use greth::reth::revm::db::components::block_hash;
 use greth::reth_pipe_exec_layer_ext_v2::{ExecutionArgs, ExecutionResult};
 use alloy_primitives::B256;
 use state::State;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 use tokio::time::{sleep, Sleep};
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 pub struct RethCoordinator {
     reth_cli: Arc<RethCli>,
     state: Arc<Mutex<State>>,
     execution_args_tx: Arc<Mutex<Option<oneshot::Sender<ExecutionArgs>>>>,
+    loops: Mutex<Vec<(&'static str, JoinHandle<Result<(), String>>)>>,
+    failure: Arc<Notify>,
 }
 
 impl RethCoordinator {
@@ -39,6 +45,8 @@ impl RethCoordinator {
             reth_cli: Arc::new(reth_cli),
             state: Arc::new(Mutex::new(state)),
             execution_args_tx: Arc::new(Mutex::new(Some(execution_args_tx))),
+            loops: Mutex::new(Vec::new()),
+            failure: Arc::new(Notify::new()),
         }
     }
 
@@ -57,30 +65,77 @@ impl RethCoordinator {
         }
     }
 
+    fn spawn_loop(
+        &self,
+        name: &'static str,
+        fut: impl Future<Output = Result<(), String>> + Send + 'static,
+    ) -> (&'static str, JoinHandle<Result<(), String>>) {
+        let failure = self.failure.clone();
+        let handle = tokio::spawn(async move {
+            let res = fut.await;
+            if let Err(e) = &res {
+                error!("{} loop failed: {}", name, e);
+                failure.notify_one();
+            }
+            res
+        });
+        (name, handle)
+    }
+
     pub async fn run(&self) {
+        let mut loops = self.loops.lock().await;
         let reth_cli = self.reth_cli.clone();
-        tokio::spawn(async move {
-            reth_cli.start_mempool().await.unwrap();
-        });
+        loops.push(self.spawn_loop("mempool", async move { reth_cli.start_mempool().await }));
         let reth_cli = self.reth_cli.clone();
-        tokio::spawn(async move {
-            reth_cli.start_execution().await.unwrap();
-        });
+        loops.push(self.spawn_loop("execution", async move { reth_cli.start_execution().await }));
         let reth_cli = self.reth_cli.clone();
-        tokio::spawn(async move {
-            reth_cli.start_commit_vote().await.unwrap();
-        });
+        loops.push(
+            self.spawn_loop("commit_vote", async move { reth_cli.start_commit_vote().await }),
+        );
         let reth_cli = self.reth_cli.clone();
-        tokio::spawn(async move {
-            reth_cli.start_commit().await.unwrap();
-        });
+        loops.push(self.spawn_loop("commit", async move { reth_cli.start_commit().await }));
+    }
+
+    /// Resolves once one of the loops started by `run` failed, the node can't make progress then.
+    pub async fn failed(&self) {
+        self.failure.notified().await
+    }
+
+    /// Stops the loops started by `run`. Call it once consensus has drained, so that no block is
+    /// left between execution and commit. Fails if a loop had already failed.
+    pub async fn shutdown(&self) -> anyhow::Result<()> {
+        let mut failed = Vec::new();
+        for (name, handle) in self.loops.lock().await.drain(..) {
+            if !handle.is_finished() {
+                handle.abort();
+            }
+            match handle.await {
+                Ok(Ok(())) => info!("{} loop stopped", name),
+                Err(e) if e.is_cancelled() => info!("{} loop stopped", name),
+                Ok(Err(e)) => failed.push(format!("{} loop failed: {}", name, e)),
+                Err(e) => failed.push(format!("{} loop panicked: {}", name, e)),
+            }
+        }
+        anyhow::ensure!(failed.is_empty(), "{}", failed.join(", "));
+        Ok(())
     }
 }
 
 #[async_trait]
 impl ExecutionChannel for RethCoordinator {
-    async fn send_user_txn(&self, _bytes: ExecTxn) -> Result<TxnHash, ExecError> {
-        panic!("Reth Coordinator does not support add_txn");
+    async fn send_user_txn(&self, txn: ExecTxn) -> Result<TxnHash, ExecError> {
+        self.send_user_txns(vec![txn]).await.pop().unwrap_or(Err(ExecError::InternalError))
+    }
+
+    async fn send_user_txns(&self, txns: Vec<ExecTxn>) -> Vec<Result<TxnHash, ExecError>> {
+        let txns = txns
+            .into_iter()
+            .map(|txn| match txn {
+                ExecTxn::RawTxn(bytes) => bytes,
+                ExecTxn::VerifiedTxn(txn) => txn.bytes,
+            })
+            .collect();
+        self.reth_cli.add_external_txns(txns).await
     }
 
     async fn recv_unbroadcasted_txn(&self) -> Result<Vec<VerifiedTxn>, ExecError> {
@@ -92,7 +147,9 @@ impl ExecutionChannel for RethCoordinator {
         payload_attr: ExternalPayloadAttr,
         txns: Vec<VerifiedTxn>,
     ) -> Result<bool, ExecError> {
-        panic!("Reth Coordinator does not support check_block_txns");
+        let costs: Vec<_> = txns.iter().map(|txn| block_budget::txn_cost(txn.bytes())).collect();
+        let mut state = self.state.lock().await;
+        Ok(state.check_new_txns(&payload_attr, &costs, block_budget::block_budget()))
     }
 
     async fn send_pending_txns(&self) -> Result<Vec<VerifiedTxnWithAccountSeqNum>, ExecError> {
@@ -117,4 +174,12 @@ impl ExecutionChannel for RethCoordinator {
     async fn recv_committed_block_info(&self, block_id: BlockId) -> Result<(), ExecError> {
         panic!("Reth Coordinator does not support recv_committed_block_info")
     }
+
+    fn conflict_keys(&self, txns: &[VerifiedTxn]) -> Option<Vec<Vec<ConflictKey>>> {
+        Some(txns.iter().map(|txn| txn_conflict_keys(txn.bytes())).collect())
+    }
+
+    fn account_seq_num(&self, account: &ExternalAccountAddress) -> Option<u64> {
+        self.reth_cli.account_nonce(account)
+    }
 }
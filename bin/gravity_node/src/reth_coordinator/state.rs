@@ -1,13 +1,12 @@
-use alloy_consensus::Transaction as _;
 use alloy_primitives::B256;
 use api_types::account::ExternalAccountAddress;
+use api_types::block_budget::{BlockBudget, BlockBudgetTracker, TxnCost};
 use api_types::VerifiedTxn;
 use api_types::{u256_define::BlockId, ExternalPayloadAttr};
 use greth::reth_pipe_exec_layer_ext_v2::ExecutionResult;
-use greth::reth_primitives::Transaction;
 use tracing::debug;
 pub struct BuildingState {
-    gas_used: u64,
+    budget: BlockBudgetTracker,
 }
 
 pub enum Status {
@@ -52,13 +51,23 @@ impl State {
         true
     }
 
-    pub fn check_new_txn(&mut self, attr: &ExternalPayloadAttr, txn: Transaction) -> bool {
-        let building_state =
-            self.building_block.entry(attr.clone()).or_insert(BuildingState { gas_used: 0 });
-        building_state.gas_used += txn.gas_limit();
-        if building_state.gas_used > 1000000 {
+    /// Adds the txns to the block being built for `attr` if all of them fit into `budget`,
+    /// otherwise leaves the block untouched.
+    pub fn check_new_txns(
+        &mut self,
+        attr: &ExternalPayloadAttr,
+        costs: &[TxnCost],
+        budget: BlockBudget,
+    ) -> bool {
+        let building_state = self
+            .building_block
+            .entry(attr.clone())
+            .or_insert_with(|| BuildingState { budget: BlockBudgetTracker::new(budget) });
+        let mut tracker = building_state.budget.clone();
+        if !costs.iter().all(|cost| tracker.try_consume(*cost)) {
             return false;
         }
+        building_state.budget = tracker;
         true
     }
 
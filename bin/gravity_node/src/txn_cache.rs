@@ -0,0 +1,158 @@
+use alloy_primitives::TxHash;
+use api_types::account::ExternalAccountAddress;
+use std::collections::{BTreeMap, HashMap};
+
+pub type TxnKey = (ExternalAccountAddress, u64);
+
+struct Entry<T> {
+    hash: TxHash,
+    /// Position of the latest insertion in `insertion_order`
+    seq: u64,
+    txn: T,
+}
+
+/// Pool txns that were handed to consensus, keyed by (sender, nonce) and indexed by hash so
+/// that pool events, which only carry the hash, can be mapped back to the consensus mempool.
+///
+/// The cache is bounded: once more than `capacity` txns are cached the least recently inserted
+/// one is evicted. An evicted txn is decoded from its bytes when it gets ordered, but pool events
+/// about it can't be forwarded anymore.
+pub struct TxnCache<T> {
+    capacity: usize,
+    txns: HashMap<TxnKey, Entry<T>>,
+    keys_by_hash: HashMap<TxHash, TxnKey>,
+    insertion_order: BTreeMap<u64, TxnKey>,
+    next_seq: u64,
+}
+
+impl<T> TxnCache<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            txns: HashMap::new(),
+            keys_by_hash: HashMap::new(),
+            insertion_order: BTreeMap::new(),
+            next_seq: 0,
+        }
+    }
+
+    pub fn contains_hash(&self, hash: &TxHash) -> bool {
+        self.keys_by_hash.contains_key(hash)
+    }
+
+    /// The txn cached for `key` if it is the one with `hash`, the pool may have replaced it since
+    /// it was handed to consensus.
+    pub fn get(&self, key: &TxnKey, hash: &TxHash) -> Option<&T> {
+        self.txns.get(key).filter(|entry| entry.hash == *hash).map(|entry| &entry.txn)
+    }
+
+    /// Inserts `txn`, returns the hash of the txn it replaced with the same sender and nonce.
+    pub fn insert(&mut self, key: TxnKey, hash: TxHash, txn: T) -> Option<TxHash> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let replaced = self.txns.insert(key.clone(), Entry { hash, seq, txn }).and_then(|old| {
+            self.insertion_order.remove(&old.seq);
+            (old.hash != hash).then_some(old.hash)
+        });
+        if let Some(replaced) = replaced {
+            self.keys_by_hash.remove(&replaced);
+        }
+        self.keys_by_hash.insert(hash, key.clone());
+        self.insertion_order.insert(seq, key);
+        while self.txns.len() > self.capacity {
+            let (_, oldest) = self.insertion_order.pop_first().expect("tracks every cached txn");
+            if let Some(entry) = self.txns.remove(&oldest) {
+                self.keys_by_hash.remove(&entry.hash);
+            }
+        }
+        replaced
+    }
+
+    pub fn remove_by_hash(&mut self, hash: &TxHash) -> Option<(TxnKey, T)> {
+        // `keys_by_hash` only holds the hash of the txn currently cached for a key
+        let key = self.keys_by_hash.remove(hash)?;
+        let entry = self.txns.remove(&key)?;
+        self.insertion_order.remove(&entry.seq);
+        Some((key, entry.txn))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(nonce: u64) -> TxnKey {
+        (ExternalAccountAddress::new([1u8; 32]), nonce)
+    }
+
+    fn hash(byte: u8) -> TxHash {
+        TxHash::repeat_byte(byte)
+    }
+
+    #[test]
+    fn test_insert_and_replace() {
+        let mut cache = TxnCache::new(4);
+        assert_eq!(cache.insert(key(0), hash(1), "a"), None);
+        assert_eq!(cache.get(&key(0), &hash(1)), Some(&"a"));
+        assert!(cache.contains_hash(&hash(1)));
+
+        // same sender and nonce, higher fee
+        assert_eq!(cache.insert(key(0), hash(2), "b"), Some(hash(1)));
+        assert_eq!(cache.get(&key(0), &hash(2)), Some(&"b"));
+        assert!(!cache.contains_hash(&hash(1)));
+        assert_eq!(cache.remove_by_hash(&hash(1)), None);
+        assert_eq!(cache.remove_by_hash(&hash(2)), Some((key(0), "b")));
+        assert_eq!(cache.get(&key(0), &hash(2)), None);
+    }
+
+    #[test]
+    fn test_evict_least_recently_inserted() {
+        let mut cache = TxnCache::new(2);
+        cache.insert(key(0), hash(0), "a");
+        cache.insert(key(1), hash(1), "b");
+        cache.insert(key(2), hash(2), "c");
+        assert_eq!(cache.get(&key(0), &hash(0)), None);
+        assert!(!cache.contains_hash(&hash(0)));
+        assert_eq!(cache.get(&key(1), &hash(1)), Some(&"b"));
+        assert_eq!(cache.get(&key(2), &hash(2)), Some(&"c"));
+
+        // a removed txn doesn't take up space
+        cache.remove_by_hash(&hash(1));
+        cache.insert(key(3), hash(3), "d");
+        assert_eq!(cache.get(&key(2), &hash(2)), Some(&"c"));
+        assert_eq!(cache.get(&key(3), &hash(3)), Some(&"d"));
+    }
+
+    #[test]
+    fn test_reinsert_refreshes_the_entry() {
+        let mut cache = TxnCache::new(2);
+        cache.insert(key(0), hash(0), "a");
+        cache.insert(key(1), hash(1), "b");
+        // the pool handed out the same txn again
+        assert_eq!(cache.insert(key(0), hash(0), "a"), None);
+        cache.insert(key(2), hash(2), "c");
+        assert_eq!(cache.get(&key(0), &hash(0)), Some(&"a"));
+        assert!(cache.contains_hash(&hash(0)));
+        assert_eq!(cache.get(&key(1), &hash(1)), None);
+
+        // replaced and then inserted back
+        cache.insert(key(2), hash(3), "d");
+        assert_eq!(cache.insert(key(2), hash(2), "c"), Some(hash(3)));
+        cache.insert(key(3), hash(4), "e");
+        assert_eq!(cache.get(&key(2), &hash(2)), Some(&"c"));
+        assert!(cache.contains_hash(&hash(2)));
+        assert!(!cache.contains_hash(&hash(3)));
+        assert_eq!(cache.get(&key(0), &hash(0)), None);
+    }
+
+    #[test]
+    fn test_replacement_after_the_original_was_ordered() {
+        let mut cache = TxnCache::new(4);
+        cache.insert(key(0), hash(1), "a");
+        // the original got ordered, then the pool replaced it with a higher fee
+        cache.insert(key(0), hash(2), "b");
+        // the ordered block carries the original, which has to be decoded from its bytes
+        assert_eq!(cache.get(&key(0), &hash(1)), None);
+        assert_eq!(cache.get(&key(0), &hash(2)), Some(&"b"));
+    }
+}
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use aptos_safety_rules::Process;
+use clap::Parser;
+use gaptos::aptos_config::config::{SafetyRulesConfig, SafetyRulesService};
+use gaptos::aptos_logger::{aptos_logger::FileWriter, info, Level, Logger};
+
+/// Runs the safety rules of a validator in their own process. It holds the consensus key and the
+/// safety data, and signs proposals, votes, timeouts and commit votes for the node, which reaches
+/// it through a `process` safety rules service.
+#[derive(Debug, Parser)]
+#[command(
+    name = "gravity-safety-rules",
+    version,
+    about = "Safety rules signer of a gravity validator"
+)]
+struct Cli {
+    /// Yaml file with the `consensus.safety_rules` section of the validator config. Its service
+    /// must be `{type: process, server_address: <listen address>}`.
+    #[arg(long = "config")]
+    config: PathBuf,
+
+    #[arg(long = "log_file")]
+    log_file: Option<PathBuf>,
+
+    #[arg(long = "log_level", default_value = "info")]
+    log_level: String,
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let content = std::fs::read_to_string(&cli.config)
+        .with_context(|| format!("failed to read {}", cli.config.display()))?;
+    let config: SafetyRulesConfig = serde_yaml::from_str(&content)
+        .with_context(|| format!("invalid safety rules config {}", cli.config.display()))?;
+    let SafetyRulesService::Process(service) = &config.service else {
+        anyhow::bail!("safety rules service must be process, got {:?}", config.service);
+    };
+    let server_address = service.server_address();
+
+    let log_level = cli
+        .log_level
+        .parse::<Level>()
+        .map_err(|_| anyhow::anyhow!("invalid log level {}", cli.log_level))?;
+    let mut logger_builder = Logger::builder();
+    logger_builder.level(log_level);
+    if let Some(log_file) = cli.log_file {
+        logger_builder.printer(Box::new(FileWriter::new(log_file)));
+    }
+    logger_builder.build();
+
+    info!("starting safety rules on {}", server_address);
+    // Serves the node until the process is killed
+    Process::new(config).start();
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use api_types::{
+    block_budget::{self, BlockBudgetTracker, TxnCost},
     compute_res::ComputeRes,
     u256_define::{BlockId, TxnHash},
     ExecError, ExecTxn, ExecutionChannel, ExternalBlock, ExternalBlockMeta, ExternalPayloadAttr,
@@ -24,6 +25,8 @@ pub struct ExecutionChannelImpl {
     // to receive the state root
     block_hash_receivers: Mutex<HashMap<BlockId, Receiver<(ComputeRes, Sender<u64>)>>>,
     block_commit_sender: Mutex<HashMap<BlockId, (u64, Sender<u64>)>>,
+    // the budget used by the block being built for each payload attr
+    building_blocks: Mutex<HashMap<ExternalPayloadAttr, BlockBudgetTracker>>,
     mempool: Mempool,
 }
 
@@ -33,6 +36,7 @@ impl ExecutionChannelImpl {
             ordered_block_sender,
             block_hash_receivers: Mutex::new(HashMap::new()),
             block_commit_sender: Mutex::new(HashMap::new()),
+            building_blocks: Mutex::new(HashMap::new()),
             mempool: Mempool::new(),
         }
     }
@@ -57,7 +61,17 @@ impl ExecutionChannel for ExecutionChannelImpl {
         payload_attr: ExternalPayloadAttr,
         txns: Vec<VerifiedTxn>,
     ) -> Result<bool, ExecError> {
-        unimplemented!("Currently not implemented");
+        let mut cost = TxnCost::default();
+        for txn in &txns {
+            cost += block_budget::txn_cost(txn.bytes());
+        }
+        let mut building_blocks = self.building_blocks.lock().await;
+        // blocks are built in timestamp order, the blocks of older attrs are done
+        building_blocks.retain(|attr, _| attr.ts >= payload_attr.ts);
+        Ok(building_blocks
+            .entry(payload_attr)
+            .or_insert_with(|| BlockBudgetTracker::new(block_budget::block_budget()))
+            .try_consume(cost))
     }
 
     async fn send_pending_txns(&self) -> Result<Vec<VerifiedTxnWithAccountSeqNum>, ExecError> {
@@ -8,11 +8,21 @@ use futures::{FutureExt, SinkExt};
 use log::*;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use crate::Log;
+use sha3::{Digest, Keccak256};
 use tokio::sync::RwLock;
 
+/// Gas charged for every txn that consumed its nonce, to simplify we use one fixed gas num.
+const TXN_GAS: u64 = 21000;
+
+fn event_topic(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
 pub struct PipelineExecutor {
     state: Arc<RwLock<State>>,
-    block_update_accounts: HashMap<u64, Vec<AccountId>>,
+    // accounts locked by block number, released once the block is committed
+    block_locked_accounts: HashMap<u64, Vec<AccountId>>,
     // stage one
     ordered_block_receiver: mpsc::Receiver<ExecutableBlock>,
     // stage two
@@ -25,7 +35,7 @@ pub struct PipelineExecutor {
     // stage three:
     commit_req_receiver: mpsc::Receiver<BlockExecutionResult>,
     compute_res_senders: HashMap<u64, Sender<(ComputeRes, Sender<u64>)>>,
-    pending_persisting_block: HashMap<u64, (Block, StateRoot)>,
+    pending_persisting_block: HashMap<u64, (Block, StateRoot, Vec<TransactionReceipt>)>,
     // stage four:
     persisting_notifiers: FuturesUnordered<futures::channel::oneshot::Receiver<u64>>,
     // stage five:
@@ -41,7 +51,7 @@ impl PipelineExecutor {
         let (commit_req_sender, commit_req_receiver) = mpsc::channel(100);
         Self {
             state,
-            block_update_accounts: HashMap::new(),
+            block_locked_accounts: HashMap::new(),
             ordered_block_receiver,
             execution_queue: FuturesUnordered::new(),
             pending_blocks: HashMap::new(),
@@ -62,9 +72,8 @@ impl PipelineExecutor {
         let execution_plan = self.create_execution_plan(block.block)?;
         {
             let mut account_locks = self.account_locks.write().await;
-            for tx in &execution_plan.block.transactions {
-                let sender = verify_signature(tx)?;
-                let sender_id = AccountId(sender);
+            // Txns with an invalid signature are dropped from the block, so they lock nothing
+            for sender_id in execution_plan.account_dependencies.keys() {
                 info!("Locking account {} for block {}", sender_id.0, block_number);
                 account_locks
                     .entry(sender_id.clone())
@@ -72,6 +81,10 @@ impl PipelineExecutor {
                     .push_back(block_number);
             }
         }
+        self.block_locked_accounts.insert(
+            block_number,
+            execution_plan.account_dependencies.keys().cloned().collect(),
+        );
         self.pending_blocks.insert(block_number, execution_plan);
         self.schedule_ready_blocks().await?;
         info!("Added block {} to execution pipeline", block_number);
@@ -82,8 +95,10 @@ impl PipelineExecutor {
         let mut account_dependencies = HashMap::new();
 
         // TODO: implement account dependencies when enable pipeline
-        for (_, tx) in block.transactions.iter().enumerate() {
-            let sender = verify_signature(tx)?;
+        for tx in block.transactions.iter() {
+            let Ok(sender) = verify_signature(tx) else {
+                continue;
+            };
             let sender_id = AccountId(sender);
             account_dependencies.entry(sender_id).or_insert_with(HashSet::new);
 
@@ -168,45 +183,96 @@ impl PipelineExecutor {
             execution_result.block_number, pending_transactions
         );
 
-        for tx in pending_transactions.iter_mut() {
+        for (index, tx) in pending_transactions.iter_mut().enumerate() {
             let state = state.read().await;
-            let receipt =
-                Self::execute_transaction(tx, &mut execution_result.state_updates, &state)?;
-
+            let Some(receipt) = Self::execute_transaction(
+                tx,
+                execution_result.block_number,
+                execution_result.receipts.len() as u64,
+                &mut execution_result.state_updates,
+                &state,
+            ) else {
+                warn!(
+                    "Dropped txn {} of block {} with an invalid signature or nonce",
+                    index, execution_result.block_number
+                );
+                continue;
+            };
+            if !receipt.status {
+                warn!(
+                    "Txn {} of block {} failed: {:?}",
+                    index, execution_result.block_number, receipt.error
+                );
+            }
             execution_result.receipts.push(receipt);
         }
 
         Ok(execution_result)
     }
 
+    /// Executes `tx` on top of `state_updates`. A failing txn doesn't abort the block but
+    /// produces a failed receipt and consumes its nonce. A txn with an invalid nonce or signature
+    /// gets no receipt: anyone can resubmit the body of a committed txn, and its receipt, which is
+    /// keyed by that body, must not be overwritten.
     fn execute_transaction(
         tx: &Transaction,
+        block_number: u64,
+        transaction_index: u64,
         state_updates: &mut HashMap<AccountId, AccountState>,
         state: &State,
-    ) -> Result<TransactionReceipt, String> {
-        let sender = verify_signature(tx)?;
-        let sender_id = AccountId(sender.clone());
-        trace!("Executing transaction from {} tx {:?}, state is {:?}", sender, tx.unsigned, state);
+    ) -> Option<TransactionReceipt> {
+        let (status, error, logs) = match Self::apply_transaction(tx, state_updates, state) {
+            Ok(logs) => (true, None, logs),
+            Err((error, true)) => (false, Some(error), Vec::new()),
+            Err((_, false)) => return None,
+        };
+        Some(TransactionReceipt {
+            transaction: tx.clone(),
+            transaction_hash: compute_transaction_hash(&tx.unsigned),
+            block_number,
+            transaction_index,
+            status,
+            error,
+            gas_used: TXN_GAS,
+            logs,
+        })
+    }
 
-        let mut sender_state = state_updates
-            .get(&sender_id)
-            .map(|account| account.clone())
-            .or_else(|| state.get_account(&sender))
-            .map(|account| AccountState {
-                nonce: account.nonce,
-                balance: account.balance,
-                kv_store: account.kv_store.clone(),
-            })
+    fn load_account(
+        account_id: &AccountId,
+        state_updates: &HashMap<AccountId, AccountState>,
+        state: &State,
+        default_balance: u64,
+    ) -> AccountState {
+        state_updates
+            .get(account_id)
+            .cloned()
+            .or_else(|| state.get_account(&account_id.0))
             .unwrap_or_else(|| AccountState {
                 nonce: 0,
-                balance: 5000000000,
+                balance: default_balance,
                 kv_store: HashMap::new(),
-            });
+            })
+    }
 
+    /// Returns the emitted logs, or the error and whether the nonce was consumed.
+    fn apply_transaction(
+        tx: &Transaction,
+        state_updates: &mut HashMap<AccountId, AccountState>,
+        state: &State,
+    ) -> Result<Vec<Log>, (String, bool)> {
+        let sender = verify_signature(tx).map_err(|e| (e, false))?;
+        let sender_id = AccountId(sender.clone());
+        trace!("Executing transaction from {} tx {:?}, state is {:?}", sender, tx.unsigned, state);
+
+        let mut sender_state = Self::load_account(&sender_id, state_updates, state, 5000000000);
         if tx.unsigned.nonce != sender_state.nonce {
-            return Err(format!(
-                "Invalid nonce, tx nonce {}, tx {:?}, state nonce {}, whole state {:?}",
-                tx.unsigned.nonce, tx, sender_state.nonce, state,
+            return Err((
+                format!(
+                    "Invalid nonce, tx nonce {}, state nonce {}",
+                    tx.unsigned.nonce, sender_state.nonce
+                ),
+                false,
             ));
         }
         sender_state.nonce += 1;
@@ -214,39 +280,34 @@ impl PipelineExecutor {
         match &tx.unsigned.kind {
             TransactionKind::Transfer { receiver, amount } => {
                 if sender_state.balance < *amount {
-                    return Err(format!("Insufficient balance"));
+                    state_updates.insert(sender_id, sender_state);
+                    return Err(("Insufficient balance".to_string(), true));
                 }
+                sender_state.balance -= amount;
+                state_updates.insert(sender_id, sender_state);
 
                 let receiver_id = AccountId(receiver.clone());
-                let mut receiver_state = if let Some(account) = state.get_account(receiver) {
-                    AccountState {
-                        nonce: account.nonce,
-                        balance: account.balance,
-                        kv_store: account.kv_store.clone(),
-                    }
-                } else {
-                    AccountState { nonce: 0, balance: 0, kv_store: HashMap::new() }
-                };
-
-                sender_state.balance -= amount;
+                let mut receiver_state = Self::load_account(&receiver_id, state_updates, state, 0);
                 receiver_state.balance += amount;
-
-                state_updates.insert(sender_id, sender_state);
                 state_updates.insert(receiver_id, receiver_state);
+
+                Ok(vec![Log {
+                    address: sender,
+                    topics: vec![event_topic(b"Transfer"), event_topic(receiver.as_bytes())],
+                    data: amount.to_be_bytes().to_vec(),
+                }])
             }
             TransactionKind::SetKV { key, value } => {
                 sender_state.kv_store.insert(key.clone(), value.clone());
                 state_updates.insert(sender_id, sender_state);
+
+                Ok(vec![Log {
+                    address: sender,
+                    topics: vec![event_topic(b"SetKV"), event_topic(key.as_bytes())],
+                    data: value.as_bytes().to_vec(),
+                }])
             }
         }
-
-        Ok(TransactionReceipt {
-            transaction: tx.clone(),
-            transaction_hash: compute_transaction_hash(&tx.unsigned),
-            status: true,
-            gas_used: 21000, // to simplify, we use one fiexd gas num
-            logs: Vec::new(),
-        })
     }
 
     pub async fn process_execution_results(
@@ -256,9 +317,6 @@ impl PipelineExecutor {
         info!("Processing execution result {:?}", execution_result);
         let execution_result = execution_result?;
         let block_number = execution_result.block_number;
-        let update_accounts = execution_result.state_updates.keys().cloned().collect::<Vec<_>>();
-
-        self.block_update_accounts.insert(block_number, update_accounts);
 
         info!("send commit request for block {}", block_number);
         let (block_commit_sender, block_commit_receiver) = oneshot::channel();
@@ -314,6 +372,7 @@ impl PipelineExecutor {
             state_guard.update_account_state(&account_id, state_update).await?;
         }
 
+        state_guard.set_current_block_number(block_number);
         let state_root = state_guard.compute_state_root()?;
 
         let compute_res = ComputeRes { data: state_root.0, txn_num: result.receipts.len() as u64, txn_status: Arc::new(None) };
@@ -326,7 +385,8 @@ impl PipelineExecutor {
 
         let mut final_block = block;
         final_block.header.state_root = state_root.0;
-        self.pending_persisting_block.insert(result.block_number, (final_block, state_root));
+        self.pending_persisting_block
+            .insert(result.block_number, (final_block, state_root, result.receipts));
 
         self.persisting_notifiers.push(persist_receiver);
 
@@ -339,11 +399,10 @@ impl PipelineExecutor {
         block_number: u64,
         storage: Arc<dyn Storage>,
     ) -> Result<(), String> {
-        let (final_block, state_root) =
+        let (final_block, state_root, receipts) =
             self.pending_persisting_block.remove(&block_number).unwrap();
-        let storage = storage.clone();
-        storage.save_block(&final_block).await.unwrap();
-        storage.save_state_root(final_block.header.number, state_root).await.unwrap();
+        storage.save_committed_block(&final_block, state_root, receipts).await?;
+        self.state.write().await.commit_block(block_number);
         let _ = self.block_commit_senders.remove(&block_number).unwrap().send(block_number);
 
         info!("Block {} persisted", block_number);
@@ -388,8 +447,8 @@ impl PipelineExecutor {
                 // Stage five: release the resource acquired and schedule for next block
                 Some(block_number_ret) = self.committing_queue.next() => {
                     let block_number = block_number_ret.unwrap();
-                    let update_accounts = self.block_update_accounts.remove(&block_number).unwrap();
-                    self.release_account_locks(block_number, update_accounts).await;
+                    let locked_accounts = self.block_locked_accounts.remove(&block_number).unwrap();
+                    self.release_account_locks(block_number, locked_accounts).await;
                     info!("Processing execution result {:?} try commit release", block_number);
                     self.schedule_ready_blocks().await.unwrap();
                     info!("Block committed");
@@ -398,3 +457,67 @@ impl PipelineExecutor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::{generate_keypair, public_key_to_address, sign_transaction},
+        storage::SledStorage,
+        BlockHeader, UnsignedTransaction,
+    };
+
+    fn block(number: u64, transactions: Vec<Transaction>) -> Block {
+        Block {
+            header: BlockHeader {
+                number,
+                parent_hash: [0; 32],
+                state_root: [0; 32],
+                transactions_root: [0; 32],
+                timestamp: 0,
+            },
+            transactions,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reincluded_txn_keeps_its_receipt() {
+        let keypair = generate_keypair();
+        let sender = AccountId(public_key_to_address(&keypair.public_key));
+        let unsigned = UnsignedTransaction {
+            nonce: 0,
+            kind: TransactionKind::SetKV { key: "k".to_string(), value: "v".to_string() },
+        };
+        let tx = Transaction {
+            signature: sign_transaction(&unsigned, &keypair.secret_key),
+            unsigned: unsigned.clone(),
+        };
+        let mut state = State::new(None);
+        let dir = std::env::temp_dir().join(format!("kvstore_reincluded_{}", std::process::id()));
+        let storage = SledStorage::new(&dir).unwrap();
+
+        let mut state_updates = HashMap::new();
+        let receipt =
+            PipelineExecutor::execute_transaction(&tx, 1, 0, &mut state_updates, &state).unwrap();
+        assert!(receipt.status);
+        let root = StateRoot([1; 32]);
+        storage.save_committed_block(&block(1, vec![tx.clone()]), root, vec![receipt]).await.unwrap();
+        state.update_account_state(&sender, state_updates.remove(&sender).unwrap()).await.unwrap();
+
+        // the same txn again, and its body with a bad signature
+        let forged = Transaction { unsigned, signature: format!("{}1b", "00".repeat(64)) };
+        let mut state_updates = HashMap::new();
+        for tx in [&tx, &forged] {
+            assert!(PipelineExecutor::execute_transaction(tx, 2, 0, &mut state_updates, &state)
+                .is_none());
+        }
+        assert!(state_updates.is_empty());
+
+        let hash = compute_transaction_hash(&tx.unsigned);
+        let receipt = storage.get_transaction_receipt(hash).await.unwrap().unwrap();
+        assert!(receipt.status);
+        assert_eq!(receipt.block_number, 1);
+        drop(storage);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
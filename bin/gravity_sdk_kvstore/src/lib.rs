@@ -8,6 +8,7 @@ pub mod cli;
 pub mod execution_channel;
 pub mod server;
 pub mod mempool;
+pub mod merkle;
 
 pub use types::*;
 pub use crypto::*;
@@ -0,0 +1,335 @@
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::sync::Arc;
+
+/// Hash of an empty subtree.
+pub const EMPTY_HASH: [u8; 32] = [0; 32];
+
+const LEAF_PREFIX: u8 = 0;
+const INTERNAL_PREFIX: u8 = 1;
+
+pub fn hash_value(value: &[u8]) -> [u8; 32] {
+    Keccak256::digest(value).into()
+}
+
+fn hash_leaf(key: &[u8; 32], value_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(key);
+    hasher.update(value_hash);
+    hasher.finalize().into()
+}
+
+fn hash_internal(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update([INTERNAL_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Whether the path of `key` goes right at `depth`.
+fn bit(key: &[u8; 32], depth: usize) -> bool {
+    key[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Empty,
+    Leaf { key: [u8; 32], value: Arc<[u8]>, value_hash: [u8; 32], hash: [u8; 32] },
+    Internal { left: Arc<Node>, right: Arc<Node>, hash: [u8; 32] },
+}
+
+impl Node {
+    fn hash(&self) -> [u8; 32] {
+        match self {
+            Node::Empty => EMPTY_HASH,
+            Node::Leaf { hash, .. } | Node::Internal { hash, .. } => *hash,
+        }
+    }
+
+    fn leaf(key: [u8; 32], value: &[u8]) -> Node {
+        let value_hash = hash_value(value);
+        Node::Leaf { key, value: value.into(), value_hash, hash: hash_leaf(&key, &value_hash) }
+    }
+
+    /// A subtree holding a single leaf is replaced by the leaf, so that every leaf sits at the
+    /// shortest prefix that tells it apart and the root only depends on the set of leaves.
+    fn internal(left: Node, right: Node) -> Node {
+        match (left, right) {
+            (Node::Empty, Node::Empty) => Node::Empty,
+            (leaf @ Node::Leaf { .. }, Node::Empty) | (Node::Empty, leaf @ Node::Leaf { .. }) => {
+                leaf
+            }
+            (left, right) => {
+                let hash = hash_internal(&left.hash(), &right.hash());
+                Node::Internal { left: Arc::new(left), right: Arc::new(right), hash }
+            }
+        }
+    }
+
+    /// Builds the subtree at `depth` holding the two leaves `a` and `b` with different keys.
+    fn split(a: Node, a_key: [u8; 32], b: Node, b_key: [u8; 32], depth: usize) -> Node {
+        match (bit(&a_key, depth), bit(&b_key, depth)) {
+            (false, true) => Node::internal(a, b),
+            (true, false) => Node::internal(b, a),
+            (false, false) => {
+                Node::internal(Node::split(a, a_key, b, b_key, depth + 1), Node::Empty)
+            }
+            (true, true) => Node::internal(Node::Empty, Node::split(a, a_key, b, b_key, depth + 1)),
+        }
+    }
+
+    fn insert(self, depth: usize, key: [u8; 32], value: &[u8]) -> Node {
+        match self {
+            Node::Empty => Node::leaf(key, value),
+            Node::Leaf { key: existing, .. } if existing == key => Node::leaf(key, value),
+            Node::Leaf { key: existing, .. } => {
+                Node::split(self, existing, Node::leaf(key, value), key, depth)
+            }
+            // Subtrees shared with a snapshot of the tree are copied before they are updated
+            Node::Internal { left, right, .. } => {
+                if bit(&key, depth) {
+                    Node::internal(
+                        Arc::unwrap_or_clone(left),
+                        Arc::unwrap_or_clone(right).insert(depth + 1, key, value),
+                    )
+                } else {
+                    Node::internal(
+                        Arc::unwrap_or_clone(left).insert(depth + 1, key, value),
+                        Arc::unwrap_or_clone(right),
+                    )
+                }
+            }
+        }
+    }
+
+    fn delete(self, depth: usize, key: &[u8; 32]) -> Node {
+        match self {
+            Node::Leaf { key: existing, .. } if existing == *key => Node::Empty,
+            Node::Internal { left, right, .. } => {
+                if bit(key, depth) {
+                    Node::internal(
+                        Arc::unwrap_or_clone(left),
+                        Arc::unwrap_or_clone(right).delete(depth + 1, key),
+                    )
+                } else {
+                    Node::internal(
+                        Arc::unwrap_or_clone(left).delete(depth + 1, key),
+                        Arc::unwrap_or_clone(right),
+                    )
+                }
+            }
+            node => node,
+        }
+    }
+}
+
+/// A sparse Merkle tree over 256 bit keys. Updates only rehash the path of the updated key, and
+/// clones share the subtrees neither of them updated.
+#[derive(Clone)]
+pub struct SparseMerkleTree {
+    root: Node,
+}
+
+impl std::fmt::Debug for SparseMerkleTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SparseMerkleTree {{ root: {} }}", hex::encode(self.root_hash()))
+    }
+}
+
+impl Default for SparseMerkleTree {
+    fn default() -> Self {
+        Self { root: Node::Empty }
+    }
+}
+
+impl SparseMerkleTree {
+    pub fn root_hash(&self) -> [u8; 32] {
+        self.root.hash()
+    }
+
+    pub fn insert(&mut self, key: [u8; 32], value: &[u8]) {
+        let root = std::mem::replace(&mut self.root, Node::Empty);
+        self.root = root.insert(0, key, value);
+    }
+
+    pub fn delete(&mut self, key: &[u8; 32]) {
+        let root = std::mem::replace(&mut self.root, Node::Empty);
+        self.root = root.delete(0, key);
+    }
+
+    pub fn get(&self, key: &[u8; 32]) -> Option<&[u8]> {
+        let mut node = &self.root;
+        let mut depth = 0;
+        loop {
+            match node {
+                Node::Leaf { key: existing, value, .. } if existing == key => {
+                    return Some(value.as_ref())
+                }
+                Node::Internal { left, right, .. } => {
+                    node = if bit(key, depth) { right.as_ref() } else { left.as_ref() };
+                    depth += 1;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    /// Proves that `key` holds its current value, or that it is absent.
+    pub fn get_proof(&self, key: &[u8; 32]) -> SparseMerkleProof {
+        let mut siblings = Vec::new();
+        let mut node = &self.root;
+        loop {
+            match node {
+                Node::Empty => return SparseMerkleProof { leaf: None, siblings },
+                Node::Leaf { key, value_hash, .. } => {
+                    return SparseMerkleProof { leaf: Some((*key, *value_hash)), siblings }
+                }
+                Node::Internal { left, right, .. } => {
+                    if bit(key, siblings.len()) {
+                        siblings.push(left.hash());
+                        node = right;
+                    } else {
+                        siblings.push(right.hash());
+                        node = left;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The siblings along the path of a key, from the root down, and the leaf the path ends at.
+/// The path ends at a leaf of another key or at an empty subtree if the key is absent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SparseMerkleProof {
+    /// (key, value hash) of the leaf the path ends at
+    pub leaf: Option<([u8; 32], [u8; 32])>,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl SparseMerkleProof {
+    /// Verifies that `key` holds `value` in the tree with root `root`, or that it is absent if
+    /// `value` is `None`.
+    pub fn verify(
+        &self,
+        root: [u8; 32],
+        key: &[u8; 32],
+        value: Option<&[u8]>,
+    ) -> Result<(), String> {
+        if self.siblings.len() > 256 {
+            return Err(format!("Proof too long: {} siblings", self.siblings.len()));
+        }
+        let leaf_hash = match (&self.leaf, value) {
+            (Some((leaf_key, value_hash)), Some(value)) => {
+                if leaf_key != key {
+                    return Err("Proof is for another key".to_string());
+                }
+                if *value_hash != hash_value(value) {
+                    return Err("Value does not match the proof".to_string());
+                }
+                hash_leaf(leaf_key, value_hash)
+            }
+            (Some((leaf_key, value_hash)), None) => {
+                if leaf_key == key {
+                    return Err("Key is present".to_string());
+                }
+                if (0..self.siblings.len()).any(|depth| bit(leaf_key, depth) != bit(key, depth)) {
+                    return Err("Leaf is not on the path of the key".to_string());
+                }
+                hash_leaf(leaf_key, value_hash)
+            }
+            (None, Some(_)) => return Err("Key is absent".to_string()),
+            (None, None) => EMPTY_HASH,
+        };
+        let computed =
+            self.siblings.iter().enumerate().rev().fold(leaf_hash, |hash, (depth, sibling)| {
+                if bit(key, depth) {
+                    hash_internal(sibling, &hash)
+                } else {
+                    hash_internal(&hash, sibling)
+                }
+            });
+        if computed != root {
+            return Err("Root does not match the proof".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(i: u32) -> [u8; 32] {
+        hash_value(&i.to_be_bytes())
+    }
+
+    #[test]
+    fn test_root_only_depends_on_content() {
+        let mut tree = SparseMerkleTree::default();
+        let mut reversed = SparseMerkleTree::default();
+        for i in 0..100 {
+            tree.insert(key(i), b"old");
+            tree.insert(key(i), &i.to_be_bytes());
+        }
+        for i in (0..150).rev() {
+            reversed.insert(key(i), &i.to_be_bytes());
+        }
+        for i in 100..150 {
+            reversed.delete(&key(i));
+        }
+        assert_eq!(tree.root_hash(), reversed.root_hash());
+
+        for i in 0..100 {
+            tree.delete(&key(i));
+        }
+        assert_eq!(tree.root_hash(), EMPTY_HASH);
+    }
+
+    #[test]
+    fn test_proofs() {
+        let mut tree = SparseMerkleTree::default();
+        let absent = key(1000);
+        let empty_proof = tree.get_proof(&absent);
+        assert!(empty_proof.verify(tree.root_hash(), &absent, None).is_ok());
+
+        for i in 0..50 {
+            tree.insert(key(i), &i.to_be_bytes());
+        }
+        let root = tree.root_hash();
+        for i in 0..50 {
+            let proof = tree.get_proof(&key(i));
+            assert!(proof.verify(root, &key(i), Some(&i.to_be_bytes())).is_ok());
+            assert!(proof.verify(root, &key(i), Some(b"wrong")).is_err());
+            assert!(proof.verify(root, &key(i), None).is_err());
+            assert!(proof.verify(EMPTY_HASH, &key(i), Some(&i.to_be_bytes())).is_err());
+        }
+        for i in 1000..1050 {
+            let proof = tree.get_proof(&key(i));
+            assert!(proof.verify(root, &key(i), None).is_ok());
+            assert!(proof.verify(root, &key(i), Some(b"value")).is_err());
+        }
+    }
+
+    #[test]
+    fn test_snapshot_is_not_updated() {
+        let mut tree = SparseMerkleTree::default();
+        for i in 0..50 {
+            tree.insert(key(i), &i.to_be_bytes());
+        }
+        let snapshot = tree.clone();
+        let root = snapshot.root_hash();
+        tree.insert(key(0), b"new");
+        tree.delete(&key(1));
+        tree.insert(key(1000), b"new");
+
+        assert_eq!(snapshot.root_hash(), root);
+        assert_eq!(snapshot.get(&key(0)), Some(&0u32.to_be_bytes()[..]));
+        assert_eq!(snapshot.get(&key(1)), Some(&1u32.to_be_bytes()[..]));
+        assert_eq!(snapshot.get(&key(1000)), None);
+        assert_eq!(tree.get(&key(0)), Some(&b"new"[..]));
+        assert_eq!(tree.get(&key(1)), None);
+    }
+}
@@ -15,7 +15,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::{
-    verify_signature, State, Storage, Transaction, TransactionReceipt,
+    kv_leaf_key, verify_signature, State, Storage, Transaction, TransactionReceipt,
     TransactionWithAccount,
 };
 
@@ -138,6 +138,23 @@ async fn get_receipt(
     Ok(Json(value))
 }
 
+#[handler]
+async fn get_receipt_by_index(
+    Json((block_number, transaction_index)): Json<(u64, u64)>,
+    Data(context): Data<&Arc<Context>>,
+) -> poem::Result<Json<Value>> {
+    info!("get_receipt_by_index: block {}, index {}", block_number, transaction_index);
+    let receipt = context
+        .storage
+        .get_transaction_receipt_by_index(block_number, transaction_index)
+        .await
+        .map_err(|_| TransactionError::InvalidTransactionHash)?
+        .ok_or(TransactionError::TransactionNotFound)?;
+
+    let value = serde_json::to_value(&receipt).map_err(TransactionError::SerializationError)?;
+    Ok(Json(value))
+}
+
 #[handler]
 async fn get_value(
     Json((account_address, key)): Json<(String, String)>,
@@ -154,6 +171,24 @@ async fn get_value(
     }
 }
 
+/// Returns the value with a proof against the state root of the last committed block, which is
+/// the root that block's `ComputeRes` carries. A missing value comes with an exclusion proof.
+#[handler]
+async fn get_value_with_proof(
+    Json((account_address, key)): Json<(String, String)>,
+    Data(context): Data<&Arc<Context>>,
+) -> poem::Result<Json<Value>> {
+    info!("get_value_with_proof: account_address: {}, key: {}", account_address, key);
+    let committed = context.state.read().await.get_value_with_proof(&account_address, &key);
+    Ok(Json(json!({
+        "value": committed.value,
+        "leaf_key": hex::encode(kv_leaf_key(&account_address, &key)),
+        "block_number": committed.block_number,
+        "state_root": committed.state_root.to_hex(),
+        "proof": committed.proof,
+    })))
+}
+
 pub struct ServerApp {
     context: Arc<Context>,
 }
@@ -171,7 +206,15 @@ impl ServerApp {
         let app = Route::new()
             .at("/add_txn", poem::post(add_txn.data(self.context.clone())))
             .at("/get_receipt", poem::post(get_receipt.data(self.context.clone())))
-            .at("/get_value", poem::post(get_value.data(self.context.clone())));
+            .at(
+                "/get_receipt_by_index",
+                poem::post(get_receipt_by_index.data(self.context.clone())),
+            )
+            .at("/get_value", poem::post(get_value.data(self.context.clone())))
+            .at(
+                "/get_value_with_proof",
+                poem::post(get_value_with_proof.data(self.context.clone())),
+            );
 
         info!("Server running at {}", addr);
         Server::new(listener::TcpListener::bind(addr)).run(app).await?;
@@ -1,12 +1,54 @@
-use sha3::Digest;
-use std::{collections::HashMap, fs::File, io::BufReader};
+use sha3::{Digest, Keccak256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::BufReader,
+};
 
-use crate::{AccountId, AccountState, StateRoot};
+use crate::{
+    merkle::{SparseMerkleProof, SparseMerkleTree},
+    AccountId, AccountState, StateRoot,
+};
+
+/// Key of the leaf holding the nonce and balance of `address`.
+pub fn account_leaf_key(address: &str) -> [u8; 32] {
+    Keccak256::digest(format!("account:{}", address)).into()
+}
+
+/// Key of the leaf holding the value of `key` in the kv store of `address`.
+pub fn kv_leaf_key(address: &str, key: &str) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("kv:{}:", address));
+    hasher.update(Keccak256::digest(key));
+    hasher.finalize().into()
+}
+
+pub fn account_leaf_value(account: &AccountState) -> Vec<u8> {
+    let mut value = account.nonce.to_be_bytes().to_vec();
+    value.extend_from_slice(&account.balance.to_be_bytes());
+    value
+}
+
+/// A kv store value as of the last committed block, with its proof against that block's state
+/// root.
+#[derive(Debug)]
+pub struct ValueWithProof {
+    pub block_number: u64,
+    pub state_root: StateRoot,
+    pub value: Option<String>,
+    pub proof: SparseMerkleProof,
+}
 
 #[derive(Debug)]
 pub struct State {
     accounts: HashMap<String, AccountState>,
     block_number: u64,
+    // commits to every account and kv entry, updated along with `accounts`
+    tree: SparseMerkleTree,
+    // `tree` after each executed block that isn't committed yet
+    executed_trees: BTreeMap<u64, SparseMerkleTree>,
+    // the last committed block and its tree, proofs are only served against it
+    committed: (u64, SparseMerkleTree),
 }
 
 impl State {
@@ -19,16 +61,36 @@ impl State {
             HashMap::new()
         };
 
-        Self {
-            accounts,
-            block_number: 0,
+        let mut tree = SparseMerkleTree::default();
+        for (address, account) in &accounts {
+            tree.insert(account_leaf_key(address), &account_leaf_value(account));
+            for (key, value) in &account.kv_store {
+                tree.insert(kv_leaf_key(address, key), value.as_bytes());
+            }
         }
+
+        let committed = (0, tree.clone());
+        Self { accounts, block_number: 0, tree, executed_trees: BTreeMap::new(), committed }
     }
 
     pub fn get_current_block_number(&self) -> u64 {
         self.block_number
     }
 
+    /// Records that the state holds the updates of `block_number`, which is executed.
+    pub fn set_current_block_number(&mut self, block_number: u64) {
+        self.block_number = block_number;
+        self.executed_trees.insert(block_number, self.tree.clone());
+    }
+
+    /// Serves proofs against the state root of `block_number` from now on, it is committed.
+    pub fn commit_block(&mut self, block_number: u64) {
+        if let Some(tree) = self.executed_trees.remove(&block_number) {
+            self.committed = (block_number, tree);
+        }
+        self.executed_trees.retain(|number, _| *number > block_number);
+    }
+
     pub fn get_account(&self, address: &str) -> Option<AccountState> {
         self.accounts.get(address).cloned()
     }
@@ -38,37 +100,103 @@ impl State {
         account_id: &AccountId,
         state_update: AccountState,
     ) -> Result<(), String> {
-        self.accounts.insert(
-            account_id.0.clone(),
-            AccountState {
-                balance: state_update.balance,
-                nonce: state_update.nonce,
-                kv_store: state_update.kv_store,
-            },
-        );
+        let address = account_id.0.as_str();
+        if let Some(old) = self.accounts.get(address) {
+            for key in old.kv_store.keys().filter(|key| !state_update.kv_store.contains_key(*key)) {
+                self.tree.delete(&kv_leaf_key(address, key));
+            }
+            for (key, value) in &state_update.kv_store {
+                if old.kv_store.get(key) != Some(value) {
+                    self.tree.insert(kv_leaf_key(address, key), value.as_bytes());
+                }
+            }
+        } else {
+            for (key, value) in &state_update.kv_store {
+                self.tree.insert(kv_leaf_key(address, key), value.as_bytes());
+            }
+        }
+        self.tree.insert(account_leaf_key(address), &account_leaf_value(&state_update));
+        self.accounts.insert(account_id.0.clone(), state_update);
         Ok(())
     }
 
+    /// Root of the sparse Merkle tree over all accounts and kv entries.
     pub fn compute_state_root(&self) -> Result<StateRoot, String> {
-        let mut accounts: Vec<_> = self.accounts.iter().collect();
-        accounts.sort_by(|a, b| a.0.cmp(b.0));
+        Ok(StateRoot(self.tree.root_hash()))
+    }
 
-        let mut hasher = sha3::Keccak256::new();
-        for (address, account) in accounts {
-            hasher.update(address.as_bytes());
-            hasher.update(&account.nonce.to_be_bytes());
-            hasher.update(&account.balance.to_be_bytes());
+    /// The value of `key` in the kv store of `address` as of the last committed block, with a
+    /// proof of its inclusion, or of its exclusion if absent. Executed blocks may still be
+    /// reverted, so their state isn't served.
+    pub fn get_value_with_proof(&self, address: &str, key: &str) -> ValueWithProof {
+        let (block_number, tree) = &self.committed;
+        let leaf_key = kv_leaf_key(address, key);
+        ValueWithProof {
+            block_number: *block_number,
+            state_root: StateRoot(tree.root_hash()),
+            value: tree.get(&leaf_key).map(|value| String::from_utf8_lossy(value).into_owned()),
+            proof: tree.get_proof(&leaf_key),
+        }
+    }
+}
 
-            let mut kv_pairs: Vec<_> = account.kv_store.iter().collect();
-            kv_pairs.sort_by(|a, b| a.0.cmp(b.0));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            for (k, v) in kv_pairs {
-                hasher.update(k.as_bytes());
-                hasher.update(v.as_bytes());
-            }
+    fn account(nonce: u64, kv: &[(&str, &str)]) -> AccountState {
+        AccountState {
+            nonce,
+            balance: 100,
+            kv_store: kv.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
         }
+    }
+
+    #[tokio::test]
+    async fn test_state_root_tracks_updates() {
+        let mut state = State::new(None);
+        let alice = AccountId("alice".to_string());
+        state.update_account_state(&alice, account(1, &[("a", "1"), ("b", "2")])).await.unwrap();
+        state.update_account_state(&alice, account(2, &[("a", "3")])).await.unwrap();
+
+        let mut expected = State::new(None);
+        expected.update_account_state(&alice, account(2, &[("a", "3")])).await.unwrap();
+        let root = state.compute_state_root().unwrap();
+        assert_eq!(root.0, expected.compute_state_root().unwrap().0);
+
+        state.set_current_block_number(1);
+        state.commit_block(1);
+        let committed = state.get_value_with_proof("alice", "a");
+        assert_eq!(committed.block_number, 1);
+        assert_eq!(committed.state_root.0, root.0);
+        assert_eq!(committed.value.as_deref(), Some("3"));
+        assert!(committed.proof.verify(root.0, &kv_leaf_key("alice", "a"), Some(b"3")).is_ok());
+        let committed = state.get_value_with_proof("alice", "b");
+        assert!(committed.value.is_none());
+        assert!(committed.proof.verify(root.0, &kv_leaf_key("alice", "b"), None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_proofs_only_for_committed_blocks() {
+        let mut state = State::new(None);
+        let alice = AccountId("alice".to_string());
+        let genesis_root = state.compute_state_root().unwrap();
+        state.update_account_state(&alice, account(1, &[("a", "1")])).await.unwrap();
+        state.set_current_block_number(1);
+        let root_1 = state.compute_state_root().unwrap();
+        state.update_account_state(&alice, account(2, &[("a", "2")])).await.unwrap();
+        state.set_current_block_number(2);
+
+        let committed = state.get_value_with_proof("alice", "a");
+        assert_eq!(committed.block_number, 0);
+        assert_eq!(committed.state_root.0, genesis_root.0);
+        assert!(committed.value.is_none());
 
-        let result: [u8; 32] = hasher.finalize().into();
-        Ok(StateRoot(result))
+        state.commit_block(1);
+        let committed = state.get_value_with_proof("alice", "a");
+        assert_eq!(committed.block_number, 1);
+        assert_eq!(committed.state_root.0, root_1.0);
+        assert_eq!(committed.value.as_deref(), Some("1"));
+        assert!(committed.proof.verify(root_1.0, &kv_leaf_key("alice", "a"), Some(b"1")).is_ok());
     }
 }
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use sled::{transaction::TransactionError, Db};
-use std::{collections::HashMap, path::Path};
+use std::path::Path;
 
 use crate::{AccountId, AccountState, Block, StateRoot, TransactionReceipt};
 
@@ -16,6 +16,19 @@ pub trait Storage: Send + Sync + 'static {
         &self,
         transaction_hash: [u8; 32],
     ) -> Result<Option<TransactionReceipt>, String>;
+    async fn get_transaction_receipt_by_index(
+        &self,
+        block_number: u64,
+        transaction_index: u64,
+    ) -> Result<Option<TransactionReceipt>, String>;
+    /// Saves the block, its state root and the receipts of its txns atomically, so that a
+    /// persisted block always has its receipts.
+    async fn save_committed_block(
+        &self,
+        block: &Block,
+        state_root: StateRoot,
+        receipts: Vec<TransactionReceipt>,
+    ) -> Result<(), String>;
     async fn save_state_root(&self, block_number: u64, root: StateRoot) -> Result<(), String>;
     async fn get_state_root(&self, block_number: u64) -> Result<Option<StateRoot>, String>;
     async fn save_account_state(
@@ -51,6 +64,43 @@ impl SledStorage {
     fn account_key(account_id: &AccountId) -> Vec<u8> {
         format!("account:{}", account_id.0).into_bytes()
     }
+
+    fn receipt_key(transaction_hash: &[u8; 32]) -> Vec<u8> {
+        format!("receipt:{}", hex::encode(transaction_hash)).into_bytes()
+    }
+
+    fn receipt_index_key(block_number: u64, transaction_index: u64) -> Vec<u8> {
+        format!("receipt_index:{}:{}", block_number, transaction_index).into_bytes()
+    }
+
+    /// Encodes `receipts` as the (key, value) pairs to insert: each receipt under its hash, and
+    /// its hash under its (block, index).
+    fn encode_receipts(receipts: &[TransactionReceipt]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, String> {
+        let mut entries = Vec::with_capacity(receipts.len() * 2);
+        for receipt in receipts {
+            let encoded = bincode::serialize(receipt)
+                .map_err(|e| format!("Failed to serialize transaction receipt: {}", e))?;
+            entries.push((Self::receipt_key(&receipt.transaction_hash), encoded));
+            entries.push((
+                Self::receipt_index_key(receipt.block_number, receipt.transaction_index),
+                receipt.transaction_hash.to_vec(),
+            ));
+        }
+        Ok(entries)
+    }
+
+    fn insert_atomically(&self, entries: Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), String> {
+        self.db
+            .transaction(|tx_db| {
+                for (key, value) in &entries {
+                    tx_db.insert(key.as_slice(), value.as_slice())?;
+                }
+                Ok(())
+            })
+            .map_err(|e: TransactionError| format!("Failed to commit transaction: {}", e))?;
+        self.db.flush().map_err(|e| format!("Failed to flush database: {}", e))?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -84,42 +134,58 @@ impl Storage for SledStorage {
         &self,
         receipts: Vec<TransactionReceipt>,
     ) -> Result<(), String> {
-        let encodes = receipts
-            .iter()
-            .map(|receipt| {
-                let encoded =
-                    bincode::serialize(receipt).expect("Failed to serialize transaction receipt");
-                (receipt.transaction_hash.clone(), encoded)
-            })
-            .collect::<HashMap<_, _>>();
-
-        self.db
-            .transaction(|tx_db| {
-                for (tx_hash, receipt) in &encodes {
-                    tx_db.insert(tx_hash, receipt.clone())?;
-                }
-                Ok(())
-            })
-            .map_err(|e: TransactionError| format!("Failed to save transaction receipts"))?;
-
-        Ok(())
+        self.insert_atomically(Self::encode_receipts(&receipts)?)
     }
 
     async fn get_transaction_receipt(
         &self,
         transaction_hash: [u8; 32],
     ) -> Result<Option<TransactionReceipt>, String> {
-        match self.db.get(transaction_hash) {
+        match self.db.get(Self::receipt_key(&transaction_hash)) {
             Ok(Some(data)) => {
                 let receipt = bincode::deserialize(&data)
-                    .map_err(|e| format!("Failed to deserialize block: {}", e))?;
-                Ok(receipt)
+                    .map_err(|e| format!("Failed to deserialize transaction receipt: {}", e))?;
+                Ok(Some(receipt))
             }
             Ok(None) => Ok(None),
-            Err(e) => Err(format!("Failed to get block: {}", e)),
+            Err(e) => Err(format!("Failed to get transaction receipt: {}", e)),
         }
     }
 
+    async fn get_transaction_receipt_by_index(
+        &self,
+        block_number: u64,
+        transaction_index: u64,
+    ) -> Result<Option<TransactionReceipt>, String> {
+        let transaction_hash =
+            match self.db.get(Self::receipt_index_key(block_number, transaction_index)) {
+                Ok(Some(data)) => <[u8; 32]>::try_from(data.as_ref())
+                    .map_err(|_| "Invalid transaction hash in receipt index".to_string())?,
+                Ok(None) => return Ok(None),
+                Err(e) => return Err(format!("Failed to get receipt index: {}", e)),
+            };
+        self.get_transaction_receipt(transaction_hash).await
+    }
+
+    async fn save_committed_block(
+        &self,
+        block: &Block,
+        state_root: StateRoot,
+        receipts: Vec<TransactionReceipt>,
+    ) -> Result<(), String> {
+        let mut entries = Self::encode_receipts(&receipts)?;
+        entries.push((
+            Self::block_key(block.header.number),
+            bincode::serialize(block).map_err(|e| format!("Failed to serialize block: {}", e))?,
+        ));
+        entries.push((
+            Self::state_root_key(block.header.number),
+            bincode::serialize(&state_root)
+                .map_err(|e| format!("Failed to serialize state root: {}", e))?,
+        ));
+        self.insert_atomically(entries)
+    }
+
     async fn save_state_root(&self, block_number: u64, root: StateRoot) -> Result<(), String> {
         let encoded = bincode::serialize(&root)
             .map_err(|e| format!("Failed to serialize state root: {}", e))?;
@@ -177,3 +243,58 @@ impl Storage for SledStorage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockHeader, Transaction, TransactionKind, UnsignedTransaction};
+
+    fn receipt(block_number: u64, transaction_index: u64, status: bool) -> TransactionReceipt {
+        TransactionReceipt {
+            transaction: Transaction {
+                unsigned: UnsignedTransaction {
+                    nonce: transaction_index,
+                    kind: TransactionKind::SetKV { key: "k".to_string(), value: "v".to_string() },
+                },
+                signature: String::new(),
+            },
+            transaction_hash: [transaction_index as u8 + 1; 32],
+            block_number,
+            transaction_index,
+            status,
+            error: (!status).then(|| "Insufficient balance".to_string()),
+            gas_used: 21000,
+            logs: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_committed_block_indexes_receipts() {
+        let dir = std::env::temp_dir().join(format!("kvstore_receipts_{}", std::process::id()));
+        let storage = SledStorage::new(&dir).unwrap();
+        let block = Block {
+            header: BlockHeader {
+                number: 7,
+                parent_hash: [0; 32],
+                state_root: [3; 32],
+                transactions_root: [0; 32],
+                timestamp: 0,
+            },
+            transactions: Vec::new(),
+        };
+        let receipts = vec![receipt(7, 0, true), receipt(7, 1, false)];
+        storage.save_committed_block(&block, StateRoot([3; 32]), receipts).await.unwrap();
+
+        assert!(storage.get_block(7).await.unwrap().is_some());
+        assert_eq!(storage.get_state_root(7).await.unwrap().unwrap().0, [3; 32]);
+        let failed = storage.get_transaction_receipt([2; 32]).await.unwrap().unwrap();
+        assert!(!failed.status);
+        assert_eq!(failed.transaction_index, 1);
+        let by_index = storage.get_transaction_receipt_by_index(7, 0).await.unwrap().unwrap();
+        assert_eq!(by_index.transaction_hash, [1; 32]);
+        assert!(storage.get_transaction_receipt_by_index(7, 2).await.unwrap().is_none());
+        assert!(storage.get_transaction_receipt([9; 32]).await.unwrap().is_none());
+        drop(storage);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
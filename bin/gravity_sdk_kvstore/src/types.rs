@@ -143,7 +143,12 @@ pub struct BlockExecutionResult {
 pub struct TransactionReceipt {
     pub transaction: Transaction,
     pub transaction_hash: [u8; 32],
+    pub block_number: u64,
+    pub transaction_index: u64,
+    /// Whether the txn was applied. A failed txn is still part of its block.
     pub status: bool,
+    /// Why the txn failed, `None` if `status` is true
+    pub error: Option<String>,
     pub gas_used: u64,
     pub logs: Vec<Log>,
 }
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::hash::{DefaultHasher, Hash, Hasher};
+use api_types::block_budget::{BlockBudget, BlockBudgetTracker, TxnCost};
 use api_types::compute_res::ComputeRes;
 use api_types::u256_define::TxnHash;
 use tokio::sync::mpsc::Receiver;
@@ -8,16 +9,15 @@ use api_types::{u256_define::BlockId, ExecError, ExecTxn, ExecutionChannel, Exte
 use crate::stateful_mempool::Mempool;
 use crate::txn::RawTxn;
 use async_trait::async_trait;
+use log::warn;
 
-#[derive(Default)]
-struct BlockStatus {
-    txn_number: u64,
-}
+/// Max total txn bytes of a block built by `check_block_txns`.
+const MAX_BLOCK_BYTES: u64 = 1024 * 1024;
 
 pub struct KvStore {
     store: Mutex<HashMap<String, String>>,
     mempool: Mempool,
-    block_status: Mutex<HashMap<ExternalPayloadAttr, BlockStatus>>,
+    block_status: Mutex<HashMap<ExternalPayloadAttr, BlockBudgetTracker>>,
     compute_res_recv: Mutex<HashMap<ExternalBlockMeta, Receiver<ComputeRes>>>,
     ordered_block: Mutex<HashMap<BlockId, ExternalBlock>>,
 }
@@ -60,20 +60,41 @@ impl ExecutionChannel for KvStore {
         Ok(TxnHash::random())
     }
 
+    async fn send_user_txns(&self, txns: Vec<ExecTxn>) -> Vec<Result<TxnHash, ExecError>> {
+        let mut results = Vec::with_capacity(txns.len());
+        let mut raw_txns = Vec::with_capacity(txns.len());
+        for txn in txns {
+            match txn {
+                ExecTxn::RawTxn(bytes) => match RawTxn::try_from_bytes(&bytes) {
+                    Ok(raw_txn) => raw_txns.push(raw_txn),
+                    Err(e) => {
+                        warn!("malformed txn: {}", e);
+                        results.push(Err(ExecError::InternalError));
+                        continue;
+                    }
+                },
+                ExecTxn::VerifiedTxn(verified_txn) => self.mempool.add_verified_txn(verified_txn).await
+            }
+            results.push(Ok(TxnHash::random()));
+        }
+        self.mempool.add_raw_txns(raw_txns).await;
+        results
+    }
+
     async fn recv_unbroadcasted_txn(&self) -> Result<Vec<VerifiedTxn>, ExecError> {
         Ok(self.mempool.recv_unbroadcasted_txn().await)
     }
 
     async fn check_block_txns(&self, payload_attr: ExternalPayloadAttr, txns: Vec<VerifiedTxn>) -> Result<bool, ExecError> {
         let mut block = self.block_status.lock().await;
-        let status = block.entry(payload_attr).or_insert(BlockStatus::default());
-        let new_txn_number = status.txn_number + txns.len() as u64;
-        if new_txn_number > 100 {
-            Ok(false)
-        } else {
-            status.txn_number = new_txn_number;
-            Ok(true)
+        let status = block
+            .entry(payload_attr)
+            .or_insert_with(|| BlockBudgetTracker::new(BlockBudget::new(None, Some(MAX_BLOCK_BYTES))));
+        let mut cost = TxnCost::default();
+        for txn in &txns {
+            cost += TxnCost { gas: 0, bytes: txn.bytes().len() as u64 };
         }
+        Ok(status.try_consume(cost))
     }
 
     async fn send_pending_txns(&self) -> Result<Vec<VerifiedTxnWithAccountSeqNum>, ExecError> {
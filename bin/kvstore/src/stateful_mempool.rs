@@ -88,6 +88,31 @@ impl Mempool {
         self.process_txn(account).await;
     }
 
+    /// Adds the txns under a single lock of the mempool, they are broadcast once it is released.
+    pub async fn add_raw_txns(&self, txns: Vec<RawTxn>) {
+        let mut accounts = Vec::new();
+        let mut broadcast = Vec::with_capacity(txns.len());
+        {
+            let mut mempool = self.mempool.lock().await;
+            for raw_txn in txns {
+                broadcast.push(raw_txn.clone().into_verified());
+                let sequence_number = raw_txn.sequence_number();
+                let account = raw_txn.account();
+                let txn = MempoolTxn { raw_txn, status: TxnStatus::Waiting };
+                mempool.entry(account.clone()).or_insert(BTreeMap::new()).insert(sequence_number, txn);
+                if !accounts.contains(&account) {
+                    accounts.push(account);
+                }
+            }
+        }
+        for txn in broadcast {
+            let _ = self.broadcast_send.send(txn).await;
+        }
+        for account in accounts {
+            self.process_txn(account).await;
+        }
+    }
+
     pub async fn recv_unbroadcasted_txn(&self) -> Vec<VerifiedTxn> {
         let mut txns = Vec::new();
         
@@ -26,6 +26,11 @@ impl RawTxn {
         txn
     }
 
+    /// Parses txn bytes submitted by a client, which may be malformed.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         serde_json::to_vec(self).unwrap()
     }
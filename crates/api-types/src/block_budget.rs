@@ -0,0 +1,147 @@
+use std::sync::OnceLock;
+
+/// The resources a single block may consume, as reported by the execution layer.
+/// A `None` limit means the execution layer does not bound that resource.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockBudget {
+    pub max_gas: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+impl BlockBudget {
+    pub fn new(max_gas: Option<u64>, max_bytes: Option<u64>) -> Self {
+        Self { max_gas, max_bytes }
+    }
+
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_gas.is_none() && self.max_bytes.is_none()
+    }
+}
+
+/// The resources a single txn consumes out of a `BlockBudget`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TxnCost {
+    pub gas: u64,
+    pub bytes: u64,
+}
+
+impl std::ops::AddAssign for TxnCost {
+    fn add_assign(&mut self, other: Self) {
+        self.gas = self.gas.saturating_add(other.gas);
+        self.bytes = self.bytes.saturating_add(other.bytes);
+    }
+}
+
+/// Implemented by the execution layer so that consensus can build blocks that fit into
+/// what the execution layer is able to include, instead of building them by txn count.
+pub trait BlockResourceMeter: Send + Sync {
+    fn block_budget(&self) -> BlockBudget;
+
+    /// `txn` is the raw txn bytes as carried by `VerifiedTxn::bytes`.
+    fn txn_cost(&self, txn: &[u8]) -> TxnCost;
+
+    /// Estimates the cost of `num_txns` txns of `num_bytes` in total which are not available
+    /// locally, e.g. a batch of another validator that wasn't fetched yet.
+    fn estimate_cost(&self, _num_txns: u64, num_bytes: u64) -> TxnCost {
+        TxnCost { gas: 0, bytes: num_bytes }
+    }
+}
+
+pub static GLOBAL_BLOCK_RESOURCE_METER: OnceLock<Box<dyn BlockResourceMeter>> = OnceLock::new();
+
+/// The budget of the next block, unlimited if no execution layer registered a meter.
+pub fn block_budget() -> BlockBudget {
+    GLOBAL_BLOCK_RESOURCE_METER.get().map(|meter| meter.block_budget()).unwrap_or_default()
+}
+
+/// The cost of `txn`, falling back to its length when no meter is registered.
+pub fn txn_cost(txn: &[u8]) -> TxnCost {
+    match GLOBAL_BLOCK_RESOURCE_METER.get() {
+        Some(meter) => meter.txn_cost(txn),
+        None => TxnCost { gas: 0, bytes: txn.len() as u64 },
+    }
+}
+
+/// The estimated cost of txns that are not available locally, see
+/// `BlockResourceMeter::estimate_cost`.
+pub fn estimate_cost(num_txns: u64, num_bytes: u64) -> TxnCost {
+    match GLOBAL_BLOCK_RESOURCE_METER.get() {
+        Some(meter) => meter.estimate_cost(num_txns, num_bytes),
+        None => TxnCost { gas: 0, bytes: num_bytes },
+    }
+}
+
+/// Accumulates txn costs against a `BlockBudget`.
+#[derive(Clone, Debug)]
+pub struct BlockBudgetTracker {
+    budget: BlockBudget,
+    used: TxnCost,
+}
+
+impl BlockBudgetTracker {
+    pub fn new(budget: BlockBudget) -> Self {
+        Self { budget, used: TxnCost::default() }
+    }
+
+    pub fn budget(&self) -> BlockBudget {
+        self.budget
+    }
+
+    pub fn used(&self) -> TxnCost {
+        self.used
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.used == TxnCost::default()
+    }
+
+    /// Whether `cost` still fits into the remaining budget.
+    pub fn fits(&self, cost: TxnCost) -> bool {
+        let within = |used: u64, cost: u64, max: Option<u64>| {
+            max.map_or(true, |max| used.saturating_add(cost) <= max)
+        };
+        within(self.used.gas, cost.gas, self.budget.max_gas)
+            && within(self.used.bytes, cost.bytes, self.budget.max_bytes)
+    }
+
+    /// Consumes `cost` if it fits, otherwise leaves the tracker untouched and returns false.
+    pub fn try_consume(&mut self, cost: TxnCost) -> bool {
+        if !self.fits(cost) {
+            return false;
+        }
+        self.used += cost;
+        true
+    }
+
+    /// Consumes `cost` regardless of the remaining budget.
+    pub fn force_consume(&mut self, cost: TxnCost) {
+        self.used += cost;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_stops_at_the_limit() {
+        let mut tracker = BlockBudgetTracker::new(BlockBudget::new(Some(100), Some(10)));
+        assert!(tracker.try_consume(TxnCost { gas: 60, bytes: 4 }));
+        assert!(!tracker.try_consume(TxnCost { gas: 50, bytes: 4 }));
+        assert!(!tracker.try_consume(TxnCost { gas: 10, bytes: 7 }));
+        assert!(tracker.try_consume(TxnCost { gas: 40, bytes: 6 }));
+        assert_eq!(tracker.used(), TxnCost { gas: 100, bytes: 10 });
+        assert!(!tracker.fits(TxnCost { gas: 1, bytes: 0 }));
+    }
+
+    #[test]
+    fn test_unlimited_budget() {
+        let mut tracker = BlockBudgetTracker::new(BlockBudget::unlimited());
+        assert!(tracker.try_consume(TxnCost { gas: u64::MAX, bytes: u64::MAX }));
+        assert!(tracker.try_consume(TxnCost { gas: u64::MAX, bytes: u64::MAX }));
+    }
+}
@@ -3,7 +3,7 @@ use rand::Rng;
 use std::{fmt, sync::Arc};
 use hex;
 
-#[derive(Clone, Deserialize, Serialize, Hash, PartialEq, Eq, Copy)]
+#[derive(Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq, Copy)]
 pub struct TxnStatus {
     pub txn_hash: [u8; 32],
     pub nonce: u64,
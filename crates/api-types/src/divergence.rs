@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use crate::compute_res::TxnStatus;
+
+/// The local execution layer computed a different result for a block than the one certified by
+/// a quorum of validators, e.g. because of nondeterminism in the execution layer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionDivergence {
+    pub epoch: u64,
+    pub round: u64,
+    pub block_id: [u8; 32],
+    pub block_number: u64,
+    /// The block hash computed by the local execution layer
+    pub local_hash: [u8; 32],
+    /// The block hash in the commit proof
+    pub certified_hash: [u8; 32],
+    pub txn_num: u64,
+    /// The txn statuses reported by the local execution layer, empty if it reported none
+    pub txn_status: Vec<TxnStatus>,
+    pub detected_at_usecs: u64,
+}
@@ -0,0 +1,41 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EquivocationKind {
+    /// Two different blocks proposed in one round
+    Proposal,
+    /// Two votes for different blocks or execution results in one round
+    Vote,
+}
+
+/// A message signed with the BLS12-381 consensus key of a validator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedConsensusMessage {
+    /// The exact bytes the signature is over, the salted BCS encoding of the block data of a
+    /// proposal or of the ledger info of a vote
+    pub message: Vec<u8>,
+    /// 96 bytes BLS12-381 signature
+    pub signature: Vec<u8>,
+}
+
+/// Proof that `author` signed two conflicting messages of the same kind in one round. The same
+/// equivocation may be reported more than once, `(epoch, round, author, kind)` identifies it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EquivocationPayload {
+    pub kind: EquivocationKind,
+    pub epoch: u64,
+    pub round: u64,
+    pub author: [u8; 32],
+    pub first: SignedConsensusMessage,
+    pub second: SignedConsensusMessage,
+    /// BCS encoding of the full consensus messages, `EquivocationEvidence` of the consensus types
+    pub evidence: Vec<u8>,
+}
+
+/// Registered by the execution layer to receive equivocations as soon as consensus detects them,
+/// e.g. to submit them to the staking contract. Called from the consensus thread, so it must
+/// not block.
+pub static GLOBAL_EQUIVOCATION_HANDLER: OnceLock<Box<dyn Fn(EquivocationPayload) + Send + Sync>> =
+    OnceLock::new();
@@ -1,8 +1,12 @@
 pub mod account;
+pub mod block_budget;
 pub mod mock_execution_layer;
 pub mod simple_hash;
+pub mod txn_filter;
 pub mod u256_define;
 pub mod compute_res;
+pub mod divergence;
+pub mod equivocation;
 use crate::account::{ExternalAccountAddress, ExternalChainId};
 use gaptos::aptos_crypto::HashValue;
 use async_trait::async_trait;
@@ -61,6 +65,11 @@ pub enum ExecError {
     DuplicateExecError,
 }
 
+/// An opaque key naming state a txn touches, e.g. a `to` address, a storage slot or a contract
+/// id. Txns sharing a key are expected to conflict when executed in parallel. Accounts are keyed
+/// by their 32 byte `ExternalAccountAddress`, the key consensus adds for the sender of a txn.
+pub type ConflictKey = Vec<u8>;
+
 pub enum ExecTxn {
     RawTxn(Vec<u8>),          // from client
     VerifiedTxn(VerifiedTxn), // from peer
@@ -82,6 +91,16 @@ pub trait ExecutionChannel: Send + Sync {
     ///
     async fn send_user_txn(&self, bytes: ExecTxn) -> Result<TxnHash, ExecError>;
 
+    /// Adds `txns` in order and returns a result per txn. The default adds them one by one,
+    /// execution layers that can add a batch at once should override it.
+    async fn send_user_txns(&self, txns: Vec<ExecTxn>) -> Vec<Result<TxnHash, ExecError>> {
+        let mut results = Vec::with_capacity(txns.len());
+        for txn in txns {
+            results.push(self.send_user_txn(txn).await);
+        }
+        results
+    }
+
     async fn recv_unbroadcasted_txn(&self) -> Result<Vec<VerifiedTxn>, ExecError>;
 
     async fn check_block_txns(
@@ -113,6 +132,21 @@ pub trait ExecutionChannel: Send + Sync {
 
     // this function is called by the execution layer commit the block hash
     async fn recv_committed_block_info(&self, block_id: BlockId) -> Result<(), ExecError>;
+
+    /// Returns the conflict keys of every txn in `txns`, in the same order, so that consensus
+    /// can spread conflicting txns across a block. The keys must only depend on the txn bytes,
+    /// since every validator has to shuffle a block the same way. `None`, the default, keeps
+    /// the block order.
+    fn conflict_keys(&self, _txns: &[VerifiedTxn]) -> Option<Vec<Vec<ConflictKey>>> {
+        None
+    }
+
+    /// Returns the sequence number (nonce) of the next txn of `account` on the execution layer,
+    /// used to drop committed txns when the mempool journal is replayed. `None`, the default,
+    /// keeps the sequence number recorded with each journaled txn.
+    fn account_seq_num(&self, _account: &ExternalAccountAddress) -> Option<u64> {
+        None
+    }
 }
 
 pub struct ExecutionArgs {
@@ -124,6 +158,32 @@ pub struct ExecutionLayer {
     pub execution_api: Arc<dyn ExecutionChannel>,
 }
 
+/// Fee and lifetime hints of an execution layer txn. The mempool orders, pulls and evicts txns by
+/// the tip they pay, txns without hints rank lowest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct TxnPriority {
+    /// Max fee per gas in wei, the gas price of legacy txns
+    pub max_fee_per_gas: Option<u128>,
+    /// Max priority fee per gas in wei, unset for legacy txns
+    pub max_priority_fee_per_gas: Option<u128>,
+    pub gas_limit: Option<u64>,
+    /// Unix timestamp in seconds after which the txn can't be included in a block
+    pub expiration_timestamp_secs: Option<u64>,
+}
+
+impl TxnPriority {
+    /// The tip per gas in wei, capped by the max fee, saturated to u64.
+    pub fn tip_per_gas(&self) -> u64 {
+        let tip = match (self.max_priority_fee_per_gas, self.max_fee_per_gas) {
+            (Some(tip), Some(max_fee)) => tip.min(max_fee),
+            (Some(tip), None) => tip,
+            (None, Some(gas_price)) => gas_price,
+            (None, None) => 0,
+        };
+        u64::try_from(tip).unwrap_or(u64::MAX)
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct VerifiedTxn {
     pub bytes: Vec<u8>,
@@ -132,6 +192,8 @@ pub struct VerifiedTxn {
     pub chain_id: ExternalChainId,
     #[serde(skip)]
     pub committed_hash: OnceCell<TxnHash>,
+    #[serde(default)]
+    pub priority: TxnPriority,
 }
 
 // implment the Debug for VerifiedTxn
@@ -164,7 +226,19 @@ impl VerifiedTxn {
         chain_id: ExternalChainId,
         committed_hash: TxnHash,
     ) -> Self {
-        Self { bytes, sender, sequence_number, chain_id, committed_hash: committed_hash.into() }
+        Self {
+            bytes,
+            sender,
+            sequence_number,
+            chain_id,
+            committed_hash: committed_hash.into(),
+            priority: TxnPriority::default(),
+        }
+    }
+
+    pub fn with_priority(mut self, priority: TxnPriority) -> Self {
+        self.priority = priority;
+        self
     }
 
     pub fn bytes(&self) -> &Vec<u8> {
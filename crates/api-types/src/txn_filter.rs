@@ -0,0 +1,218 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The call a txn makes, decoded from the raw txn bytes by the execution layer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TxnCall {
+    /// Callee, left padded to 32 bytes like senders. `None` for contract creation.
+    pub to: Option<[u8; 32]>,
+    /// First 4 bytes of the call data
+    pub selector: Option<[u8; 4]>,
+}
+
+/// Implemented by the execution layer to deny txns with its own logic, on top of the
+/// `TxnFilterRules` set by the operator.
+pub trait TxnFilter: Send + Sync {
+    /// `sender` is left padded to 32 bytes, `txn` is the raw txn as carried by
+    /// `VerifiedTxn::bytes`. Returns why the txn is denied.
+    fn check(&self, sender: &[u8; 32], txn: &[u8]) -> Result<(), String>;
+}
+
+pub static GLOBAL_TXN_FILTER: OnceLock<Box<dyn TxnFilter>> = OnceLock::new();
+
+/// Decodes the call of a raw txn, `None` if it can't be decoded. Without a decoder, the `to`
+/// and selector rules never match.
+pub static GLOBAL_TXN_CALL_DECODER: OnceLock<Box<dyn Fn(&[u8]) -> Option<TxnCall> + Send + Sync>> =
+    OnceLock::new();
+
+/// Deny rules, addresses and selectors are hex encoded with or without `0x`. Addresses are 20
+/// or 32 bytes.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxnFilterRules {
+    #[serde(default)]
+    pub deny_senders: Vec<String>,
+    #[serde(default)]
+    pub deny_to: Vec<String>,
+    #[serde(default)]
+    pub deny_selectors: Vec<String>,
+    pub max_txn_bytes: Option<u64>,
+}
+
+#[derive(Default)]
+struct CompiledRules {
+    rules: TxnFilterRules,
+    deny_senders: HashSet<[u8; 32]>,
+    deny_to: HashSet<[u8; 32]>,
+    deny_selectors: HashSet<[u8; 4]>,
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| format!("invalid hex {:?}: {}", value, e))
+}
+
+fn parse_address(value: &str) -> Result<[u8; 32], String> {
+    let bytes = decode_hex(value)?;
+    if bytes.len() != 20 && bytes.len() != 32 {
+        return Err(format!("invalid address {:?}: expected 20 or 32 bytes", value));
+    }
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(padded)
+}
+
+fn parse_selector(value: &str) -> Result<[u8; 4], String> {
+    decode_hex(value)?
+        .try_into()
+        .map_err(|_| format!("invalid selector {:?}: expected 4 bytes", value))
+}
+
+impl CompiledRules {
+    fn new(rules: TxnFilterRules) -> Result<Self, String> {
+        Ok(Self {
+            deny_senders: rules
+                .deny_senders
+                .iter()
+                .map(|s| parse_address(s))
+                .collect::<Result<_, _>>()?,
+            deny_to: rules.deny_to.iter().map(|s| parse_address(s)).collect::<Result<_, _>>()?,
+            deny_selectors: rules
+                .deny_selectors
+                .iter()
+                .map(|s| parse_selector(s))
+                .collect::<Result<_, _>>()?,
+            rules,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rules == TxnFilterRules::default()
+    }
+
+    fn check(&self, sender: &[u8; 32], txn: &[u8]) -> Result<(), String> {
+        if let Some(max_txn_bytes) = self.rules.max_txn_bytes {
+            if txn.len() as u64 > max_txn_bytes {
+                return Err(format!("txn of {} bytes exceeds {} bytes", txn.len(), max_txn_bytes));
+            }
+        }
+        if self.deny_senders.contains(sender) {
+            return Err(format!("sender 0x{} is denied", hex::encode(sender)));
+        }
+        if self.deny_to.is_empty() && self.deny_selectors.is_empty() {
+            return Ok(());
+        }
+        let Some(call) = GLOBAL_TXN_CALL_DECODER.get().and_then(|decode| decode(txn)) else {
+            return Ok(());
+        };
+        if let Some(to) = call.to.filter(|to| self.deny_to.contains(to)) {
+            return Err(format!("callee 0x{} is denied", hex::encode(to)));
+        }
+        if let Some(selector) = call.selector.filter(|s| self.deny_selectors.contains(s)) {
+            return Err(format!("selector 0x{} is denied", hex::encode(selector)));
+        }
+        Ok(())
+    }
+}
+
+static TXN_FILTER_RULES: OnceLock<RwLock<Arc<CompiledRules>>> = OnceLock::new();
+
+fn active_rules() -> &'static RwLock<Arc<CompiledRules>> {
+    TXN_FILTER_RULES.get_or_init(Default::default)
+}
+
+/// Replaces the deny rules, takes effect for the next txn admitted to or pulled from mempool.
+pub fn set_txn_filter_rules(rules: TxnFilterRules) -> Result<(), String> {
+    let compiled = Arc::new(CompiledRules::new(rules)?);
+    *active_rules().write().unwrap() = compiled;
+    Ok(())
+}
+
+pub fn txn_filter_rules() -> TxnFilterRules {
+    active_rules().read().unwrap().rules.clone()
+}
+
+/// Checks `txn` against the deny rules and the filter of the execution layer.
+/// Applied when a txn enters mempool and again when mempool hands it out in `get_batch`, ordered
+/// blocks are executed as they are.
+pub fn check_txn(sender: &[u8; 32], txn: &[u8]) -> Result<(), String> {
+    let compiled = active_rules().read().unwrap().clone();
+    if !compiled.is_empty() {
+        compiled.check(sender, txn)?;
+    }
+    match GLOBAL_TXN_FILTER.get() {
+        Some(filter) => filter.check(sender, txn),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padded(address: [u8; 20]) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[12..].copy_from_slice(&address);
+        bytes
+    }
+
+    #[test]
+    fn test_rules() {
+        // the first 20 bytes of the txn are the callee, the next 4 the selector
+        GLOBAL_TXN_CALL_DECODER.get_or_init(|| {
+            Box::new(|txn: &[u8]| {
+                (txn.len() >= 24).then(|| TxnCall {
+                    to: Some(padded(txn[..20].try_into().unwrap())),
+                    selector: Some(txn[20..24].try_into().unwrap()),
+                })
+            })
+        });
+        let sender = padded([1; 20]);
+        let mut txn = vec![2u8; 20];
+        txn.extend([0xa9, 0x05, 0x9c, 0xbb]);
+        assert!(check_txn(&sender, &txn).is_ok());
+
+        let rules = TxnFilterRules {
+            deny_senders: vec![format!("0x{}", hex::encode([1u8; 20]))],
+            ..Default::default()
+        };
+        set_txn_filter_rules(rules.clone()).unwrap();
+        assert_eq!(txn_filter_rules(), rules);
+        assert!(check_txn(&sender, &txn).is_err());
+        assert!(check_txn(&padded([3; 20]), &txn).is_ok());
+
+        set_txn_filter_rules(TxnFilterRules {
+            deny_to: vec![hex::encode([2u8; 20])],
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(check_txn(&sender, &txn).is_err());
+        assert!(check_txn(&sender, &[2; 10]).is_ok());
+
+        set_txn_filter_rules(TxnFilterRules {
+            deny_selectors: vec!["0xa9059cbb".to_string()],
+            max_txn_bytes: Some(100),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(check_txn(&sender, &txn).is_err());
+        assert!(check_txn(&sender, &[0; 101]).is_err());
+        assert!(check_txn(&sender, &[0; 100]).is_ok());
+
+        assert!(set_txn_filter_rules(TxnFilterRules {
+            deny_selectors: vec!["0xa9059c".to_string()],
+            ..Default::default()
+        })
+        .is_err());
+        assert!(set_txn_filter_rules(TxnFilterRules {
+            deny_to: vec!["0x1234".to_string()],
+            ..Default::default()
+        })
+        .is_err());
+        set_txn_filter_rules(TxnFilterRules::default()).unwrap();
+        assert!(check_txn(&sender, &txn).is_ok());
+    }
+}
@@ -4,15 +4,20 @@ use std::{
     sync::Arc,
 };
 
-use crate::network::{build_network_interfaces, consensus_network_configuration, extract_network_ids, mempool_network_configuration};
+use crate::network::{
+    consensus_network_configuration, consensus_observer_network_configuration,
+    create_network_interfaces, create_network_runtime, extract_network_configs,
+    extract_network_ids, mempool_network_configuration, register_client_and_service_with_network,
+};
 use api_types::{u256_define::BlockId, ExecutionChannel};
 use block_buffer_manager::get_block_buffer_manager;
 use gaptos::aptos_config::{
-    config::{NetworkConfig, NodeConfig, Peer, PeerRole},
+    config::{NodeConfig, Peer, PeerRole},
     network_id::NetworkId,
 };
-use aptos_consensus::consensusdb::{BlockNumberSchema, BlockSchema, ConsensusDB};
+use aptos_consensus::consensusdb::{BlockSchema, ConsensusDB};
 use aptos_consensus::{
+    consensus_observer::{network_message::ConsensusObserverMessage, publisher::ConsensusPublisher},
     gravity_state_computer::ConsensusAdapterArgs, network_interface::ConsensusMsg,
     persistent_liveness_storage::StorageWriteProxy, quorum_store::quorum_store_db::QuorumStoreDB,
 };
@@ -20,6 +25,7 @@ use aptos_consensus::{
 use gaptos::aptos_consensus_notifications::ConsensusNotifier;
 use gaptos::aptos_crypto::{hash::GENESIS_BLOCK_ID, x25519, HashValue};
 use gaptos::aptos_event_notifications::EventSubscriptionService;
+use gaptos::aptos_logger::info;
 use aptos_mempool::{MempoolClientRequest, MempoolSyncMsg, QuorumStoreRequest};
 use gaptos::aptos_mempool_notifications::MempoolNotificationListener;
 use aptos_network::application::{
@@ -28,10 +34,9 @@ use aptos_network::application::{
 };
 use aptos_network_builder::builder::NetworkBuilder;
 use gaptos::aptos_storage_interface::DbReaderWriter;
-use gaptos::aptos_types::account_address::AccountAddress;
+use gaptos::aptos_types::{account_address::AccountAddress, chain_id::ChainId};
 use gaptos::aptos_validator_transaction_pool::VTxnPoolState;
 use futures::channel::mpsc::{Receiver, Sender};
-use serde::{Deserialize, Serialize};
 use tokio::runtime::Runtime;
 
 const RECENT_BLOCKS_RANGE: u64 = 256;
@@ -61,32 +66,98 @@ pub fn check_bootstrap_config(node_config_path: Option<PathBuf>) -> NodeConfig {
     })
 }
 
-pub fn init_network_interfaces<T, E>(
-    network_builder: &mut NetworkBuilder,
-    network_id: NetworkId,
-    network_config: &NetworkConfig,
+/// The application interfaces over all the networks of the node
+pub struct NetworkInterfaces {
+    /// Only built on the validator network
+    pub consensus: Option<ApplicationNetworkInterfaces<ConsensusMsg>>,
+    pub mempool: ApplicationNetworkInterfaces<MempoolSyncMsg>,
+    /// Only built when the consensus observer or publisher is enabled
+    pub consensus_observer: Option<ApplicationNetworkInterfaces<ConsensusObserverMessage>>,
+}
+
+/// Starts every configured network (validator, vfn and public) on its own runtime, and
+/// registers the protocols of the role of the network: consensus between validators only,
+/// mempool and the consensus observer on all networks.
+pub fn init_network_interfaces(
     node_config: &NodeConfig,
+    chain_id: ChainId,
+    event_subscription_service: &mut EventSubscriptionService,
     peers_and_metadata: Arc<PeersAndMetadata>,
-) -> (ApplicationNetworkInterfaces<T>, ApplicationNetworkInterfaces<E>)
-where
-    T: Serialize + for<'de> Deserialize<'de> + Send + Sync + Clone + 'static,
-    E: Serialize + for<'de> Deserialize<'de> + Send + Sync + Clone + 'static,
-{
-    let consensus_network_interfaces = build_network_interfaces::<T>(
-        network_builder,
-        network_id,
-        &network_config,
-        consensus_network_configuration(node_config),
-        peers_and_metadata.clone(),
-    );
-    let mempool_interfaces = build_network_interfaces::<E>(
-        network_builder,
-        network_id,
-        &network_config,
-        mempool_network_configuration(node_config),
-        peers_and_metadata.clone(), 
-    );
-    (consensus_network_interfaces, mempool_interfaces)
+) -> (Vec<Runtime>, NetworkInterfaces) {
+    let observer_or_publisher_enabled = node_config.consensus_observer.observer_enabled
+        || node_config.consensus_observer.publisher_enabled;
+    let mut runtimes = vec![];
+    let mut consensus_network_handle = None;
+    let mut mempool_network_handles = vec![];
+    let mut consensus_observer_network_handles = vec![];
+    for network_config in extract_network_configs(node_config) {
+        let network_id = network_config.network_id;
+        // Create a network runtime for the config
+        let runtime = create_network_runtime(&network_config);
+        // Entering gives us a runtime to instantiate all the pieces of the builder
+        let _enter = runtime.enter();
+        let mut network_builder = NetworkBuilder::create(
+            chain_id,
+            node_config.base.role,
+            &network_config,
+            gaptos::aptos_time_service::TimeService::real(),
+            Some(&mut *event_subscription_service),
+            peers_and_metadata.clone(),
+        );
+        if network_id.is_validator_network() {
+            consensus_network_handle = Some(register_client_and_service_with_network(
+                &mut network_builder,
+                network_id,
+                &network_config,
+                consensus_network_configuration(node_config),
+                true,
+            ));
+        }
+        mempool_network_handles.push(register_client_and_service_with_network(
+            &mut network_builder,
+            network_id,
+            &network_config,
+            mempool_network_configuration(node_config),
+            true,
+        ));
+        if observer_or_publisher_enabled {
+            consensus_observer_network_handles.push(register_client_and_service_with_network(
+                &mut network_builder,
+                network_id,
+                &network_config,
+                consensus_observer_network_configuration(node_config),
+                false,
+            ));
+        }
+        // Build and start the network on the runtime
+        network_builder.build(runtime.handle().clone());
+        network_builder.start();
+        info!("started network {}", network_id);
+        runtimes.push(runtime);
+    }
+
+    let network_interfaces = NetworkInterfaces {
+        consensus: consensus_network_handle.map(|handle| {
+            create_network_interfaces(
+                vec![handle],
+                consensus_network_configuration(node_config),
+                peers_and_metadata.clone(),
+            )
+        }),
+        mempool: create_network_interfaces(
+            mempool_network_handles,
+            mempool_network_configuration(node_config),
+            peers_and_metadata.clone(),
+        ),
+        consensus_observer: observer_or_publisher_enabled.then(|| {
+            create_network_interfaces(
+                consensus_observer_network_handles,
+                consensus_observer_network_configuration(node_config),
+                peers_and_metadata,
+            )
+        }),
+    };
+    (runtimes, network_interfaces)
 }
 
 /// Spawns a new thread for the node inspection service
@@ -104,6 +175,7 @@ pub fn start_consensus(
     consensus_notifier: ConsensusNotifier,
     consensus_to_mempool_sender: Sender<QuorumStoreRequest>,
     db: DbReaderWriter,
+    consensus_publisher: Option<Arc<ConsensusPublisher>>,
     arg: &mut ConsensusAdapterArgs,
 ) -> (Runtime, Arc<StorageWriteProxy>, Arc<QuorumStoreDB>) {
     let consensus_reconfig_subscription = event_subscription_service
@@ -120,11 +192,63 @@ pub fn start_consensus(
         db.clone(),
         consensus_reconfig_subscription,
         vtxn_pool,
-        None,
+        consensus_publisher,
         arg,
     )
 }
 
+/// Starts the consensus publisher if enabled, it streams the ordered blocks of this validator to
+/// the consensus observers of its fullnodes.
+pub fn start_consensus_publisher(
+    node_config: &NodeConfig,
+    consensus_observer_interfaces: Option<&ApplicationNetworkInterfaces<ConsensusObserverMessage>>,
+) -> (Option<Runtime>, Option<Arc<ConsensusPublisher>>) {
+    let Some(consensus_observer_interfaces) = consensus_observer_interfaces else {
+        return (None, None);
+    };
+    if !node_config.consensus_observer.publisher_enabled {
+        return (None, None);
+    }
+    let runtime = gaptos::aptos_runtimes::spawn_named_runtime("publisher".into(), None);
+    let (consensus_publisher, outbound_message_receiver) = ConsensusPublisher::new(
+        consensus_observer_interfaces.network_client.clone(),
+        node_config.consensus_observer,
+    );
+    runtime.spawn(consensus_publisher.clone().start(outbound_message_receiver));
+    (Some(runtime), Some(Arc::new(consensus_publisher)))
+}
+
+/// Starts the consensus observer runtime. It follows the chain if the observer is enabled, which
+/// nodes without a validator network need, and forwards subscription requests to the publisher.
+pub fn start_consensus_observer(
+    node_config: &NodeConfig,
+    event_subscription_service: &mut EventSubscriptionService,
+    consensus_observer_interfaces: ApplicationNetworkInterfaces<ConsensusObserverMessage>,
+    consensus_publisher: Option<Arc<ConsensusPublisher>>,
+    consensus_notifier: ConsensusNotifier,
+    consensus_to_mempool_sender: Sender<QuorumStoreRequest>,
+    db: DbReaderWriter,
+    execution_api: Arc<dyn ExecutionChannel>,
+) -> Runtime {
+    // A publisher only node doesn't follow the epochs
+    let observer_reconfig_subscription = node_config.consensus_observer.observer_enabled.then(|| {
+        event_subscription_service
+            .subscribe_to_reconfigurations()
+            .expect("Consensus observer must subscribe to reconfigurations")
+    });
+    aptos_consensus::consensus_provider::start_consensus_observer(
+        node_config,
+        consensus_observer_interfaces.network_client,
+        consensus_observer_interfaces.network_service_events,
+        consensus_publisher,
+        Arc::new(consensus_notifier),
+        consensus_to_mempool_sender,
+        db,
+        observer_reconfig_subscription,
+        Some(execution_api),
+    )
+}
+
 pub fn init_mempool(
     node_config: &NodeConfig,
     db: &DbReaderWriter,
@@ -157,13 +281,18 @@ pub fn init_peers_and_metadata(
     node_config: &NodeConfig,
     consensus_db: &Arc<ConsensusDB>,
 ) -> Arc<PeersAndMetadata> {
-    let listen_address = node_config.validator_network.as_ref().unwrap().listen_address.to_string();
+    let network_ids = extract_network_ids(node_config);
+    let peers_and_metadata = PeersAndMetadata::new(&network_ids);
+    // The peers of the vfn and public networks come from their seeds, only the validators are
+    // taken from the gravity node configs
+    let Some(validator_network) = node_config.validator_network.as_ref() else {
+        return peers_and_metadata;
+    };
+    let listen_address = validator_network.listen_address.to_string();
     let gravity_node_config = consensus_db
         .node_config_set
         .get(&listen_address)
         .expect(&format!("addr {:?} has no config", listen_address));
-    let network_ids = extract_network_ids(node_config);
-    let peers_and_metadata = PeersAndMetadata::new(&network_ids);
     let mut peer_set = HashMap::new();
     for trusted_peer in &gravity_node_config.trusted_peers_map {
         let trusted_peer_config = consensus_db
@@ -203,11 +332,10 @@ pub async fn init_block_buffer_manager(
     };
     
     let mut block_number_to_block_id = consensus_db
-            .get_all::<BlockNumberSchema>()
+            .get_block_ids_by_number_range(start_block_number, u64::MAX)
             .unwrap()
             .into_iter()
-            .filter(|(_, block_number)| block_number >= &start_block_number)
-            .map(|(block_id, block_number)| (block_number, BlockId::from_bytes(block_id.as_slice())))
+            .map(|(block_number, block_id)| (block_number, BlockId::from_bytes(block_id.as_slice())))
             .collect::<HashMap<u64, BlockId>>();
     if start_block_number == 0 {
         block_number_to_block_id.insert(0u64, BlockId::from_bytes(GENESIS_BLOCK_ID.as_slice()));
@@ -1,13 +1,24 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use crate::{
     bootstrap::{
-        init_block_buffer_manager, init_mempool, init_network_interfaces, init_peers_and_metadata, start_consensus, start_node_inspection_service
+        init_block_buffer_manager, init_mempool, init_network_interfaces, init_peers_and_metadata,
+        start_consensus, start_consensus_observer, start_consensus_publisher,
+        start_node_inspection_service,
     },
     consensus_mempool_handler::{ConsensusToMempoolHandler, MempoolNotificationHandler},
-    https::{https_server, HttpsServerArgs},
+    https::{
+        admin::{admin_server, AdminServerArgs},
+        health::{health_server, HealthServerArgs},
+        https_server,
+        txn_filter::load_txn_filter_from_env,
+        HttpsServerArgs,
+    },
     logger,
-    network::{create_network_runtime, extract_network_configs},
+    network::extract_network_configs,
 };
 use api_types::{
     compute_res::ComputeRes, u256_define::BlockId, ConsensusApi, ExecError, ExecutionLayer,
@@ -15,28 +26,33 @@ use api_types::{
 };
 use gaptos::aptos_build_info as aptos_build_info;
 use gaptos::aptos_build_info::build_information;
-use gaptos::aptos_config::{config::NodeConfig, network_id::NetworkId};
+use gaptos::aptos_config::config::NodeConfig;
 use aptos_consensus::consensusdb::ConsensusDB;
 use aptos_consensus::gravity_state_computer::ConsensusAdapterArgs;
 use gaptos::aptos_event_notifications::EventNotificationSender;
 use gaptos::aptos_logger::{info, warn};
-use aptos_network_builder::builder::NetworkBuilder;
 use gaptos::aptos_storage_interface::DbReaderWriter;
 use gaptos::aptos_telemetry::service::start_telemetry_service;
 use async_trait::async_trait;
 
 use gaptos::aptos_types::chain_id::ChainId;
 use futures::channel::mpsc;
+use block_buffer_manager::get_block_buffer_manager;
 use tokio::runtime::Runtime;
 
 #[cfg(unix)]
 #[global_allocator]
 static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
+/// How long each runtime gets to finish its tasks during shutdown.
+const RUNTIME_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub struct ConsensusEngine {
     address: String,
     execution_layer: ExecutionLayer,
-    runtimes: Vec<Runtime>,
+    consensus_db: Arc<ConsensusDB>,
+    // in start order, taken on shutdown
+    runtimes: Mutex<Vec<Runtime>>,
 }
 
 fn fail_point_check(node_config: &NodeConfig) {
@@ -74,8 +90,10 @@ impl ConsensusEngine {
         let consensus_db =
             Arc::new(ConsensusDB::new(node_config.storage.dir(), &node_config.node_config_path));
         let peers_and_metadata = init_peers_and_metadata(&node_config, &consensus_db);
+        let health_args = HealthServerArgs::from_env(&node_config, peers_and_metadata.clone());
         let (remote_log_receiver, logger_filter_update) =
             logger::create_logger(&node_config, Some(node_config.log_file_path.clone()));
+        load_txn_filter_from_env();
         let mut runtimes = vec![];
         if let Some(runtime) = start_telemetry_service(
             node_config.clone(),
@@ -91,30 +109,13 @@ impl ConsensusEngine {
             gaptos::aptos_event_notifications::EventSubscriptionService::new(Arc::new(
                 gaptos::aptos_infallible::RwLock::new(db.clone()),
             ));
-        let network_configs = extract_network_configs(&node_config);
-
-        let network_config = network_configs.get(0).unwrap();
-        // Create a network runtime for the config
-        let runtime = create_network_runtime(&network_config);
-        // Entering gives us a runtime to instantiate all the pieces of the builder
-        let _enter = runtime.enter();
-        let chain_id = ChainId::from(chain_id);
-        let mut network_builder = NetworkBuilder::create(
-            chain_id,
-            node_config.base.role,
-            &network_config,
-            gaptos::aptos_time_service::TimeService::real(),
-            Some(&mut event_subscription_service),
-            peers_and_metadata.clone(),
-        );
-        let network_id: NetworkId = network_config.network_id;
-        let (consensus_network_interfaces, mempool_interfaces) = init_network_interfaces(
-            &mut network_builder,
-            network_id,
-            &network_config,
+        let (network_runtimes, network_interfaces) = init_network_interfaces(
             &node_config,
+            ChainId::from(chain_id),
+            &mut event_subscription_service,
             peers_and_metadata.clone(),
         );
+        runtimes.extend(network_runtimes);
         let state_sync_config = node_config.state_sync;
         // The consensus_listener would listenes the request sent by ExecutionProxy's commit function
         // And then send NotifyCommit request to mempool which is named consensus_to_mempool_sender in Gravity
@@ -122,10 +123,6 @@ impl ConsensusEngine {
             gaptos::aptos_consensus_notifications::new_consensus_notifier_listener_pair(
                 state_sync_config.state_sync_driver.commit_notification_timeout_ms,
             );
-        // Build and start the network on the runtime
-        network_builder.build(runtime.handle().clone());
-        network_builder.start();
-        runtimes.push(runtime);
 
         // Start the node inspection service
         start_node_inspection_service(&node_config, peers_and_metadata.clone());
@@ -152,7 +149,7 @@ impl ConsensusEngine {
             &node_config,
             &db,
             &mut event_subscription_service,
-            mempool_interfaces,
+            network_interfaces.mempool,
             _mempool_client_receiver,
             consensus_to_mempool_receiver,
             mempool_listener,
@@ -161,17 +158,45 @@ impl ConsensusEngine {
         );
         runtimes.extend(mempool_runtime);
         init_block_buffer_manager(&consensus_db, latest_block_number).await;
-        let mut args = ConsensusAdapterArgs::new(consensus_db);
-        let (consensus_runtime, _, _) = start_consensus(
-            &node_config,
-            &mut event_subscription_service,
-            consensus_network_interfaces,
-            consensus_notifier,
-            consensus_to_mempool_sender,
-            db,
-            &mut args,
-        );
-        runtimes.push(consensus_runtime);
+        let (publisher_runtime, consensus_publisher) =
+            start_consensus_publisher(&node_config, network_interfaces.consensus_observer.as_ref());
+        runtimes.extend(publisher_runtime);
+        // The observer also routes the subscription requests of fullnodes to the publisher, so it
+        // runs on validators whenever the publisher is enabled
+        let observer_runtime = network_interfaces.consensus_observer.map(|interfaces| {
+            start_consensus_observer(
+                &node_config,
+                &mut event_subscription_service,
+                interfaces,
+                consensus_publisher.clone(),
+                consensus_notifier.clone(),
+                consensus_to_mempool_sender.clone(),
+                db.clone(),
+                execution_layer.execution_api.clone(),
+            )
+        });
+        runtimes.extend(observer_runtime);
+        match network_interfaces.consensus {
+            Some(consensus_network_interfaces) => {
+                let mut args = ConsensusAdapterArgs::new(consensus_db.clone());
+                args.set_execution_api(execution_layer.execution_api.clone());
+                let (consensus_runtime, _, _) = start_consensus(
+                    &node_config,
+                    &mut event_subscription_service,
+                    consensus_network_interfaces,
+                    consensus_notifier,
+                    consensus_to_mempool_sender,
+                    db,
+                    consensus_publisher,
+                    &mut args,
+                );
+                runtimes.push(consensus_runtime);
+            }
+            None => assert!(
+                node_config.consensus_observer.observer_enabled,
+                "a node without validator network needs consensus_observer.observer_enabled"
+            ),
+        }
         // trigger this to make epoch manager invoke new epoch
         let args = HttpsServerArgs {
             address: node_config.https_server_address,
@@ -191,17 +216,54 @@ impl ConsensusEngine {
         };
         let runtime = gaptos::aptos_runtimes::spawn_named_runtime("Http".into(), None);
         runtime.spawn(async move { https_server(args) });
+        if let Some(admin_args) = AdminServerArgs::from_env(consensus_db.clone()) {
+            runtime.spawn(admin_server(admin_args));
+        }
+        if let Some(health_args) = health_args {
+            runtime.spawn(health_server(health_args));
+        }
         runtimes.push(runtime);
         let arc_consensus_engine = Arc::new(Self {
-            address: node_config.validator_network.as_ref().unwrap().listen_address.to_string(),
+            // the validator network comes last, if any
+            address: extract_network_configs(&node_config)
+                .last()
+                .map(|network| network.listen_address.to_string())
+                .unwrap_or_default(),
             execution_layer: execution_layer.clone(),
-            runtimes,
+            consensus_db,
+            runtimes: Mutex::new(runtimes),
         });
         crate::coex::register_hook_func(arc_consensus_engine.clone());
         // process new round should be after init retƒh hash
         let _ = event_subscription_service.notify_initial_configs(1_u64);
         arc_consensus_engine
     }
+
+    /// Stops the node in order: the blocks ordered so far are executed and committed, then the
+    /// http servers, consensus, mempool and network are stopped and ConsensusDB is flushed.
+    /// Blocks ordered after the call are left to be recovered from ConsensusDB on restart.
+    /// Returns the latest committed block number, or an error if draining timed out, in which
+    /// case the node is stopped anyway.
+    pub async fn shutdown(&self, drain_timeout: Duration) -> anyhow::Result<u64> {
+        let stop_block_number = get_block_buffer_manager().begin_shutdown().await;
+        info!("shutting down, draining blocks up to {}", stop_block_number);
+        let drained = get_block_buffer_manager().wait_drained(drain_timeout).await;
+        if let Err(e) = &drained {
+            warn!("shutting down without draining: {}", e);
+        }
+
+        let runtimes = std::mem::take(&mut *self.runtimes.lock().unwrap());
+        // Runtimes can't be shut down from within an async context
+        tokio::task::spawn_blocking(move || {
+            for runtime in runtimes.into_iter().rev() {
+                runtime.shutdown_timeout(RUNTIME_SHUTDOWN_TIMEOUT);
+            }
+        })
+        .await?;
+        self.consensus_db.flush()?;
+        info!("consensus engine stopped");
+        drained
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,319 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, Write},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use api_types::txn_filter::TxnFilterRules;
+use aptos_consensus::consensusdb::ConsensusDB;
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Path as UrlPath, Query, State},
+    http::{header::AUTHORIZATION, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use gaptos::aptos_logger::{info, warn};
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs1KeyDer, PrivatePkcs8KeyDer},
+    server::WebPkiClientVerifier,
+    RootCertStore, ServerConfig,
+};
+
+use super::{
+    equivocation::{get_equivocations, EquivocationQuery},
+    execution_divergence::{delete_execution_divergences, get_execution_divergences},
+    heap_profiler::{control_profiler, ControlProfileRequest},
+    log_filter::{get_log_filter, reset_log_filter, set_log_filter, SetLogFilterRequest},
+    profiling::{CpuProfileRequest, HeapDumpRequest, ProfileStore},
+    set_failpoints::{set_failpoint, FailpointConf},
+    txn_filter::{get_txn_filter, set_txn_filter},
+};
+
+pub const ADMIN_ADDRESS_ENV: &str = "GRAVITY_ADMIN_ADDRESS";
+pub const ADMIN_TOKEN_ENV: &str = "GRAVITY_ADMIN_TOKEN";
+pub const ADMIN_TOKEN_FILE_ENV: &str = "GRAVITY_ADMIN_TOKEN_FILE";
+pub const ADMIN_CERT_PEM_ENV: &str = "GRAVITY_ADMIN_CERT_PEM";
+pub const ADMIN_KEY_PEM_ENV: &str = "GRAVITY_ADMIN_KEY_PEM";
+pub const ADMIN_CLIENT_CA_PEM_ENV: &str = "GRAVITY_ADMIN_CLIENT_CA_PEM";
+pub const ADMIN_AUDIT_LOG_ENV: &str = "GRAVITY_ADMIN_AUDIT_LOG";
+pub const ADMIN_PROFILE_DIR_ENV: &str = "GRAVITY_ADMIN_PROFILE_DIR";
+
+const DEFAULT_ADMIN_ADDRESS: &str = "127.0.0.1:1999";
+/// Request bodies above this size are rejected, admin requests are tiny.
+const MAX_ADMIN_BODY_BYTES: usize = 64 * 1024;
+/// Only this much of a request body is written to the audit log.
+const MAX_AUDITED_BODY_BYTES: usize = 1024;
+
+/// Clients must present a certificate signed by `client_ca_pem`.
+#[derive(Clone, Debug)]
+pub struct AdminTls {
+    pub cert_pem: PathBuf,
+    pub key_pem: PathBuf,
+    pub client_ca_pem: PathBuf,
+}
+
+pub struct AdminServerArgs {
+    pub address: String,
+    /// Requests must carry `Authorization: Bearer <token>`
+    pub token: Option<String>,
+    pub tls: Option<AdminTls>,
+    /// Every admin request is appended to this file, in addition to the node log.
+    pub audit_log: Option<PathBuf>,
+    /// Cpu profiles and heap dumps are written here, and served from here.
+    pub profile_dir: PathBuf,
+    /// Equivocation evidence and execution divergences are read from here
+    pub consensus_db: Arc<ConsensusDB>,
+}
+
+impl AdminServerArgs {
+    /// Reads the admin listener config from the `GRAVITY_ADMIN_*` env vars. Returns `None` if
+    /// neither a token nor mTLS is configured, since the admin API is never served without auth.
+    pub fn from_env(consensus_db: Arc<ConsensusDB>) -> Option<Self> {
+        let env_path = |name| std::env::var(name).ok().filter(|s| !s.is_empty()).map(PathBuf::from);
+        let token = match (std::env::var(ADMIN_TOKEN_ENV).ok(), env_path(ADMIN_TOKEN_FILE_ENV)) {
+            (Some(token), _) => Some(token),
+            (None, Some(path)) => Some(
+                std::fs::read_to_string(&path)
+                    .unwrap_or_else(|e| panic!("failed to read admin token {:?}: {}", path, e)),
+            ),
+            (None, None) => None,
+        }
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty());
+        let tls = match (
+            env_path(ADMIN_CERT_PEM_ENV),
+            env_path(ADMIN_KEY_PEM_ENV),
+            env_path(ADMIN_CLIENT_CA_PEM_ENV),
+        ) {
+            (Some(cert_pem), Some(key_pem), Some(client_ca_pem)) => {
+                Some(AdminTls { cert_pem, key_pem, client_ca_pem })
+            }
+            (None, None, None) => None,
+            _ => panic!(
+                "{}, {} and {} must be set together",
+                ADMIN_CERT_PEM_ENV, ADMIN_KEY_PEM_ENV, ADMIN_CLIENT_CA_PEM_ENV
+            ),
+        };
+        if token.is_none() && tls.is_none() {
+            warn!(
+                "admin api is disabled, set {} or {} to enable it",
+                ADMIN_TOKEN_ENV, ADMIN_CLIENT_CA_PEM_ENV
+            );
+            return None;
+        }
+        Some(Self {
+            address: std::env::var(ADMIN_ADDRESS_ENV)
+                .unwrap_or_else(|_| DEFAULT_ADMIN_ADDRESS.to_string()),
+            token,
+            tls,
+            audit_log: env_path(ADMIN_AUDIT_LOG_ENV),
+            profile_dir: env_path(ADMIN_PROFILE_DIR_ENV)
+                .unwrap_or_else(|| std::env::temp_dir().join("gravity_profiles")),
+            consensus_db,
+        })
+    }
+}
+
+struct AdminState {
+    token: Option<String>,
+    audit_log: Option<Mutex<File>>,
+}
+
+impl AdminState {
+    fn audit(&self, peer: SocketAddr, action: &str, body: &[u8], status: StatusCode) {
+        let body = String::from_utf8_lossy(&body[..body.len().min(MAX_AUDITED_BODY_BYTES)]);
+        let timestamp =
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let entry = format!(
+            "ts_ms={} peer={} action=\"{}\" body={:?} status={}",
+            timestamp,
+            peer,
+            action,
+            body,
+            status.as_u16()
+        );
+        info!("admin audit {}", entry);
+        if let Some(audit_log) = &self.audit_log {
+            let mut file = audit_log.lock().unwrap();
+            if let Err(e) = writeln!(file, "{}", entry).and_then(|_| file.flush()) {
+                warn!("failed to write admin audit log: {}", e);
+            }
+        }
+    }
+
+    fn authorized(&self, req: &Request<Body>) -> bool {
+        let Some(token) = &self.token else {
+            // mTLS already authenticated the client during the handshake
+            return true;
+        };
+        req.headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes()))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Authenticates every admin request and records it in the audit log, whether it was accepted
+/// or not.
+async fn authorize_and_audit(
+    State(state): State<Arc<AdminState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let action = format!("{} {}", req.method(), req.uri().path());
+    if !state.authorized(&req) {
+        state.audit(peer, &action, &[], StatusCode::UNAUTHORIZED);
+        return (StatusCode::UNAUTHORIZED, "unauthorized").into_response();
+    }
+    let (parts, body) = req.into_parts();
+    let body = match to_bytes(body, MAX_ADMIN_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(_) => {
+            state.audit(peer, &action, &[], StatusCode::PAYLOAD_TOO_LARGE);
+            return (StatusCode::PAYLOAD_TOO_LARGE, "request body too large").into_response();
+        }
+    };
+    let response = next.run(Request::from_parts(parts, Body::from(body.clone()))).await;
+    state.audit(peer, &action, &body, response.status());
+    response
+}
+
+fn load_certs(path: &Path) -> Vec<CertificateDer<'static>> {
+    let file = File::open(path).unwrap_or_else(|e| panic!("failed to open {:?}: {}", path, e));
+    rustls_pemfile::certs(&mut BufReader::new(file))
+        .unwrap_or_else(|e| panic!("failed to parse certs {:?}: {}", path, e))
+        .into_iter()
+        .map(CertificateDer::from)
+        .collect()
+}
+
+fn load_private_key(path: &Path) -> PrivateKeyDer<'static> {
+    let read = || {
+        BufReader::new(
+            File::open(path).unwrap_or_else(|e| panic!("failed to open {:?}: {}", path, e)),
+        )
+    };
+    if let Some(key) = rustls_pemfile::pkcs8_private_keys(&mut read())
+        .ok()
+        .and_then(|keys| keys.into_iter().next())
+    {
+        return PrivatePkcs8KeyDer::from(key).into();
+    }
+    match rustls_pemfile::rsa_private_keys(&mut read())
+        .ok()
+        .and_then(|keys| keys.into_iter().next())
+    {
+        Some(key) => PrivatePkcs1KeyDer::from(key).into(),
+        None => panic!("no private key found in {:?}", path),
+    }
+}
+
+fn mutual_tls_config(tls: &AdminTls) -> RustlsConfig {
+    let mut client_roots = RootCertStore::empty();
+    for cert in load_certs(&tls.client_ca_pem) {
+        client_roots.add(cert).unwrap_or_else(|e| panic!("invalid client ca cert: {}", e));
+    }
+    let verifier = WebPkiClientVerifier::builder(Arc::new(client_roots))
+        .build()
+        .unwrap_or_else(|e| panic!("failed to build admin client verifier: {}", e));
+    let mut config = ServerConfig::builder()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(load_certs(&tls.cert_pem), load_private_key(&tls.key_pem))
+        .unwrap_or_else(|e| panic!("admin cert {:?} doesn't work: {}", tls.cert_pem, e));
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    RustlsConfig::from_config(Arc::new(config))
+}
+
+/// Serves the debug and maintenance endpoints. Unlike the public tx api it is meant to be bound
+/// to localhost or a management network, and every request must authenticate.
+pub async fn admin_server(args: AdminServerArgs) {
+    // The public server may have installed it already
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    let audit_log = args.audit_log.as_ref().map(|path| {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap_or_else(|e| panic!("failed to open admin audit log {:?}: {}", path, e));
+        Mutex::new(file)
+    });
+    let state = Arc::new(AdminState { token: args.token, audit_log });
+
+    let set_fail_point_lambda =
+        |Json(request): Json<FailpointConf>| async move { set_failpoint(request).await };
+    let control_profiler_lambda =
+        |Json(request): Json<ControlProfileRequest>| async move { control_profiler(request).await };
+    let set_log_filter_lambda =
+        |Json(request): Json<SetLogFilterRequest>| async move { set_log_filter(request).await };
+    let set_txn_filter_lambda =
+        |Json(rules): Json<TxnFilterRules>| async move { set_txn_filter(rules).await };
+    let profiles = Arc::new(ProfileStore::new(args.profile_dir.clone()));
+    let profiles_clone = profiles.clone();
+    let cpu_profile_lambda = |Json(request): Json<CpuProfileRequest>| async move {
+        profiles_clone.cpu_profile(request).await
+    };
+    let profiles_clone = profiles.clone();
+    let heap_dump_lambda = |Json(request): Json<HeapDumpRequest>| async move {
+        profiles_clone.heap_dump(request).await
+    };
+    let profiles_clone = profiles.clone();
+    let list_profiles_lambda = || async move { profiles_clone.list().await };
+    let download_profile_lambda =
+        |UrlPath(name): UrlPath<String>| async move { profiles.download(&name).await };
+    let consensus_db = args.consensus_db.clone();
+    let get_equivocations_lambda = |query: Query<EquivocationQuery>| async move {
+        get_equivocations(consensus_db, query).await
+    };
+    let consensus_db = args.consensus_db.clone();
+    let get_execution_divergences_lambda =
+        || async move { get_execution_divergences(consensus_db).await };
+    let consensus_db = args.consensus_db.clone();
+    let delete_execution_divergences_lambda =
+        || async move { delete_execution_divergences(consensus_db).await };
+
+    let app = Router::new()
+        .route("/set_failpoint", post(set_fail_point_lambda))
+        .route("/mem_prof", post(control_profiler_lambda))
+        .route("/log_filter", get(get_log_filter).post(set_log_filter_lambda))
+        .route("/log_filter/reset", post(reset_log_filter))
+        .route("/txn_filter", get(get_txn_filter).post(set_txn_filter_lambda))
+        .route("/cpu_prof", post(cpu_profile_lambda))
+        .route("/heap_dump", post(heap_dump_lambda))
+        .route("/profiles", get(list_profiles_lambda))
+        .route("/profiles/:name", get(download_profile_lambda))
+        .route("/equivocations", get(get_equivocations_lambda))
+        .route(
+            "/execution_divergences",
+            get(get_execution_divergences_lambda).delete(delete_execution_divergences_lambda),
+        )
+        .layer(middleware::from_fn_with_state(state, authorize_and_audit));
+    let addr: SocketAddr = args.address.parse().unwrap();
+    if !addr.ip().is_loopback() {
+        warn!("admin server listens on non loopback address {}", addr);
+    }
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    match &args.tls {
+        Some(tls) => {
+            info!("admin server listen address {} with mutual tls", addr);
+            axum_server::bind_rustls(addr, mutual_tls_config(tls)).serve(service).await
+        }
+        None => {
+            info!("admin server listen address {}", addr);
+            axum_server::bind(addr).serve(service).await
+        }
+    }
+    .unwrap_or_else(|e| panic!("failed to bind admin server due to {:?}", e));
+}
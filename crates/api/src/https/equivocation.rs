@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use api_types::equivocation::{EquivocationKind, EquivocationPayload, SignedConsensusMessage};
+use aptos_consensus::consensusdb::ConsensusDB;
+use axum::{extract::Query, http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize)]
+pub struct EquivocationQuery {
+    /// Only return the evidence of this epoch and later
+    #[serde(default)]
+    epoch: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct SignedMessage {
+    pub message: String,
+    pub signature: String,
+}
+
+/// `EquivocationPayload` with hex encoded bytes
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Equivocation {
+    pub kind: EquivocationKind,
+    pub epoch: u64,
+    pub round: u64,
+    pub author: String,
+    pub first: SignedMessage,
+    pub second: SignedMessage,
+    pub evidence: String,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+impl From<&SignedConsensusMessage> for SignedMessage {
+    fn from(signed: &SignedConsensusMessage) -> Self {
+        Self { message: to_hex(&signed.message), signature: to_hex(&signed.signature) }
+    }
+}
+
+impl From<EquivocationPayload> for Equivocation {
+    fn from(payload: EquivocationPayload) -> Self {
+        Self {
+            kind: payload.kind,
+            epoch: payload.epoch,
+            round: payload.round,
+            author: to_hex(&payload.author),
+            first: (&payload.first).into(),
+            second: (&payload.second).into(),
+            evidence: to_hex(&payload.evidence),
+        }
+    }
+}
+
+// example:
+// curl -H "Authorization: Bearer $TOKEN" http://127.0.0.1:1999/equivocations?epoch=2
+pub async fn get_equivocations(
+    consensus_db: Arc<ConsensusDB>,
+    Query(query): Query<EquivocationQuery>,
+) -> impl IntoResponse {
+    let evidence = match consensus_db.get_equivocation_evidence(query.epoch) {
+        Ok(evidence) => evidence,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    match evidence
+        .iter()
+        .map(|e| e.to_payload().map(Equivocation::from))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(equivocations) => Json(equivocations).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use api_types::{compute_res::TxnStatus, divergence::ExecutionDivergence};
+use aptos_consensus::consensusdb::ConsensusDB;
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct DivergedTxn {
+    pub txn_hash: String,
+    pub nonce: u64,
+    pub sender: String,
+    pub is_discarded: bool,
+}
+
+/// `ExecutionDivergence` with hex encoded hashes
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Divergence {
+    pub epoch: u64,
+    pub round: u64,
+    pub block_id: String,
+    pub block_number: u64,
+    pub local_hash: String,
+    pub certified_hash: String,
+    pub txn_num: u64,
+    pub txns: Vec<DivergedTxn>,
+    pub detected_at_usecs: u64,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+impl From<&TxnStatus> for DivergedTxn {
+    fn from(status: &TxnStatus) -> Self {
+        Self {
+            txn_hash: to_hex(&status.txn_hash),
+            nonce: status.nonce,
+            sender: to_hex(&status.sender),
+            is_discarded: status.is_discarded,
+        }
+    }
+}
+
+impl From<ExecutionDivergence> for Divergence {
+    fn from(divergence: ExecutionDivergence) -> Self {
+        Self {
+            epoch: divergence.epoch,
+            round: divergence.round,
+            block_id: to_hex(&divergence.block_id),
+            block_number: divergence.block_number,
+            local_hash: to_hex(&divergence.local_hash),
+            certified_hash: to_hex(&divergence.certified_hash),
+            txn_num: divergence.txn_num,
+            txns: divergence.txn_status.iter().map(DivergedTxn::from).collect(),
+            detected_at_usecs: divergence.detected_at_usecs,
+        }
+    }
+}
+
+// example:
+// curl -H "Authorization: Bearer $TOKEN" http://127.0.0.1:1999/execution_divergences
+pub async fn get_execution_divergences(consensus_db: Arc<ConsensusDB>) -> impl IntoResponse {
+    match consensus_db.get_execution_divergences() {
+        Ok(divergences) => {
+            Json(divergences.into_iter().map(Divergence::from).collect::<Vec<_>>()).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// The node keeps halting until it is restarted, so that the operator fixes the execution layer
+/// before it votes again.
+// example:
+// curl -X DELETE -H "Authorization: Bearer $TOKEN" http://127.0.0.1:1999/execution_divergences
+pub async fn delete_execution_divergences(consensus_db: Arc<ConsensusDB>) -> impl IntoResponse {
+    match consensus_db.delete_execution_divergences() {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
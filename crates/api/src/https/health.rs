@@ -0,0 +1,319 @@
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use aptos_network::application::storage::PeersAndMetadata;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use block_buffer_manager::get_block_buffer_manager;
+use gaptos::aptos_config::{config::NodeConfig, network_id::NetworkId};
+use gaptos::aptos_logger::{info, warn};
+use gaptos::aptos_types::PeerId;
+use serde::{Deserialize, Serialize};
+
+pub const HEALTH_ADDRESS_ENV: &str = "GRAVITY_HEALTH_ADDRESS";
+pub const HEALTH_MAX_COMMIT_AGE_SECS_ENV: &str = "GRAVITY_HEALTH_MAX_COMMIT_AGE_SECS";
+pub const HEALTH_MAX_BLOCK_GAP_ENV: &str = "GRAVITY_HEALTH_MAX_BLOCK_GAP";
+pub const HEALTH_MIN_VALIDATOR_PEERS_ENV: &str = "GRAVITY_HEALTH_MIN_VALIDATOR_PEERS";
+pub const HEALTH_EXECUTION_DEADLINE_SECS_ENV: &str = "GRAVITY_HEALTH_EXECUTION_DEADLINE_SECS";
+pub const HEALTH_LIVE_MAX_COMMIT_AGE_SECS_ENV: &str = "GRAVITY_HEALTH_LIVE_MAX_COMMIT_AGE_SECS";
+
+const DEFAULT_MAX_COMMIT_AGE_SECS: u64 = 30;
+const DEFAULT_MAX_BLOCK_GAP: u64 = 100;
+const DEFAULT_EXECUTION_DEADLINE_SECS: u64 = 10;
+
+#[derive(Clone, Debug)]
+pub struct HealthThresholds {
+    /// Not ready if nothing was committed for this long
+    pub max_commit_age: Duration,
+    /// Not ready if more blocks than this are ordered but not committed
+    pub max_block_gap: u64,
+    /// Not ready with fewer connected validator peers. Defaults to the peers needed for a
+    /// quorum, assuming equal voting power.
+    pub min_validator_peers: Option<usize>,
+    /// Not ready if an ordered block waits longer than this for the execution layer
+    pub execution_deadline: Duration,
+    /// Not live if nothing was committed for this long, never checked if unset
+    pub live_max_commit_age: Option<Duration>,
+}
+
+impl HealthThresholds {
+    pub fn from_env() -> Self {
+        let env_u64 = |name| {
+            std::env::var(name).ok().map(|value: String| {
+                value.parse::<u64>().unwrap_or_else(|e| panic!("invalid {} {}: {}", name, value, e))
+            })
+        };
+        Self {
+            max_commit_age: Duration::from_secs(
+                env_u64(HEALTH_MAX_COMMIT_AGE_SECS_ENV).unwrap_or(DEFAULT_MAX_COMMIT_AGE_SECS),
+            ),
+            max_block_gap: env_u64(HEALTH_MAX_BLOCK_GAP_ENV).unwrap_or(DEFAULT_MAX_BLOCK_GAP),
+            min_validator_peers: env_u64(HEALTH_MIN_VALIDATOR_PEERS_ENV).map(|n| n as usize),
+            execution_deadline: Duration::from_secs(
+                env_u64(HEALTH_EXECUTION_DEADLINE_SECS_ENV)
+                    .unwrap_or(DEFAULT_EXECUTION_DEADLINE_SECS),
+            ),
+            live_max_commit_age: env_u64(HEALTH_LIVE_MAX_COMMIT_AGE_SECS_ENV)
+                .map(Duration::from_secs),
+        }
+    }
+}
+
+pub struct HealthServerArgs {
+    pub address: String,
+    pub thresholds: HealthThresholds,
+    pub peers_and_metadata: Arc<PeersAndMetadata>,
+    pub peer_id: Option<PeerId>,
+}
+
+impl HealthServerArgs {
+    /// Returns `None` if `GRAVITY_HEALTH_ADDRESS` isn't set.
+    pub fn from_env(
+        node_config: &NodeConfig,
+        peers_and_metadata: Arc<PeersAndMetadata>,
+    ) -> Option<Self> {
+        let address = std::env::var(HEALTH_ADDRESS_ENV).ok().filter(|s| !s.is_empty())?;
+        Some(Self {
+            address,
+            thresholds: HealthThresholds::from_env(),
+            peers_and_metadata,
+            peer_id: node_config.validator_network.as_ref().map(|network| network.peer_id()),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub failed_checks: Vec<String>,
+    pub buffer_ready: bool,
+    pub shutting_down: bool,
+    pub last_committed_round: u64,
+    pub latest_commit_block_number: u64,
+    pub secs_since_last_commit: u64,
+    pub highest_ordered_block_number: u64,
+    /// Blocks ordered but not committed yet
+    pub ordered_commit_gap: u64,
+    pub connected_validator_peers: usize,
+    pub validator_peers: usize,
+    pub min_validator_peers: usize,
+    /// How long the oldest block not executed yet waits for the execution layer
+    pub secs_waiting_for_execution: Option<u64>,
+}
+
+impl HealthReport {
+    fn check_live(&mut self, thresholds: &HealthThresholds) {
+        if let Some(max_commit_age) = thresholds.live_max_commit_age {
+            if self.buffer_ready && self.secs_since_last_commit > max_commit_age.as_secs() {
+                self.failed_checks.push(format!(
+                    "no commit for {}s, more than {}s",
+                    self.secs_since_last_commit,
+                    max_commit_age.as_secs()
+                ));
+            }
+        }
+        self.healthy = self.failed_checks.is_empty();
+    }
+
+    fn check_ready(&mut self, thresholds: &HealthThresholds) {
+        if !self.buffer_ready {
+            self.failed_checks.push("block buffer is not ready".to_string());
+        }
+        if self.shutting_down {
+            self.failed_checks.push("node is shutting down".to_string());
+        }
+        if self.secs_since_last_commit > thresholds.max_commit_age.as_secs() {
+            self.failed_checks.push(format!(
+                "no commit for {}s, more than {}s",
+                self.secs_since_last_commit,
+                thresholds.max_commit_age.as_secs()
+            ));
+        }
+        if self.ordered_commit_gap > thresholds.max_block_gap {
+            self.failed_checks.push(format!(
+                "{} blocks ordered but not committed, more than {}",
+                self.ordered_commit_gap, thresholds.max_block_gap
+            ));
+        }
+        if self.connected_validator_peers < self.min_validator_peers {
+            self.failed_checks.push(format!(
+                "{} of {} validator peers connected, less than {}",
+                self.connected_validator_peers, self.validator_peers, self.min_validator_peers
+            ));
+        }
+        if let Some(waiting) = self.secs_waiting_for_execution {
+            if waiting > thresholds.execution_deadline.as_secs() {
+                self.failed_checks.push(format!(
+                    "execution layer hasn't answered for {}s, more than {}s",
+                    waiting,
+                    thresholds.execution_deadline.as_secs()
+                ));
+            }
+        }
+        self.healthy = self.failed_checks.is_empty();
+    }
+}
+
+/// The validator peers needed besides this node for a quorum, assuming equal voting power.
+fn quorum_peers(validator_peers: usize) -> usize {
+    // a quorum is 2n/3 + 1 validators, including this one
+    (validator_peers + 1) * 2 / 3
+}
+
+fn secs_since(time: SystemTime) -> u64 {
+    SystemTime::now().duration_since(time).unwrap_or_default().as_secs()
+}
+
+struct HealthState {
+    thresholds: HealthThresholds,
+    peers_and_metadata: Arc<PeersAndMetadata>,
+    peer_id: Option<PeerId>,
+}
+
+impl HealthState {
+    async fn report(&self) -> HealthReport {
+        let buffer = get_block_buffer_manager().health().await;
+        let validator_peers = self
+            .peers_and_metadata
+            .get_trusted_peers(&NetworkId::Validator)
+            .map(|peers| peers.keys().filter(|peer| Some(**peer) != self.peer_id).count())
+            .unwrap_or(0);
+        let connected_validator_peers = self
+            .peers_and_metadata
+            .get_connected_peers_and_metadata()
+            .map(|peers| {
+                peers.keys().filter(|peer| peer.network_id() == NetworkId::Validator).count()
+            })
+            .unwrap_or(0);
+        HealthReport {
+            healthy: true,
+            failed_checks: Vec::new(),
+            buffer_ready: buffer.ready,
+            shutting_down: buffer.stopping,
+            last_committed_round: aptos_consensus::counters::LAST_COMMITTED_ROUND.get() as u64,
+            latest_commit_block_number: buffer.latest_commit_block_number,
+            secs_since_last_commit: secs_since(buffer.latest_commit_time),
+            highest_ordered_block_number: buffer.highest_ordered_block_number,
+            ordered_commit_gap: buffer
+                .highest_ordered_block_number
+                .saturating_sub(buffer.latest_commit_block_number),
+            connected_validator_peers,
+            validator_peers,
+            min_validator_peers: self
+                .thresholds
+                .min_validator_peers
+                .unwrap_or_else(|| quorum_peers(validator_peers)),
+            secs_waiting_for_execution: buffer.oldest_unexecuted_block_time.map(secs_since),
+        }
+    }
+}
+
+fn respond(report: HealthReport) -> (StatusCode, Json<HealthReport>) {
+    let status = if report.healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+async fn live(State(state): State<Arc<HealthState>>) -> (StatusCode, Json<HealthReport>) {
+    let mut report = state.report().await;
+    report.check_live(&state.thresholds);
+    respond(report)
+}
+
+async fn ready(State(state): State<Arc<HealthState>>) -> (StatusCode, Json<HealthReport>) {
+    let mut report = state.report().await;
+    report.check_ready(&state.thresholds);
+    if !report.healthy {
+        warn!("not ready: {}", report.failed_checks.join(", "));
+    }
+    respond(report)
+}
+
+/// Serves `/health/live` and `/health/ready` for orchestrator probes over plain http, they
+/// answer 200 or 503 with a `HealthReport`.
+pub async fn health_server(args: HealthServerArgs) {
+    let state = Arc::new(HealthState {
+        thresholds: args.thresholds,
+        peers_and_metadata: args.peers_and_metadata,
+        peer_id: args.peer_id,
+    });
+    let app = Router::new()
+        .route("/health/live", get(live))
+        .route("/health/ready", get(ready))
+        .with_state(state);
+    let addr: SocketAddr = args.address.parse().unwrap();
+    info!("health server listen address {}", addr);
+    axum_server::bind(addr)
+        .serve(app.into_make_service())
+        .await
+        .unwrap_or_else(|e| panic!("failed to bind health server due to {:?}", e));
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{quorum_peers, HealthReport, HealthThresholds};
+
+    fn report() -> HealthReport {
+        HealthReport {
+            healthy: true,
+            failed_checks: Vec::new(),
+            buffer_ready: true,
+            shutting_down: false,
+            last_committed_round: 10,
+            latest_commit_block_number: 8,
+            secs_since_last_commit: 1,
+            highest_ordered_block_number: 10,
+            ordered_commit_gap: 2,
+            connected_validator_peers: 2,
+            validator_peers: 3,
+            min_validator_peers: quorum_peers(3),
+            secs_waiting_for_execution: Some(0),
+        }
+    }
+
+    fn thresholds() -> HealthThresholds {
+        HealthThresholds {
+            max_commit_age: Duration::from_secs(30),
+            max_block_gap: 100,
+            min_validator_peers: None,
+            execution_deadline: Duration::from_secs(10),
+            live_max_commit_age: None,
+        }
+    }
+
+    #[test]
+    fn test_quorum_peers() {
+        assert_eq!(quorum_peers(0), 0);
+        assert_eq!(quorum_peers(3), 2);
+        assert_eq!(quorum_peers(6), 4);
+    }
+
+    #[test]
+    fn test_checks() {
+        let mut healthy = report();
+        healthy.check_ready(&thresholds());
+        assert!(healthy.healthy, "{:?}", healthy.failed_checks);
+
+        let mut stuck = report();
+        stuck.secs_since_last_commit = 600;
+        stuck.connected_validator_peers = 1;
+        stuck.secs_waiting_for_execution = Some(11);
+        let mut live = stuck.clone();
+        live.check_live(&thresholds());
+        assert!(live.healthy);
+        stuck.check_ready(&thresholds());
+        assert!(!stuck.healthy);
+        assert_eq!(stuck.failed_checks.len(), 3);
+
+        let mut not_live = report();
+        not_live.secs_since_last_commit = 600;
+        not_live.check_live(&HealthThresholds {
+            live_max_commit_age: Some(Duration::from_secs(300)),
+            ..thresholds()
+        });
+        assert!(!not_live.healthy);
+    }
+}
@@ -15,6 +15,7 @@ pub struct HeapProfiler {
 
 const PROF_ACTIVE: &[u8] = b"prof.active\0";
 const PROF_THREAD_ACTIVE_INIT: &[u8] = b"prof.thread_active_init\0";
+const PROF_DUMP: &[u8] = b"prof.dump\0";
 
 pub static PROFILER: Lazy<HeapProfiler> = Lazy::new(|| HeapProfiler::new());
 
@@ -67,4 +68,21 @@ impl HeapProfiler {
         }
         Ok(())
     }
+
+    /// Writes the current heap profile to `path`. Profiling must be active.
+    pub fn dump(&self, path: &std::path::Path) -> Result<(), String> {
+        let _guard = self.mutex.lock().unwrap();
+        let path_str = path.to_str().ok_or_else(|| format!("invalid dump path {:?}", path))?;
+        let path_cstr = std::ffi::CString::new(path_str)
+            .map_err(|e| format!("invalid dump path {:?}: {}", path, e))?;
+        if let Err(err) =
+            unsafe { raw::write(PROF_DUMP, path_cstr.as_ptr() as *const std::os::raw::c_char) }
+        {
+            let err = format!("jemalloc heap profiling dump failed: {}", err);
+            warn!("{}", err);
+            return Err(err);
+        }
+        info!("jemalloc heap profile dumped to {:?}", path);
+        Ok(())
+    }
 }
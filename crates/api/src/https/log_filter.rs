@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::log_filter::LOG_FILTER_CONTROLLER;
+
+#[derive(Deserialize, Serialize)]
+pub struct SetLogFilterRequest {
+    /// `level` or `module=level` directives, e.g. `info,aptos_consensus=trace`
+    pub filter: String,
+    /// Reverts to the default filter after this many seconds
+    pub revert_after_secs: Option<u64>,
+}
+
+fn logger_not_initialized() -> axum::response::Response {
+    (StatusCode::SERVICE_UNAVAILABLE, "logger is not initialized").into_response()
+}
+
+pub async fn get_log_filter() -> impl IntoResponse {
+    match LOG_FILTER_CONTROLLER.get() {
+        Some(controller) => Json(controller.status()).into_response(),
+        None => logger_not_initialized(),
+    }
+}
+
+pub async fn set_log_filter(request: SetLogFilterRequest) -> impl IntoResponse {
+    let Some(controller) = LOG_FILTER_CONTROLLER.get() else {
+        return logger_not_initialized();
+    };
+    let revert_after = request.revert_after_secs.map(Duration::from_secs);
+    match controller.set(&request.filter, revert_after) {
+        Ok(status) => Json(status).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+pub async fn reset_log_filter() -> impl IntoResponse {
+    match LOG_FILTER_CONTROLLER.get() {
+        Some(controller) => Json(controller.reset()).into_response(),
+        None => logger_not_initialized(),
+    }
+}
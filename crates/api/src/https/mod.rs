@@ -1,24 +1,31 @@
+pub mod admin;
+mod equivocation;
+mod execution_divergence;
+pub mod health;
 pub mod heap_profiler;
+mod log_filter;
+pub mod profiling;
 mod set_failpoints;
+mod stream;
 mod tx;
+pub mod txn_filter;
 use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 
 use api_types::ExecutionChannel;
 use gaptos::aptos_crypto::HashValue;
 use gaptos::aptos_logger::info;
 use axum::{
-    body::Body,
-    extract::Path,
-    http::Request,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path},
+    http::{HeaderMap, Request},
     middleware::{self, Next},
     response::Response,
     routing::{get, post},
     Json, Router,
 };
 use axum_server::tls_rustls::RustlsConfig;
-use heap_profiler::control_profiler;
-use set_failpoints::{set_failpoint, FailpointConf};
-use tx::{get_tx_by_hash, submit_tx, TxRequest};
+use stream::stream_committed_blocks;
+use tx::{get_tx_by_hash, submit_tx, submit_txs, TxRequest, MAX_BATCH_BODY_BYTES};
 
 pub struct HttpsServerArgs {
     pub address: String,
@@ -35,32 +42,33 @@ async fn ensure_https(req: Request<Body>, next: Next) -> Response {
 }
 
 pub async fn https_server(args: HttpsServerArgs) {
-    rustls::crypto::ring::default_provider().install_default().unwrap();
+    // The admin server may have installed it already
+    let _ = rustls::crypto::ring::default_provider().install_default();
     let execution_api_clone = args.execution_api.clone();
     let submit_tx_lambda = |Json(request): Json<TxRequest>| async move {
         submit_tx(request, execution_api_clone).await
     };
 
     let execution_api_clone = args.execution_api.clone();
-    let get_tx_by_hash_lambda = |Path(request): Path<HashValue>| async move {
-        get_tx_by_hash(request, execution_api_clone).await
+    let submit_txs_lambda = |headers: HeaderMap, body: Bytes| async move {
+        submit_txs(headers, body, execution_api_clone).await
     };
 
-    let set_fail_point_lambda =
-        |Json(request): Json<FailpointConf>| async move { set_failpoint(request).await };
-
-    let control_profiler_lambda = |Json(request): Json<heap_profiler::ControlProfileRequest>| async move {
-        control_profiler(request).await
+    let execution_api_clone = args.execution_api.clone();
+    let get_tx_by_hash_lambda = |Path(request): Path<HashValue>| async move {
+        get_tx_by_hash(request, execution_api_clone).await
     };
 
-    let https_app = Router::new()
+    // The debug and maintenance endpoints are served by `admin::admin_server`
+    let app = Router::new()
         .route("/tx/submit_tx", post(submit_tx_lambda))
+        .route(
+            "/tx/submit_txs",
+            post(submit_txs_lambda).layer(DefaultBodyLimit::max(MAX_BATCH_BODY_BYTES)),
+        )
         .route("/tx/get_tx_by_hash/:hash_value", get(get_tx_by_hash_lambda))
+        .route("/stream/committed_blocks", get(stream_committed_blocks))
         .layer(middleware::from_fn(ensure_https));
-    let http_app = Router::new()
-        .route("/set_failpoint", post(set_fail_point_lambda))
-        .route("/mem_prof", post(control_profiler_lambda));
-    let app = Router::new().merge(https_app).merge(http_app);
     let addr: SocketAddr = args.address.parse().unwrap();
     match (args.cert_pem.clone(), args.key_pem.clone()) {
         (Some(cert_path), Some(key_path)) => {
@@ -92,14 +100,25 @@ pub async fn https_server(args: HttpsServerArgs) {
 #[cfg(test)]
 mod test {
     use api_types::mock_execution_layer::MockExecutionApi;
+    use aptos_consensus::consensusdb::ConsensusDB;
     use fail::fail_point;
+    use gaptos::aptos_temppath::TempPath;
     use rcgen::generate_simple_self_signed;
-    use reqwest::ClientBuilder;
+    use reqwest::{Client, ClientBuilder};
     use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
 
-    use crate::https::tx::TxResponse;
+    use crate::https::tx::{BatchTxRequest, BatchTxResponse, TxResponse};
 
-    use super::{https_server, HttpsServerArgs};
+    use super::{
+        admin::{admin_server, AdminServerArgs},
+        equivocation::Equivocation,
+        execution_divergence::Divergence,
+        https_server,
+        profiling::{CpuProfileFormat, CpuProfileRequest, ProfileArtifact},
+        HttpsServerArgs,
+    };
+
+    const ADMIN_TOKEN: &str = "admin_token";
 
     fn test_fail_point() -> Option<()> {
         fail_point!("unit_test_fail_point", |_| {
@@ -109,47 +128,62 @@ mod test {
         None
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
-    async fn work() {
+    /// Starts the public server with a self signed certificate written to `dir`, returns a client
+    /// trusting it.
+    async fn start_https_server(dir: &TempPath, port: u16) -> Client {
         let subject_alt_names = vec!["127.0.0.1".to_string()];
         let cert = generate_simple_self_signed(subject_alt_names).unwrap();
-
-        let cert_pem = cert.serialize_pem().unwrap();
-        let key_pem = cert.serialize_private_key_pem();
-        let dir = env!("CARGO_MANIFEST_DIR").to_owned();
-        fs::create_dir(dir.clone() + "/src/https/test").unwrap();
-        fs::write(dir.clone() + "/src/https/test/cert.pem", cert_pem).unwrap();
-        fs::write(dir.clone() + "/src/https/test/key.pem", key_pem).unwrap();
+        let cert_path = dir.path().join("cert.pem");
+        let key_path = dir.path().join("key.pem");
+        fs::write(&cert_path, cert.serialize_pem().unwrap()).unwrap();
+        fs::write(&key_path, cert.serialize_private_key_pem()).unwrap();
 
         let args = HttpsServerArgs {
-            address: "127.0.0.1:5425".to_owned(),
+            address: format!("127.0.0.1:{}", port),
             execution_api: Arc::new(MockExecutionApi {}),
-            cert_pem: Some(PathBuf::from(dir.clone() + "/src/https/test/cert.pem")),
-            key_pem: Some(PathBuf::from(dir.clone() + "/src/https/test/key.pem")),
+            cert_pem: Some(cert_path.clone()),
+            key_pem: Some(key_path),
         };
         let _handler = tokio::spawn(https_server(args));
         tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
         // read a local binary pem encoded certificate
-        let pem = std::fs::read(dir.clone() + "/src/https/test/cert.pem").unwrap();
+        let pem = fs::read(cert_path).unwrap();
         let cert = reqwest::Certificate::from_pem(&pem).unwrap();
 
-        let client = ClientBuilder::new()
+        ClientBuilder::new()
             .add_root_certificate(cert)
             .danger_accept_invalid_hostnames(true)
             .danger_accept_invalid_certs(true)
-            //.use_rustls_tls()
             .build()
-            .unwrap();
+            .unwrap()
+    }
 
-        // test set_fail_point
-        assert!(test_fail_point().is_none());
-        let mut map = HashMap::new();
-        map.insert("name", "unit_test_fail_point");
-        map.insert("action", "return");
-        let res =
-            client.post("http://127.0.0.1:5425/set_failpoint").json(&map).send().await.unwrap();
-        assert!(res.status().is_success(), "res is {:?}", res);
-        assert!(test_fail_point().is_some());
+    /// Starts the admin server over http, its audit log, profiles and ConsensusDB are kept in
+    /// `dir`.
+    async fn start_admin_server(dir: &TempPath, port: u16) -> Client {
+        let admin_args = AdminServerArgs {
+            address: format!("127.0.0.1:{}", port),
+            token: Some(ADMIN_TOKEN.to_owned()),
+            tls: None,
+            audit_log: Some(dir.path().join("audit.log")),
+            profile_dir: dir.path().join("profiles"),
+            consensus_db: Arc::new(ConsensusDB::new(dir.path(), &PathBuf::new())),
+        };
+        let _admin_handler = tokio::spawn(admin_server(admin_args));
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        Client::new()
+    }
+
+    fn temp_dir() -> TempPath {
+        let dir = TempPath::new();
+        dir.create_as_dir().unwrap();
+        dir
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_tx() {
+        let dir = temp_dir();
+        let client = start_https_server(&dir, 5425).await;
 
         let body = client.get("https://127.0.0.1:5425/tx/get_tx_by_hash/aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")
             .send()
@@ -166,5 +200,141 @@ mod test {
         let res =
             client.post("https://127.0.0.1:5425/tx/submit_tx").json(&map).send().await.unwrap();
         assert!(res.status().is_success());
+
+        let batch = BatchTxRequest { txns: vec!["0x01020304".to_owned(), "not hex".to_owned()] };
+        let res = client
+            .post("https://127.0.0.1:5425/tx/submit_txs")
+            .json(&batch)
+            .send()
+            .await
+            .unwrap()
+            .json::<BatchTxResponse>()
+            .await
+            .unwrap();
+        assert_eq!(res.results.len(), 2);
+        assert!(res.results[0].hash.is_some() && res.results[0].error.is_none());
+        assert!(res.results[1].hash.is_none() && res.results[1].error.is_some());
+
+        // the debug endpoints are only served by the admin server
+        let mut map = HashMap::new();
+        map.insert("name", "unit_test_fail_point");
+        map.insert("actions", "return");
+        let res =
+            client.post("https://127.0.0.1:5425/set_failpoint").json(&map).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_admin_auth() {
+        let dir = temp_dir();
+        let client = start_admin_server(&dir, 5426).await;
+
+        assert!(test_fail_point().is_none());
+        let mut map = HashMap::new();
+        map.insert("name", "unit_test_fail_point");
+        map.insert("actions", "return");
+        let res =
+            client.post("http://127.0.0.1:5426/set_failpoint").json(&map).send().await.unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let res = client
+            .post("http://127.0.0.1:5426/set_failpoint")
+            .bearer_auth("wrong_token")
+            .json(&map)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let res = client
+            .post("http://127.0.0.1:5426/set_failpoint")
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&map)
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_success(), "res is {:?}", res);
+        assert!(test_fail_point().is_some());
+        let audit_log = fs::read_to_string(dir.path().join("audit.log")).unwrap();
+        assert_eq!(audit_log.lines().count(), 3);
+        assert!(audit_log.contains("action=\"POST /set_failpoint\""));
+        assert!(audit_log.lines().last().unwrap().ends_with("status=200"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_profiles() {
+        let dir = temp_dir();
+        let client = start_admin_server(&dir, 5427).await;
+
+        // capture a cpu profile, and download it from the profile list
+        let res = client
+            .post("http://127.0.0.1:5427/cpu_prof")
+            .bearer_auth(ADMIN_TOKEN)
+            .json(&CpuProfileRequest {
+                seconds: Some(1),
+                frequency: None,
+                format: CpuProfileFormat::Flamegraph,
+            })
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_success(), "res is {:?}", res);
+        let profiles = client
+            .get("http://127.0.0.1:5427/profiles")
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json::<Vec<ProfileArtifact>>()
+            .await
+            .unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert!(profiles[0].name.ends_with(".svg"));
+        let svg = client
+            .get(format!("http://127.0.0.1:5427/profiles/{}", profiles[0].name))
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(svg.contains("<svg"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_safety_reports() {
+        let dir = temp_dir();
+        let client = start_admin_server(&dir, 5428).await;
+
+        // no equivocation has been seen by this node
+        let equivocations = client
+            .get("http://127.0.0.1:5428/equivocations?epoch=1")
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json::<Vec<Equivocation>>()
+            .await
+            .unwrap();
+        assert!(equivocations.is_empty());
+
+        // the execution of this node never diverged
+        let divergences = client
+            .get("http://127.0.0.1:5428/execution_divergences")
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json::<Vec<Divergence>>()
+            .await
+            .unwrap();
+        assert!(divergences.is_empty());
+        let status = client
+            .delete("http://127.0.0.1:5428/execution_divergences")
+            .bearer_auth(ADMIN_TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .status();
+        assert!(status.is_success());
     }
 }
@@ -0,0 +1,239 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use gaptos::aptos_logger::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+#[cfg(feature = "jemalloc-profiling")]
+use super::heap_profiler::PROFILER;
+
+const DEFAULT_CPU_PROFILE_SECS: u64 = 30;
+const MAX_CPU_PROFILE_SECS: u64 = 300;
+const DEFAULT_CPU_PROFILE_FREQUENCY: i32 = 99;
+/// Frames of these libraries are dropped, sampling them from the signal handler can deadlock.
+const CPU_PROFILE_BLOCKLIST: [&str; 4] = ["libc", "libgcc", "pthread", "vdso"];
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CpuProfileFormat {
+    /// Symbolized flamegraph svg, viewable in a browser
+    #[default]
+    Flamegraph,
+    /// pprof protobuf, for `go tool pprof`
+    Pprof,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct CpuProfileRequest {
+    pub seconds: Option<u64>,
+    pub frequency: Option<i32>,
+    #[serde(default)]
+    pub format: CpuProfileFormat,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct HeapDumpRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProfileArtifact {
+    pub name: String,
+    pub size: u64,
+    pub created_at_secs: u64,
+}
+
+/// Captured cpu profiles and heap dumps, kept in one directory so they can be listed and
+/// downloaded through the admin api.
+pub struct ProfileStore {
+    dir: PathBuf,
+    // the cpu profiler is process wide, only one capture may run at a time
+    cpu_profiling: Mutex<()>,
+}
+
+fn timestamp_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() &&
+        !name.starts_with('.') &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn error(status: StatusCode, msg: impl Into<String>) -> Response {
+    (status, msg.into()).into_response()
+}
+
+fn capture_cpu_profile(
+    path: &Path,
+    duration: Duration,
+    frequency: i32,
+    format: CpuProfileFormat,
+) -> Result<(), String> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency)
+        .blocklist(&CPU_PROFILE_BLOCKLIST)
+        .build()
+        .map_err(|e| format!("failed to start cpu profiler: {}", e))?;
+    std::thread::sleep(duration);
+    let report =
+        guard.report().build().map_err(|e| format!("failed to build cpu profile: {}", e))?;
+    let file =
+        std::fs::File::create(path).map_err(|e| format!("failed to create {:?}: {}", path, e))?;
+    match format {
+        CpuProfileFormat::Flamegraph => {
+            report.flamegraph(file).map_err(|e| format!("failed to write flamegraph: {}", e))
+        }
+        CpuProfileFormat::Pprof => {
+            use pprof::protos::Message;
+            let mut file = file;
+            report
+                .pprof()
+                .map_err(|e| format!("failed to build pprof profile: {}", e))?
+                .write_to_writer(&mut file)
+                .map_err(|e| format!("failed to write pprof profile: {}", e))
+        }
+    }
+}
+
+impl ProfileStore {
+    pub fn new(dir: PathBuf) -> Self {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("failed to create profile dir {:?}: {}", dir, e);
+        }
+        Self { dir, cpu_profiling: Mutex::new(()) }
+    }
+
+    /// Samples the stacks of all threads for `seconds`, and stores the profile.
+    pub async fn cpu_profile(&self, request: CpuProfileRequest) -> Response {
+        let seconds = request.seconds.unwrap_or(DEFAULT_CPU_PROFILE_SECS);
+        if seconds == 0 || seconds > MAX_CPU_PROFILE_SECS {
+            return error(
+                StatusCode::BAD_REQUEST,
+                format!("seconds must be in 1..={}", MAX_CPU_PROFILE_SECS),
+            );
+        }
+        let frequency = request.frequency.unwrap_or(DEFAULT_CPU_PROFILE_FREQUENCY);
+        if frequency <= 0 {
+            return error(StatusCode::BAD_REQUEST, "frequency must be positive");
+        }
+        let Ok(_running) = self.cpu_profiling.try_lock() else {
+            return error(StatusCode::CONFLICT, "a cpu profile is already being captured");
+        };
+        let extension = match request.format {
+            CpuProfileFormat::Flamegraph => "svg",
+            CpuProfileFormat::Pprof => "pb",
+        };
+        let name = format!("cpu-{}.{}", timestamp_secs(), extension);
+        let path = self.dir.join(&name);
+        info!("capturing cpu profile {} for {}s at {}hz", name, seconds, frequency);
+        let format = request.format;
+        let captured = tokio::task::spawn_blocking(move || {
+            capture_cpu_profile(&path, Duration::from_secs(seconds), frequency, format)
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("cpu profiler panicked: {}", e)));
+        match captured {
+            Ok(()) => Json(self.artifact(&name)).into_response(),
+            Err(e) => {
+                warn!("{}", e);
+                error(StatusCode::INTERNAL_SERVER_ERROR, e)
+            }
+        }
+    }
+
+    /// Dumps the jemalloc heap profile, which needs `prof.active` to be set by `/mem_prof`.
+    /// Use `jeprof --svg <gravity_node binary> <dump>` to symbolize it.
+    pub async fn heap_dump(&self, request: HeapDumpRequest) -> Response {
+        if !valid_name(&request.name) {
+            return error(StatusCode::BAD_REQUEST, "name may only hold [A-Za-z0-9_.-]");
+        }
+        #[cfg(feature = "jemalloc-profiling")]
+        {
+            let name = format!("heap-{}-{}.heap", request.name, timestamp_secs());
+            match PROFILER.dump(&self.dir.join(&name)) {
+                Ok(()) => Json(self.artifact(&name)).into_response(),
+                Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+            }
+        }
+        #[cfg(not(feature = "jemalloc-profiling"))]
+        error(StatusCode::BAD_REQUEST, "jemalloc profiling is not enabled")
+    }
+
+    fn artifact(&self, name: &str) -> ProfileArtifact {
+        let metadata = std::fs::metadata(self.dir.join(name)).ok();
+        ProfileArtifact {
+            name: name.to_string(),
+            size: metadata.as_ref().map_or(0, |m| m.len()),
+            created_at_secs: metadata
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    pub async fn list(&self) -> Response {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        };
+        let mut artifacts = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .map(|name| self.artifact(&name))
+            .collect::<Vec<_>>();
+        artifacts.sort_by(|a, b| (a.created_at_secs, &a.name).cmp(&(b.created_at_secs, &b.name)));
+        Json(artifacts).into_response()
+    }
+
+    pub async fn download(&self, name: &str) -> Response {
+        if !valid_name(name) {
+            return error(StatusCode::BAD_REQUEST, "invalid profile name");
+        }
+        match tokio::fs::read(self.dir.join(name)).await {
+            Ok(content) => {
+                let content_type = match Path::new(name).extension().and_then(|e| e.to_str()) {
+                    Some("svg") => "image/svg+xml",
+                    _ => "application/octet-stream",
+                };
+                (
+                    [
+                        (header::CONTENT_TYPE, content_type.to_string()),
+                        (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", name)),
+                    ],
+                    content,
+                )
+                    .into_response()
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                error(StatusCode::NOT_FOUND, format!("no profile named {}", name))
+            }
+            Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::valid_name;
+
+    #[test]
+    fn test_valid_name() {
+        assert!(valid_name("cpu-1700000000.svg"));
+        assert!(valid_name("heap-before_sync-1700000000.heap"));
+        assert!(!valid_name(""));
+        assert!(!valid_name(".."));
+        assert!(!valid_name("../secret"));
+        assert!(!valid_name("a/b"));
+    }
+}
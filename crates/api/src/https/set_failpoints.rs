@@ -7,6 +7,8 @@ use anyhow::{format_err, Result};
 #[cfg(feature = "failpoints")]
 use gaptos::aptos_logger::prelude::*;
 use axum::response::IntoResponse;
+#[cfg(feature = "failpoints")]
+use axum::Json;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
@@ -0,0 +1,169 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use block_buffer_manager::{block_buffer_manager::CommittedBlockEvent, get_block_buffer_manager};
+use futures::{stream, Stream};
+use gaptos::aptos_logger::warn;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    /// Only stream txns of this sender, hex encoded 20 or 32 bytes
+    sender: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CommittedTxn {
+    pub txn_hash: String,
+    pub sender: String,
+    pub nonce: u64,
+    pub is_discarded: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CommittedBlock {
+    pub block_number: u64,
+    pub block_id: String,
+    pub block_hash: Option<String>,
+    pub compute_res_hash: String,
+    /// Number of txns in the block, including the ones filtered out
+    pub txn_num: u64,
+    pub txns: Vec<CommittedTxn>,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Senders are stored left padded to 32 bytes, so 20 byte addresses are padded the same way.
+fn parse_sender(sender: &str) -> Result<[u8; 32], String> {
+    let bytes = hex::decode(sender.trim_start_matches("0x"))
+        .map_err(|e| format!("invalid sender {:?}: {}", sender, e))?;
+    if bytes.len() != 20 && bytes.len() != 32 {
+        return Err(format!("invalid sender {:?}: expected 20 or 32 bytes", sender));
+    }
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(padded)
+}
+
+impl CommittedBlock {
+    /// Returns `None` if `sender` is set and has no txn in the block.
+    fn from_event(event: &CommittedBlockEvent, sender: Option<&[u8; 32]>) -> Option<Self> {
+        let txns: Vec<CommittedTxn> = event
+            .compute_res
+            .txn_status
+            .as_ref()
+            .iter()
+            .flatten()
+            .filter(|status| sender.map_or(true, |sender| status.sender == *sender))
+            .map(|status| CommittedTxn {
+                txn_hash: to_hex(&status.txn_hash),
+                sender: to_hex(&status.sender),
+                nonce: status.nonce,
+                is_discarded: status.is_discarded,
+            })
+            .collect();
+        if sender.is_some() && txns.is_empty() {
+            return None;
+        }
+        Some(Self {
+            block_number: event.block_number,
+            block_id: to_hex(event.block_id.as_bytes()),
+            block_hash: event.block_hash.map(|hash| to_hex(&hash)),
+            compute_res_hash: to_hex(&event.compute_res.data),
+            txn_num: event.compute_res.txn_num,
+            txns,
+        })
+    }
+}
+
+// example:
+// curl -N https://127.0.0.1:1998/stream/committed_blocks?sender=0x...
+// Each `committed_block` event holds a `CommittedBlock`. A `lagged` event holds the number of
+// blocks the client missed because it read too slowly.
+pub async fn stream_committed_blocks(
+    Query(query): Query<StreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let sender = match query.sender {
+        Some(sender) => Some(parse_sender(&sender).map_err(|e| (StatusCode::BAD_REQUEST, e))?),
+        None => None,
+    };
+    let receiver = get_block_buffer_manager().subscribe_committed_blocks();
+    let events = stream::unfold(receiver, move |mut receiver| async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(committed) => {
+                    let Some(block) = CommittedBlock::from_event(&committed, sender.as_ref())
+                    else {
+                        continue;
+                    };
+                    Event::default().event("committed_block").json_data(block).unwrap()
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("committed block stream lagged by {} blocks", skipped);
+                    Event::default().event("lagged").data(skipped.to_string())
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok(event), receiver));
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod test {
+    use api_types::{
+        compute_res::{ComputeRes, TxnStatus},
+        u256_define::BlockId,
+    };
+    use block_buffer_manager::block_buffer_manager::CommittedBlockEvent;
+
+    use super::{parse_sender, CommittedBlock};
+
+    #[test]
+    fn test_sender_filter() {
+        let address = [7u8; 20];
+        let sender = parse_sender(&format!("0x{}", hex::encode(address))).unwrap();
+        assert_eq!(sender[..12], [0u8; 12]);
+        assert_eq!(sender[12..], address);
+        assert_eq!(parse_sender(&hex::encode([7u8; 32])).unwrap(), [7u8; 32]);
+        assert!(parse_sender("0x1234").is_err());
+        assert!(parse_sender("not hex").is_err());
+
+        let status = |sender: [u8; 32], nonce| TxnStatus {
+            txn_hash: [nonce as u8; 32],
+            nonce,
+            sender,
+            is_discarded: nonce == 2,
+        };
+        let event = CommittedBlockEvent {
+            block_number: 10,
+            block_id: BlockId::new([1; 32]),
+            block_hash: None,
+            compute_res: ComputeRes::new(
+                [2; 32],
+                3,
+                vec![status(sender, 1), status([9; 32], 1), status(sender, 2)],
+            ),
+        };
+
+        let all = CommittedBlock::from_event(&event, None).unwrap();
+        assert_eq!(all.block_number, 10);
+        assert_eq!(all.txn_num, 3);
+        assert_eq!(all.txns.len(), 3);
+
+        let filtered = CommittedBlock::from_event(&event, Some(&sender)).unwrap();
+        assert_eq!(filtered.txn_num, 3);
+        assert_eq!(filtered.txns.iter().map(|txn| txn.nonce).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(filtered.txns[1].is_discarded);
+
+        assert!(CommittedBlock::from_event(&event, Some(&[8; 32])).is_none());
+    }
+}
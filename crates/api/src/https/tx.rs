@@ -3,7 +3,11 @@ use std::sync::Arc;
 use api_types::{ExecTxn, ExecutionChannel};
 use gaptos::aptos_crypto::HashValue;
 use gaptos::aptos_logger::info;
-use axum::{http::StatusCode, response::Json as JsonResponse};
+use axum::{
+    body::Bytes,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    response::Json as JsonResponse,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -20,6 +24,30 @@ pub struct SubmitResponse {
     //    authenticator: (),
 }
 
+/// Upper bound of txns in one `submit_txs` request
+pub const MAX_BATCH_TXNS: usize = 10_000;
+/// Upper bound of the `submit_txs` request body
+pub const MAX_BATCH_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+#[derive(Serialize, Deserialize)]
+pub struct BatchTxRequest {
+    /// Hex encoded txns, with or without `0x`
+    pub txns: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct BatchTxResult {
+    /// Hex encoded hash, set if the txn was accepted
+    pub hash: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchTxResponse {
+    /// One result per txn, in request order
+    pub results: Vec<BatchTxResult>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TxResponse {
     pub tx: Vec<u8>,
@@ -51,4 +79,183 @@ pub async fn get_tx_by_hash(
 ) -> Result<JsonResponse<TxResponse>, StatusCode> {
     info!("get transaction by hash {}", request);
     Ok(JsonResponse(TxResponse { tx: vec![] }))
-}
\ No newline at end of file
+}
+/// Decodes a `submit_txs` body, either json with hex encoded txns or, with content type
+/// `application/octet-stream`, the BCS encoding of `Vec<Vec<u8>>`. Txns that fail to decode
+/// are returned as errors in their position.
+fn decode_batch(
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<Vec<Result<Vec<u8>, String>>, (StatusCode, String)> {
+    let content_type = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let txns = if content_type.starts_with("application/octet-stream") {
+        let txns: Vec<Vec<u8>> = bcs::from_bytes(body)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid bcs body: {}", e)))?;
+        txns.into_iter().map(Ok).collect::<Vec<_>>()
+    } else {
+        let request: BatchTxRequest = serde_json::from_slice(body)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid json body: {}", e)))?;
+        request
+            .txns
+            .iter()
+            .map(|txn| {
+                hex::decode(txn.trim_start_matches("0x"))
+                    .map_err(|e| format!("invalid hex txn: {}", e))
+            })
+            .collect()
+    };
+    if txns.len() > MAX_BATCH_TXNS {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("{} txns exceed the limit of {}", txns.len(), MAX_BATCH_TXNS),
+        ));
+    }
+    Ok(txns)
+}
+
+// example:
+// curl -X POST -H "Content-Type:application/json" -d '{"txns": ["0x01020304", "0x0506"]}' https://127.0.0.1:1998/tx/submit_txs
+pub async fn submit_txs(
+    headers: HeaderMap,
+    body: Bytes,
+    execution_api: Arc<dyn ExecutionChannel>,
+) -> Result<JsonResponse<BatchTxResponse>, (StatusCode, String)> {
+    let decoded = decode_batch(&headers, &body)?;
+    // `None` marks the txns that are submitted, their results are filled in below
+    let mut results = Vec::with_capacity(decoded.len());
+    let mut txns = Vec::with_capacity(decoded.len());
+    for txn in decoded {
+        match txn {
+            Ok(txn) => {
+                txns.push(ExecTxn::RawTxn(txn));
+                results.push(None);
+            }
+            Err(e) => results.push(Some(BatchTxResult { hash: None, error: Some(e) })),
+        }
+    }
+    let mut submitted = execution_api.send_user_txns(txns).await.into_iter();
+    let results = results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| match submitted.next() {
+                Some(Ok(hash)) => {
+                    BatchTxResult { hash: Some(hex::encode(hash.bytes())), error: None }
+                }
+                Some(Err(e)) => BatchTxResult { hash: None, error: Some(format!("{:?}", e)) },
+                None => BatchTxResult { hash: None, error: Some("not submitted".to_string()) },
+            })
+        })
+        .collect();
+    Ok(JsonResponse(BatchTxResponse { results }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use api_types::{
+        compute_res::ComputeRes,
+        u256_define::{BlockId, TxnHash},
+        ExecError, ExecTxn, ExecutionChannel, ExternalBlock, ExternalBlockMeta,
+        ExternalPayloadAttr, VerifiedTxn, VerifiedTxnWithAccountSeqNum,
+    };
+    use async_trait::async_trait;
+    use axum::{
+        body::Bytes,
+        http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    };
+
+    use super::{decode_batch, submit_txs, BatchTxRequest, MAX_BATCH_TXNS};
+
+    /// Counts the calls adding txns, only batches are accepted.
+    #[derive(Default)]
+    struct BatchOnlyExecutionApi {
+        batches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ExecutionChannel for BatchOnlyExecutionApi {
+        async fn send_user_txn(&self, _txn: ExecTxn) -> Result<TxnHash, ExecError> {
+            panic!("txns are submitted one by one");
+        }
+
+        async fn send_user_txns(&self, txns: Vec<ExecTxn>) -> Vec<Result<TxnHash, ExecError>> {
+            self.batches.fetch_add(1, Ordering::SeqCst);
+            txns.iter().map(|_| Ok(TxnHash::random())).collect()
+        }
+
+        async fn recv_unbroadcasted_txn(&self) -> Result<Vec<VerifiedTxn>, ExecError> {
+            unimplemented!()
+        }
+
+        async fn check_block_txns(
+            &self,
+            _payload_attr: ExternalPayloadAttr,
+            _txns: Vec<VerifiedTxn>,
+        ) -> Result<bool, ExecError> {
+            unimplemented!()
+        }
+
+        async fn send_pending_txns(&self) -> Result<Vec<VerifiedTxnWithAccountSeqNum>, ExecError> {
+            unimplemented!()
+        }
+
+        async fn recv_ordered_block(
+            &self,
+            _parent_id: BlockId,
+            _ordered_block: ExternalBlock,
+        ) -> Result<(), ExecError> {
+            unimplemented!()
+        }
+
+        async fn send_executed_block_hash(
+            &self,
+            _head: ExternalBlockMeta,
+        ) -> Result<ComputeRes, ExecError> {
+            unimplemented!()
+        }
+
+        async fn recv_committed_block_info(&self, _block_id: BlockId) -> Result<(), ExecError> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_txs_as_one_batch() {
+        let execution_api = Arc::new(BatchOnlyExecutionApi::default());
+        let request = BatchTxRequest {
+            txns: vec!["0x01".to_owned(), "not hex".to_owned(), "0x02".to_owned()],
+        };
+        let body = Bytes::from(serde_json::to_vec(&request).unwrap());
+        let response = submit_txs(HeaderMap::new(), body, execution_api.clone()).await.unwrap();
+        assert_eq!(execution_api.batches.load(Ordering::SeqCst), 1);
+        let accepted: Vec<_> =
+            response.0.results.iter().map(|result| result.hash.is_some()).collect();
+        assert_eq!(accepted, vec![true, false, true]);
+    }
+
+    #[test]
+    fn test_decode_batch() {
+        let mut headers = HeaderMap::new();
+        let json = br#"{"txns": ["0x0102", "0304", "zz"]}"#;
+        let txns = decode_batch(&headers, json).unwrap();
+        assert_eq!(txns[0], Ok(vec![1, 2]));
+        assert_eq!(txns[1], Ok(vec![3, 4]));
+        assert!(txns[2].is_err());
+        assert_eq!(decode_batch(&headers, b"[1, 2]").unwrap_err().0, StatusCode::BAD_REQUEST);
+
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"));
+        let bcs_body = bcs::to_bytes(&vec![vec![1u8, 2], vec![]]).unwrap();
+        assert_eq!(decode_batch(&headers, &bcs_body).unwrap(), vec![Ok(vec![1, 2]), Ok(vec![])]);
+        assert_eq!(decode_batch(&headers, &[0xff]).unwrap_err().0, StatusCode::BAD_REQUEST);
+
+        let too_many = bcs::to_bytes(&vec![Vec::<u8>::new(); MAX_BATCH_TXNS + 1]).unwrap();
+        assert_eq!(
+            decode_batch(&headers, &too_many).unwrap_err().0,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+}
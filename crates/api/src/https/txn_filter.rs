@@ -0,0 +1,32 @@
+use api_types::txn_filter::{set_txn_filter_rules, txn_filter_rules, TxnFilterRules};
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use gaptos::aptos_logger::info;
+
+pub async fn get_txn_filter() -> impl IntoResponse {
+    Json(txn_filter_rules())
+}
+
+/// Replaces all deny rules, an empty `TxnFilterRules` clears them.
+pub async fn set_txn_filter(rules: TxnFilterRules) -> impl IntoResponse {
+    info!("set txn filter rules {:?}", rules);
+    match set_txn_filter_rules(rules) {
+        Ok(()) => Json(txn_filter_rules()).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+pub const TXN_FILTER_FILE_ENV: &str = "GRAVITY_TXN_FILTER_FILE";
+
+/// Loads the initial deny rules from the yaml file at `GRAVITY_TXN_FILTER_FILE`, if set.
+pub fn load_txn_filter_from_env() {
+    let Ok(path) = std::env::var(TXN_FILTER_FILE_ENV) else {
+        return;
+    };
+    let content = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read txn filter file {:?}: {}", path, e));
+    let rules: TxnFilterRules = serde_yaml::from_str(&content)
+        .unwrap_or_else(|e| panic!("invalid txn filter file {:?}: {}", path, e));
+    set_txn_filter_rules(rules)
+        .unwrap_or_else(|e| panic!("invalid txn filter file {:?}: {}", path, e));
+    info!("loaded txn filter rules {:?} from {:?}", txn_filter_rules(), path);
+}
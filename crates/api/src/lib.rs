@@ -1,5 +1,6 @@
 mod bootstrap;
 mod consensus_mempool_handler;
+mod log_filter;
 mod logger;
 mod mock_db;
 mod network;
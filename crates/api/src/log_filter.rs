@@ -0,0 +1,139 @@
+use std::{
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+use gaptos::aptos_logger::{info, AptosData, Filter};
+use serde::{Deserialize, Serialize};
+
+/// Registered by `logger::create_logger`, used by the admin api.
+pub static LOG_FILTER_CONTROLLER: OnceLock<LogFilterController> = OnceLock::new();
+
+const LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+/// Checks that `spec` is a comma separated list of `level` or `module=level` directives, e.g.
+/// `info,aptos_consensus=trace`. The logger itself silently ignores invalid directives.
+pub fn validate_filter(spec: &str) -> Result<(), String> {
+    let is_level = |level: &str| LEVELS.iter().any(|l| l.eq_ignore_ascii_case(level));
+    let mut directives = 0;
+    for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        let valid = match directive.split_once('=') {
+            Some((module, level)) => {
+                !module.is_empty() &&
+                    module.chars().all(|c| c.is_alphanumeric() || c == '_' || c == ':') &&
+                    is_level(level)
+            }
+            None => is_level(directive),
+        };
+        if !valid {
+            return Err(format!("invalid log filter directive {:?}", directive));
+        }
+        directives += 1;
+    }
+    if directives == 0 {
+        return Err("empty log filter".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogFilterStatus {
+    pub filter: String,
+    pub default_filter: String,
+    /// Seconds until `filter` is reverted to `default_filter`
+    pub revert_in_secs: Option<u64>,
+}
+
+struct ActiveFilter {
+    filter: String,
+    revert_at: Option<SystemTime>,
+    // bumped on every change so that a pending revert of an older change is skipped
+    generation: u64,
+}
+
+/// Changes the local log filter at runtime, optionally reverting it after a timeout.
+pub struct LogFilterController {
+    logger: Arc<AptosData>,
+    default_filter: String,
+    active: Mutex<ActiveFilter>,
+}
+
+impl LogFilterController {
+    pub fn new(logger: Arc<AptosData>, default_filter: String) -> Self {
+        let active =
+            ActiveFilter { filter: default_filter.clone(), revert_at: None, generation: 0 };
+        Self { logger, default_filter, active: Mutex::new(active) }
+    }
+
+    pub fn status(&self) -> LogFilterStatus {
+        let active = self.active.lock().unwrap();
+        LogFilterStatus {
+            filter: active.filter.clone(),
+            default_filter: self.default_filter.clone(),
+            revert_in_secs: active.revert_at.map(|revert_at| {
+                revert_at.duration_since(SystemTime::now()).unwrap_or_default().as_secs()
+            }),
+        }
+    }
+
+    fn apply(&self, active: &mut ActiveFilter, filter: &str, revert_at: Option<SystemTime>) {
+        let mut builder = Filter::builder();
+        builder.parse(filter);
+        self.logger.set_local_filter(builder.build());
+        active.filter = filter.to_string();
+        active.revert_at = revert_at;
+        active.generation += 1;
+        info!("log filter set to {:?}, revert at {:?}", filter, revert_at);
+    }
+
+    /// Sets the log filter, and reverts it to the default after `revert_after` if given.
+    /// Must be called within a tokio runtime when `revert_after` is set.
+    pub fn set(
+        &'static self,
+        filter: &str,
+        revert_after: Option<Duration>,
+    ) -> Result<LogFilterStatus, String> {
+        validate_filter(filter)?;
+        {
+            let mut active = self.active.lock().unwrap();
+            let revert_at = revert_after.map(|after| SystemTime::now() + after);
+            self.apply(&mut active, filter, revert_at);
+            if let Some(revert_after) = revert_after {
+                let generation = active.generation;
+                tokio::spawn(async move {
+                    tokio::time::sleep(revert_after).await;
+                    let mut active = self.active.lock().unwrap();
+                    if active.generation == generation {
+                        self.apply(&mut active, &self.default_filter, None);
+                    }
+                });
+            }
+        }
+        Ok(self.status())
+    }
+
+    pub fn reset(&self) -> LogFilterStatus {
+        {
+            let mut active = self.active.lock().unwrap();
+            self.apply(&mut active, &self.default_filter, None);
+        }
+        self.status()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::validate_filter;
+
+    #[test]
+    fn test_validate_filter() {
+        assert!(validate_filter("info").is_ok());
+        assert!(validate_filter("INFO, aptos_consensus=trace,aptos_mempool::core=Debug").is_ok());
+        assert!(validate_filter("").is_err());
+        assert!(validate_filter(" , ").is_err());
+        assert!(validate_filter("verbose").is_err());
+        assert!(validate_filter("aptos_consensus=").is_err());
+        assert!(validate_filter("=trace").is_err());
+        assert!(validate_filter("aptos consensus=trace").is_err());
+    }
+}
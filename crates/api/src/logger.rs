@@ -11,6 +11,8 @@ use gaptos::aptos_logger::{
 use futures::channel::mpsc;
 use std::path::PathBuf;
 
+use crate::log_filter::{LogFilterController, LOG_FILTER_CONTROLLER};
+
 const TELEMETRY_LOG_INGEST_BUFFER_SIZE: usize = 128;
 
 // Simple macro to help print out feature configurations
@@ -56,6 +58,10 @@ pub fn create_logger(
 
     // Create the logger and the logger filter updater
     let logger = logger_builder.build();
+    // The builder takes the local filter from RUST_LOG if set, and from the level otherwise
+    let default_filter = std::env::var("RUST_LOG")
+        .unwrap_or_else(|_| node_config.logger.level.to_string().to_lowercase());
+    let _ = LOG_FILTER_CONTROLLER.set(LogFilterController::new(logger.clone(), default_filter));
     let logger_filter_updater: LoggerFilterUpdater = LoggerFilterUpdater::new(logger, logger_builder);
 
     // Log the build information and the config
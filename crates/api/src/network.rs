@@ -71,6 +71,26 @@ pub fn consensus_network_configuration(node_config: &NodeConfig) -> NetworkAppli
     NetworkApplicationConfig::new(network_client_config, network_service_config)
 }
 
+/// Returns the network application config for the consensus observer client and service
+pub fn consensus_observer_network_configuration(
+    node_config: &NodeConfig,
+) -> NetworkApplicationConfig {
+    let direct_send_protocols = vec![ProtocolId::ConsensusObserver];
+    let rpc_protocols = vec![ProtocolId::ConsensusObserverRpc];
+
+    let network_client_config =
+        NetworkClientConfig::new(direct_send_protocols.clone(), rpc_protocols.clone());
+    let network_service_config = NetworkServiceConfig::new(
+        direct_send_protocols,
+        rpc_protocols,
+        aptos_channel::Config::new(
+            node_config.consensus_observer.max_network_channel_size as usize,
+        )
+        .queue_style(QueueStyle::FIFO),
+    );
+    NetworkApplicationConfig::new(network_client_config, network_service_config)
+}
+
 /// Returns the network application config for the mempool client and service
 pub fn mempool_network_configuration(node_config: &NodeConfig) -> NetworkApplicationConfig {
     let direct_send_protocols = vec![ProtocolId::MempoolDirectSend];
@@ -113,7 +133,7 @@ pub async fn mock_mempool_client_sender(mut mc_sender: aptos_mempool::MempoolCli
     }
 }
 
-struct ApplicationNetworkHandle<T> {
+pub struct ApplicationNetworkHandle<T> {
     pub network_id: NetworkId,
     pub network_sender: NetworkSender<T>,
     pub network_events: NetworkEvents<T>,
@@ -121,7 +141,7 @@ struct ApplicationNetworkHandle<T> {
 
 /// Creates an application network inteface using the given
 /// handles and config.
-fn create_network_interfaces<
+pub fn create_network_interfaces<
     T: Serialize + for<'de> Deserialize<'de> + Send + Sync + Clone + 'static,
 >(
     network_handles: Vec<ApplicationNetworkHandle<T>>,
@@ -154,7 +174,7 @@ fn create_network_interfaces<
 }
 
 /// Registers a new application client and service with the network
-fn register_client_and_service_with_network<
+pub fn register_client_and_service_with_network<
     T: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static,
 >(
     network_builder: &mut NetworkBuilder,
@@ -171,30 +191,6 @@ fn register_client_and_service_with_network<
     ApplicationNetworkHandle { network_id, network_sender, network_events }
 }
 
-pub fn build_network_interfaces<T>(
-    network_builder: &mut NetworkBuilder,
-    network_id: NetworkId,
-    network_config: &NetworkConfig,
-    application_config: NetworkApplicationConfig,
-    peers_and_metadata: Arc<PeersAndMetadata>,
-) -> ApplicationNetworkInterfaces<T>
-where
-    T: Serialize + for<'de> Deserialize<'de> + Send + Sync + Clone + 'static,
-{
-    let consensus_network_handle = register_client_and_service_with_network(
-        network_builder,
-        network_id,
-        &network_config,
-        application_config.clone(),
-        true,
-    );
-    create_network_interfaces(
-        vec![consensus_network_handle],
-        application_config,
-        peers_and_metadata.clone(),
-    )
-}
-
 /// Creates a network runtime for the given network config
 pub fn create_network_runtime(network_config: &NetworkConfig) -> Runtime {
     let network_id = network_config.network_id;
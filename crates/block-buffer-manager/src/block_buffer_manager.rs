@@ -12,13 +12,36 @@ use std::{
 use tokio::{sync::Mutex, time::Instant};
 
 use api_types::{
+    account::ExternalAccountAddress,
     compute_res::{self, ComputeRes, TxnStatus}, u256_define::BlockId, ExternalBlock, VerifiedTxn,
     VerifiedTxnWithAccountSeqNum,
 };
 use itertools::Itertools;
 
+/// Identifies a txn in the execution layer's pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxnRef {
+    pub sender: ExternalAccountAddress,
+    pub sequence_number: u64,
+    pub hash: [u8; 32],
+}
+
+/// A change of the execution layer's txn pool. The consensus mempool applies them in order.
+#[derive(Debug, Clone)]
+pub enum TxnPoolEvent {
+    /// A new txn became pending.
+    Pending(VerifiedTxnWithAccountSeqNum),
+    /// `replaced` was replaced by `replacement`, which has the same sender and sequence number.
+    Replaced { replaced: TxnRef, replacement: VerifiedTxnWithAccountSeqNum },
+    /// The txn was dropped from the pool, e.g. evicted or no longer valid.
+    Discarded(TxnRef),
+    /// The txn was included in a block, so no txn of `sender` up to `sequence_number` can be
+    /// included anymore.
+    Mined { sender: ExternalAccountAddress, sequence_number: u64 },
+}
+
 pub struct TxnBuffer {
-    txns: Mutex<Vec<VerifiedTxnWithAccountSeqNum>>,
+    events: Mutex<Vec<TxnPoolEvent>>,
 }
 
 pub struct BlockHashRef {
@@ -38,6 +61,8 @@ pub enum BlockState {
 pub enum BufferState {
     Uninitialized,
     Ready,
+    /// Blocks up to the stop block number are still executed and committed, later ones are not.
+    Stopping,
 }
 
 #[derive(Default)]
@@ -50,6 +75,28 @@ pub struct BlockProfile {
     pub get_committed_blocks_time: Option<SystemTime>,
 }
 
+/// Published when consensus commits an executed block.
+#[derive(Debug, Clone)]
+pub struct CommittedBlockEvent {
+    pub block_number: u64,
+    pub block_id: BlockId,
+    pub block_hash: Option<[u8; 32]>,
+    pub compute_res: ComputeRes,
+}
+
+/// Progress of the buffer, for health checks.
+#[derive(Debug, Clone)]
+pub struct BufferHealth {
+    pub ready: bool,
+    pub stopping: bool,
+    pub latest_commit_block_number: u64,
+    /// When `latest_commit_block_number` last advanced, or when the buffer was initialized
+    pub latest_commit_time: SystemTime,
+    pub highest_ordered_block_number: u64,
+    /// When the oldest block that isn't executed yet was ordered
+    pub oldest_unexecuted_block_time: Option<SystemTime>,
+}
+
 pub struct BlockStateMachine {
     sender: tokio::sync::broadcast::Sender<()>,
     blocks: HashMap<u64, BlockState>,
@@ -57,6 +104,8 @@ pub struct BlockStateMachine {
     latest_commit_block_number: u64,
     latest_finalized_block_number: u64,
     block_number_to_block_id: HashMap<u64, BlockId>,
+    stop_block_number: Option<u64>,
+    latest_commit_time: SystemTime,
 }
 
 pub struct BlockBufferManagerConfig {
@@ -64,6 +113,8 @@ pub struct BlockBufferManagerConfig {
     pub max_wait_timeout: Duration,
     pub remove_committed_blocks_interval: Duration,
     pub max_block_size: usize,
+    /// Subscribers lagging more than this many committed blocks miss events
+    pub committed_block_channel_size: usize,
 }
 
 impl Default for BlockBufferManagerConfig {
@@ -73,12 +124,14 @@ impl Default for BlockBufferManagerConfig {
             max_wait_timeout: Duration::from_secs(5),
             remove_committed_blocks_interval: Duration::from_secs(1),
             max_block_size: 256,
+            committed_block_channel_size: 1024,
         }
     }
 }
 
 pub struct BlockBufferManager {
     txn_buffer: TxnBuffer,
+    committed_block_sender: tokio::sync::broadcast::Sender<CommittedBlockEvent>,
     block_state_machine: Mutex<BlockStateMachine>,
     buffer_state: AtomicU8,
     config: BlockBufferManagerConfig,
@@ -87,8 +140,11 @@ pub struct BlockBufferManager {
 impl BlockBufferManager {
     pub fn new(config: BlockBufferManagerConfig) -> Arc<Self> {
         let (sender, _recv) = tokio::sync::broadcast::channel(1024);
+        let (committed_block_sender, _recv) =
+            tokio::sync::broadcast::channel(config.committed_block_channel_size);
         let block_buffer_manager = Self {
-            txn_buffer: TxnBuffer { txns: Mutex::new(Vec::new()) },
+            txn_buffer: TxnBuffer { events: Mutex::new(Vec::new()) },
+            committed_block_sender,
             block_state_machine: Mutex::new(BlockStateMachine {
                 sender,
                 blocks: HashMap::new(),
@@ -96,6 +152,8 @@ impl BlockBufferManager {
                 latest_finalized_block_number: 0,
                 block_number_to_block_id: HashMap::new(),
                 profile: HashMap::new(),
+                stop_block_number: None,
+                latest_commit_time: SystemTime::now(),
             }),
             buffer_state: AtomicU8::new(BufferState::Uninitialized as u8),
             config,
@@ -140,6 +198,7 @@ impl BlockBufferManager {
         block_state_machine.latest_commit_block_number = latest_commit_block_number;
         block_state_machine.latest_finalized_block_number = latest_commit_block_number;
         block_state_machine.block_number_to_block_id = block_number_to_block_id;
+        block_state_machine.latest_commit_time = SystemTime::now();
         self.buffer_state.store(BufferState::Ready as u8, Ordering::SeqCst);
     }
 
@@ -161,32 +220,92 @@ impl BlockBufferManager {
     }
 
     pub async fn push_txns(&self, txn: Vec<VerifiedTxnWithAccountSeqNum>) {
-        let mut txns = self.txn_buffer.txns.lock().await;
-        txns.extend(txn);
+        let mut events = self.txn_buffer.events.lock().await;
+        events.extend(txn.into_iter().map(TxnPoolEvent::Pending));
     }
 
     pub fn is_ready(&self) -> bool {
-        self.buffer_state.load(Ordering::SeqCst) == BufferState::Ready as u8
+        self.buffer_state.load(Ordering::SeqCst) != BufferState::Uninitialized as u8
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        self.buffer_state.load(Ordering::SeqCst) == BufferState::Stopping as u8
+    }
+
+    /// Stops handing out blocks ordered from now on for execution, so that the blocks in flight
+    /// can drain through commit. Returns the number of the last block that will be committed.
+    pub async fn begin_shutdown(&self) -> u64 {
+        let mut block_state_machine = self.block_state_machine.lock().await;
+        if let Some(stop_block_number) = block_state_machine.stop_block_number {
+            return stop_block_number;
+        }
+        let stop_block_number = block_state_machine
+            .blocks
+            .keys()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(block_state_machine.latest_commit_block_number);
+        info!(
+            "begin shutdown, drain blocks up to {} from latest commit block {}",
+            stop_block_number, block_state_machine.latest_commit_block_number
+        );
+        block_state_machine.stop_block_number = Some(stop_block_number);
+        self.buffer_state.store(BufferState::Stopping as u8, Ordering::SeqCst);
+        let _ = block_state_machine.sender.send(());
+        stop_block_number
+    }
+
+    /// Waits until every block up to the stop block number is committed, returns the latest
+    /// commit block number either way.
+    pub async fn wait_drained(&self, timeout: Duration) -> Result<u64, anyhow::Error> {
+        let start = Instant::now();
+        loop {
+            let (latest_commit_block_number, stop_block_number) = {
+                let block_state_machine = self.block_state_machine.lock().await;
+                (
+                    block_state_machine.latest_commit_block_number,
+                    block_state_machine.stop_block_number.unwrap_or(0),
+                )
+            };
+            if latest_commit_block_number >= stop_block_number {
+                info!("drained all blocks up to {}", latest_commit_block_number);
+                return Ok(latest_commit_block_number);
+            }
+            if start.elapsed() > timeout {
+                return Err(anyhow::anyhow!(
+                    "Timeout draining blocks after {:?}, committed {} of {}",
+                    start.elapsed(),
+                    latest_commit_block_number,
+                    stop_block_number
+                ));
+            }
+            let _ = self.wait_for_change(self.config.wait_for_change_timeout).await;
+        }
     }
 
     pub async fn push_txn(&self, txn: VerifiedTxnWithAccountSeqNum) {
-        let mut txns = self.txn_buffer.txns.lock().await;
-        txns.push(txn);
+        self.push_txn_event(TxnPoolEvent::Pending(txn)).await;
+    }
+
+    pub async fn push_txn_event(&self, event: TxnPoolEvent) {
+        let mut events = self.txn_buffer.events.lock().await;
+        events.push(event);
     }
 
-    pub async fn pop_txns(
+    pub async fn pop_txn_events(
         &self,
         max_size: usize,
-    ) -> Result<Vec<VerifiedTxnWithAccountSeqNum>, anyhow::Error> {
-        let mut txns = self.txn_buffer.txns.lock().await;
-        info!("pop_txns with remain txn {}", txns.len());
+    ) -> Result<Vec<TxnPoolEvent>, anyhow::Error> {
+        let mut events = self.txn_buffer.events.lock().await;
+        info!("pop_txn_events with remain event {}", events.len());
 
-        if txns.len() <= max_size {
-            let result = std::mem::take(&mut *txns);
+        if events.len() <= max_size {
+            let result = std::mem::take(&mut *events);
             return Ok(result);
         } else {
             // take 0..max_size
-            let result = txns.drain(0..max_size).collect();
+            let result = events.drain(0..max_size).collect();
             return Ok(result);
         }
     }
@@ -245,10 +364,18 @@ impl BlockBufferManager {
             }
 
             let mut block_state_machine = self.block_state_machine.lock().await;
+            let stop_block_number = block_state_machine.stop_block_number.unwrap_or(u64::MAX);
+            if start_num > stop_block_number {
+                // Nothing is executed past the stop block number
+                return Ok(Vec::new());
+            }
             // get block num, block num + 1
             let mut result = Vec::new();
             let mut current_num = start_num;
-            while let Some(block) = block_state_machine.blocks.get(&current_num) {
+            while current_num <= stop_block_number {
+                let Some(block) = block_state_machine.blocks.get(&current_num) else {
+                    break;
+                };
                 match block {
                     BlockState::Ordered { block, parent_id } => {
                         result.push((block.clone(), *parent_id));
@@ -291,7 +418,9 @@ impl BlockBufferManager {
         let start = Instant::now();
         info!("get_executed_res start {:?} num {:?}", block_id, block_num);
         loop {
-            if start.elapsed() > self.config.max_wait_timeout {
+            // Blocks ordered after shutdown began are never executed, their callers wait until
+            // the consensus runtime is shut down instead of failing
+            if start.elapsed() > self.config.max_wait_timeout && !self.is_stopping() {
                 return Err(anyhow::anyhow!(
                     "get_executed_res timeout for block {:?} after {:?} block_number: {:?}",
                     block_id,
@@ -409,6 +538,13 @@ impl BlockBufferManager {
                 match state {
                     BlockState::Computed { id, compute_res } => {
                         if *id == block_id_num_hash.block_id {
+                            // No subscriber is fine
+                            let _ = self.committed_block_sender.send(CommittedBlockEvent {
+                                block_number: block_id_num_hash.num,
+                                block_id: block_id_num_hash.block_id,
+                                block_hash: block_id_num_hash.hash,
+                                compute_res: compute_res.clone(),
+                            });
                             *state = BlockState::Committed {
                                 hash: block_id_num_hash.hash,
                                 compute_res: compute_res.clone(),
@@ -517,12 +653,47 @@ impl BlockBufferManager {
             latest_commit_block_number, latest_finalized_block_number
         );
         let mut block_state_machine = self.block_state_machine.lock().await;
+        if latest_commit_block_number > block_state_machine.latest_commit_block_number {
+            block_state_machine.latest_commit_time = SystemTime::now();
+        }
         block_state_machine.latest_commit_block_number = latest_commit_block_number;
         block_state_machine.latest_finalized_block_number = latest_finalized_block_number;
         let _ = block_state_machine.sender.send(());
         Ok(())
     }
 
+    /// Streams the blocks committed from now on, in commit order.
+    pub fn subscribe_committed_blocks(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<CommittedBlockEvent> {
+        self.committed_block_sender.subscribe()
+    }
+
+    pub async fn health(&self) -> BufferHealth {
+        let block_state_machine = self.block_state_machine.lock().await;
+        let oldest_unexecuted_block_time = block_state_machine
+            .blocks
+            .iter()
+            .filter(|(_, state)| matches!(state, BlockState::Ordered { .. }))
+            .filter_map(|(num, _)| block_state_machine.profile.get(num))
+            .filter_map(|profile| profile.set_ordered_block_time)
+            .min();
+        BufferHealth {
+            ready: self.is_ready(),
+            stopping: self.is_stopping(),
+            latest_commit_block_number: block_state_machine.latest_commit_block_number,
+            latest_commit_time: block_state_machine.latest_commit_time,
+            highest_ordered_block_number: block_state_machine
+                .blocks
+                .keys()
+                .copied()
+                .max()
+                .unwrap_or(0)
+                .max(block_state_machine.latest_commit_block_number),
+            oldest_unexecuted_block_time,
+        }
+    }
+
     pub async fn latest_commit_block_number(&self) -> u64 {
         let block_state_machine = self.block_state_machine.lock().await;
         block_state_machine.latest_commit_block_number
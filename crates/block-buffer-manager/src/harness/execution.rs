@@ -0,0 +1,84 @@
+//! A mock execution layer driving a `BlockBufferManager` the way `RethCli` does: one loop
+//! executes the ordered blocks, another persists the committed ones.
+
+use super::{node::NodeDisk, sim_hash, CommittedBlock};
+use crate::block_buffer_manager::BlockBufferManager;
+use api_types::ExternalBlock;
+use rand::{rngs::StdRng, Rng};
+use std::{sync::Arc, time::Duration};
+
+/// How the execution layer of a node misbehaves
+pub(crate) struct ExecutionFaults {
+    pub latency: (Duration, Duration),
+}
+
+impl ExecutionFaults {
+    fn latency(&self, rng: &mut StdRng) -> Duration {
+        let (min, max) = self.latency;
+        Duration::from_nanos(rng.gen_range(min.as_nanos() as u64, max.as_nanos() as u64 + 1))
+    }
+}
+
+/// The result of executing `block` on top of the result of its parent.
+fn compute_block_hash(parent_hash: &[u8; 32], block: &ExternalBlock) -> [u8; 32] {
+    let mut parts: Vec<&[u8]> = vec![parent_hash.as_slice(), block.block_meta.block_id.as_bytes()];
+    parts.extend(block.txns.iter().map(|txn| txn.bytes.as_slice()));
+    sim_hash(&parts)
+}
+
+pub(crate) async fn run_execution(
+    buffer: Arc<BlockBufferManager>,
+    disk: Arc<NodeDisk>,
+    faults: ExecutionFaults,
+    mut rng: StdRng,
+) {
+    let (mut next_block_number, mut parent_hash) = match disk.last_committed() {
+        Some(block) => (block.number + 1, block.block_hash),
+        None => (1, [0u8; 32]),
+    };
+    loop {
+        let blocks = match buffer.get_ordered_blocks(next_block_number, None).await {
+            Ok(blocks) => blocks,
+            // Nothing was ordered in time, keep waiting
+            Err(_) => continue,
+        };
+        for (block, _parent_id) in blocks {
+            tokio::time::sleep(faults.latency(&mut rng)).await;
+            let block_number = block.block_meta.block_number;
+            let block_hash = compute_block_hash(&parent_hash, &block);
+            buffer
+                .set_compute_res(
+                    block.block_meta.block_id,
+                    block_hash,
+                    block_number,
+                    Arc::new(None),
+                )
+                .await
+                .expect("set_compute_res failed");
+            parent_hash = block_hash;
+            next_block_number = block_number + 1;
+        }
+    }
+}
+
+pub(crate) async fn run_commit(buffer: Arc<BlockBufferManager>, disk: Arc<NodeDisk>) {
+    let mut next_block_number = disk.last_committed().map_or(1, |block| block.number + 1);
+    loop {
+        let blocks = match buffer.get_committed_blocks(next_block_number, None).await {
+            Ok(blocks) => blocks,
+            Err(_) => continue,
+        };
+        for block in blocks {
+            disk.persist(CommittedBlock {
+                number: block.num,
+                block_id: block.block_id,
+                block_hash: block.hash.expect("committed block has a hash"),
+            });
+            next_block_number = block.num + 1;
+        }
+        buffer
+            .set_state(next_block_number - 1, next_block_number - 1)
+            .await
+            .expect("set_state failed");
+    }
+}
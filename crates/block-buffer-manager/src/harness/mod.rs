@@ -0,0 +1,189 @@
+//! A deterministic test harness for the `BlockBufferManager` of several nodes: blocks are fed to
+//! the buffer of each node, executed asynchronously by a mock execution layer, and committed once
+//! a quorum of nodes reported the same result. Nodes talk over `aptos-memsocket` and the tests run
+//! on tokio's paused clock, so a run only depends on its `SimConfig`.
+//!
+//! Consensus is mocked: a fixed leader broadcasts the ordered blocks, and nodes broadcast their
+//! compute results like commit votes. So the harness covers how the buffer handles slow execution
+//! and restarts, not the behavior of the consensus crate. Faults are injected through `Fault`.
+
+mod execution;
+mod network;
+mod node;
+mod tests;
+
+use anyhow::ensure;
+use api_types::u256_define::BlockId;
+use node::SimNode;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{task::JoinSet, time::Instant};
+
+pub(crate) struct SimConfig {
+    pub num_nodes: usize,
+    pub num_blocks: u64,
+    pub txns_per_block: usize,
+    /// Time between two blocks ordered by the leader
+    pub block_interval: Duration,
+    /// The execution time of a block is drawn uniformly from this range
+    pub execution_latency: (Duration, Duration),
+    /// Delay of every message between two nodes
+    pub network_delay: Duration,
+    pub seed: u64,
+    pub faults: Vec<Fault>,
+    /// Simulated time after which the run is stopped
+    pub timeout: Duration,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            num_nodes: 4,
+            num_blocks: 30,
+            txns_per_block: 5,
+            block_interval: Duration::from_millis(200),
+            execution_latency: (Duration::from_millis(50), Duration::from_millis(300)),
+            network_delay: Duration::from_millis(10),
+            seed: 0,
+            faults: vec![],
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+impl SimConfig {
+    /// Quorum of a BFT validator set of `num_nodes` equal votes
+    pub fn quorum(&self) -> usize {
+        self.num_nodes * 2 / 3 + 1
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Fault {
+    /// The execution layer of `node` takes `factor` times longer for every block
+    SlowExecution { node: usize, factor: u32 },
+    /// `node` crashes once it committed `after_block`, and restarts after `downtime`. Only its
+    /// persisted state survives, i.e. the ordered blocks and the blocks committed by its
+    /// execution layer. The leader, node 0, never crashes.
+    NodeCrash { node: usize, after_block: u64, downtime: Duration },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct CommittedBlock {
+    pub number: u64,
+    pub block_id: BlockId,
+    pub block_hash: [u8; 32],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct NodeReport {
+    /// Blocks persisted by the execution layer, in commit order
+    pub committed: Vec<CommittedBlock>,
+    pub restarts: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SimReport {
+    pub nodes: Vec<NodeReport>,
+}
+
+impl SimReport {
+    /// Checks that every node committed blocks 1, 2, ... without gaps, and that the nodes
+    /// committed the same blocks with the same compute results.
+    pub fn check_invariants(&self) -> anyhow::Result<()> {
+        for (index, node) in self.nodes.iter().enumerate() {
+            for (expected_number, block) in (1..).zip(&node.committed) {
+                ensure!(
+                    block.number == expected_number,
+                    "node {} committed block {} where block {} was expected",
+                    index,
+                    block.number,
+                    expected_number
+                );
+            }
+        }
+        let Some(longest) = self.nodes.iter().max_by_key(|node| node.committed.len()) else {
+            return Ok(());
+        };
+        for (index, node) in self.nodes.iter().enumerate() {
+            for (block, expected) in node.committed.iter().zip(&longest.committed) {
+                ensure!(
+                    block.block_id == expected.block_id,
+                    "node {} committed block {} with id {:?}, another node with id {:?}",
+                    index,
+                    block.number,
+                    block.block_id,
+                    expected.block_id
+                );
+                ensure!(
+                    block.block_hash == expected.block_hash,
+                    "node {} committed block {} with result {:?}, another node with {:?}",
+                    index,
+                    block.number,
+                    BlockId(block.block_hash),
+                    BlockId(expected.block_hash)
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn num_committed(&self) -> Vec<usize> {
+        self.nodes.iter().map(|node| node.committed.len()).collect()
+    }
+}
+
+/// Runs the nodes until every node committed all blocks, or until the timeout.
+pub(crate) async fn run_nodes(config: SimConfig) -> SimReport {
+    assert!(
+        config.faults.iter().all(|fault| !matches!(fault, Fault::NodeCrash { node: 0, .. })),
+        "the leader never crashes"
+    );
+    let config = Arc::new(config);
+    let nodes: Vec<Arc<SimNode>> =
+        (0..config.num_nodes).map(|index| Arc::new(SimNode::new(index, config.clone()))).collect();
+    let ports: Arc<Vec<u16>> = Arc::new(nodes.iter().map(|node| node.port()).collect());
+    for node in &nodes {
+        node.start(&ports).await;
+    }
+
+    let mut crashes = JoinSet::new();
+    for fault in &config.faults {
+        if let Fault::NodeCrash { node, after_block, downtime } = *fault {
+            let node = nodes[node].clone();
+            let ports = ports.clone();
+            crashes.spawn(async move {
+                node.wait_committed(after_block).await;
+                node.crash();
+                tokio::time::sleep(downtime).await;
+                node.start(&ports).await;
+            });
+        }
+    }
+
+    let deadline = Instant::now() + config.timeout;
+    while Instant::now() < deadline && !nodes.iter().all(|node| node.is_done()) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let report = SimReport { nodes: nodes.iter().map(|node| node.report()).collect() };
+    for node in &nodes {
+        node.crash();
+    }
+    report
+}
+
+/// A deterministic 32 byte hash, standing in for block ids, txn hashes and state roots.
+pub(crate) fn sim_hash(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    for (index, chunk) in hash.chunks_mut(8).enumerate() {
+        let mut hasher = DefaultHasher::new();
+        index.hash(&mut hasher);
+        parts.hash(&mut hasher);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    hash
+}
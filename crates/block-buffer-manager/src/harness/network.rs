@@ -0,0 +1,133 @@
+//! Length prefixed bcs messages between simulated nodes over `aptos-memsocket`.
+
+use api_types::u256_define::BlockId;
+use aptos_memsocket::{MemoryListener, MemorySocket};
+use futures::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    StreamExt,
+};
+use serde::{Deserialize, Serialize};
+use std::{io, time::Duration};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinSet,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct OrderedBlock {
+    pub block_number: u64,
+    pub block_id: BlockId,
+    pub parent_id: BlockId,
+    pub timestamp_usecs: u64,
+    pub txns: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum SimMsg {
+    /// A block ordered by the leader
+    Ordered(OrderedBlock),
+    /// The compute result of `author` for a block, standing in for a commit vote
+    CommitVote { author: usize, block_number: u64, block_id: BlockId, block_hash: [u8; 32] },
+    /// Sent by a restarted node to catch up on the blocks and votes it missed from `from` on
+    SyncRequest { author: usize, from: u64 },
+}
+
+async fn write_msg(socket: &mut MemorySocket, msg: &SimMsg) -> io::Result<()> {
+    let bytes = bcs::to_bytes(msg).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    socket.write_all(&(bytes.len() as u32).to_le_bytes()).await?;
+    socket.write_all(&bytes).await?;
+    socket.flush().await
+}
+
+async fn read_msg(socket: &mut MemorySocket) -> io::Result<SimMsg> {
+    let mut len = [0u8; 4];
+    socket.read_exact(&mut len).await?;
+    let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+    socket.read_exact(&mut bytes).await?;
+    bcs::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Forwards the messages of every accepted connection to `inbox`.
+async fn accept(mut listener: MemoryListener, inbox: UnboundedSender<SimMsg>) {
+    let mut readers = JoinSet::new();
+    let mut incoming = listener.incoming();
+    while let Some(Ok(mut socket)) = incoming.next().await {
+        let inbox = inbox.clone();
+        readers.spawn(async move {
+            while let Ok(msg) = read_msg(&mut socket).await {
+                if inbox.send(msg).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+/// Sends messages to the peer listening on `port` in order, connecting lazily. Messages sent
+/// while the peer is down are lost, like on a real network.
+async fn send_to_peer(port: u16, delay: Duration, mut outbox: UnboundedReceiver<SimMsg>) {
+    let mut socket: Option<MemorySocket> = None;
+    while let Some(msg) = outbox.recv().await {
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        if socket.is_none() {
+            socket = MemorySocket::connect(port).ok();
+        }
+        if let Some(conn) = socket.as_mut() {
+            if write_msg(conn, &msg).await.is_err() {
+                socket = None;
+            }
+        }
+    }
+}
+
+/// The connections of one node to all nodes, itself included. Lives as long as the tasks it was
+/// started on.
+#[derive(Clone)]
+pub(crate) struct Network {
+    inbox: UnboundedSender<SimMsg>,
+    outboxes: Vec<Option<UnboundedSender<SimMsg>>>,
+}
+
+impl Network {
+    /// Accepts connections on `listener` and connects to the other nodes on `ports`. Returns the
+    /// network and the stream of received messages.
+    pub fn start(
+        author: usize,
+        listener: MemoryListener,
+        ports: &[u16],
+        delay: Duration,
+        tasks: &mut JoinSet<()>,
+    ) -> (Self, UnboundedReceiver<SimMsg>) {
+        let (inbox, msgs) = unbounded_channel();
+        tasks.spawn(accept(listener, inbox.clone()));
+        let outboxes = ports
+            .iter()
+            .enumerate()
+            .map(|(peer, port)| {
+                if peer == author {
+                    return None;
+                }
+                let (outbox, outgoing) = unbounded_channel();
+                tasks.spawn(send_to_peer(*port, delay, outgoing));
+                Some(outbox)
+            })
+            .collect();
+        (Self { inbox, outboxes }, msgs)
+    }
+
+    pub fn send(&self, to: usize, msg: SimMsg) {
+        // The receiving side is gone once the node crashed
+        let _ = match &self.outboxes[to] {
+            Some(outbox) => outbox.send(msg),
+            None => self.inbox.send(msg),
+        };
+    }
+
+    pub fn broadcast(&self, msg: SimMsg) {
+        for to in 0..self.outboxes.len() {
+            self.send(to, msg.clone());
+        }
+    }
+}
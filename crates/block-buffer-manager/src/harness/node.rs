@@ -0,0 +1,396 @@
+//! A simulated node: the state it persists, and the tasks of the incarnation between two crashes.
+
+use super::{
+    execution::{self, ExecutionFaults},
+    network::{Network, OrderedBlock, SimMsg},
+    sim_hash, CommittedBlock, Fault, NodeReport, SimConfig,
+};
+use crate::block_buffer_manager::{BlockBufferManager, BlockBufferManagerConfig, BlockHashRef};
+use api_types::{
+    account::{ExternalAccountAddress, ExternalChainId},
+    u256_define::{BlockId, TxnHash},
+    ExternalBlock, ExternalBlockMeta, VerifiedTxn,
+};
+use aptos_memsocket::MemoryListener;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::{
+    sync::{
+        mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+        watch,
+    },
+    task::JoinSet,
+};
+
+/// The state of a node which survives crashes, like its databases
+pub(crate) struct NodeDisk {
+    /// Ordered blocks by number, like the consensus db
+    ordered: Mutex<BTreeMap<u64, OrderedBlock>>,
+    /// Blocks persisted by the execution layer
+    committed: Mutex<Vec<CommittedBlock>>,
+    committed_number: watch::Sender<u64>,
+}
+
+impl NodeDisk {
+    fn new() -> Self {
+        Self {
+            ordered: Mutex::new(BTreeMap::new()),
+            committed: Mutex::new(Vec::new()),
+            committed_number: watch::channel(0).0,
+        }
+    }
+
+    pub fn last_committed(&self) -> Option<CommittedBlock> {
+        self.committed.lock().unwrap().last().cloned()
+    }
+
+    pub fn persist(&self, block: CommittedBlock) {
+        let block_number = block.number;
+        self.committed.lock().unwrap().push(block);
+        self.committed_number.send_replace(block_number);
+    }
+
+    fn ordered_from(&self, block_number: u64) -> Vec<OrderedBlock> {
+        self.ordered.lock().unwrap().range(block_number..).map(|(_, block)| block.clone()).collect()
+    }
+}
+
+pub(crate) struct SimNode {
+    index: usize,
+    port: u16,
+    config: Arc<SimConfig>,
+    disk: Arc<NodeDisk>,
+    /// The listener of the first incarnation, bound on creation to reserve the port
+    listener: Mutex<Option<MemoryListener>>,
+    /// The tasks of the running incarnation, dropping them aborts them
+    tasks: Mutex<Option<JoinSet<()>>>,
+    starts: AtomicUsize,
+}
+
+impl SimNode {
+    pub fn new(index: usize, config: Arc<SimConfig>) -> Self {
+        let listener = MemoryListener::bind(0).expect("bind memsocket listener");
+        Self {
+            index,
+            port: listener.local_addr(),
+            config,
+            disk: Arc::new(NodeDisk::new()),
+            listener: Mutex::new(Some(listener)),
+            tasks: Mutex::new(None),
+            starts: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Starts an incarnation of the node from what it persisted, like a process restart.
+    pub async fn start(&self, ports: &[u16]) {
+        let start = self.starts.fetch_add(1, Ordering::SeqCst);
+        let listener = self.listener.lock().unwrap().take().unwrap_or_else(|| {
+            MemoryListener::bind(self.port).expect("port of the crashed node is released")
+        });
+
+        let buffer = BlockBufferManager::new(BlockBufferManagerConfig::default());
+        let committed = self.disk.committed.lock().unwrap().clone();
+        let latest_block_number = committed.last().map_or(0, |block| block.number);
+        buffer
+            .init(
+                latest_block_number,
+                committed.iter().map(|block| (block.number, block.block_id)).collect(),
+            )
+            .await;
+
+        let mut tasks = JoinSet::new();
+        let (network, msgs) =
+            Network::start(self.index, listener, ports, self.config.network_delay, &mut tasks);
+        tasks.spawn(execution::run_execution(
+            buffer.clone(),
+            self.disk.clone(),
+            self.execution_faults(),
+            StdRng::seed_from_u64(self.seed(start)),
+        ));
+        tasks.spawn(execution::run_commit(buffer.clone(), self.disk.clone()));
+        let (executing, executing_rx) = unbounded_channel();
+        let (executed, executed_rx) = unbounded_channel();
+        tasks.spawn(wait_executed(buffer.clone(), executing_rx, executed));
+
+        let mut consensus = Consensus {
+            index: self.index,
+            quorum: self.config.quorum(),
+            buffer,
+            disk: self.disk.clone(),
+            network: network.clone(),
+            executing,
+            next_commit: latest_block_number + 1,
+            own_results: BTreeMap::new(),
+            votes: BTreeMap::new(),
+        };
+        // Like consensus on startup, hand the ordered blocks which the execution layer did not
+        // persist to the buffer again
+        for block in self.disk.ordered_from(latest_block_number + 1) {
+            consensus.order(block).await;
+        }
+        if start > 0 {
+            network.broadcast(SimMsg::SyncRequest {
+                author: self.index,
+                from: latest_block_number + 1,
+            });
+        }
+        tasks.spawn(consensus.run(msgs, executed_rx));
+        if self.index == 0 {
+            tasks.spawn(propose(network, self.config.clone()));
+        }
+        *self.tasks.lock().unwrap() = Some(tasks);
+    }
+
+    /// Aborts the running incarnation, losing everything which is not persisted.
+    pub fn crash(&self) {
+        self.tasks.lock().unwrap().take();
+    }
+
+    pub async fn wait_committed(&self, block_number: u64) {
+        let mut committed_number = self.disk.committed_number.subscribe();
+        while *committed_number.borrow() < block_number {
+            if committed_number.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.disk.last_committed().map_or(0, |block| block.number) >= self.config.num_blocks
+    }
+
+    pub fn report(&self) -> NodeReport {
+        NodeReport {
+            committed: self.disk.committed.lock().unwrap().clone(),
+            restarts: self.starts.load(Ordering::SeqCst).saturating_sub(1),
+        }
+    }
+
+    fn execution_faults(&self) -> ExecutionFaults {
+        let mut faults = ExecutionFaults { latency: self.config.execution_latency };
+        for fault in &self.config.faults {
+            if let Fault::SlowExecution { node, factor } = *fault {
+                if node == self.index {
+                    faults.latency = (faults.latency.0 * factor, faults.latency.1 * factor);
+                }
+            }
+        }
+        faults
+    }
+
+    /// A seed per node and incarnation, derived from the seed of the run
+    fn seed(&self, start: usize) -> u64 {
+        let hash = sim_hash(&[
+            &self.config.seed.to_le_bytes()[..],
+            &(self.index as u64).to_le_bytes()[..],
+            &(start as u64).to_le_bytes()[..],
+        ]);
+        u64::from_le_bytes(hash[..8].try_into().expect("8 bytes"))
+    }
+}
+
+/// The leader orders a block every `block_interval`.
+async fn propose(network: Network, config: Arc<SimConfig>) {
+    let mut parent_id = BlockId([0u8; 32]);
+    for block_number in 1..=config.num_blocks {
+        tokio::time::sleep(config.block_interval).await;
+        let number = block_number.to_le_bytes();
+        let block_id =
+            BlockId(sim_hash(&[&b"block"[..], &number[..], &config.seed.to_le_bytes()[..]]));
+        let txns = (0..config.txns_per_block as u64)
+            .map(|index| sim_hash(&[&b"txn"[..], &number[..], &index.to_le_bytes()[..]]).to_vec())
+            .collect();
+        network.broadcast(SimMsg::Ordered(OrderedBlock {
+            block_number,
+            block_id,
+            parent_id,
+            timestamp_usecs: block_number * config.block_interval.as_micros() as u64,
+            txns,
+        }));
+        parent_id = block_id;
+    }
+}
+
+/// Reports the compute results of the blocks handed to the buffer, in that order.
+async fn wait_executed(
+    buffer: Arc<BlockBufferManager>,
+    mut executing: UnboundedReceiver<(u64, BlockId)>,
+    executed: UnboundedSender<(u64, BlockId, [u8; 32])>,
+) {
+    while let Some((block_number, block_id)) = executing.recv().await {
+        let compute_res = loop {
+            if let Ok(compute_res) = buffer.get_executed_res(block_id, block_number).await {
+                break compute_res;
+            }
+        };
+        if executed.send((block_number, block_id, compute_res.data)).is_err() {
+            return;
+        }
+    }
+}
+
+fn external_block(block: &OrderedBlock) -> ExternalBlock {
+    let txns = block
+        .txns
+        .iter()
+        .enumerate()
+        .map(|(index, bytes)| {
+            VerifiedTxn::new(
+                bytes.clone(),
+                ExternalAccountAddress::new([0u8; 32]),
+                index as u64,
+                ExternalChainId::new(0),
+                TxnHash::new(sim_hash(&[bytes.as_slice()])),
+            )
+        })
+        .collect();
+    ExternalBlock {
+        block_meta: ExternalBlockMeta {
+            block_id: block.block_id,
+            block_number: block.block_number,
+            usecs: block.timestamp_usecs,
+            randomness: None,
+            block_hash: None,
+        },
+        txns,
+    }
+}
+
+/// Stands in for consensus on one node: hands the ordered blocks to the buffer, votes with its
+/// own compute results and commits the blocks in order once a quorum voted for its result.
+struct Consensus {
+    index: usize,
+    quorum: usize,
+    buffer: Arc<BlockBufferManager>,
+    disk: Arc<NodeDisk>,
+    network: Network,
+    executing: UnboundedSender<(u64, BlockId)>,
+    next_commit: u64,
+    own_results: BTreeMap<u64, (BlockId, [u8; 32])>,
+    /// Voters of the blocks not yet committed, by block number and result
+    votes: BTreeMap<u64, BTreeMap<[u8; 32], BTreeSet<usize>>>,
+}
+
+impl Consensus {
+    async fn run(
+        mut self,
+        mut msgs: UnboundedReceiver<SimMsg>,
+        mut executed: UnboundedReceiver<(u64, BlockId, [u8; 32])>,
+    ) {
+        loop {
+            // biased, so that a run only depends on its seed
+            tokio::select! {
+                biased;
+                Some((block_number, block_id, block_hash)) = executed.recv() => {
+                    self.network.broadcast(SimMsg::CommitVote {
+                        author: self.index,
+                        block_number,
+                        block_id,
+                        block_hash,
+                    });
+                    self.own_results.insert(block_number, (block_id, block_hash));
+                    self.try_commit().await;
+                },
+                Some(msg) = msgs.recv() => self.on_msg(msg).await,
+                else => break,
+            }
+        }
+    }
+
+    async fn order(&mut self, block: OrderedBlock) {
+        self.buffer
+            .set_ordered_blocks(block.parent_id, external_block(&block))
+            .await
+            .expect("set_ordered_blocks failed");
+        let _ = self.executing.send((block.block_number, block.block_id));
+    }
+
+    async fn on_msg(&mut self, msg: SimMsg) {
+        match msg {
+            SimMsg::Ordered(block) => {
+                {
+                    let mut ordered = self.disk.ordered.lock().unwrap();
+                    if ordered.contains_key(&block.block_number) {
+                        return;
+                    }
+                    ordered.insert(block.block_number, block.clone());
+                }
+                self.order(block).await;
+            }
+            SimMsg::CommitVote { author, block_number, block_id: _, block_hash } => {
+                if block_number < self.next_commit {
+                    return;
+                }
+                self.votes
+                    .entry(block_number)
+                    .or_default()
+                    .entry(block_hash)
+                    .or_default()
+                    .insert(author);
+                self.try_commit().await;
+            }
+            SimMsg::SyncRequest { author, from } => {
+                if author == self.index {
+                    return;
+                }
+                for block in self.disk.ordered_from(from) {
+                    self.network.send(author, SimMsg::Ordered(block));
+                }
+                // The results of a previous incarnation are only known for persisted blocks
+                let mut results: BTreeMap<u64, (BlockId, [u8; 32])> = self
+                    .disk
+                    .committed
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|block| block.number >= from)
+                    .map(|block| (block.number, (block.block_id, block.block_hash)))
+                    .collect();
+                results.extend(self.own_results.range(from..).map(|(number, res)| (*number, *res)));
+                for (block_number, (block_id, block_hash)) in results {
+                    self.network.send(
+                        author,
+                        SimMsg::CommitVote {
+                            author: self.index,
+                            block_number,
+                            block_id,
+                            block_hash,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    async fn try_commit(&mut self) {
+        while let Some(&(block_id, own_hash)) = self.own_results.get(&self.next_commit) {
+            let block_number = self.next_commit;
+            let Some(votes) = self.votes.get(&block_number) else {
+                break;
+            };
+            if votes.get(&own_hash).map_or(0, BTreeSet::len) < self.quorum {
+                break;
+            }
+            self.buffer
+                .set_commit_blocks(vec![BlockHashRef {
+                    block_id,
+                    num: block_number,
+                    hash: Some(own_hash),
+                }])
+                .await
+                .expect("set_commit_blocks failed");
+            self.votes.remove(&block_number);
+            self.next_commit += 1;
+        }
+    }
+}
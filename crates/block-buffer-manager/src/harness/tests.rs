@@ -0,0 +1,55 @@
+use super::{run_nodes, Fault, SimConfig};
+use std::time::Duration;
+
+#[tokio::test(start_paused = true)]
+async fn test_honest_nodes_commit_the_same_blocks() {
+    let config = SimConfig::default();
+    let num_blocks = config.num_blocks as usize;
+    let report = run_nodes(config).await;
+    report.check_invariants().unwrap();
+    assert_eq!(report.num_committed(), vec![num_blocks; 4]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_slow_execution_layer_catches_up() {
+    let config = SimConfig {
+        faults: vec![Fault::SlowExecution { node: 1, factor: 5 }],
+        ..Default::default()
+    };
+    let num_blocks = config.num_blocks as usize;
+    let report = run_nodes(config).await;
+    report.check_invariants().unwrap();
+    assert_eq!(report.num_committed(), vec![num_blocks; 4]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_crashed_node_recovers() {
+    let config = SimConfig {
+        faults: vec![Fault::NodeCrash {
+            node: 2,
+            after_block: 5,
+            downtime: Duration::from_secs(3),
+        }],
+        ..Default::default()
+    };
+    let num_blocks = config.num_blocks as usize;
+    let report = run_nodes(config).await;
+    report.check_invariants().unwrap();
+    assert_eq!(report.num_committed(), vec![num_blocks; 4]);
+    assert_eq!(report.nodes[2].restarts, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_same_seed_same_report() {
+    let config = || SimConfig {
+        seed: 7,
+        faults: vec![
+            Fault::SlowExecution { node: 3, factor: 3 },
+            Fault::NodeCrash { node: 1, after_block: 10, downtime: Duration::from_secs(2) },
+        ],
+        ..Default::default()
+    };
+    let report = run_nodes(config()).await;
+    report.check_invariants().unwrap();
+    assert_eq!(report, run_nodes(config()).await);
+}
@@ -3,6 +3,9 @@ use std::sync::{Arc, OnceLock};
 use block_buffer_manager::BlockBufferManager;
 
 pub mod block_buffer_manager;
+#[cfg(test)]
+mod harness;
+
 static GLOBAL_BLOCK_BUFFER_MANAGER: OnceLock<Arc<BlockBufferManager>> = OnceLock::new();
 
 pub fn get_block_buffer_manager() -> &'static Arc<BlockBufferManager> {
@@ -24,6 +24,7 @@ node_arg=""
 bin_version="release"
 mode="cluster"
 recover="false"
+config_dir=""
 
 # Logging functions
 log_info() {
@@ -54,6 +55,7 @@ show_help() {
         echo "                      - $m (${VALID_MODES[$m]})"
     done
     echo "  -r, --recover          Preserve existing data (default: false)"
+    echo "  -c, --config_dir DIR   Use a cluster generated by gravity-genesis instead of the bundled configs"
     echo "  -h, --help             Show this help message"
     echo
     echo "Examples:"
@@ -102,7 +104,12 @@ validate_params() {
         exit 1
     fi
 
-    if [[ "$mode" == "single" && "$node_arg" != "node1" ]]; then
+    if [[ -n "$config_dir" && ! -d "$config_dir/$node_arg/genesis" ]]; then
+        log_error "No genesis for $node_arg found in $config_dir"
+        exit 1
+    fi
+
+    if [[ -z "$config_dir" && "$mode" == "single" && "$node_arg" != "node1" ]]; then
         log_error "Single mode only supports 'node1'"
         exit 1
     fi
@@ -130,6 +137,10 @@ while [[ "$#" -gt 0 ]]; do
     -r|--recover)
         recover="true"
         ;;
+    -c|--config_dir)
+        config_dir="$2"
+        shift
+        ;;
     -h|--help)
         show_help
         exit 0
@@ -174,14 +185,19 @@ main() {
     # Copy files
     log_info "Copying configuration files"
 
-    cp -r "$SCRIPT_DIR/$node_arg/genesis" "/tmp/$node_arg"
-
-    if [[ "$mode" == "cluster" ]]; then
+    if [[ -n "$config_dir" ]]; then
+        log_info "Using generated configs from $config_dir"
+        cp -r "$config_dir/$node_arg/genesis" "/tmp/$node_arg"
+        cp -r "$config_dir/nodes_config.json" "/tmp/$node_arg/genesis/nodes_config.json"
+        cp -r "$config_dir/discovery" "/tmp/$node_arg/discovery"
+    elif [[ "$mode" == "cluster" ]]; then
         log_info "Setting up cluster mode"
+        cp -r "$SCRIPT_DIR/$node_arg/genesis" "/tmp/$node_arg"
         cp -r "$SCRIPT_DIR/four_nodes_config.json" "/tmp/$node_arg/genesis/nodes_config.json"
         cp -r "$SCRIPT_DIR/four_nodes_discovery" "/tmp/$node_arg/discovery"
     else
         log_info "Setting up single node mode"
+        cp -r "$SCRIPT_DIR/$node_arg/genesis" "/tmp/$node_arg"
         cp -r "$SCRIPT_DIR/single_node_config.json" "/tmp/$node_arg/genesis/nodes_config.json"
         cp -r "$SCRIPT_DIR/single_node_discovery" "/tmp/$node_arg/discovery"
     fi
@@ -3,5 +3,18 @@
 SCRIPT_DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" && pwd )"
 WORKSPACE=$SCRIPT_DIR/..
 
-cat ${WORKSPACE}/script/node.pid | xargs kill -9
+# SIGTERM lets the node commit the blocks in flight before it exits, only kill it if that hangs
+STOP_TIMEOUT_SECS=${STOP_TIMEOUT_SECS:-90}
+
+pid=$(cat ${WORKSPACE}/script/node.pid)
+kill -TERM $pid
+for ((i = 0; i < STOP_TIMEOUT_SECS; i++)); do
+    if ! kill -0 $pid 2>/dev/null; then
+        rm ${WORKSPACE}/script/node.pid
+        exit 0
+    fi
+    sleep 1
+done
+echo "node $pid did not stop within ${STOP_TIMEOUT_SECS}s, killing it"
+kill -9 $pid
 rm ${WORKSPACE}/script/node.pid